@@ -6,38 +6,31 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::{metadata::get_metadata, FilesMapChange, ProcessedFiles};
-use crate::{Error, Result, Safe, XorUrl};
-use bytes::Bytes;
-use log::info;
-use sn_client::Error as ClientError;
-use std::{
-    fs,
-    path::{Path, PathBuf},
+use super::{
+    content_type_for, metadata::get_metadata, FilesMapChange, ProcessedFiles, UploadJournal,
 };
+use crate::{Error, Result, Safe, SafeUrl, XorUrl};
+use log::{debug, info};
+use sn_client::Error as ClientError;
+use std::path::{Path, PathBuf};
+use tokio::{fs::File, io::BufReader};
 use walkdir::{DirEntry, WalkDir};
 
 const MAX_RECURSIVE_DEPTH: usize = 10_000;
 
-// Upload a file to the Network
+// Upload a file to the Network, streaming its content so it's never entirely held in memory
 pub(crate) async fn upload_file_to_net(safe: &Safe, path: &Path) -> Result<XorUrl> {
-    let data = fs::read(path).map_err(|err| {
-        Error::InvalidInput(format!("Failed to read file from local location: {}", err))
-    })?;
-    let data = Bytes::from(data);
-
-    let mut mime_type_for_xorurl = mime_guess::from_path(&path).first_raw();
-    let result = match safe
-        .store_bytes(data.to_owned(), mime_type_for_xorurl)
-        .await
-    {
-        Ok(xorurl) => Ok(xorurl),
-        Err(Error::InvalidMediaType(_)) => {
-            // Let's then upload it and set media-type to be simply raw content
-            mime_type_for_xorurl = None;
-            safe.store_bytes(data.clone(), mime_type_for_xorurl).await
+    // If the media-type is not supported, let's upload it as simply raw content
+    let mime_type_for_xorurl = mime_guess::from_path(path)
+        .first_raw()
+        .filter(|media_type| SafeUrl::is_media_type_supported(media_type));
+
+    let (file, size) = open_file(path).await?;
+    let result = match &safe.upload_journal {
+        Some(journal) if !safe.dry_run_mode => {
+            upload_with_journal(safe, journal, path, file, size, mime_type_for_xorurl).await
         }
-        other_err => other_err,
+        _ => safe.store_stream(file, size, mime_type_for_xorurl).await,
     };
 
     // If the upload verification failed, the file could still have been uploaded successfully,
@@ -46,7 +39,10 @@ pub(crate) async fn upload_file_to_net(safe: &Safe, path: &Path) -> Result<XorUr
         // Let's obtain the xorurl with using dry-run mode.
         // Use a dry runner only for this next operation
         let dry_runner = Safe::dry_runner(Some(safe.xorurl_base));
-        let xorurl = dry_runner.store_bytes(data, mime_type_for_xorurl).await?;
+        let (file, size) = open_file(path).await?;
+        let xorurl = dry_runner
+            .store_stream(file, size, mime_type_for_xorurl)
+            .await?;
 
         Err(Error::ContentUploadVerificationFailed(xorurl))
     } else {
@@ -54,6 +50,62 @@ pub(crate) async fn upload_file_to_net(safe: &Safe, path: &Path) -> Result<XorUr
     }
 }
 
+// Upload a file to the Network skipping the chunks the journal has recorded as already stored,
+// and recording in it each of the chunks stored, as well as the file once it's been verified.
+async fn upload_with_journal(
+    safe: &Safe,
+    journal: &UploadJournal,
+    path: &Path,
+    file: BufReader<File>,
+    size: usize,
+    media_type: Option<&str>,
+) -> Result<XorUrl> {
+    let journal_err =
+        |err| Error::FileSystemError(format!("Failed to update upload journal: {}", err));
+    let file_key = UploadJournal::file_key(path).map_err(journal_err)?;
+    if let Some(xorurl) = journal.stored_file(&file_key) {
+        debug!("Skipping upload of {}, already stored", path.display());
+        return Ok(xorurl);
+    }
+
+    let content_type = content_type_for(media_type)?;
+    let stored_chunks = journal.stored_chunks(&file_key);
+    debug!(
+        "Uploading {} bytes from {}, {} chunks already stored",
+        size,
+        path.display(),
+        stored_chunks.len()
+    );
+
+    let client = safe.get_safe_client()?;
+    let address = client
+        .resume_upload_stream(file, size, &stored_chunks, |chunk| {
+            journal.chunk_stored(&file_key, chunk)?;
+            Ok(())
+        })
+        .await?;
+
+    // let's now verify all the chunks, including those stored before resuming, can be retrieved
+    let _ = client.read_stream(address, tokio::io::sink()).await?;
+
+    let xorurl = SafeUrl::from_bytes(address, content_type)?.encode(safe.xorurl_base);
+    journal
+        .file_stored(&file_key, &xorurl)
+        .map_err(journal_err)?;
+
+    Ok(xorurl)
+}
+
+// Open a local file for reading, returning it along with its size
+async fn open_file(path: &Path) -> Result<(BufReader<File>, usize)> {
+    let map_err =
+        |err| Error::InvalidInput(format!("Failed to read file from local location: {}", err));
+    let file = File::open(path).await.map_err(map_err)?;
+    let size = file.metadata().await.map_err(map_err)?.len() as usize;
+
+    Ok((BufReader::new(file), size))
+}
+
 // Simply change Windows style path separator into `/`
 pub(crate) fn normalise_path_separator(from: &str) -> String {
     str::replace(from, "\\", "/")
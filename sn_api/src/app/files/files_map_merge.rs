@@ -0,0 +1,362 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{metadata::FileMeta, FileInfo, FilesMap};
+use crate::{app::consts::*, app::nrs::VersionHash, register::EntryHash, Error, Result};
+use sn_interface::types::register::Register;
+use std::{
+    collections::{btree_map, BTreeMap, BTreeSet},
+    fmt,
+    str::FromStr,
+};
+
+/// Policy to apply when concurrent versions of a `FilesContainer` made
+/// different changes to the same path, and they need to be merged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Keep the item with the most recent 'modified' timestamp.
+    LastModifiedWins,
+    /// Keep all conflicting files, the most recently modified one under its original
+    /// path, and the others with the version they come from as a suffix of their name.
+    KeepBoth,
+    /// Fail with an `Error::ConflictingFilesContainerVersions`.
+    Fail,
+}
+
+/// Default policy is LastModifiedWins
+impl Default for ConflictPolicy {
+    fn default() -> Self {
+        Self::LastModifiedWins
+    }
+}
+
+impl FromStr for ConflictPolicy {
+    type Err = Error;
+    fn from_str(str: &str) -> Result<Self> {
+        match str {
+            "last-modified" => Ok(Self::LastModifiedWins),
+            "keep-both" => Ok(Self::KeepBoth),
+            "fail" => Ok(Self::Fail),
+            other => Err(Error::InvalidInput(format!(
+                "Invalid conflict policy: {}. Supported values are last-modified, keep-both, and fail",
+                other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for ConflictPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::LastModifiedWins => write!(f, "last-modified"),
+            Self::KeepBoth => write!(f, "keep-both"),
+            Self::Fail => write!(f, "fail"),
+        }
+    }
+}
+
+// Find the closest entry which is an ancestor of all the given head entries,
+// i.e. the version all the concurrent branches were created from.
+// Returns None if the branches don't share any history.
+pub(crate) fn find_common_ancestor(
+    register: &Register,
+    heads: &BTreeSet<EntryHash>,
+) -> Result<Option<EntryHash>> {
+    let mut common: Option<BTreeMap<EntryHash, usize>> = None;
+    for head in heads {
+        let ancestors = ancestors_with_distance(register, *head)?;
+        common = Some(match common {
+            None => ancestors,
+            Some(common) => common
+                .into_iter()
+                .filter_map(|(hash, dist)| ancestors.get(&hash).map(|d| (hash, dist + d)))
+                .collect(),
+        });
+    }
+
+    // Amongst all common ancestors, pick the one with the shortest total distance to the heads
+    Ok(common.and_then(|common| {
+        common
+            .into_iter()
+            .min_by_key(|(hash, dist)| (*dist, *hash))
+            .map(|(hash, _)| hash)
+    }))
+}
+
+// Walk the history of an entry, returning all of its ancestors (including itself)
+// along with the minimum number of hops required to reach each of them.
+fn ancestors_with_distance(
+    register: &Register,
+    entry: EntryHash,
+) -> Result<BTreeMap<EntryHash, usize>> {
+    let mut ancestors = BTreeMap::from([(entry, 0)]);
+    let mut to_visit = vec![entry];
+    let mut dist = 0;
+    while !to_visit.is_empty() {
+        dist += 1;
+        let mut next = vec![];
+        for hash in to_visit {
+            for (child, _) in register.children(hash)? {
+                if let btree_map::Entry::Vacant(entry) = ancestors.entry(child) {
+                    entry.insert(dist);
+                    next.push(child);
+                }
+            }
+        }
+        to_visit = next;
+    }
+
+    Ok(ancestors)
+}
+
+// Three-way merge of the FilesMaps of concurrent versions of a FilesContainer,
+// applying the given policy for paths which were changed differently by several branches.
+pub(crate) fn merge_files_maps(
+    base: &FilesMap,
+    branches: &[(VersionHash, FilesMap)],
+    policy: ConflictPolicy,
+) -> Result<FilesMap> {
+    let paths: BTreeSet<&String> = base
+        .keys()
+        .chain(branches.iter().flat_map(|(_, files_map)| files_map.keys()))
+        .collect();
+
+    let mut merged = FilesMap::new();
+    let mut conflicts = Vec::new();
+    for path in paths {
+        let base_item = base.get(path);
+
+        // Collect the distinct changes the branches made to this path, where None means removed
+        let mut changes = Vec::<(VersionHash, Option<&FileInfo>)>::new();
+        for (version, files_map) in branches {
+            let item = files_map.get(path);
+            if item != base_item && changes.iter().all(|(_, other)| *other != item) {
+                changes.push((*version, item));
+            }
+        }
+
+        match changes.as_slice() {
+            [] => {
+                if let Some(item) = base_item {
+                    merged.insert(path.clone(), item.clone());
+                }
+            }
+            [(_, item)] => {
+                if let Some(item) = item {
+                    merged.insert(path.clone(), (*item).clone());
+                }
+            }
+            _ => {
+                if policy == ConflictPolicy::Fail {
+                    conflicts.push(path.clone());
+                    continue;
+                }
+
+                // A change to the content always wins over its removal,
+                // so the latest modified version goes first
+                let mut updates: Vec<(VersionHash, &FileInfo)> = changes
+                    .into_iter()
+                    .filter_map(|(version, item)| item.map(|item| (version, item)))
+                    .collect();
+                updates.sort_by_key(|(version, item)| {
+                    std::cmp::Reverse((modified_timestamp(item), *version))
+                });
+
+                let mut updates = updates.into_iter();
+                if let Some((_, winner)) = updates.next() {
+                    merged.insert(path.clone(), winner.clone());
+                }
+
+                if policy == ConflictPolicy::KeepBoth {
+                    for (version, item) in updates {
+                        // directories and symlinks cannot be meaningfully duplicated
+                        if FileMeta::filetype_is_file(&item[PREDICATE_TYPE]) {
+                            merged.insert(suffixed_path(path, &version), item.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if !conflicts.is_empty() {
+        return Err(Error::ConflictingFilesContainerVersions(
+            format!(
+                "Concurrent versions of the FilesContainer made different changes to {} path/s",
+                conflicts.len()
+            ),
+            conflicts,
+        ));
+    }
+
+    Ok(merged)
+}
+
+fn modified_timestamp(item: &FileInfo) -> i64 {
+    item.get(PREDICATE_MODIFIED)
+        .and_then(|modified| modified.parse().ok())
+        .unwrap_or_default()
+}
+
+// Insert the version as a suffix of the file name, keeping its extension, if any.
+fn suffixed_path(path: &str, version: &VersionHash) -> String {
+    let name_start = path.rfind('/').map_or(0, |i| i + 1);
+    match path[name_start..].rfind('.') {
+        Some(i) if i > 0 => {
+            let (stem, ext) = path.split_at(name_start + i);
+            format!("{}.{}{}", stem, version, ext)
+        }
+        _ => format!("{}.{}", path, version),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::{bail, Result};
+    use sn_interface::types::{register::User, Keypair};
+
+    fn file_item(link: &str, modified: u64) -> FileInfo {
+        BTreeMap::from([
+            (PREDICATE_LINK.to_string(), link.to_string()),
+            (PREDICATE_TYPE.to_string(), "text/plain".to_string()),
+            (PREDICATE_MODIFIED.to_string(), modified.to_string()),
+        ])
+    }
+
+    fn version(byte: u8) -> VersionHash {
+        VersionHash::from(&EntryHash([byte; 32]))
+    }
+
+    #[test]
+    fn merge_should_apply_non_conflicting_changes_from_all_branches() -> Result<()> {
+        let base = FilesMap::from([
+            ("/a.txt".to_string(), file_item("a", 1)),
+            ("/b.txt".to_string(), file_item("b", 1)),
+            ("/c.txt".to_string(), file_item("c", 1)),
+        ]);
+
+        let mut branch1 = base.clone();
+        branch1.insert("/a.txt".to_string(), file_item("a1", 2));
+        branch1.remove("/c.txt");
+        let mut branch2 = base.clone();
+        branch2.insert("/d.txt".to_string(), file_item("d", 2));
+
+        let merged = merge_files_maps(
+            &base,
+            &[(version(1), branch1), (version(2), branch2)],
+            ConflictPolicy::Fail,
+        )?;
+
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged["/a.txt"][PREDICATE_LINK], "a1");
+        assert_eq!(merged["/b.txt"][PREDICATE_LINK], "b");
+        assert_eq!(merged["/d.txt"][PREDICATE_LINK], "d");
+        Ok(())
+    }
+
+    #[test]
+    fn merge_conflict_last_modified_wins() -> Result<()> {
+        let base = FilesMap::from([("/a.txt".to_string(), file_item("a", 1))]);
+        let branch1 = FilesMap::from([("/a.txt".to_string(), file_item("a1", 3))]);
+        let branch2 = FilesMap::from([("/a.txt".to_string(), file_item("a2", 2))]);
+        let branch3 = FilesMap::new();
+
+        let merged = merge_files_maps(
+            &base,
+            &[
+                (version(1), branch1),
+                (version(2), branch2),
+                (version(3), branch3),
+            ],
+            ConflictPolicy::LastModifiedWins,
+        )?;
+
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged["/a.txt"][PREDICATE_LINK], "a1");
+        Ok(())
+    }
+
+    #[test]
+    fn merge_conflict_keep_both() -> Result<()> {
+        let base = FilesMap::new();
+        let branch1 = FilesMap::from([("/dir/a.txt".to_string(), file_item("a1", 1))]);
+        let branch2 = FilesMap::from([("/dir/a.txt".to_string(), file_item("a2", 2))]);
+
+        let merged = merge_files_maps(
+            &base,
+            &[(version(1), branch1), (version(2), branch2)],
+            ConflictPolicy::KeepBoth,
+        )?;
+
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged["/dir/a.txt"][PREDICATE_LINK], "a2");
+        let suffixed = format!("/dir/a.{}.txt", version(1));
+        assert_eq!(merged[&suffixed][PREDICATE_LINK], "a1");
+        Ok(())
+    }
+
+    #[test]
+    fn merge_conflict_fail() -> Result<()> {
+        let base = FilesMap::from([("/a.txt".to_string(), file_item("a", 1))]);
+        let branch1 = FilesMap::from([("/a.txt".to_string(), file_item("a1", 2))]);
+        let branch2 = FilesMap::from([("/a.txt".to_string(), file_item("a2", 2))]);
+
+        match merge_files_maps(
+            &base,
+            &[(version(1), branch1), (version(2), branch2)],
+            ConflictPolicy::Fail,
+        ) {
+            Err(Error::ConflictingFilesContainerVersions(msg, paths)) => {
+                assert_eq!(paths, vec!["/a.txt".to_string()]);
+                // the paths are part of the error message
+                let err = Error::ConflictingFilesContainerVersions(msg, paths);
+                assert!(err.to_string().ends_with(": /a.txt"));
+                Ok(())
+            }
+            other => bail!("Unexpected merge result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn common_ancestor_of_forked_branches() -> Result<()> {
+        let keypair = Keypair::new_ed25519();
+        let mut register = Register::new_owned(
+            User::Key(keypair.public_key()),
+            xor_name::rand::random(),
+            1_100,
+        );
+
+        let (root, _) = register.write(b"root".to_vec(), BTreeSet::new())?;
+        let (v1, _) = register.write(b"v1".to_vec(), BTreeSet::from([root]))?;
+        let (branch1, _) = register.write(b"branch1".to_vec(), BTreeSet::from([v1]))?;
+        let (branch2a, _) = register.write(b"branch2a".to_vec(), BTreeSet::from([v1]))?;
+        let (branch2b, _) = register.write(b"branch2b".to_vec(), BTreeSet::from([branch2a]))?;
+
+        let heads = BTreeSet::from([branch1, branch2b]);
+        assert_eq!(find_common_ancestor(&register, &heads)?, Some(v1));
+
+        let (unrelated, _) = register.write(b"unrelated".to_vec(), BTreeSet::new())?;
+        let heads = BTreeSet::from([branch1, unrelated]);
+        assert_eq!(find_common_ancestor(&register, &heads)?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn conflict_policy_from_str() -> Result<()> {
+        for policy in [
+            ConflictPolicy::LastModifiedWins,
+            ConflictPolicy::KeepBoth,
+            ConflictPolicy::Fail,
+        ] {
+            assert_eq!(ConflictPolicy::from_str(&policy.to_string())?, policy);
+        }
+        assert!(ConflictPolicy::from_str("whatever").is_err());
+        Ok(())
+    }
+}
@@ -8,34 +8,46 @@
 
 mod file_system;
 mod files_map;
+mod files_map_merge;
 mod metadata;
 mod realpath;
+mod upload_journal;
 
 use crate::{
-    app::consts::*, app::nrs::VersionHash, resolver::Range, ContentType, DataType, Error, Result,
-    Safe, SafeUrl, XorUrl,
+    app::consts::*,
+    app::nrs::VersionHash,
+    register::{Entry, EntryHash},
+    resolver::Range,
+    ContentType, DataType, Error, Result, Safe, SafeUrl, XorUrl,
 };
 use bytes::{Buf, Bytes};
 use file_system::{
     file_system_dir_walk, file_system_single_file, normalise_path_separator, upload_file_to_net,
 };
 use files_map::add_or_update_file_item;
+use files_map_merge::{find_common_ancestor, merge_files_maps};
+use futures::{Stream, StreamExt};
 use log::{debug, info, warn};
 use relative_path::RelativePath;
 use sn_client::Client;
+use sn_interface::messaging::data::RegisterCmd;
 use std::{
-    collections::{BTreeMap, HashSet},
-    iter::FromIterator,
+    collections::{BTreeMap, BTreeSet, HashSet},
     path::{Path, PathBuf},
     str,
+    sync::Arc,
 };
+use tokio::io::{AsyncRead, AsyncWrite};
 use xor_name::XorName;
 
 pub(crate) use files_map::{file_map_for_path, get_file_link_and_metadata};
 pub(crate) use metadata::FileMeta;
 pub(crate) use realpath::RealPath;
+pub(crate) use upload_journal::UploadJournal;
 
 pub use files_map::{FileInfo, FilesMap, FilesMapChange, GetAttr};
+pub use files_map_merge::ConflictPolicy;
+pub use sn_client::FileVerification;
 
 // List of files uploaded with details if they were added, updated or removed from FilesContainer
 pub type ProcessedFiles = BTreeMap<PathBuf, FilesMapChange>;
@@ -117,7 +129,9 @@ impl Safe {
 
             // Write pointer to files_map onto our register
             let reg_address = self.get_register_address(&reg_url)?;
-            let entry = files_map_xorurl.as_bytes().to_vec();
+            let entry = self
+                .register_entry_for_value(files_map_xorurl.as_bytes().to_vec())
+                .await?;
             let client = self.get_safe_client()?;
             let (entry_hash, reg_op) = client
                 .write_to_register(reg_address, entry, Default::default())
@@ -155,11 +169,93 @@ impl Safe {
         self.fetch_files_container(&safe_url).await
     }
 
-    /// Fetch a `FilesContainer` from a `SafeUrl` without performing any type of URL resolution
+    /// # Watch a `FilesContainer` for new versions.
+    ///
+    /// Returns a stream yielding each new version of the `FilesContainer`, along with its
+    /// `FilesMap`, as it's written to the network, for as long as the stream is not dropped.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::Safe;
+    /// # use futures::StreamExt;
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   safe.connect(None, None, None).await.unwrap();
+    ///     let xorurl = safe.files_container_create().await.unwrap();
+    ///     let versions = safe.files_container_watch(&xorurl).await.unwrap();
+    ///     futures::pin_mut!(versions);
+    ///     while let Some(Ok((version, files_map))) = versions.next().await {
+    ///         println!("FilesContainer is now at version {}: {:?}", version, files_map);
+    ///     }
+    /// # });
+    /// ```
+    pub async fn files_container_watch(
+        &self,
+        url: &str,
+    ) -> Result<impl Stream<Item = Result<(VersionHash, FilesMap)>> + '_> {
+        debug!("Watching files container at: {:?}", url);
+        let mut safe_url = self.parse_and_resolve_url(url).await?;
+        safe_url.set_content_version(None);
+        let address = self.get_register_address(&safe_url)?;
+
+        let updates = self
+            .get_safe_client()?
+            .watch_register(address)
+            .await
+            .map_err(|err| {
+                Error::NetDataError(format!(
+                    "Failed to watch FilesContainer at \"{}\": {:?}",
+                    safe_url, err
+                ))
+            })?;
+
+        Ok(updates.filter_map(move |cmd| async move {
+            // only edits write a new version of the FilesContainer, their entry being the
+            // FilesMap of the version, so it's fetched right away
+            let (hash, entry) = match cmd {
+                RegisterCmd::Edit(edit) => {
+                    let crdt_op = edit.op.edit.crdt_op;
+                    (EntryHash(crdt_op.hash()), crdt_op.value)
+                }
+                _ => return None,
+            };
+            let version = VersionHash::from(&hash);
+            debug!("New version of FilesContainer written: {}", version);
+
+            let files_map: Result<FilesMap> = async {
+                // the entry may point to the FilesMap XOR-URL rather than hold it
+                let entry = self.register_resolve_entry(entry).await?;
+                self.fetch_files_map(str::from_utf8(&entry)?).await
+            }
+            .await;
+            Some(files_map.map(|files_map| (version, files_map)))
+        }))
+    }
+
+    /// Fetch a `FilesContainer` from a `SafeUrl` without performing any type of URL resolution.
+    /// If it has concurrent versions, their FilesMaps are merged, without the merge being
+    /// written to the network, and returned along with the latest of those versions.
     pub(crate) async fn fetch_files_container(
         &self,
         safe_url: &SafeUrl,
     ) -> Result<Option<(VersionHash, FilesMap)>> {
+        let current = self.fetch_current_files_container(safe_url).await?;
+        Ok(current.and_then(|(versions, files_map)| {
+            versions
+                .into_iter()
+                .max()
+                .map(|version| (version, files_map))
+        }))
+    }
+
+    // Private helper to fetch all the current versions of a FilesContainer, along with its
+    // FilesMap, which is merged from those of the versions when there are concurrent ones.
+    async fn fetch_current_files_container(
+        &self,
+        safe_url: &SafeUrl,
+    ) -> Result<Option<(HashSet<VersionHash>, FilesMap)>> {
         // fetch register entries and wrap errors
         debug!(
             "Fetching FilesContainer from {}, address type: {:?}",
@@ -185,35 +281,132 @@ impl Safe {
                 err => Error::NetDataError(format!("Failed to get current version: {}", err)),
             })?;
 
-        // take the 1st entry (TODO Multiple entries)
         debug!(
             "Retrieved {} entries for register at {}",
             entries.len(),
             safe_url.to_string()
         );
-        if entries.len() > 1 {
-            return Err(Error::NotImplementedError("Multiple file container entries not managed, this happends when 2 clients write concurrently to a file container".to_string()));
-        }
-        let first_entry = entries.iter().next();
-        let (version, files_map_xorurl) = if let Some((v, m)) = first_entry {
-            (v.into(), str::from_utf8(m)?)
-        } else {
-            warn!("FilesContainer found at \"{:?}\" was empty", safe_url);
-            return Ok(None);
+        let versions: HashSet<VersionHash> = entries.iter().map(|(hash, _)| hash.into()).collect();
+        let files_map = match entries.iter().next() {
+            // This happens when 2 clients write concurrently to a FilesContainer
+            Some(_) if entries.len() > 1 => {
+                self.merge_files_container_versions(safe_url, entries)
+                    .await?
+            }
+            Some((_, entry)) => self.fetch_files_map(str::from_utf8(entry)?).await?,
+            None => {
+                warn!("FilesContainer found at \"{:?}\" was empty", safe_url);
+                return Ok(None);
+            }
+        };
+        debug!("Files map retrieved.... {:?}", &versions);
+
+        Ok(Some((versions, files_map)))
+    }
+
+    // Private helper to merge the FilesMaps of concurrent versions of a FilesContainer.
+    // The closest version all branches were created from is used as the base of a three-way
+    // merge, with conflicts resolved as per the conflict policy set on this instance.
+    // Nothing is written to the network, the merge only becomes a version of the
+    // FilesContainer when the next version is written, see `files_container_merge`.
+    async fn merge_files_container_versions(
+        &self,
+        safe_url: &SafeUrl,
+        entries: BTreeSet<(EntryHash, Entry)>,
+    ) -> Result<FilesMap> {
+        let address = self.get_register_address(safe_url)?;
+        let register = self.get_safe_client()?.get_register(address).await?;
+
+        let heads: BTreeSet<EntryHash> = entries.iter().map(|(hash, _)| *hash).collect();
+        let base = match find_common_ancestor(&register, &heads)? {
+            Some(hash) => {
+                debug!(
+                    "Common ancestor of FilesContainer versions found: {:?}",
+                    hash
+                );
+                let entry = self
+                    .register_resolve_entry(register.get(hash)?.clone())
+                    .await?;
+                self.fetch_files_map(str::from_utf8(&entry)?).await?
+            }
+            None => FilesMap::default(),
         };
 
-        // Using the FilesMap XOR-URL we can now fetch the FilesMap and deserialise it
+        let mut branches = Vec::new();
+        for (hash, entry) in &entries {
+            let files_map = self.fetch_files_map(str::from_utf8(entry)?).await?;
+            branches.push((VersionHash::from(hash), files_map));
+        }
+
+        info!(
+            "Merging {} concurrent versions of FilesContainer at {} with conflict policy '{}'",
+            branches.len(),
+            safe_url,
+            self.files_conflict_policy
+        );
+        merge_files_maps(&base, &branches, self.files_conflict_policy)
+    }
+
+    // Private helper to fetch and deserialise a FilesMap from its XOR-URL
+    async fn fetch_files_map(&self, files_map_xorurl: &str) -> Result<FilesMap> {
         let files_map_url = SafeUrl::from_xorurl(files_map_xorurl)?;
         let serialised_files_map = self.fetch_data(&files_map_url, None).await?;
-        let files_map = serde_json::from_slice(serialised_files_map.chunk()).map_err(|err| {
+        serde_json::from_slice(serialised_files_map.chunk()).map_err(|err| {
             Error::ContentError(format!(
                 "Couldn't deserialise the FilesMap stored in the FilesContainer: {:?}",
                 err
             ))
-        })?;
-        debug!("Files map retrieved.... {:?}", &version);
+        })
+    }
 
-        Ok(Some((version, files_map)))
+    /// # Merge the concurrent versions of a `FilesContainer`.
+    ///
+    /// When several versions were written concurrently, their `FilesMap`s are merged as per the
+    /// conflict policy set on this instance, and written as a new version having all of them as
+    /// parents. Reading a `FilesContainer` merges its concurrent versions too, but doesn't write
+    /// the result, neither does this when there is a single current version.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// # use sn_api::Safe;
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   safe.connect(None, None, None).await.unwrap();
+    ///     let (xorurl, _processed_files, _files_map) = safe.files_container_create_from("./testdata", None, true, true).await.unwrap();
+    ///     let (version, files_map) = safe.files_container_merge(&xorurl).await.unwrap().unwrap();
+    ///     println!("FilesContainer is now at version: {}", version);
+    ///     println!("FilesMap of the merged version is: {:?}", files_map);
+    /// # });
+    /// ```
+    pub async fn files_container_merge(
+        &self,
+        url: &str,
+    ) -> Result<Option<(VersionHash, FilesMap)>> {
+        let mut safe_url = self.parse_and_resolve_url(url).await?;
+
+        // If the FilesContainer URL was resolved from an NRS name we need to remove
+        // the version from it so we can fetch all its current versions
+        safe_url.set_content_version(None);
+
+        let (current_versions, files_map) =
+            match self.fetch_current_files_container(&safe_url).await? {
+                Some(current) => current,
+                None => return Ok(None),
+            };
+        if current_versions.len() == 1 {
+            return Ok(current_versions
+                .into_iter()
+                .next()
+                .map(|version| (version, files_map)));
+        }
+
+        let new_version = self
+            .append_version_to_files_container(current_versions, &files_map, url, safe_url, false)
+            .await?;
+
+        Ok(Some((new_version, files_map)))
     }
 
     /// # Sync up local folder with the content on a `FilesContainer`.
@@ -264,11 +457,10 @@ impl Safe {
         // the version from it so we can fetch latest version of it for sync-ing
         safe_url.set_content_version(None);
 
-        let (current_version, current_files_map) =
-            match self.fetch_files_container(&safe_url).await? {
-                Some((version, files_map)) => (Some(version), files_map),
-                None => (None, FilesMap::default()),
-            };
+        let (current_versions, current_files_map) = self
+            .fetch_current_files_container(&safe_url)
+            .await?
+            .unwrap_or_default();
 
         // Let's generate the list of local files paths, without uploading any new file yet.
         // Use a dry runner only for this next operation
@@ -293,7 +485,7 @@ impl Safe {
 
         self.update_files_container(
             success_count,
-            current_version,
+            current_versions,
             new_files_map,
             processed_files,
             url,
@@ -330,7 +522,7 @@ impl Safe {
         follow_links: bool,
     ) -> Result<(Option<(VersionHash, FilesMap)>, ProcessedFiles)> {
         debug!("Adding file to FilesContainer at {}", url);
-        let (safe_url, current_version, current_files_map) =
+        let (safe_url, current_versions, current_files_map) =
             validate_files_add_params(self, source_file, url, update_nrs).await?;
 
         let dst_path = Path::new(safe_url.path());
@@ -364,7 +556,7 @@ impl Safe {
 
         self.update_files_container(
             success_count,
-            current_version,
+            current_versions,
             new_files_map,
             processed_files,
             url,
@@ -399,7 +591,7 @@ impl Safe {
         force: bool,
         update_nrs: bool,
     ) -> Result<(Option<(VersionHash, FilesMap)>, ProcessedFiles)> {
-        let (safe_url, current_version, current_files_map) =
+        let (safe_url, current_versions, current_files_map) =
             validate_files_add_params(self, "", url, update_nrs).await?;
 
         let new_file_xorurl = self.store_bytes(data, None).await?;
@@ -410,7 +602,7 @@ impl Safe {
 
         self.update_files_container(
             success_count,
-            current_version,
+            current_versions,
             new_files_map,
             processed_files,
             url,
@@ -465,50 +657,54 @@ impl Safe {
         // the version from it so we can fetch latest version of it
         safe_url.set_content_version(None);
 
-        let (current_version, files_map) = match self.fetch_files_container(&safe_url).await? {
-            Some(info) => info,
-            None => {
-                return Err(Error::EmptyContent(format!(
-                    "FilesContainer found at \"{}\" was empty",
-                    safe_url
-                )))
-            }
-        };
+        let (current_versions, files_map) =
+            match self.fetch_current_files_container(&safe_url).await? {
+                Some(current) => current,
+                None => {
+                    return Err(Error::EmptyContent(format!(
+                        "FilesContainer found at \"{}\" was empty",
+                        safe_url
+                    )))
+                }
+            };
 
         let (processed_files, new_files_map, success_count) =
             files_map_remove_path(Path::new(dst_path), files_map, recursive)?;
 
-        let version = if success_count == 0 {
-            current_version
-        } else {
-            self.append_version_to_files_container(
-                HashSet::from_iter([current_version]),
-                &new_files_map,
+        let (updated, processed_files) = self
+            .update_files_container(
+                success_count,
+                current_versions,
+                new_files_map,
+                processed_files,
                 url,
                 safe_url,
                 update_nrs,
             )
-            .await?
-        };
+            .await?;
+        // the container was not empty, so it cannot be now
+        let (version, new_files_map) =
+            updated.ok_or_else(|| Error::EmptyContent(url.to_string()))?;
 
         Ok((version, processed_files, new_files_map))
     }
 
     // Private helper to append new FilesMap entry to container, and/or return
-    // information regarding the update and new version if so
+    // information regarding the update and new version if so.
+    // Concurrent current versions are collapsed into the new version, even without changes.
     #[allow(clippy::too_many_arguments)]
     async fn update_files_container(
         &self,
         files_map_changes_count: u64,
-        current_version: Option<VersionHash>,
+        current_versions: HashSet<VersionHash>,
         new_files_map: FilesMap,
         processed_files: ProcessedFiles,
         url: &str,
         safe_url: SafeUrl,
         update_nrs: bool,
     ) -> Result<(Option<(VersionHash, FilesMap)>, ProcessedFiles)> {
-        if files_map_changes_count == 0 {
-            if let Some(version) = current_version {
+        if files_map_changes_count == 0 && current_versions.len() <= 1 {
+            if let Some(version) = current_versions.into_iter().next() {
                 // We had a FilesMap but there were no changes to it, so let's
                 // return the existing version and files map, along with
                 // details about the processed files.
@@ -520,16 +716,11 @@ impl Safe {
                 Ok((None, processed_files))
             }
         } else {
-            // There were changes to current FilesMap, so append new version to the container
-            let parent_versions = if let Some(version) = current_version {
-                HashSet::from_iter([version])
-            } else {
-                HashSet::new()
-            };
-
+            // There were changes to current FilesMap, or concurrent versions of it were
+            // merged, so append new version to the container
             let new_version = self
                 .append_version_to_files_container(
-                    parent_versions,
+                    current_versions,
                     &new_files_map,
                     url,
                     safe_url,
@@ -600,19 +791,7 @@ impl Safe {
     /// # });
     /// ```
     pub async fn store_bytes(&self, bytes: Bytes, media_type: Option<&str>) -> Result<XorUrl> {
-        let content_type = media_type.map_or_else(
-            || Ok(ContentType::Raw),
-            |media_type_str| {
-                if SafeUrl::is_media_type_supported(media_type_str) {
-                    Ok(ContentType::MediaType(media_type_str.to_string()))
-                } else {
-                    Err(Error::InvalidMediaType(format!(
-                        "Media-type '{}' not supported. You can pass 'None' as the 'media_type' for this content to be treated as raw",
-                        media_type_str
-                    )))
-                }
-            },
-        )?;
+        let content_type = content_type_for(media_type)?;
 
         let address = if self.dry_run_mode {
             debug!(
@@ -631,6 +810,65 @@ impl Safe {
         Ok(xorurl)
     }
 
+    /// # Keep a journal of the files uploaded
+    ///
+    /// Keep a local journal, at the given location, of the chunks the network acknowledged to
+    /// have stored for each of the files being uploaded, e.g. when creating or syncing a `FilesContainer`.
+    /// If `resume` is true, the chunks already recorded in an existing journal are not uploaded again,
+    /// thus an upload which was interrupted continues from where it stopped.
+    /// Otherwise any existing journal is discarded.
+    pub fn enable_upload_journal(&mut self, path: &Path, resume: bool) -> Result<()> {
+        let journal = UploadJournal::open(path, resume).map_err(|err| {
+            Error::FileSystemError(format!(
+                "Failed to open upload journal at {}: {}",
+                path.display(),
+                err
+            ))
+        })?;
+        self.upload_journal = Some(Arc::new(journal));
+        Ok(())
+    }
+
+    /// # Store a file from a stream
+    ///
+    /// Store `size` bytes read from an `AsyncRead` source onto the network, as `store_bytes` does,
+    /// but reading, encrypting and uploading the content a few chunks at a time,
+    /// so the whole content is never held in memory.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// # use sn_api::Safe;
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   safe.connect(None, None, None).await.unwrap();
+    ///     let data = b"Something super good";
+    ///     let xorurl = safe.store_stream(&data[..], data.len(), Some("text/plain")).await.unwrap();
+    ///     let received_data = safe.files_get(&xorurl, None).await.unwrap();
+    ///     assert_eq!(received_data, data.as_ref());
+    /// # });
+    /// ```
+    pub async fn store_stream<R: AsyncRead + Unpin>(
+        &self,
+        reader: R,
+        size: usize,
+        media_type: Option<&str>,
+    ) -> Result<XorUrl> {
+        let content_type = content_type_for(media_type)?;
+
+        let address = if self.dry_run_mode {
+            debug!("Calculating network address for {} bytes of data", size);
+            Client::calculate_address_from_stream(reader, size).await?
+        } else {
+            debug!("Streaming {} bytes of data", size);
+            let client = self.get_safe_client()?;
+            client.upload_stream_and_verify(reader, size).await?
+        };
+        let xorurl = SafeUrl::from_bytes(address, content_type)?.encode(self.xorurl_base);
+
+        Ok(xorurl)
+    }
+
     /// # Get a file
     /// Get file from the network.
     ///
@@ -653,6 +891,101 @@ impl Safe {
         self.fetch_data(&safe_url, range).await
     }
 
+    /// # Get a file into a stream
+    /// Get file from the network, writing its content to an `AsyncWrite` destination
+    /// a few chunks at a time, so the whole content is never held in memory.
+    /// Returns the number of bytes written.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// # use sn_api::Safe;
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   safe.connect(None, None, None).await.unwrap();
+    ///     let data = b"Something super good";
+    ///     let xorurl = safe.store_bytes(bytes::Bytes::from_static(data), None).await.unwrap();
+    ///     let mut received_data = vec![];
+    ///     let _ = safe.files_get_stream(&xorurl, &mut received_data).await.unwrap();
+    ///     assert_eq!(received_data, data);
+    /// # });
+    /// ```
+    pub async fn files_get_stream<W: AsyncWrite + Unpin>(
+        &self,
+        url: &str,
+        writer: W,
+    ) -> Result<u64> {
+        let safe_url = self.parse_and_resolve_url(url).await?;
+        match safe_url.data_type() {
+            DataType::File => {
+                let address = safe_url.xorname();
+                debug!("Attempting to stream data from {:?}", address);
+                let client = self.get_safe_client()?;
+                client
+                    .read_stream(address, writer)
+                    .await
+                    .map_err(|e| Error::NetDataError(format!("Failed to GET file: {:?}", e)))
+            }
+            other => Err(Error::ContentError(format!("{}", other))),
+        }
+    }
+
+    /// # Verify a file
+    /// Check that each chunk of a file can be retrieved from each of the nodes expected
+    /// to hold a copy of it, reporting the copies which could not be retrieved.
+    ///
+    /// ## Example
+    /// ```no_run
+    /// # use sn_api::Safe;
+    /// # let mut safe = Safe::default();
+    /// # let rt = tokio::runtime::Runtime::new().unwrap();
+    /// # rt.block_on(async {
+    /// #   safe.connect(None, None, None).await.unwrap();
+    ///     let data = b"Something super good";
+    ///     let xorurl = safe.store_bytes(bytes::Bytes::from_static(data), None).await.unwrap();
+    ///     let verification = safe.files_verify(&xorurl).await.unwrap();
+    ///     assert!(verification.is_complete());
+    /// # });
+    /// ```
+    pub async fn files_verify(&self, url: &str) -> Result<FileVerification> {
+        let safe_url = self.parse_and_resolve_url(url).await?;
+        match safe_url.data_type() {
+            DataType::File => {
+                let address = safe_url.xorname();
+                debug!("Attempting to verify file at {:?}", address);
+                let client = self.get_safe_client()?;
+                client
+                    .verify_file(address)
+                    .await
+                    .map_err(|e| Error::NetDataError(format!("Failed to verify file: {:?}", e)))
+            }
+            other => Err(Error::ContentError(format!("{}", other))),
+        }
+    }
+
+    /// # Repair a file
+    /// Store again the chunks of a file found to be missing by [`Safe::files_verify`], from the
+    /// local copy of the file at `location`. Returns the names of the chunks stored, or which
+    /// would be stored when in dry-run mode.
+    pub async fn files_repair(
+        &self,
+        location: &Path,
+        verification: &FileVerification,
+    ) -> Result<Vec<XorName>> {
+        if self.dry_run_mode {
+            return Ok(verification.missing.keys().copied().collect());
+        }
+
+        let bytes = tokio::fs::read(location).await.map_err(|err| {
+            Error::InvalidInput(format!("Failed to read file from local location: {}", err))
+        })?;
+        let client = self.get_safe_client()?;
+        client
+            .repair_file(Bytes::from(bytes), verification)
+            .await
+            .map_err(|e| Error::NetDataError(format!("Failed to repair file: {:?}", e)))
+    }
+
     /// Fetch a file from a `SafeUrl` without performing any type of URL resolution
     pub(crate) async fn fetch_data(&self, safe_url: &SafeUrl, range: Range) -> Result<Bytes> {
         match safe_url.data_type() {
@@ -712,7 +1045,7 @@ async fn validate_files_add_params(
     source_file: &str,
     url: &str,
     update_nrs: bool,
-) -> Result<(SafeUrl, Option<VersionHash>, FilesMap)> {
+) -> Result<(SafeUrl, HashSet<VersionHash>, FilesMap)> {
     let safe_url = SafeUrl::from_url(url)?;
 
     // If NRS name shall be updated then the URL has to be an NRS-URL
@@ -747,12 +1080,12 @@ async fn validate_files_add_params(
         }
     }
 
-    let (current_version, current_files_map) = match safe.fetch_files_container(&safe_url).await? {
-        Some((version, files_map)) => (Some(version), files_map),
-        None => (None, FilesMap::default()),
-    };
+    let (current_versions, current_files_map) = safe
+        .fetch_current_files_container(&safe_url)
+        .await?
+        .unwrap_or_default();
 
-    Ok((safe_url, current_version, current_files_map))
+    Ok((safe_url, current_versions, current_files_map))
 }
 
 // From the location path and the destination path chosen by the user, calculate
@@ -962,6 +1295,23 @@ async fn files_map_sync(
     Ok((processed_files, updated_files_map, success_count))
 }
 
+// Get the ContentType corresponding to the given media-type, if it's supported
+fn content_type_for(media_type: Option<&str>) -> Result<ContentType> {
+    media_type.map_or_else(
+        || Ok(ContentType::Raw),
+        |media_type_str| {
+            if SafeUrl::is_media_type_supported(media_type_str) {
+                Ok(ContentType::MediaType(media_type_str.to_string()))
+            } else {
+                Err(Error::InvalidMediaType(format!(
+                    "Media-type '{}' not supported. You can pass 'None' as the 'media_type' for this content to be treated as raw",
+                    media_type_str
+                )))
+            }
+        },
+    )
+}
+
 async fn is_file_item_modified(safe: &Safe, local_filename: &Path, file_item: &FileInfo) -> bool {
     if FileMeta::filetype_is_file(&file_item[PREDICATE_TYPE]) {
         // Use a dry runner only for this next operation
@@ -1211,6 +1561,7 @@ mod tests {
     use anyhow::{anyhow, bail, Result};
     use assert_matches::assert_matches;
     use rand::{distributions::Alphanumeric, thread_rng, Rng};
+    use std::time::Duration;
 
     const TEST_DATA_FOLDER: &str = "./testdata/";
     const TEST_DATA_FOLDER_NO_SLASH: &str = "./testdata";
@@ -1326,6 +1677,27 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_files_verify_and_repair() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let location = Path::new("./testdata/test.md");
+        let file_xorurl = safe
+            .store_bytes(Bytes::from(std::fs::read(location)?), None)
+            .await?;
+
+        let verification = retry_loop!(safe.files_verify(&file_xorurl));
+        assert!(verification.is_complete(), "{:?}", verification.missing);
+        assert_eq!(verification.chunks.len(), 1);
+
+        // the chunks missing are stored again from the local copy of the file
+        let mut incomplete = verification.clone();
+        let _ = incomplete.missing.insert(verification.address, vec![0]);
+        let repaired = safe.files_repair(location, &incomplete).await?;
+        assert_eq!(repaired, vec![verification.address]);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_files_container_create_from_file() -> Result<()> {
         let safe = new_safe_instance().await?;
@@ -2168,6 +2540,34 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_files_container_watch() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let (xorurl, _, _) = new_files_container_from_testdata(&safe).await?;
+
+        let versions = safe.files_container_watch(&xorurl).await?;
+        futures::pin_mut!(versions);
+
+        let (version1_content, _) = retry_loop!(safe.files_container_sync(
+            "./testdata/subfolder/",
+            &xorurl,
+            true,
+            false,
+            true, // this sets the delete flag,
+            false,
+        ));
+        let (version1, files_map1) =
+            version1_content.ok_or_else(|| anyhow!("files container was unexpectedly empty"))?;
+
+        let (version, files_map) = tokio::time::timeout(Duration::from_secs(30), versions.next())
+            .await?
+            .ok_or_else(|| anyhow!("the watch stream ended unexpectedly"))??;
+        assert_eq!(version, version1);
+        assert_eq!(files_map, files_map1);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_files_container_get_with_version() -> Result<()> {
         let safe = new_safe_instance().await?;
@@ -0,0 +1,254 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::XorUrl;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    path::Path,
+    sync::Mutex,
+    time::UNIX_EPOCH,
+};
+use xor_name::XorName;
+
+// Each line of the journal file is one of these records, serialised as JSON.
+// Records are only ever appended, so a record being written when the upload was
+// interrupted is the only one which can be found incomplete.
+#[derive(Serialize, Deserialize)]
+enum JournalRecord {
+    // The network acknowledged a chunk of the file was stored.
+    ChunkStored { file: String, chunk: XorName },
+    // All the chunks of the file were stored and verified.
+    FileStored { file: String, xorurl: XorUrl },
+}
+
+// Upload progress of a single file.
+#[derive(Default)]
+struct FileProgress {
+    stored_chunks: BTreeSet<XorName>,
+    xorurl: Option<XorUrl>,
+}
+
+/// Local journal of the chunks the network acknowledged to have stored for each
+/// file being uploaded, so an interrupted upload can be resumed by skipping them.
+///
+/// Files are keyed by their path, size and modification time, thus a file
+/// modified after an interrupted upload is uploaded again from scratch.
+pub(crate) struct UploadJournal {
+    file: Mutex<File>,
+    progress: Mutex<BTreeMap<String, FileProgress>>,
+}
+
+impl UploadJournal {
+    /// Opens the journal at the given location, creating it if it doesn't exist.
+    /// If `resume` is false any existing record is discarded.
+    pub(crate) fn open(path: &Path, resume: bool) -> io::Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut progress = BTreeMap::<String, FileProgress>::new();
+        if resume && path.exists() {
+            for line in BufReader::new(File::open(path)?).lines() {
+                match serde_json::from_str(&line?) {
+                    Ok(JournalRecord::ChunkStored { file, chunk }) => {
+                        let _ = progress
+                            .entry(file)
+                            .or_default()
+                            .stored_chunks
+                            .insert(chunk);
+                    }
+                    Ok(JournalRecord::FileStored { file, xorurl }) => {
+                        progress.entry(file).or_default().xorurl = Some(xorurl);
+                    }
+                    Err(err) => warn!("Ignoring invalid upload journal record: {}", err),
+                }
+            }
+            debug!(
+                "Upload journal loaded from {} with records for {} files",
+                path.display(),
+                progress.len()
+            );
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(path)?;
+        if !resume {
+            file.set_len(0)?;
+        } else if file.metadata()?.len() > 0 {
+            // make sure a record left incomplete is not merged with the next one
+            let mut last_byte = [0];
+            let _ = file.seek(SeekFrom::End(-1))?;
+            file.read_exact(&mut last_byte)?;
+            if last_byte[0] != b'\n' {
+                file.write_all(b"\n")?;
+            }
+        }
+
+        Ok(Self {
+            file: Mutex::new(file),
+            progress: Mutex::new(progress),
+        })
+    }
+
+    /// Returns the key identifying the current content of a local file in the journal.
+    pub(crate) fn file_key(path: &Path) -> io::Result<String> {
+        let metadata = fs::metadata(path)?;
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |duration| duration.as_nanos());
+
+        Ok(format!(
+            "{}:{}:{}",
+            fs::canonicalize(path)?.display(),
+            metadata.len(),
+            modified
+        ))
+    }
+
+    /// Returns the names of the chunks of a file known to have been stored on the network.
+    pub(crate) fn stored_chunks(&self, file: &str) -> BTreeSet<XorName> {
+        self.lock_progress()
+            .get(file)
+            .map(|progress| progress.stored_chunks.clone())
+            .unwrap_or_default()
+    }
+
+    /// Returns the XOR-URL of a file if it was completely uploaded.
+    pub(crate) fn stored_file(&self, file: &str) -> Option<XorUrl> {
+        self.lock_progress()
+            .get(file)
+            .and_then(|progress| progress.xorurl.clone())
+    }
+
+    /// Records a chunk of a file was stored on the network.
+    pub(crate) fn chunk_stored(&self, file: &str, chunk: XorName) -> io::Result<()> {
+        self.append(&JournalRecord::ChunkStored {
+            file: file.to_string(),
+            chunk,
+        })?;
+        let _ = self
+            .lock_progress()
+            .entry(file.to_string())
+            .or_default()
+            .stored_chunks
+            .insert(chunk);
+
+        Ok(())
+    }
+
+    /// Records a file was completely uploaded to the given XOR-URL.
+    pub(crate) fn file_stored(&self, file: &str, xorurl: &str) -> io::Result<()> {
+        self.append(&JournalRecord::FileStored {
+            file: file.to_string(),
+            xorurl: xorurl.to_string(),
+        })?;
+        self.lock_progress()
+            .entry(file.to_string())
+            .or_default()
+            .xorurl = Some(xorurl.to_string());
+
+        Ok(())
+    }
+
+    fn append(&self, record: &JournalRecord) -> io::Result<()> {
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+
+        // a failed write could only leave an incomplete record, which is ignored when loading it
+        let mut file = self
+            .file
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        file.write_all(&line)?;
+        file.flush()
+    }
+
+    fn lock_progress(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, FileProgress>> {
+        // the map is always left in a consistent state, so it's fine to use it even if poisoned
+        self.progress
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UploadJournal;
+    use anyhow::Result;
+    use assert_fs::prelude::*;
+    use std::{collections::BTreeSet, fs, io::Write};
+    use xor_name::XorName;
+
+    #[test]
+    fn upload_journal_is_resumed_from_disk() -> Result<()> {
+        let tmp_dir = assert_fs::TempDir::new()?;
+        let journal_file = tmp_dir.child("journal");
+        let journal_path = journal_file.path();
+        let chunk_a = XorName::random(&mut rand::thread_rng());
+        let chunk_b = XorName::random(&mut rand::thread_rng());
+
+        let journal = UploadJournal::open(journal_path, false)?;
+        journal.chunk_stored("file_a", chunk_a)?;
+        journal.chunk_stored("file_a", chunk_b)?;
+        journal.file_stored("file_b", "safe://file_b")?;
+        drop(journal);
+
+        // simulate an interrupted write of the last record
+        let mut file = fs::OpenOptions::new().append(true).open(journal_path)?;
+        file.write_all(b"{\"ChunkStored\":{\"fi")?;
+        drop(file);
+
+        let journal = UploadJournal::open(journal_path, true)?;
+        journal.chunk_stored("file_c", chunk_a)?;
+        drop(journal);
+
+        let journal = UploadJournal::open(journal_path, true)?;
+        assert_eq!(
+            journal.stored_chunks("file_a"),
+            BTreeSet::from([chunk_a, chunk_b])
+        );
+        assert_eq!(journal.stored_chunks("file_c"), BTreeSet::from([chunk_a]));
+        assert_eq!(journal.stored_file("file_a"), None);
+        assert_eq!(
+            journal.stored_file("file_b"),
+            Some("safe://file_b".to_string())
+        );
+        assert!(journal.stored_chunks("file_d").is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn upload_journal_is_discarded_if_not_resuming() -> Result<()> {
+        let tmp_dir = assert_fs::TempDir::new()?;
+        let journal_file = tmp_dir.child("journal");
+        let journal_path = journal_file.path();
+
+        let journal = UploadJournal::open(journal_path, false)?;
+        journal.chunk_stored("file_a", XorName::random(&mut rand::thread_rng()))?;
+        drop(journal);
+
+        let journal = UploadJournal::open(journal_path, false)?;
+        assert!(journal.stored_chunks("file_a").is_empty());
+        drop(journal);
+
+        let journal = UploadJournal::open(journal_path, true)?;
+        assert!(journal.stored_chunks("file_a").is_empty());
+
+        Ok(())
+    }
+}
@@ -34,11 +34,13 @@ pub mod test_helpers;
 
 use super::{common, constants, Error, Result};
 
+use files::{ConflictPolicy, UploadJournal};
 use sn_client::Client;
 use sn_dbc::Owner;
 use sn_interface::types::Keypair;
+use wallet::CoinSelection;
 
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 use tracing::debug;
 
 const APP_NOT_CONNECTED: &str = "Application is not connected to the network";
@@ -48,6 +50,9 @@ pub struct Safe {
     client: Option<Client>,
     pub xorurl_base: XorUrlBase,
     pub dry_run_mode: bool,
+    pub files_conflict_policy: ConflictPolicy,
+    pub wallet_coin_selection: CoinSelection,
+    upload_journal: Option<Arc<UploadJournal>>,
 }
 
 impl Safe {
@@ -57,6 +62,9 @@ impl Safe {
             client: None,
             xorurl_base: xorurl_base.unwrap_or(DEFAULT_XORURL_BASE),
             dry_run_mode: true,
+            files_conflict_policy: ConflictPolicy::default(),
+            wallet_coin_selection: CoinSelection::default(),
+            upload_journal: None,
         }
     }
 
@@ -71,6 +79,9 @@ impl Safe {
             client: None,
             xorurl_base: xorurl_base.unwrap_or(DEFAULT_XORURL_BASE),
             dry_run_mode: false,
+            files_conflict_policy: ConflictPolicy::default(),
+            wallet_coin_selection: CoinSelection::default(),
+            upload_journal: None,
         };
 
         safe.connect(keypair, timeout, dbc_owner).await?;
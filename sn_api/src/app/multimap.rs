@@ -100,6 +100,7 @@ impl Safe {
             return Ok(EntryHash(rand::thread_rng().gen::<[u8; 32]>()));
         }
 
+        let data = self.register_entry_for_value(data).await?;
         let client = self.get_safe_client()?;
 
         let (entry_hash, op_batch) = client.write_to_register(address, data, replace).await?;
@@ -229,8 +230,11 @@ impl Safe {
 
 #[cfg(test)]
 mod tests {
-    use crate::{app::test_helpers::new_safe_instance, retry_loop_for_pattern};
+    use crate::{
+        app::test_helpers::new_safe_instance, register::MAX_REG_ENTRY_SIZE, retry_loop_for_pattern,
+    };
     use anyhow::Result;
+    use sn_interface::types::utils::random_bytes;
     use std::collections::BTreeSet;
 
     #[tokio::test]
@@ -289,6 +293,25 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_multimap_insert_large_value() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let key = b"key".to_vec();
+        let val = random_bytes(4 * MAX_REG_ENTRY_SIZE).to_vec();
+        let key_val = (key.clone(), val);
+
+        let xorurl = safe.multimap_create(None, 25_000).await?;
+        let hash = safe
+            .multimap_insert(&xorurl, key_val.clone(), BTreeSet::new())
+            .await?;
+
+        let received_data = retry_loop_for_pattern!(safe.multimap_get_by_key(&xorurl, &key), Ok(v) if !v.is_empty())?;
+        assert_eq!(received_data, BTreeSet::from([(hash, key_val.clone())]));
+        assert_eq!(safe.multimap_get_by_hash(&xorurl, hash).await?, key_val);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_multimap_get_by_hash() -> Result<()> {
         let safe = new_safe_instance().await?;
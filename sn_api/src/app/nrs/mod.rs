@@ -12,7 +12,11 @@ pub use crate::app::multimap::Multimap;
 pub use crate::safeurl::{ContentType, DataType, VersionHash};
 pub use nrs_map::NrsMap;
 
-use crate::{app::Safe, register::EntryHash, Error, Result, SafeUrl};
+use crate::{
+    app::Safe,
+    register::{EntryHash, HistoryEntry, Permissions, Policy, User},
+    Error, Result, SafeUrl,
+};
 
 use log::{debug, info};
 use std::collections::{BTreeMap, BTreeSet};
@@ -33,6 +37,21 @@ impl Safe {
     /// Note that this NRS SafeUrl is not linked to anything yet. You just registered the topname here.
     /// You can now associate public_names (with that topname) to links using `nrs_associate` or `nrs_add`
     pub async fn nrs_create(&self, top_name: &str) -> Result<SafeUrl> {
+        self.nrs_create_with_writers(top_name, &BTreeSet::new())
+            .await
+    }
+
+    /// # Creates a `nrs_map_container` for a chosen top name which several users can write to
+    ///
+    /// Registers the given NRS top name on the network as `nrs_create` does, but also granting
+    /// the given users write access to it, so they can associate public names to links with it,
+    /// e.g. to let several CI keys publish subnames. Only the owner can change who can write to it,
+    /// see `nrs_set_permissions`.
+    pub async fn nrs_create_with_writers(
+        &self,
+        top_name: &str,
+        writers: &BTreeSet<User>,
+    ) -> Result<SafeUrl> {
         info!("Creating an NRS map for: {}", top_name);
 
         let mut nrs_url = validate_nrs_top_name(top_name)?;
@@ -44,7 +63,12 @@ impl Safe {
         }
 
         let _ = self
-            .multimap_create(Some(nrs_xorname), NRS_MAP_TYPE_TAG)
+            .register_create_with_writers(
+                Some(nrs_xorname),
+                NRS_MAP_TYPE_TAG,
+                ContentType::Multimap,
+                writers,
+            )
             .await?;
 
         Ok(nrs_url)
@@ -189,6 +213,111 @@ impl Safe {
         Ok((url, nrs_map))
     }
 
+    /// Get the history of the Nrs Map Container of the given top name, i.e. its versions along with
+    /// the ones each of them was created from, in topological order (from the oldest to the newest).
+    /// The history goes down `depth` levels below the latest versions, or down to the very first
+    /// version if no `depth` is provided.
+    pub async fn nrs_history(
+        &self,
+        top_name: &str,
+        depth: Option<usize>,
+    ) -> Result<Vec<HistoryEntry>> {
+        info!("Getting history of NRS top name: {}", top_name);
+        let url = validate_nrs_top_name(top_name)?;
+        self.register_fetch_history(&url, depth).await
+    }
+
+    /// Transfer the ownership of a registered top name to a new owner, e.g. another team member's
+    /// public key, keeping the users allowed to write to it.
+    /// Only the current owner of the top name is allowed to do this.
+    pub async fn nrs_transfer(&self, top_name: &str, new_owner: User) -> Result<()> {
+        info!(
+            "Transferring ownership of NRS top name {} to {:?}",
+            top_name, new_owner
+        );
+        let url = validate_nrs_top_name(top_name)?;
+        self.register_update_owner(&url, new_owner).await
+    }
+
+    /// Set the permissions of a user on a registered top name, e.g. to grant or revoke
+    /// the write access needed to associate public names to links with it.
+    /// Only the owner of the top name is allowed to do this.
+    pub async fn nrs_set_permissions(
+        &self,
+        top_name: &str,
+        user: User,
+        permissions: Option<Permissions>,
+    ) -> Result<()> {
+        info!(
+            "Setting permissions of {:?} on NRS top name {}",
+            user, top_name
+        );
+        let url = validate_nrs_top_name(top_name)?;
+        self.register_update_permissions(&url, user, permissions)
+            .await
+    }
+
+    /// Get the policy of a registered top name, i.e. its owner and the permissions of its users
+    pub async fn nrs_policy(&self, top_name: &str) -> Result<Policy> {
+        let url = validate_nrs_top_name(top_name)?;
+        self.register_fetch_policy(&url).await
+    }
+
+    /// Get all the entries of the Nrs Map Container of the given top name, i.e. each public name
+    /// along with the version of its entry and the link it's associated to.
+    /// A public name with conflicting entries, written concurrently, is listed once per entry.
+    pub async fn nrs_list(
+        &self,
+        top_name: &str,
+    ) -> Result<BTreeSet<(String, VersionHash, SafeUrl)>> {
+        info!("Listing entries of NRS top name: {}", top_name);
+        let url = validate_nrs_top_name(top_name)?;
+        let multimap = match self.fetch_multimap(&url).await {
+            Ok(multimap) => multimap,
+            Err(Error::EmptyContent(_)) => Multimap::new(),
+            Err(err) => return Err(err),
+        };
+
+        multimap
+            .into_iter()
+            .map(|(hash, (public_name, link))| {
+                let public_name = str::from_utf8(&public_name)?.to_owned();
+                let link = SafeUrl::from_url(str::from_utf8(&link)?)?;
+                Ok((public_name, VersionHash::from(&hash), link))
+            })
+            .collect()
+    }
+
+    /// Find the public names, within the Nrs Map Containers of the given top names, which are
+    /// associated to a link pointing to the same content as the given URL, regardless of the
+    /// version and path of the link, e.g. all the public names linking to any version of a
+    /// FilesContainer. As the top names registered by a user cannot be enumerated from the
+    /// network, the ones to look into need to be provided.
+    /// Returns each public name found along with the link it's associated to.
+    pub async fn nrs_find_links_to(
+        &self,
+        url: &str,
+        top_names: &[String],
+    ) -> Result<Vec<(String, SafeUrl)>> {
+        info!("Looking for public names linking to: {}", url);
+        let target = SafeUrl::from_url(url)?;
+        let mut found = Vec::new();
+        for top_name in top_names {
+            let nrs_map = match self.nrs_get_subnames_map(top_name, None).await {
+                Ok(map) | Err(Error::ConflictingNrsEntries(_, _, map)) => map,
+                Err(err) => return Err(err),
+            };
+            found.extend(
+                nrs_map
+                    .map
+                    .into_iter()
+                    .filter(|(_, link)| link.address() == target.address()),
+            );
+        }
+
+        Ok(found)
+    }
+
     /// Get the mapping of all subNames and their associated `SafeUrl` for the Nrs Map Container at the given public name
     pub async fn nrs_get_subnames_map(
         &self,
@@ -442,6 +571,118 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_nrs_create_with_writers_and_transfer() -> Result<()> {
+        let site_name = random_nrs_name();
+        let safe = new_safe_instance().await?;
+        let writer_safe = new_safe_instance().await?;
+        let owner = User::Key(safe.get_safe_client()?.public_key());
+        let writer = User::Key(writer_safe.get_safe_client()?.public_key());
+
+        let files_container = TestDataFilesContainer::get_container(["/testdata/test.md"]).await?;
+
+        let _ = safe
+            .nrs_create_with_writers(&site_name, &BTreeSet::from([writer]))
+            .await?;
+        let policy = safe.nrs_policy(&site_name).await?;
+        assert_eq!(*policy.owner(), owner);
+        assert_eq!(policy.permissions(writer), Some(Permissions::new(true)));
+
+        // the writer can publish subnames but cannot change who can write to it
+        let public_name = format!("ci.{site_name}");
+        let _ = writer_safe
+            .nrs_associate(&public_name, &files_container["/testdata/test.md"])
+            .await?;
+        assert!(matches!(
+            writer_safe
+                .nrs_set_permissions(&site_name, writer, None)
+                .await,
+            Err(Error::AccessDenied(_))
+        ));
+
+        // once transferred, the new owner is the only one who can change it
+        safe.nrs_transfer(&site_name, writer).await?;
+        assert_eq!(*safe.nrs_policy(&site_name).await?.owner(), writer);
+        assert!(matches!(
+            safe.nrs_transfer(&site_name, owner).await,
+            Err(Error::AccessDenied(_))
+        ));
+        writer_safe
+            .nrs_set_permissions(&site_name, owner, Some(Permissions::new(false)))
+            .await?;
+        assert_eq!(
+            writer_safe.nrs_policy(&site_name).await?.permissions(owner),
+            Some(Permissions::new(false))
+        );
+
+        let (link, _) = safe.nrs_get(&public_name, None).await?;
+        assert_eq!(link, Some(files_container["/testdata/test.md"].clone()));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_nrs_list_and_find_links_to() -> Result<()> {
+        let site_name = random_nrs_name();
+        let safe = new_safe_instance().await?;
+
+        let files_container =
+            TestDataFilesContainer::get_container(["/testdata/test.md", "/testdata/another.md"])
+                .await?;
+        let other_container = TestDataFilesContainer::get_container([]).await?;
+
+        safe.nrs_create(&site_name).await?;
+        let test_url = safe
+            .nrs_associate(
+                &format!("test.{site_name}"),
+                &files_container["/testdata/test.md"],
+            )
+            .await?;
+        let another_url = safe
+            .nrs_associate(
+                &format!("another.{site_name}"),
+                &files_container["/testdata/another.md"],
+            )
+            .await?;
+        let _ = safe.nrs_associate(&site_name, &other_container.url).await?;
+
+        let entries = safe.nrs_list(&site_name).await?;
+        assert_eq!(entries.len(), 3);
+        assert!(entries.contains(&(
+            format!("test.{site_name}"),
+            test_url
+                .content_version()
+                .ok_or_else(|| anyhow!("NRS url should be versioned"))?,
+            files_container["/testdata/test.md"].clone()
+        )));
+        assert!(entries.contains(&(
+            format!("another.{site_name}"),
+            another_url
+                .content_version()
+                .ok_or_else(|| anyhow!("NRS url should be versioned"))?,
+            files_container["/testdata/another.md"].clone()
+        )));
+
+        let mut unversioned_container = files_container.url.clone();
+        unversioned_container.set_content_version(None);
+        let mut found = safe
+            .nrs_find_links_to(
+                &unversioned_container.to_string(),
+                std::slice::from_ref(&site_name),
+            )
+            .await?;
+        found.sort();
+        assert_eq!(
+            found
+                .iter()
+                .map(|(name, _)| name.clone())
+                .collect::<Vec<_>>(),
+            vec![format!("another.{site_name}"), format!("test.{site_name}")]
+        );
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_nrs_associate_with_subname() -> Result<()> {
         let site_name = random_nrs_name();
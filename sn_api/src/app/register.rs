@@ -6,7 +6,9 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-pub use sn_interface::types::register::{Entry, EntryHash};
+pub use sn_interface::types::register::{
+    Action, Entry, EntryHash, HistoryEntry, Permissions, Policy, User, MAX_REG_ENTRY_SIZE,
+};
 
 use crate::safeurl::{ContentType, SafeUrl, XorUrl};
 use crate::{Error, Result, Safe};
@@ -14,14 +16,14 @@ use crate::{Error, Result, Safe};
 use sn_client::Error as ClientError;
 use sn_interface::{
     messaging::data::Error as ErrorMsg,
-    types::{
-        register::{Permissions, Policy, User},
-        DataAddress, Error as SafeNdError, RegisterAddress,
-    },
+    types::{DataAddress, Error as SafeNdError, RegisterAddress},
 };
 
+use bytes::Bytes;
+use futures::future::try_join_all;
 use log::debug;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet};
 use tracing::info;
 use xor_name::XorName;
@@ -34,6 +36,19 @@ impl Safe {
         name: Option<XorName>,
         tag: u64,
         content_type: ContentType,
+    ) -> Result<XorUrl> {
+        self.register_create_with_writers(name, tag, content_type, &BTreeSet::new())
+            .await
+    }
+
+    /// Create a Register on the network, granting write access to the given users
+    /// besides its owner
+    pub(crate) async fn register_create_with_writers(
+        &self,
+        name: Option<XorName>,
+        tag: u64,
+        content_type: ContentType,
+        writers: &BTreeSet<User>,
     ) -> Result<XorUrl> {
         debug!(
             "Storing Register data with tag type: {}, xorname: {:?}, dry_run: {}",
@@ -56,7 +71,7 @@ impl Safe {
 
         // Store the Register on the network
         let (_, op_batch) = client
-            .create_register(xorname, tag, policy(owner))
+            .create_register(xorname, tag, policy(owner, writers))
             .await
             .map_err(|e| {
                 Error::NetDataError(format!(
@@ -129,7 +144,13 @@ impl Safe {
         match result {
             Ok(data) => {
                 debug!("Register retrieved from {}...", url);
-                Ok(data)
+                let entries = try_join_all(data.into_iter().map(|(hash, entry)| async move {
+                    self.register_resolve_entry(entry)
+                        .await
+                        .map(|entry| (hash, entry))
+                }))
+                .await?;
+                Ok(entries.into_iter().collect())
             }
             Err(Error::EmptyContent(_)) => Err(Error::EmptyContent(format!(
                 "Register found at \"{}\" was empty",
@@ -143,6 +164,66 @@ impl Safe {
         }
     }
 
+    /// Read the history of a Register, i.e. its entries along with the ones each of them was
+    /// written on top of, in topological order (from the oldest to the newest).
+    /// If the URL has a version, the history is read from that entry, otherwise from the latest
+    /// entries, going down `depth` levels below it, or down to the very first entries if not set.
+    pub async fn register_history(
+        &self,
+        url: &str,
+        depth: Option<usize>,
+    ) -> Result<Vec<HistoryEntry>> {
+        let safeurl = self.parse_and_resolve_url(url).await?;
+        self.register_fetch_history(&safeurl, depth).await
+    }
+
+    /// Fetch the history of a Register from a `SafeUrl` without performing any type of URL resolution
+    pub(crate) async fn register_fetch_history(
+        &self,
+        url: &SafeUrl,
+        depth: Option<usize>,
+    ) -> Result<Vec<HistoryEntry>> {
+        debug!("Fetching Register history from {}", url);
+        let address = self.get_register_address(url)?;
+        let from = url.content_version().map(|v| v.entry_hash());
+        let client = self.get_safe_client()?;
+        let history = client
+            .get_register_history(address, from, depth)
+            .await
+            .map_err(|err| match (err, from) {
+                (ClientError::NetworkDataError(SafeNdError::NoSuchEntry), Some(hash)) => {
+                    Error::HashNotFound(hash)
+                }
+                (
+                    ClientError::ErrorMsg {
+                        source: ErrorMsg::AccessDenied(_),
+                        ..
+                    },
+                    _,
+                ) => Error::AccessDenied(format!(
+                    "Couldn't read history of Register found at \"{}\"",
+                    url
+                )),
+                (
+                    ClientError::ErrorMsg {
+                        source: ErrorMsg::DataNotFound(_),
+                        ..
+                    },
+                    _,
+                ) => Error::ContentNotFound(format!("No Register found at \"{}\"", url)),
+                (err, _) => Error::NetDataError(format!(
+                    "Failed to read history of Register data: {:?}",
+                    err
+                )),
+            })?;
+
+        try_join_all(history.into_iter().map(|item| async move {
+            let entry = self.register_resolve_entry(item.entry).await?;
+            Ok(HistoryEntry { entry, ..item })
+        }))
+        .await
+    }
+
     /// Fetch a Register from a `SafeUrl` without performing any type of URL resolution
     pub(crate) async fn register_fetch_entry(
         &self,
@@ -153,7 +234,7 @@ impl Safe {
         // e.g. safe://mysafeurl#ce56a3504c8f27bfeb13bdf9051c2e91409230ea
         let address = self.get_register_address(url)?;
         let client = self.get_safe_client()?;
-        client
+        let entry = client
             .get_register_entry(address, hash)
             .await
             .map_err(|err| {
@@ -170,7 +251,59 @@ impl Safe {
                         err
                     ))
                 }
-            })
+            })?;
+
+        self.register_resolve_entry(entry).await
+    }
+
+    /// Returns the entry to be written to a Register in place of the given value.
+    /// If the value doesn't fit in a Register entry, it's stored in chunks and
+    /// the entry returned is a pointer to them, which is resolved back to the value when read.
+    pub(crate) async fn register_entry_for_value(&self, value: Entry) -> Result<Entry> {
+        // values which could be mistaken for a pointer are stored in chunks as well
+        if value.len() <= MAX_REG_ENTRY_SIZE && !value.starts_with(ENTRY_POINTER_MARK) {
+            return Ok(value);
+        }
+
+        let size = value.len();
+        let client = self.get_safe_client()?;
+        let address = client.upload(Bytes::from(value)).await.map_err(|err| {
+            Error::NetDataError(format!(
+                "Failed to store Register entry value of {} bytes: {:?}",
+                size, err
+            ))
+        })?;
+        debug!(
+            "Register entry value of {} bytes stored in chunks at {:?}",
+            size, address
+        );
+
+        EntryPointer::Chunks { address, size }.encode()
+    }
+
+    /// Returns the value held by a Register entry, fetching it if the entry is a pointer to it.
+    pub(crate) async fn register_resolve_entry(&self, entry: Entry) -> Result<Entry> {
+        match EntryPointer::decode(&entry)? {
+            None => Ok(entry),
+            Some(EntryPointer::Chunks { address, size }) => {
+                let client = self.get_safe_client()?;
+                let value = client.read_bytes(address).await.map_err(|err| {
+                    Error::NetDataError(format!(
+                        "Failed to fetch Register entry value from {:?}: {:?}",
+                        address, err
+                    ))
+                })?;
+                if value.len() != size {
+                    return Err(Error::ContentError(format!(
+                        "Register entry value fetched from {:?} has {} bytes but {} were expected",
+                        address,
+                        value.len(),
+                        size
+                    )));
+                }
+                Ok(value.to_vec())
+            }
+        }
     }
 
     /// Write value to a Register on the network
@@ -186,6 +319,7 @@ impl Safe {
             return Ok(EntryHash(rand::thread_rng().gen::<[u8; 32]>()));
         }
 
+        let entry = self.register_entry_for_value(entry).await?;
         let client = self.get_safe_client()?;
         let (entry_hash, op_batch) = match client.write_to_register(address, entry, parents).await {
             Ok(data) => data,
@@ -214,6 +348,98 @@ impl Safe {
         Ok(entry_hash)
     }
 
+    /// Get the policy of a Register, i.e. its owner and the permissions of its users
+    pub async fn register_policy(&self, url: &str) -> Result<Policy> {
+        let reg_url = self.parse_and_resolve_url(url).await?;
+        self.register_fetch_policy(&reg_url).await
+    }
+
+    /// Set the permissions of a user on a Register, e.g. to grant or revoke write access,
+    /// or remove them so the permissions of `User::Anyone` apply to the user.
+    /// Only the owner of the Register is allowed to do this.
+    pub async fn register_set_permissions(
+        &self,
+        url: &str,
+        user: User,
+        permissions: Option<Permissions>,
+    ) -> Result<()> {
+        let reg_url = self.parse_and_resolve_url(url).await?;
+        self.register_update_permissions(&reg_url, user, permissions)
+            .await
+    }
+
+    /// Transfer the ownership of a Register to a new owner, keeping its current permissions.
+    /// Only the owner of the Register is allowed to do this.
+    pub async fn register_transfer_ownership(&self, url: &str, new_owner: User) -> Result<()> {
+        let reg_url = self.parse_and_resolve_url(url).await?;
+        self.register_update_owner(&reg_url, new_owner).await
+    }
+
+    /// Fetch the policy of a Register from a `SafeUrl` without performing any type of URL resolution
+    pub(crate) async fn register_fetch_policy(&self, url: &SafeUrl) -> Result<Policy> {
+        let address = self.get_register_address(url)?;
+        let client = self.get_safe_client()?;
+        client
+            .get_register_policy(address)
+            .await
+            .map_err(|err| register_policy_error(err, url))
+    }
+
+    /// Set the permissions of a user on a Register at a `SafeUrl` without performing any
+    /// type of URL resolution
+    pub(crate) async fn register_update_permissions(
+        &self,
+        url: &SafeUrl,
+        user: User,
+        permissions: Option<Permissions>,
+    ) -> Result<()> {
+        let address = self.get_register_address(url)?;
+        let mut policy = self.register_fetch_policy(url).await?;
+        match permissions {
+            Some(permissions) => {
+                let _ = policy.permissions.insert(user, permissions);
+            }
+            None => {
+                let _ = policy.permissions.remove(&user);
+            }
+        }
+        debug!("Setting permissions of {:?} on Register at {}", user, url);
+        if self.dry_run_mode {
+            return Ok(());
+        }
+
+        let client = self.get_safe_client()?;
+        let op_batch = client
+            .update_register_policy(address, policy)
+            .await
+            .map_err(|err| register_policy_error(err, url))?;
+        client.publish_register_ops(op_batch).await?;
+
+        Ok(())
+    }
+
+    /// Transfer the ownership of a Register at a `SafeUrl` without performing any
+    /// type of URL resolution
+    pub(crate) async fn register_update_owner(&self, url: &SafeUrl, new_owner: User) -> Result<()> {
+        let address = self.get_register_address(url)?;
+        debug!(
+            "Transferring ownership of Register at {} to {:?}",
+            url, new_owner
+        );
+        if self.dry_run_mode {
+            return Ok(());
+        }
+
+        let client = self.get_safe_client()?;
+        let op_batch = client
+            .transfer_register_ownership(address, new_owner)
+            .await
+            .map_err(|err| register_policy_error(err, url))?;
+        client.publish_register_ops(op_batch).await?;
+
+        Ok(())
+    }
+
     pub(crate) fn get_register_address(&self, url: &SafeUrl) -> Result<RegisterAddress> {
         let address = match url.address() {
             DataAddress::Register(reg_address) => reg_address,
@@ -229,16 +455,72 @@ impl Safe {
     }
 }
 
-fn policy(owner: User) -> Policy {
-    let mut permissions = BTreeMap::new();
+// Prefix of a Register entry which is a pointer to its actual value.
+const ENTRY_POINTER_MARK: &[u8] = b"\0safe-entry-pointer\0";
+
+/// Pointer held by a Register entry in place of a value which doesn't fit in it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum EntryPointer {
+    /// The value is stored in chunks, with the given address and size in bytes.
+    Chunks { address: XorName, size: usize },
+}
+
+impl EntryPointer {
+    // Serialises the pointer into a Register entry.
+    fn encode(&self) -> Result<Entry> {
+        let serialised = rmp_serde::to_vec_named(self).map_err(|err| {
+            Error::Serialisation(format!(
+                "Couldn't serialise the Register entry pointer '{:?}': {:?}",
+                self, err
+            ))
+        })?;
+        Ok([ENTRY_POINTER_MARK, &serialised].concat())
+    }
+
+    // Parses a Register entry as a pointer, returning `None` if the entry is not a pointer.
+    fn decode(entry: &[u8]) -> Result<Option<Self>> {
+        match entry.strip_prefix(ENTRY_POINTER_MARK) {
+            None => Ok(None),
+            Some(serialised) => rmp_serde::from_slice(serialised).map(Some).map_err(|err| {
+                Error::ContentError(format!("Couldn't parse Register entry pointer: {:?}", err))
+            }),
+        }
+    }
+}
+
+// Helper to map the errors returned when reading or changing the policy of a Register
+fn register_policy_error(err: ClientError, url: &SafeUrl) -> Error {
+    match err {
+        ClientError::NetworkDataError(SafeNdError::AccessDenied(_))
+        | ClientError::ErrorMsg {
+            source: ErrorMsg::AccessDenied(_),
+            ..
+        } => Error::AccessDenied(format!(
+            "Couldn't change the policy of Register found at \"{}\"",
+            url
+        )),
+        err => Error::NetDataError(format!("Failed to access Register policy: {:?}", err)),
+    }
+}
+
+fn policy(owner: User, writers: &BTreeSet<User>) -> Policy {
+    let mut permissions: BTreeMap<_, _> = writers
+        .iter()
+        .map(|writer| (*writer, Permissions::new(true)))
+        .collect();
     let _ = permissions.insert(owner, Permissions::new(true));
     Policy { owner, permissions }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{app::test_helpers::new_safe_instance, ContentType, Error};
+    use super::{EntryPointer, Permissions, User, ENTRY_POINTER_MARK, MAX_REG_ENTRY_SIZE};
+    use crate::{
+        app::test_helpers::new_safe_instance, nrs::VersionHash, ContentType, Error, SafeUrl,
+    };
     use anyhow::{bail, Result};
+    use sn_interface::types::utils::random_bytes;
+    use std::collections::BTreeSet;
 
     #[tokio::test]
     async fn test_register_create() -> Result<()> {
@@ -262,6 +544,85 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_register_entry_pointer_encoding() -> Result<()> {
+        let pointer = EntryPointer::Chunks {
+            address: xor_name::rand::random(),
+            size: 5_000,
+        };
+        let entry = pointer.encode()?;
+        assert!(entry.len() <= MAX_REG_ENTRY_SIZE);
+        assert_eq!(EntryPointer::decode(&entry)?, Some(pointer));
+
+        assert_eq!(EntryPointer::decode(b"safe://some-url")?, None);
+        assert!(EntryPointer::decode(ENTRY_POINTER_MARK).is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_register_write_large_entry() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let xorurl = safe.register_create(None, 25_000, ContentType::Raw).await?;
+
+        let large_entry = random_bytes(10 * MAX_REG_ENTRY_SIZE).to_vec();
+        let hash1 = safe
+            .register_write(&xorurl, large_entry.clone(), Default::default())
+            .await?;
+        assert_eq!(safe.register_read_entry(&xorurl, hash1).await?, large_entry);
+
+        // an entry which looks like a pointer is stored in chunks as well
+        let pointer_like_entry = [ENTRY_POINTER_MARK, b"data"].concat();
+        let hash2 = safe
+            .register_write(&xorurl, pointer_like_entry.clone(), BTreeSet::from([hash1]))
+            .await?;
+        assert_eq!(
+            safe.register_read(&xorurl).await?,
+            BTreeSet::from([(hash2, pointer_like_entry)])
+        );
+
+        let history = safe.register_history(&xorurl, None).await?;
+        assert_eq!(history[0].entry, large_entry);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_register_history() -> Result<()> {
+        let safe = new_safe_instance().await?;
+
+        let xorurl = safe.register_create(None, 25_000, ContentType::Raw).await?;
+        assert!(safe.register_history(&xorurl, None).await?.is_empty());
+
+        let hash1 = safe
+            .register_write(&xorurl, b"first".to_vec(), Default::default())
+            .await?;
+        let hash2 = safe
+            .register_write(&xorurl, b"second".to_vec(), BTreeSet::from([hash1]))
+            .await?;
+        let hash3 = safe
+            .register_write(&xorurl, b"third".to_vec(), BTreeSet::from([hash2]))
+            .await?;
+
+        let history = safe.register_history(&xorurl, None).await?;
+        let hashes: Vec<_> = history.iter().map(|item| item.hash).collect();
+        assert_eq!(hashes, vec![hash1, hash2, hash3]);
+        assert_eq!(history[2].entry, b"third".to_vec());
+        assert_eq!(history[2].children, BTreeSet::from([hash2]));
+
+        let history = safe.register_history(&xorurl, Some(1)).await?;
+        let hashes: Vec<_> = history.iter().map(|item| item.hash).collect();
+        assert_eq!(hashes, vec![hash2, hash3]);
+
+        let mut url = SafeUrl::from_url(&xorurl)?;
+        url.set_content_version(Some(VersionHash::from(&hash2)));
+        let history = safe.register_history(&url.to_string(), None).await?;
+        let hashes: Vec<_> = history.iter().map(|item| item.hash).collect();
+        assert_eq!(hashes, vec![hash1, hash2]);
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_register_owner_permissions() -> Result<()> {
         let safe = new_safe_instance().await?;
@@ -297,4 +658,48 @@ mod tests {
         let _ = safe.register_read(&xorurl).await?;
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_register_policy_update_and_ownership_transfer() -> Result<()> {
+        let safe = new_safe_instance().await?;
+        let xorurl = safe.register_create(None, 25_000, ContentType::Raw).await?;
+
+        let other_safe = new_safe_instance().await?;
+        let other_user = User::Key(other_safe.get_safe_client()?.public_key());
+
+        // grant write access to the other user
+        safe.register_set_permissions(&xorurl, other_user, Some(Permissions::new(true)))
+            .await?;
+        let policy = safe.register_policy(&xorurl).await?;
+        assert_eq!(policy.permissions(other_user), Some(Permissions::new(true)));
+        let _ = other_safe
+            .register_write(&xorurl, b"other data".to_vec(), Default::default())
+            .await?;
+
+        // hand ownership over to the other user
+        let owner = User::Key(safe.get_safe_client()?.public_key());
+        safe.register_transfer_ownership(&xorurl, other_user)
+            .await?;
+        let policy = safe.register_policy(&xorurl).await?;
+        assert_eq!(*policy.owner(), other_user);
+
+        // the previous owner can no longer change the policy
+        match safe
+            .register_set_permissions(&xorurl, owner, Some(Permissions::new(true)))
+            .await
+        {
+            Err(Error::AccessDenied(_)) => {}
+            Err(err) => bail!("Error returned is not the expected: {:?}", err),
+            Ok(_) => bail!("Policy update succeeded unexpectedly".to_string()),
+        }
+
+        // but the new owner can
+        other_safe
+            .register_set_permissions(&xorurl, owner, Some(Permissions::new(false)))
+            .await?;
+        let policy = other_safe.register_policy(&xorurl).await?;
+        assert_eq!(policy.permissions(owner), Some(Permissions::new(false)));
+
+        Ok(())
+    }
 }
@@ -8,27 +8,126 @@
 
 pub use sn_dbc::{self as dbc, Dbc, Token};
 
-use super::{helpers::parse_tokens_amount, register::EntryHash};
+use super::{
+    helpers::{parse_tokens_amount, systemtime_to_rfc3339},
+    register::EntryHash,
+};
 use crate::{
     safeurl::{ContentType, SafeUrl, XorUrl},
     Error, Result, Safe,
 };
 use bytes::Bytes;
 use log::{debug, warn};
-use sn_client::Client;
+use serde::{Deserialize, Serialize};
+use sn_client::{Client, Error as ClientError};
 use sn_dbc::{
     rng, AmountSecrets, Error as DbcError, Hash, KeyImage, Owner, OwnerOnce, PublicKey,
     RingCtTransaction, SpentProof, SpentProofShare, TransactionBuilder,
 };
-use std::collections::{BTreeMap, BTreeSet, HashSet};
+use sn_interface::{messaging::data::Error as ErrorMsg, types::Error as SafeNdError};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fmt,
+    str::FromStr,
+    time::SystemTime,
+};
 
 /// Type tag to use for the Wallet stored on Register
 pub const WALLET_TYPE_TAG: u64 = 1_000;
 
+/// Type tag to use for the history of a Wallet, which is stored on a Register
+/// with the same name as the Wallet's one.
+pub const WALLET_HISTORY_TYPE_TAG: u64 = 1_001;
+
 /// Set of spendable DBCs mapped to their friendly name as defined/chosen by the user when
 /// depositing DBCs into a wallet.
 pub type WalletSpendableDbcs = BTreeMap<String, (Dbc, EntryHash)>;
 
+/// Strategy to select which of the spendable DBCs in a wallet are spent as inputs when reissuing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CoinSelection {
+    /// Spend DBCs in the order they are listed in the wallet, i.e. sorted by their name.
+    InOrder,
+    /// Spend the DBCs with the largest balances first.
+    LargestFirst,
+    /// Spend the DBCs with the smallest balances first, consolidating dust into the change.
+    SmallestFirst,
+    /// Spend as few DBCs as possible, leaving the least change that number of inputs allows.
+    MinInputs,
+    /// Prefer one or two DBCs which add up to the exact amount so no change is generated,
+    /// falling back to `MinInputs` when there are none.
+    ExactMatch,
+}
+
+/// Default selection is InOrder
+impl Default for CoinSelection {
+    fn default() -> Self {
+        Self::InOrder
+    }
+}
+
+impl FromStr for CoinSelection {
+    type Err = Error;
+    fn from_str(str: &str) -> Result<Self> {
+        match str {
+            "in-order" => Ok(Self::InOrder),
+            "largest-first" => Ok(Self::LargestFirst),
+            "smallest-first" => Ok(Self::SmallestFirst),
+            "min-inputs" => Ok(Self::MinInputs),
+            "exact-match" => Ok(Self::ExactMatch),
+            other => Err(Error::InvalidInput(format!(
+                "Invalid coin selection: {}. Supported values are in-order, largest-first, \
+                smallest-first, min-inputs, and exact-match",
+                other
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for CoinSelection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InOrder => write!(f, "in-order"),
+            Self::LargestFirst => write!(f, "largest-first"),
+            Self::SmallestFirst => write!(f, "smallest-first"),
+            Self::MinInputs => write!(f, "min-inputs"),
+            Self::ExactMatch => write!(f, "exact-match"),
+        }
+    }
+}
+
+/// The outcome a reissue would have, as planned without spending anything: the DBCs from the
+/// wallet which would be spent, the outputs, and the change deposited back into the wallet.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletReissuePlan {
+    pub spent: Vec<(String, Token)>,
+    pub outputs: Vec<(Token, Option<bls::PublicKey>)>,
+    pub change: Token,
+}
+
+/// A transaction recorded in the history of a wallet.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WalletTx {
+    /// A DBC was deposited into the wallet with the given name.
+    Deposit { name: String, amount: Token },
+    /// DBCs from the wallet were reissued. The spent DBCs are listed by their name in the wallet,
+    /// and the outputs by their amount and the public key of their owner, which is `None` for
+    /// bearer DBCs. The change, if any, was deposited back into the wallet with the given name.
+    Reissue {
+        spent: Vec<(String, Token)>,
+        outputs: Vec<(Token, Option<bls::PublicKey>)>,
+        change: Option<(String, Token)>,
+    },
+}
+
+/// An entry in the history of a wallet, i.e. a transaction along with the time it was recorded at,
+/// formatted as per RFC 3339.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletHistoryEntry {
+    pub timestamp: String,
+    pub tx: WalletTx,
+}
+
 // Number of attempts to make trying to spend inputs when reissuing DBCs
 // As the spend and query cmds are cascaded closely, there is high chance
 // that the first two query attempts could both be failed.
@@ -68,6 +167,15 @@ impl Safe {
         let mut safeurl = SafeUrl::from_url(&xorurl)?;
         safeurl.set_content_type(ContentType::Wallet)?;
 
+        // The history of the wallet is kept on its own Register
+        let _ = self
+            .register_create(
+                Some(safeurl.xorname()),
+                WALLET_HISTORY_TYPE_TAG,
+                ContentType::Raw,
+            )
+            .await?;
+
         Ok(safeurl.to_string())
     }
 
@@ -131,6 +239,14 @@ impl Safe {
         let safeurl = self.parse_and_resolve_url(wallet_url).await?;
         self.insert_dbc_into_wallet(&safeurl, &dbc_to_deposit, spendable_name.clone())
             .await?;
+        self.record_wallet_tx(
+            &safeurl,
+            WalletTx::Deposit {
+                name: spendable_name.clone(),
+                amount,
+            },
+        )
+        .await;
 
         debug!(
             "A spendable DBC deposited (amount: {}) into wallet at {}, with name: {}",
@@ -227,6 +343,39 @@ impl Safe {
         Ok(balances)
     }
 
+    /// Fetch the history of a wallet from a Url performing all type of URL resolution required.
+    /// Return the transactions recorded for the wallet, from the oldest to the newest.
+    /// Wallets created before histories were kept have no history, thus an empty list is returned.
+    pub async fn wallet_history(&self, wallet_url: &str) -> Result<Vec<WalletHistoryEntry>> {
+        let safeurl = self.parse_and_resolve_url(wallet_url).await?;
+        let history_url = wallet_history_url(&safeurl)?;
+        let history = match self.register_fetch_history(&history_url, None).await {
+            Ok(history) => history,
+            Err(Error::ContentNotFound(_)) => {
+                debug!("No history found for wallet at {}", safeurl);
+                return Ok(vec![]);
+            }
+            Err(err) => {
+                return Err(Error::ContentError(format!(
+                    "Failed to read history of wallet: {}",
+                    err
+                )))
+            }
+        };
+
+        let mut entries = vec![];
+        for item in history.iter() {
+            match rmp_serde::from_slice(&item.entry) {
+                Ok(entry) => entries.push(entry),
+                Err(err) => {
+                    warn!("Ignoring entry found in wallet history since it cannot be deserialised: {:?}", err);
+                }
+            }
+        }
+
+        Ok(entries)
+    }
+
     /// Check the total balance of a wallet found at a given XOR-URL
     pub async fn wallet_balance(&self, wallet_url: &str) -> Result<Token> {
         debug!("Finding total wallet balance for: {}", wallet_url);
@@ -306,32 +455,28 @@ impl Safe {
     /// this function allows to reissue from a single wallet several output DBCs instead
     /// of a single one. If there is change from the transaction, the change DBC will be
     /// deposited in the source wallet.
+    ///
+    /// The input DBCs are chosen from the wallet as per the `wallet_coin_selection` strategy.
     pub async fn wallet_reissue_many(
         &self,
         wallet_url: &str,
         outputs: Vec<(String, Option<bls::PublicKey>)>,
     ) -> Result<Vec<Dbc>> {
-        let mut total_output_amount = Token::zero();
-        let mut outputs_owners = Vec::<(Token, OwnerOnce)>::new();
-        for (amount, owner_pk) in outputs.into_iter() {
-            let output_amount = parse_tokens_amount(&amount)?;
-            if output_amount.as_nano() == 0 {
-                return Err(Error::InvalidAmount(
-                    "Output amount to reissue needs to be larger than zero (0).".to_string(),
-                ));
-            }
-            total_output_amount = total_output_amount.checked_add(output_amount).unwrap();
-
-            let output_owner = if let Some(pk) = owner_pk {
-                let owner = Owner::from(pk);
-                OwnerOnce::from_owner_base(owner, &mut rng::thread_rng())
-            } else {
-                let owner = Owner::from_random_secret_key(&mut rng::thread_rng());
-                OwnerOnce::from_owner_base(owner, &mut rng::thread_rng())
-            };
-
-            outputs_owners.push((output_amount, output_owner));
-        }
+        let (total_output_amount, recipients) = parse_reissue_outputs(outputs)?;
+        let outputs_owners = recipients
+            .iter()
+            .map(|(output_amount, owner_pk)| {
+                let owner = if let Some(pk) = owner_pk {
+                    Owner::from(*pk)
+                } else {
+                    Owner::from_random_secret_key(&mut rng::thread_rng())
+                };
+                (
+                    *output_amount,
+                    OwnerOnce::from_owner_base(owner, &mut rng::thread_rng()),
+                )
+            })
+            .collect::<Vec<(Token, OwnerOnce)>>();
 
         let safeurl = self.parse_and_resolve_url(wallet_url).await?;
         let spendable_dbcs = self.fetch_wallet(&safeurl).await?;
@@ -339,45 +484,19 @@ impl Safe {
         // We'll combine one or more input DBCs and reissue:
         // - one output DBC for the recipient,
         // - and a second DBC for the change, which will be stored in the source wallet.
+        let (selected, change_amount) = select_wallet_inputs(
+            spendable_dbcs,
+            total_output_amount,
+            self.wallet_coin_selection,
+        )?;
+
         let mut input_dbcs_to_spend = Vec::<Dbc>::new();
         let mut input_dbcs_entries_hash = BTreeSet::<EntryHash>::new();
-        let mut total_input_amount = 0;
-        let mut change_amount = total_output_amount;
-        for (name, (dbc, entry_hash)) in spendable_dbcs.into_iter() {
-            let dbc_balance = match dbc.amount_secrets_bearer() {
-                Ok(amount_secrets) => amount_secrets.amount(),
-                Err(err) => {
-                    warn!("Ignoring input DBC found in wallet (entry: {}) due to error in revealing secret amount: {:?}", name, err);
-                    continue;
-                }
-            };
-
-            // Add this DBC as input to be spent
+        let mut spent = Vec::<(String, Token)>::new();
+        for ((name, (dbc, entry_hash)), dbc_balance) in selected {
             input_dbcs_to_spend.push(dbc);
             input_dbcs_entries_hash.insert(entry_hash);
-            total_input_amount += dbc_balance.as_nano();
-
-            // If we've already combined input DBCs for the total output amount, then stop
-            match change_amount.checked_sub(dbc_balance) {
-                Some(pending_output) => {
-                    change_amount = pending_output;
-                    if change_amount.as_nano() == 0 {
-                        break;
-                    }
-                }
-                None => {
-                    change_amount =
-                        Token::from_nano(dbc_balance.as_nano() - change_amount.as_nano());
-                    break;
-                }
-            }
-        }
-
-        // Make sure total input amount gathered with input DBCs are enough for the output amount
-        if total_input_amount < total_output_amount.as_nano() {
-            return Err(Error::NotEnoughBalance(
-                Token::from_nano(total_input_amount).to_string(),
-            ));
+            spent.push((name, dbc_balance));
         }
 
         // We can now reissue the output DBCs
@@ -391,22 +510,62 @@ impl Safe {
             ));
         }
 
-        if let Some(change_dbc) = change_dbc {
-            self.insert_dbc_into_wallet(
-                &safeurl,
-                &change_dbc,
-                format!("change-dbc-{}", &hex::encode(change_dbc.hash())[0..8]),
-            )
-            .await?;
-        }
+        let change = if let Some(change_dbc) = change_dbc {
+            let change_name = format!("change-dbc-{}", &hex::encode(change_dbc.hash())[0..8]);
+            self.insert_dbc_into_wallet(&safeurl, &change_dbc, change_name.clone())
+                .await?;
+            Some((change_name, change_amount))
+        } else {
+            None
+        };
 
         // (virtually) remove input DBCs in the source wallet
         self.multimap_remove(&safeurl.to_string(), input_dbcs_entries_hash)
             .await?;
 
+        self.record_wallet_tx(
+            &safeurl,
+            WalletTx::Reissue {
+                spent,
+                outputs: recipients,
+                change,
+            },
+        )
+        .await;
+
         Ok(output_dbcs.into_iter().map(|(dbc, _, _)| dbc).collect())
     }
 
+    /// Plan the reissue of several DBCs from a wallet without spending anything.
+    ///
+    /// The wallet is only read to find out which of its DBCs would be spent as inputs, as per
+    /// the `wallet_coin_selection` strategy, and the change which would be deposited back into it.
+    pub async fn wallet_reissue_plan(
+        &self,
+        wallet_url: &str,
+        outputs: Vec<(String, Option<bls::PublicKey>)>,
+    ) -> Result<WalletReissuePlan> {
+        let (total_output_amount, outputs) = parse_reissue_outputs(outputs)?;
+
+        let safeurl = self.parse_and_resolve_url(wallet_url).await?;
+        let spendable_dbcs = self.fetch_wallet(&safeurl).await?;
+
+        let (selected, change) = select_wallet_inputs(
+            spendable_dbcs,
+            total_output_amount,
+            self.wallet_coin_selection,
+        )?;
+
+        Ok(WalletReissuePlan {
+            spent: selected
+                .into_iter()
+                .map(|((name, _), amount)| (name, amount))
+                .collect(),
+            outputs,
+            change,
+        })
+    }
+
     ///
     /// Private helpers
     ///
@@ -439,6 +598,65 @@ impl Safe {
         Ok(())
     }
 
+    /// Record a transaction in the history of a wallet.
+    /// The reissue has already happened by the time the transaction is recorded, so a failure to
+    /// record it is logged rather than returned, in which case the history misses that transaction.
+    async fn record_wallet_tx(&self, safeurl: &SafeUrl, tx: WalletTx) {
+        if self.dry_run_mode {
+            return;
+        }
+        if let Err(err) = self.append_wallet_history_entry(safeurl, tx.clone()).await {
+            warn!(
+                "Failed to record transaction in history of wallet at {}: {:?}. Transaction: {:?}",
+                safeurl, err, tx
+            );
+        }
+    }
+
+    async fn append_wallet_history_entry(&self, safeurl: &SafeUrl, tx: WalletTx) -> Result<()> {
+        let history_entry = WalletHistoryEntry {
+            timestamp: systemtime_to_rfc3339(SystemTime::now()),
+            tx,
+        };
+        let entry = rmp_serde::to_vec_named(&history_entry).map_err(|err| {
+            Error::Serialisation(format!(
+                "Failed to serialise wallet history entry: {:?}",
+                err
+            ))
+        })?;
+        let entry = self.register_entry_for_value(entry).await?;
+
+        let history_url = wallet_history_url(safeurl)?;
+        let address = self.get_register_address(&history_url)?;
+        let client = self.get_safe_client()?;
+
+        // New entries are written on top of the latest ones, so they are kept in order. The history
+        // Register is created here for wallets which were created before histories were kept.
+        let latest = match client.read_register(address).await {
+            Ok(entries) => entries.into_iter().map(|(hash, _)| hash).collect(),
+            Err(ClientError::NetworkDataError(SafeNdError::NoSuchEntry)) => BTreeSet::new(),
+            Err(ClientError::ErrorMsg {
+                source: ErrorMsg::DataNotFound(_),
+                ..
+            }) => {
+                let _ = self
+                    .register_create(
+                        Some(history_url.xorname()),
+                        WALLET_HISTORY_TYPE_TAG,
+                        ContentType::Raw,
+                    )
+                    .await?;
+                BTreeSet::new()
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let (_, op_batch) = client.write_to_register(address, entry, latest).await?;
+        client.publish_register_ops(op_batch).await?;
+
+        Ok(())
+    }
+
     /// Reissue DBCs and log the spent input DBCs on the network. Return the output DBC and the
     /// change DBC if there is one.
     pub(super) async fn reissue_dbcs(
@@ -545,6 +763,184 @@ impl Safe {
     }
 }
 
+// Amounts of the outputs of a reissue, along with the public key of their owner
+type ReissueOutputs = Vec<(Token, Option<bls::PublicKey>)>;
+
+// A spendable DBC of a wallet along with its name in the wallet
+type SpendableDbc = (String, (Dbc, EntryHash));
+
+// Private helper to parse the amounts of the outputs of a reissue, returning them
+// along with their total amount
+fn parse_reissue_outputs(
+    outputs: Vec<(String, Option<bls::PublicKey>)>,
+) -> Result<(Token, ReissueOutputs)> {
+    let mut total_output_amount = Token::zero();
+    let mut recipients = ReissueOutputs::new();
+    for (amount, owner_pk) in outputs.into_iter() {
+        let output_amount = parse_tokens_amount(&amount)?;
+        if output_amount.as_nano() == 0 {
+            return Err(Error::InvalidAmount(
+                "Output amount to reissue needs to be larger than zero (0).".to_string(),
+            ));
+        }
+        total_output_amount = total_output_amount
+            .checked_add(output_amount)
+            .ok_or_else(|| {
+                Error::InvalidAmount("Total amount to reissue is too large.".to_string())
+            })?;
+        recipients.push((output_amount, owner_pk));
+    }
+
+    Ok((total_output_amount, recipients))
+}
+
+// Private helper to select the spendable DBCs of a wallet to use as inputs for a reissue,
+// returning them along with their balance, and the change amount.
+fn select_wallet_inputs(
+    spendable_dbcs: WalletSpendableDbcs,
+    total_output_amount: Token,
+    selection: CoinSelection,
+) -> Result<(Vec<(SpendableDbc, Token)>, Token)> {
+    let candidates = spendable_dbcs
+        .into_iter()
+        .filter_map(
+            |(name, (dbc, entry_hash))| match dbc.amount_secrets_bearer() {
+                Ok(amount_secrets) => Some(((name, (dbc, entry_hash)), amount_secrets.amount())),
+                Err(err) => {
+                    warn!("Ignoring input DBC found in wallet (entry: {}) due to error in revealing secret amount: {:?}", name, err);
+                    None
+                }
+            },
+        )
+        .collect();
+
+    select_inputs(candidates, total_output_amount, selection)
+}
+
+// Select the candidates to spend for the target amount as per the selection strategy.
+// Candidates are expected in the order they are listed in the wallet.
+fn select_inputs<T>(
+    mut candidates: Vec<(T, Token)>,
+    target: Token,
+    selection: CoinSelection,
+) -> Result<(Vec<(T, Token)>, Token)> {
+    let total_available = candidates
+        .iter()
+        .map(|(_, amount)| amount.as_nano())
+        .sum::<u64>();
+    if total_available < target.as_nano() {
+        return Err(Error::NotEnoughBalance(
+            Token::from_nano(total_available).to_string(),
+        ));
+    }
+
+    // The sorts are stable, thus DBCs with the same balance are kept in wallet order
+    let selected_indexes = match selection {
+        CoinSelection::InOrder => accumulate_inputs(&candidates, target),
+        CoinSelection::LargestFirst => {
+            candidates.sort_by(|(_, a), (_, b)| b.cmp(a));
+            accumulate_inputs(&candidates, target)
+        }
+        CoinSelection::SmallestFirst => {
+            candidates.sort_by_key(|(_, amount)| *amount);
+            accumulate_inputs(&candidates, target)
+        }
+        CoinSelection::MinInputs => {
+            candidates.sort_by(|(_, a), (_, b)| b.cmp(a));
+            min_inputs(&candidates, target)
+        }
+        CoinSelection::ExactMatch => {
+            candidates.sort_by(|(_, a), (_, b)| b.cmp(a));
+            exact_match_inputs(&candidates, target)
+                .unwrap_or_else(|| min_inputs(&candidates, target))
+        }
+    };
+
+    let selected_total = selected_indexes
+        .iter()
+        .map(|i| candidates[*i].1.as_nano())
+        .sum::<u64>();
+    let change = Token::from_nano(selected_total - target.as_nano());
+
+    let selected = candidates
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| selected_indexes.contains(i))
+        .map(|(_, candidate)| candidate)
+        .collect();
+
+    Ok((selected, change))
+}
+
+// Take candidates in the order given until their total covers the target amount
+fn accumulate_inputs<T>(candidates: &[(T, Token)], target: Token) -> BTreeSet<usize> {
+    let mut selected = BTreeSet::new();
+    let mut total = 0;
+    for (i, (_, amount)) in candidates.iter().enumerate() {
+        if total >= target.as_nano() {
+            break;
+        }
+        let _ = selected.insert(i);
+        total += amount.as_nano();
+    }
+    selected
+}
+
+// Take the fewest candidates which cover the target amount. Candidates are expected sorted by
+// descending amount, the largest ones are taken but the last one, which is replaced by the
+// smallest candidate still covering the target amount, to minimise the change.
+fn min_inputs<T>(candidates: &[(T, Token)], target: Token) -> BTreeSet<usize> {
+    let mut selected = accumulate_inputs(candidates, target);
+    if let Some(last) = selected.iter().next_back().copied() {
+        let _ = selected.remove(&last);
+        let total = selected
+            .iter()
+            .map(|i| candidates[*i].1.as_nano())
+            .sum::<u64>();
+        let pending = target.as_nano() - total;
+        let smallest_covering = (last..candidates.len())
+            .take_while(|i| candidates[*i].1.as_nano() >= pending)
+            .last()
+            .unwrap_or(last);
+        let _ = selected.insert(smallest_covering);
+    }
+    selected
+}
+
+// Find one candidate, or otherwise two, adding up to exactly the target amount
+fn exact_match_inputs<T>(candidates: &[(T, Token)], target: Token) -> Option<BTreeSet<usize>> {
+    if let Some(i) = candidates.iter().position(|(_, amount)| *amount == target) {
+        return Some(BTreeSet::from([i]));
+    }
+
+    for (i, (_, amount)) in candidates.iter().enumerate() {
+        let pending = match target.checked_sub(*amount) {
+            Some(pending) if pending.as_nano() > 0 => pending,
+            _ => continue,
+        };
+        if let Some(j) = candidates
+            .iter()
+            .enumerate()
+            .skip(i + 1)
+            .position(|(_, (_, other))| *other == pending)
+        {
+            return Some(BTreeSet::from([i, i + 1 + j]));
+        }
+    }
+
+    None
+}
+
+// Private helper to get the URL of the Register where the history of a wallet is kept
+fn wallet_history_url(wallet_url: &SafeUrl) -> Result<SafeUrl> {
+    let url = SafeUrl::from_register(
+        wallet_url.xorname(),
+        WALLET_HISTORY_TYPE_TAG,
+        ContentType::Raw,
+    )?;
+    Ok(url)
+}
+
 // Private helper to verify if a set of spent proof shares are valid for a given key_image and TX
 fn verify_spent_proof_shares_for_tx<'a>(
     key_image: KeyImage,
@@ -912,6 +1308,48 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_wallet_history() -> Result<()> {
+        let (safe, dbc, dbc_balance) = new_safe_instance_with_dbc().await?;
+        let wallet_xorurl = safe.wallet_create().await?;
+        assert!(safe.wallet_history(&wallet_xorurl).await?.is_empty());
+
+        safe.wallet_deposit(&wallet_xorurl, Some("my-dbc"), &dbc, None)
+            .await?;
+        let pk = bls::SecretKey::random().public_key();
+        let _ = safe
+            .wallet_reissue(&wallet_xorurl, "2.35", Some(pk))
+            .await?;
+
+        let history = safe.wallet_history(&wallet_xorurl).await?;
+        assert_eq!(history.len(), 2);
+        assert_eq!(
+            history[0].tx,
+            WalletTx::Deposit {
+                name: "my-dbc".to_string(),
+                amount: dbc_balance
+            }
+        );
+        match &history[1].tx {
+            WalletTx::Reissue {
+                spent,
+                outputs,
+                change,
+            } => {
+                let output_amount = Token::from_nano(2_350_000_000);
+                assert_eq!(*spent, vec![("my-dbc".to_string(), dbc_balance)]);
+                assert_eq!(*outputs, vec![(output_amount, Some(pk))]);
+                let change_amount = dbc_balance
+                    .checked_sub(output_amount)
+                    .ok_or_else(|| anyhow!("Unexpected overflow"))?;
+                assert!(matches!(change, Some((_, amount)) if *amount == change_amount));
+            }
+            other => return Err(anyhow!("Unexpected transaction in history: {:?}", other)),
+        }
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_wallet_reissue_with_multiple_input_dbcs() -> Result<()> {
         let (safe, dbc1, dbc1_balance) = new_safe_instance_with_dbc().await?;
@@ -953,6 +1391,92 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_wallet_reissue_plan() -> Result<()> {
+        let (mut safe, dbc1, dbc1_balance) = new_safe_instance_with_dbc().await?;
+        let (dbc2, dbc2_balance) = get_next_bearer_dbc().await?;
+        let wallet_xorurl = safe.wallet_create().await?;
+
+        safe.wallet_deposit(&wallet_xorurl, Some("deposited-dbc-1"), &dbc1, None)
+            .await?;
+        safe.wallet_deposit(&wallet_xorurl, Some("deposited-dbc-2"), &dbc2, None)
+            .await?;
+
+        let (smallest_name, smallest_balance) = if dbc1_balance <= dbc2_balance {
+            ("deposited-dbc-1", dbc1_balance)
+        } else {
+            ("deposited-dbc-2", dbc2_balance)
+        };
+
+        safe.wallet_coin_selection = CoinSelection::SmallestFirst;
+        let amount_to_reissue = Token::from_nano(smallest_balance.as_nano() - 100);
+        let plan = safe
+            .wallet_reissue_plan(&wallet_xorurl, vec![(amount_to_reissue.to_string(), None)])
+            .await?;
+
+        assert_eq!(
+            plan.spent,
+            vec![(smallest_name.to_string(), smallest_balance)]
+        );
+        assert_eq!(plan.outputs, vec![(amount_to_reissue, None)]);
+        assert_eq!(plan.change, Token::from_nano(100));
+
+        // nothing was spent from the wallet
+        let current_balance = safe.wallet_balance(&wallet_xorurl).await?;
+        assert_eq!(
+            current_balance.as_nano(),
+            dbc1_balance.as_nano() + dbc2_balance.as_nano()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_wallet_select_inputs() -> Result<()> {
+        let candidates = vec![
+            ("a", Token::from_nano(5)),
+            ("b", Token::from_nano(1)),
+            ("c", Token::from_nano(8)),
+            ("d", Token::from_nano(3)),
+        ];
+        let select = |target, selection| -> Result<(Vec<&str>, u64)> {
+            let (selected, change) =
+                select_inputs(candidates.clone(), Token::from_nano(target), selection)?;
+            Ok((
+                selected.into_iter().map(|(name, _)| name).collect(),
+                change.as_nano(),
+            ))
+        };
+
+        assert_eq!(select(6, CoinSelection::InOrder)?, (vec!["a", "b"], 0));
+        assert_eq!(select(6, CoinSelection::LargestFirst)?, (vec!["c"], 2));
+        assert_eq!(
+            select(6, CoinSelection::SmallestFirst)?,
+            (vec!["b", "d", "a"], 3)
+        );
+        assert_eq!(select(4, CoinSelection::MinInputs)?, (vec!["a"], 1));
+        assert_eq!(select(11, CoinSelection::MinInputs)?, (vec!["c", "d"], 0));
+        assert_eq!(select(3, CoinSelection::ExactMatch)?, (vec!["d"], 0));
+        assert_eq!(select(9, CoinSelection::ExactMatch)?, (vec!["c", "b"], 0));
+        assert_eq!(
+            select(15, CoinSelection::ExactMatch)?,
+            (vec!["c", "a", "d"], 1)
+        );
+        assert_eq!(select(2, CoinSelection::ExactMatch)?, (vec!["d"], 1));
+
+        match select_inputs(
+            candidates,
+            Token::from_nano(18),
+            CoinSelection::LargestFirst,
+        ) {
+            Err(Error::NotEnoughBalance(balance)) => {
+                assert_eq!(balance, Token::from_nano(17).to_string());
+                Ok(())
+            }
+            other => Err(anyhow!("Unexpected result: {:?}", other)),
+        }
+    }
+
     #[tokio::test]
     async fn test_wallet_reissue_with_single_input_dbc() -> Result<()> {
         let (safe, dbc, dbc_balance) = new_safe_instance_with_dbc().await?;
@@ -84,6 +84,9 @@ pub enum Error {
     /// A file with same name already exists on target FilesContainer with same link"
     #[error("File named \"{0}\" already exists on target. Use the 'force' flag to replace it.")]
     FileNameConflict(String),
+    /// Concurrent versions of a FilesContainer made different changes to the same paths
+    #[error("ConflictingFilesContainerVersions: {0}: {}", .1.join(", "))]
+    ConflictingFilesContainerVersions(String, Vec<String>),
     /// InvalidAmount
     #[error("InvalidAmount: {0}")]
     InvalidAmount(String),
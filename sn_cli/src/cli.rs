@@ -18,16 +18,17 @@ use crate::{
         networks::networks_commander,
         node::node_commander,
         nrs::nrs_commander,
+        register::register_commander,
         setup::setup_commander,
         update::update_commander,
-        wallet::wallet_commander,
+        wallet::{wallet_commander, WalletSubCommands},
         xorurl::{xorurl_commander, xorurl_of_files},
         OutputFmt, SubCommands,
     },
 };
 use clap::{AppSettings::ColoredHelp, Parser};
 use color_eyre::{eyre::eyre, Result};
-use sn_api::{Safe, XorUrlBase};
+use sn_api::{files::ConflictPolicy, Safe, XorUrlBase};
 use std::env;
 use std::path::PathBuf;
 use tracing::{debug, warn};
@@ -54,6 +55,9 @@ pub struct CmdArgs {
     /// Base encoding to be used for XOR-URLs generated. Currently supported: base32z (default), base32 and base64
     #[clap(long = "xorurl", global(true))]
     xorurl_base: Option<XorUrlBase>,
+    /// How to resolve conflicting changes when merging concurrent versions of a FilesContainer: [last-modified, keep-both, fail]
+    #[clap(long = "conflict-policy", global(true))]
+    conflict_policy: Option<ConflictPolicy>,
 }
 
 pub async fn run() -> Result<()> {
@@ -64,6 +68,9 @@ pub async fn run() -> Result<()> {
     if let Some(base) = args.xorurl_base {
         safe.xorurl_base = base;
     }
+    if let Some(policy) = args.conflict_policy {
+        safe.files_conflict_policy = policy;
+    }
 
     let mut config = get_config().await?;
 
@@ -162,15 +169,21 @@ async fn process_commands(mut safe: &mut Safe, args: CmdArgs, config: &mut Confi
             // available to connect to the network with them (unless dry-run was set),
             // otherwise the connection created will be with read-only access and some
             // of these commands will fail if they require write access.
-            if !safe.dry_run_mode {
+            // A dry-run reissue still needs to read the wallet to plan which DBCs would be spent.
+            let reads_in_dry_run = matches!(
+                other,
+                SubCommands::Wallet(WalletSubCommands::Reissue { .. })
+            );
+            if !safe.dry_run_mode || reads_in_dry_run {
                 connect(safe, config).await?;
             }
 
             match other {
                 SubCommands::Cat(cmd) => cat_commander(cmd, output_fmt, safe).await,
                 SubCommands::Dog(cmd) => dog_commander(cmd, output_fmt, safe).await,
-                SubCommands::Files(cmd) => files_commander(cmd, output_fmt, safe).await,
-                SubCommands::Nrs(cmd) => nrs_commander(cmd, output_fmt, safe).await,
+                SubCommands::Files(cmd) => files_commander(cmd, output_fmt, safe, config).await,
+                SubCommands::Nrs(cmd) => nrs_commander(cmd, output_fmt, safe, config).await,
+                SubCommands::Register(cmd) => register_commander(cmd, output_fmt, safe).await,
                 SubCommands::Wallet(cmd) => wallet_commander(cmd, output_fmt, safe, config).await,
                 _ => Err(eyre!("Unknown safe subcommand")),
             }
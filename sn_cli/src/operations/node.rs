@@ -10,10 +10,10 @@ use super::helpers::download_and_install_node;
 use crate::operations::config::NetworkLauncher;
 use color_eyre::{eyre::bail, eyre::eyre, eyre::WrapErr, Result};
 use std::{
-    fs::create_dir_all,
-    io::{self, Write},
-    net::SocketAddr,
-    path::PathBuf,
+    fs::{self, create_dir_all},
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpStream},
+    path::{Path, PathBuf},
     process::{Command, Stdio},
 };
 use tracing::debug;
@@ -165,6 +165,45 @@ pub fn node_shutdown(node_path: Option<PathBuf>) -> Result<()> {
     kill_nodes(node_exec_name)
 }
 
+/// Asks the node with the given admin endpoint to leave its section gracefully, authenticating
+/// with the token read from the given file
+pub fn node_leave(admin_addr: SocketAddr, admin_token_file: &Path) -> Result<()> {
+    let token = fs::read_to_string(admin_token_file).wrap_err_with(|| {
+        format!(
+            "Failed to read the node admin token from {}",
+            admin_token_file.display()
+        )
+    })?;
+
+    let mut stream = TcpStream::connect(admin_addr)
+        .wrap_err_with(|| format!("Failed to connect to node admin endpoint at {admin_addr}"))?;
+
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "leave",
+        "token": token.trim(),
+    });
+    writeln!(stream, "{}", request).wrap_err("Failed to send leave request to the node")?;
+
+    let mut response = String::new();
+    let _ = BufReader::new(stream)
+        .read_line(&mut response)
+        .wrap_err("Failed to read the response from the node")?;
+    let response: serde_json::Value = serde_json::from_str(&response)
+        .wrap_err_with(|| format!("Invalid response from the node: {response}"))?;
+
+    if let Some(error) = response.get("error") {
+        bail!("Node refused to leave: {}", error);
+    }
+
+    println!(
+        "Node at {} is leaving its section, it will shut down once its data has been handed off",
+        admin_addr
+    );
+    Ok(())
+}
+
 fn get_initial_sn_launch_args(
     node_directory_path: PathBuf,
     node_data_dir_name: &str,
@@ -10,15 +10,18 @@ use super::{
     files_get::{process_get_command, FileExistsAction, ProgressIndicator},
     helpers::{
         gen_processed_files_table, get_from_arg_or_stdin, get_from_stdin, get_target_url, if_tty,
-        notice_dry_run, parse_stdin_arg, pluralize, serialise_output,
+        notice_dry_run, parse_stdin_arg, pluralize, print_version_graph, serialise_output,
+        xorname_to_hex,
     },
     OutputFmt,
 };
+use crate::operations::config::Config;
 use ansi_term::Colour;
 use bytes::Bytes;
 use clap::Subcommand;
 use color_eyre::{eyre::bail, eyre::eyre, Result};
 use comfy_table::Table;
+use futures::StreamExt;
 use serde::Serialize;
 use sn_api::{
     files::{FilesMap, ProcessedFiles},
@@ -30,11 +33,12 @@ use std::{
     collections::{BTreeMap, HashMap},
     path::{Component, Path, PathBuf},
 };
-use tracing::debug;
+use tracing::{debug, warn};
 
 type FileDetails = BTreeMap<String, String>;
 
 const UNKNOWN_FILE_NAME: &str = "<unknown>";
+const UPLOAD_JOURNAL_FILENAME: &str = "upload_journal";
 
 // Differentiates between nodes in a file system.
 #[derive(Debug, Serialize, PartialEq)]
@@ -105,6 +109,9 @@ pub enum FilesSubCommands {
         /// Follow symlinks
         #[clap(short = 'l', long = "follow-links")]
         follow_links: bool,
+        /// Resume a previous upload which was interrupted, skipping the files and chunks already stored
+        #[clap(long = "resume")]
+        resume: bool,
     },
     /// Get a file or folder from the SAFE Network
     Get {
@@ -141,6 +148,9 @@ pub enum FilesSubCommands {
         /// Automatically update the NRS name to link to the new version of the FilesContainer. This is only allowed if an NRS URL was provided, and if the NRS name is currently linked to a specific version of the FilesContainer
         #[clap(short = 'u', long = "update-nrs")]
         update_nrs: bool,
+        /// Resume a previous sync which was interrupted, skipping the files and chunks already stored
+        #[clap(long = "resume")]
+        resume: bool,
     },
     #[clap(name = "add")]
     /// Add a file to an existing FilesContainer on the network
@@ -192,12 +202,43 @@ pub enum FilesSubCommands {
         #[clap(short = 'd', long = "details")]
         details: bool,
     },
+    #[clap(name = "log")]
+    /// Show the graph of versions of an existing FilesContainer on the network
+    Log {
+        /// The target FilesContainer, optionally including a version to show the history from
+        target: Option<String>,
+        /// Maximum number of levels of previous versions to show (default is to show all of them)
+        #[clap(short = 'd', long = "depth")]
+        depth: Option<usize>,
+    },
+    #[clap(name = "merge")]
+    /// Merge the versions of an existing FilesContainer which were written concurrently into a new version, as per the conflict policy
+    Merge {
+        /// The target FilesContainer to merge the versions of
+        target: Option<String>,
+    },
+    #[clap(name = "watch")]
+    /// Watch an existing FilesContainer on the network, listing its files each time a new version is written
+    Watch {
+        /// The target FilesContainer to watch
+        target: Option<String>,
+    },
+    #[clap(name = "verify")]
+    /// Verify that each chunk of a file can be retrieved from each of the nodes expected to hold a copy of it
+    Verify {
+        /// The URL of the file to verify
+        target: Option<String>,
+        /// Store again the chunks found missing, from the local copy of the file at this location
+        #[clap(long = "repair", value_name = "LOCAL_COPY")]
+        repair: Option<String>,
+    },
 }
 
 pub async fn files_commander(
     cmd: FilesSubCommands,
     output_fmt: OutputFmt,
-    safe: &Safe,
+    safe: &mut Safe,
+    config: &Config,
 ) -> Result<()> {
     match cmd {
         FilesSubCommands::Put {
@@ -205,11 +246,15 @@ pub async fn files_commander(
             dst,
             recursive,
             follow_links,
+            resume,
         } => {
             // create FilesContainer from a given path to local files/folders
             if safe.dry_run_mode && OutputFmt::Pretty == output_fmt {
                 notice_dry_run();
             }
+            if !safe.dry_run_mode {
+                safe.enable_upload_journal(&upload_journal_path(config), resume)?;
+            }
             let (files_container_xorurl, processed_files, _) = safe
                 .files_container_create_from(&location, dst.as_deref(), recursive, follow_links)
                 .await?;
@@ -237,12 +282,16 @@ pub async fn files_commander(
             follow_links,
             delete,
             update_nrs,
+            resume,
         } => {
             let target = get_from_arg_or_stdin(target, None)?;
             let mut target_url = get_target_url(&target)?;
             if safe.dry_run_mode && OutputFmt::Pretty == output_fmt {
                 notice_dry_run();
             }
+            if !safe.dry_run_mode {
+                safe.enable_upload_journal(&upload_journal_path(config), resume)?;
+            }
             // Update the FilesContainer on the Network
             let (content, processed_files) = safe
                 .files_container_sync(
@@ -399,6 +448,29 @@ pub async fn files_commander(
         FilesSubCommands::Tree { target, details } => {
             process_tree_command(safe, target, details, output_fmt).await
         }
+        FilesSubCommands::Log { target, depth } => {
+            let target_url =
+                get_from_arg_or_stdin(target, Some("...awaiting target URl from STDIN"))?;
+
+            debug!("Getting history of container {:?}", target_url);
+            let history = safe.register_history(&target_url, depth).await?;
+            print_version_graph(
+                &format!(
+                    "FilesContainer at \"{}\" has {} {}:",
+                    target_url,
+                    history.len(),
+                    pluralize("version", "versions", history.len() as u64)
+                ),
+                &history,
+                output_fmt,
+            );
+            Ok(())
+        }
+        FilesSubCommands::Merge { target } => process_merge_command(safe, target, output_fmt).await,
+        FilesSubCommands::Watch { target } => process_watch_command(safe, target, output_fmt).await,
+        FilesSubCommands::Verify { target, repair } => {
+            process_verify_command(safe, target, repair, output_fmt).await
+        }
         FilesSubCommands::Get {
             source,
             dst,
@@ -409,6 +481,164 @@ pub async fn files_commander(
     }
 }
 
+// processes the `safe files merge` command.
+async fn process_merge_command(
+    safe: &Safe,
+    target: Option<XorUrl>,
+    output_fmt: OutputFmt,
+) -> Result<()> {
+    let target_url = get_from_arg_or_stdin(target, Some("...awaiting target URl from STDIN"))?;
+
+    if safe.dry_run_mode && OutputFmt::Pretty == output_fmt {
+        notice_dry_run();
+    }
+
+    debug!("Merging versions of container {:?}", target_url);
+    let (version, files_map) = safe
+        .files_container_merge(&target_url)
+        .await?
+        .ok_or_else(|| eyre!("FilesContainer found at \"{}\" is empty", target_url))?;
+
+    if OutputFmt::Pretty == output_fmt {
+        println!(
+            "FilesContainer at \"{}\" is now at version: {}",
+            target_url, version
+        );
+    } else {
+        println!(
+            "{}",
+            serialise_output(&(&target_url, version, files_map), output_fmt)
+        );
+    }
+
+    Ok(())
+}
+
+// processes the `safe files watch` command.
+async fn process_watch_command(
+    safe: &Safe,
+    target: Option<XorUrl>,
+    output_fmt: OutputFmt,
+) -> Result<()> {
+    let target_url = get_from_arg_or_stdin(target, Some("...awaiting target URl from STDIN"))?;
+
+    debug!("Watching container {:?}", target_url);
+    let versions = safe.files_container_watch(&target_url).await?;
+    futures::pin_mut!(versions);
+
+    if OutputFmt::Pretty == output_fmt {
+        println!(
+            "Watching FilesContainer at \"{}\" for new versions...",
+            target_url
+        );
+    }
+    while let Some(next_version) = versions.next().await {
+        // a version which cannot be fetched doesn't stop us from watching for the next ones
+        let (version, files_map) = match next_version {
+            Ok(next_version) => next_version,
+            Err(err) => {
+                warn!("Failed to fetch a new version of {}: {}", target_url, err);
+                eprintln!("Warning: failed to fetch a new version: {}", err);
+                continue;
+            }
+        };
+        if OutputFmt::Pretty == output_fmt {
+            let (total, filtered_filesmap) = filter_files_map(&files_map, &target_url)?;
+            print_files_map(&filtered_filesmap, total, Some(version), &target_url);
+        } else {
+            println!(
+                "{}",
+                serialise_output(&(&target_url, version, files_map), output_fmt)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+// processes the `safe files verify` command.
+async fn process_verify_command(
+    safe: &Safe,
+    target: Option<XorUrl>,
+    repair: Option<String>,
+    output_fmt: OutputFmt,
+) -> Result<()> {
+    let target_url = get_from_arg_or_stdin(target, Some("...awaiting target URl from STDIN"))?;
+
+    debug!("Verifying file at {:?}", target_url);
+    let verification = safe.files_verify(&target_url).await?;
+
+    let repaired = match &repair {
+        Some(location) if !verification.is_complete() => {
+            if safe.dry_run_mode && OutputFmt::Pretty == output_fmt {
+                notice_dry_run();
+            }
+            safe.files_repair(Path::new(location), &verification)
+                .await?
+        }
+        _ => vec![],
+    };
+
+    if OutputFmt::Pretty == output_fmt {
+        if verification.is_complete() {
+            println!(
+                "All {} {} of the file at \"{}\" were retrieved from all their holders",
+                verification.chunks.len(),
+                pluralize("chunk", "chunks", verification.chunks.len() as u64),
+                target_url
+            );
+        } else {
+            println!(
+                "{} out of {} {} of the file at \"{}\" could not be retrieved from some of their holders:",
+                verification.missing.len(),
+                verification.chunks.len(),
+                pluralize("chunk", "chunks", verification.chunks.len() as u64),
+                target_url
+            );
+            let mut table = Table::new();
+            table.add_row(&vec!["CHUNK", "MISSING AT HOLDERS"]);
+            for (name, holders) in &verification.missing {
+                let holders = holders
+                    .iter()
+                    .map(|index| format!("#{}", index))
+                    .collect::<Vec<_>>();
+                table.add_row(&vec![xorname_to_hex(name), holders.join(", ")]);
+            }
+            println!("{table}");
+
+            if repair.is_some() {
+                println!(
+                    "{} {} stored again from the local copy of the file",
+                    repaired.len(),
+                    pluralize("chunk was", "chunks were", repaired.len() as u64)
+                );
+            } else {
+                println!("The chunks missing can be stored again from a local copy of the file with the --repair option");
+            }
+        }
+    } else {
+        let missing = verification
+            .missing
+            .iter()
+            .map(|(name, holders)| (xorname_to_hex(name), holders))
+            .collect::<BTreeMap<_, _>>();
+        let repaired = repaired.iter().map(xorname_to_hex).collect::<Vec<_>>();
+        println!(
+            "{}",
+            serialise_output(
+                &(&target_url, verification.chunks.len(), missing, repaired),
+                output_fmt
+            )
+        );
+    }
+
+    if !verification.is_complete() && repair.is_none() {
+        bail!("Some chunks of the file could not be retrieved from all their holders");
+    }
+
+    Ok(())
+}
+
 // processes the `safe files tree` command.
 async fn process_tree_command(
     safe: &Safe,
@@ -458,6 +688,14 @@ async fn process_tree_command(
     Ok(())
 }
 
+// Location of the journal used to resume interrupted uploads
+fn upload_journal_path(config: &Config) -> PathBuf {
+    let mut path = config.cli_config_path.clone();
+    path.pop();
+    path.push(UPLOAD_JOURNAL_FILENAME);
+    path
+}
+
 fn print_serialized_output(
     xorurl: XorUrl,
     change_version: Option<VersionHash>,
@@ -10,20 +10,17 @@ use super::{
     helpers::{div_or, pluralize, processed_files_err_report, prompt_user},
     OutputFmt,
 };
-use bytes::Buf;
 use color_eyre::{eyre::bail, eyre::eyre, eyre::WrapErr, Result};
 use console::Term;
 use sn_api::{
     files::{FilesMap, GetAttr},
-    resolver::Range,
     resolver::SafeData,
     DataType, Result as ApiResult, Safe, SafeUrl, XorUrl,
 };
-use std::{
-    collections::BTreeMap,
-    fs,
-    io::{BufWriter, Write},
-    path::Path,
+use std::{collections::BTreeMap, fs, path::Path};
+use tokio::{
+    fs::File,
+    io::{AsyncWriteExt, BufWriter},
 };
 use tracing::{debug, info, trace, warn};
 
@@ -619,51 +616,36 @@ fn denormalize_slashes(p: &str) -> String {
 async fn download_file_from_net(safe: &Safe, xorurl: &str, path: &Path, size: u64) -> Result<u64> {
     debug!("downloading file {} to {}", xorurl, path.display());
 
-    // TODO: download the file by concurrently (spawning tasks/threads) pulling chunks.
-    // The chunk_size can be based on https://stackoverflow.com/questions/8803515/optimal-buffer-size-for-write2
-    // which can be 4096 to match common disk block size, but that seems a bit small for the
-    // network, so I multiplied by 16. Perhaps should make it a param so caller can decide.
-    let mut rcvd: u64 = 0;
-    let mut bytes_written: u64 = 0;
+    if SafeUrl::from_url(xorurl)?.data_type() != DataType::File {
+        bail!("URL target is not immutable data");
+    }
 
-    let fh = file_create(path)?;
+    let fh = File::from_std(file_create(path)?);
     let mut stream = BufWriter::new(fh);
 
-    // gets public or private, based on xorurl type
-    let filedata = files_get(safe, xorurl, None).await?;
-    bytes_written += stream_write(&mut stream, &filedata, path)? as u64;
-    rcvd += filedata.len() as u64;
-    trace!("received {} bytes of {}", rcvd, size,);
+    // gets public or private, based on xorurl type, and streams it
+    // into the file a few chunks at a time rather than holding it all in memory.
+    let bytes_written = safe.files_get_stream(xorurl, &mut stream).await?;
+    trace!("received {} bytes of {}", bytes_written, size);
 
     // Close may generate an error, so we do a flush/sync first to detect such.
     // see https://github.com/rust-lang/rust/pull/63410#issuecomment-519965351
-    let fh = bufwriter_into_inner(stream, path)?;
-    file_sync_all(&fh, path)?;
+    stream
+        .flush()
+        .await
+        .with_context(|| format!("Error flushing file \"{}\"", path.display()))?;
+    file_sync_all(&stream.into_inner(), path).await?;
 
-    Ok(bytes_written as u64)
+    Ok(bytes_written)
 }
 
 // syncs file to filesystem.
-fn file_sync_all(f: &fs::File, path: &Path) -> Result<()> {
+async fn file_sync_all(f: &File, path: &Path) -> Result<()> {
     f.sync_all()
+        .await
         .with_context(|| format!("Error syncing file: \"{}\"", path.display(),))
 }
 
-// causes BufWriter to flush() file.
-fn bufwriter_into_inner<W: Write>(w: BufWriter<W>, path: &Path) -> Result<W> {
-    match w.into_inner() {
-        Ok(inner) => Ok(inner),
-        Err(err) => Err(eyre!("Error flushing file \"{}\": {}", path.display(), err)),
-    }
-}
-
-// Writes data to a file/stream.
-fn stream_write(writer: &mut dyn Write, data: &[u8], path: &Path) -> Result<usize> {
-    writer
-        .write(data)
-        .with_context(|| format!("Error writing to file: \"{}\"", path.display(),))
-}
-
 // Creates a file, ready for writing.
 fn file_create(path: &Path) -> Result<fs::File> {
     fs::File::create(path).with_context(|| format!("Couldn't create file: \"{}\"", path.display(),))
@@ -680,15 +662,3 @@ fn create_dir_all(dir_path: &Path) -> Result<()> {
     fs::create_dir_all(&dir_path)
         .with_context(|| format!("Couldn't create path: \"{}\"", dir_path.display(),))
 }
-
-/// # Get Public or Private file
-/// Get immutable files from the network.
-pub async fn files_get(safe: &Safe, url: &str, range: Range) -> Result<Vec<u8>> {
-    match SafeUrl::from_url(url)?.data_type() {
-        DataType::File => {
-            let bytes = safe.files_get(url, range).await?;
-            Ok(bytes.chunk().to_vec())
-        }
-        _ => Err(eyre!("URL target is not immutable data")),
-    }
-}
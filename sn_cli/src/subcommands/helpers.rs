@@ -8,18 +8,22 @@
 
 use super::OutputFmt;
 use ansi_term::Style;
-use color_eyre::{eyre::bail, eyre::eyre, eyre::WrapErr, Result};
+use color_eyre::{eyre::bail, eyre::eyre, eyre::WrapErr, Help, Result};
 use comfy_table::{Cell, CellAlignment, Table};
 use num_traits::Float;
-use serde::ser::Serialize;
+use serde::Serialize;
 use sn_api::{
     files::{FilesMapChange, ProcessedFiles},
     multimap::Multimap,
-    nrs::NrsMap,
+    nrs::{NrsMap, VersionHash},
+    register::{HistoryEntry, User},
     wallet::Dbc,
-    Safe, SafeUrl,
+    PublicKey, Safe, SafeUrl,
+};
+use std::{
+    collections::BTreeSet,
+    io::{stdin, stdout, Read, Write},
 };
-use std::io::{stdin, stdout, Read, Write};
 use tracing::{debug, warn};
 use xor_name::XorName;
 
@@ -238,6 +242,52 @@ pub fn print_nrs_map(nrs_map: &NrsMap) {
     });
 }
 
+// A version in the graph of versions of some content, as output by the `log` commands.
+#[derive(Serialize)]
+struct VersionGraphNode {
+    version: String,
+    previous_versions: Vec<String>,
+    latest: bool,
+}
+
+// prints the graph of versions from a content's history, newest version first.
+pub fn print_version_graph(header: &str, history: &[HistoryEntry], output_fmt: OutputFmt) {
+    let previous: BTreeSet<_> = history
+        .iter()
+        .flat_map(|item| item.children.iter())
+        .collect();
+    let graph: Vec<VersionGraphNode> = history
+        .iter()
+        .rev()
+        .map(|item| VersionGraphNode {
+            version: VersionHash::from(&item.hash).to_string(),
+            previous_versions: item
+                .children
+                .iter()
+                .map(|hash| VersionHash::from(hash).to_string())
+                .collect(),
+            latest: !previous.contains(&item.hash),
+        })
+        .collect();
+
+    if OutputFmt::Pretty == output_fmt {
+        println!("{}", header);
+        let mut table = Table::new();
+        table.add_row(&vec!["", "Version", "Previous versions"]);
+        for node in graph.iter() {
+            table.add_row(&vec![
+                if node.latest { "*" } else { "" },
+                &node.version,
+                &node.previous_versions.join("\n"),
+            ]);
+        }
+        println!("{table}");
+        println!("Versions marked with * are the latest ones in this log.");
+    } else {
+        println!("{}", serialise_output(&graph, output_fmt));
+    }
+}
+
 // returns singular or plural version of string, based on count.
 pub fn pluralize<'a>(singular: &'a str, plural: &'a str, count: u64) -> &'a str {
     if count == 1 {
@@ -268,6 +318,25 @@ pub fn div_or<X: Float>(num: X, den: X, default: X) -> X {
 /// Get the target URL from the link as a string.
 ///
 /// If the user hasn't prefixed the link with `safe://`, we'll do that for them here.
+// Parses a user of a Register, which is either a hex-encoded BLS public key or 'anyone'
+pub fn parse_register_user(user: &str) -> Result<User> {
+    if user.eq_ignore_ascii_case("anyone") {
+        return Ok(User::Anyone);
+    }
+    let public_key = PublicKey::bls_from_hex(user).map_err(|err| {
+        eyre!(err).suggestion("Please provide a hex-encoded BLS public key, or 'anyone'.")
+    })?;
+    Ok(User::Key(public_key))
+}
+
+// Formats a user of a Register the same way it's parsed by `parse_register_user`
+pub fn register_user_to_string(user: User) -> String {
+    match user {
+        User::Anyone => "anyone".to_string(),
+        User::Key(public_key) => format!("{:x}", public_key),
+    }
+}
+
 pub fn get_target_url(link: &str) -> Result<SafeUrl> {
     if !link.starts_with("safe://") {
         return Ok(SafeUrl::from_url(&format!("safe://{}", link))?);
@@ -18,6 +18,7 @@ pub mod keys;
 pub mod networks;
 pub mod node;
 pub mod nrs;
+pub mod register;
 pub mod safe_id;
 pub mod setup;
 pub mod update;
@@ -92,6 +93,9 @@ pub enum SubCommands {
     #[clap(name = "nrs", subcommand, global_settings(&[AppSettings::DisableVersion]))]
     /// Manage public names on the SAFE Network
     Nrs(nrs::NrsSubCommands),
+    #[clap(name = "register", subcommand, global_settings(&[AppSettings::DisableVersion]))]
+    /// Manage the owner and permissions of Registers on the SAFE Network
+    Register(register::RegisterSubCommands),
     #[clap(name = "keys", subcommand, global_settings(&[AppSettings::DisableVersion]))]
     /// Manage keys on the SAFE Network
     Keys(keys::KeysSubCommands),
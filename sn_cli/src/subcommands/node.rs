@@ -117,6 +117,18 @@ pub enum NodeSubCommands {
         #[clap(long = "node-path", env = "SN_NODE_PATH")]
         node_path: Option<PathBuf>,
     },
+    /// Make a running node leave its section gracefully and shut down. Its data is handed
+    /// off to other nodes of the section before it exits.
+    #[clap(name = "leave")]
+    Leave {
+        /// Address of the admin endpoint of the node, as set with its --admin-addr option
+        #[clap(long = "admin-addr")]
+        admin_addr: SocketAddr,
+        /// Path of the file holding the token for the admin endpoint, i.e. the `admin_token`
+        /// file in the root dir of the node
+        #[clap(long = "admin-token-file")]
+        admin_token_file: PathBuf,
+    },
     #[clap(name = "update")]
     /// Update to latest sn_node released version
     Update {
@@ -224,6 +236,10 @@ pub async fn node_commander(
             Ok(())
         }
         Some(NodeSubCommands::Killall { node_path }) => node_shutdown(node_path),
+        Some(NodeSubCommands::Leave {
+            admin_addr,
+            admin_token_file,
+        }) => node_leave(admin_addr, &admin_token_file),
         Some(NodeSubCommands::Update { node_path }) => node_update(node_path),
         None => Err(eyre!("Missing node subcommand")),
     }
@@ -6,17 +6,29 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use std::fmt::Write as _;
+use std::{collections::BTreeSet, fmt::Write as _, path::PathBuf};
 
 use super::{
-    helpers::{get_from_arg_or_stdin, get_target_url, serialise_output},
+    helpers::{
+        get_from_arg_or_stdin, get_target_url, parse_register_user, pluralize, print_version_graph,
+        register_user_to_string, serialise_output,
+    },
     OutputFmt,
 };
+use crate::operations::config::Config;
 use clap::Subcommand;
 use color_eyre::{eyre::eyre, Help, Result};
 use comfy_table::Table;
 use sn_api::Error::{InvalidInput, NetDataError, NrsNameAlreadyExists, UnversionedContentError};
-use sn_api::{Safe, SafeUrl};
+use sn_api::{
+    nrs::VersionHash,
+    register::{Permissions, User},
+    resolver::SafeData,
+    Safe, SafeUrl,
+};
+use tokio::fs;
+
+const REGISTERED_TOP_NAMES_FILENAME: &str = "nrs_top_names.json";
 
 #[derive(Subcommand, Debug)]
 pub enum NrsSubCommands {
@@ -51,6 +63,47 @@ pub enum NrsSubCommands {
         /// This should be wrapped in double quotes on bash based systems.
         #[clap(short = 'l', long = "link")]
         link: Option<String>,
+        /// Grant write access to the topname to another user, so they can add subnames to it.
+        /// It should be a hex-encoded BLS public key. This argument can be used multiple times
+        /// to grant write access to several users.
+        #[clap(short = 'w', long = "writer")]
+        writers: Vec<String>,
+    },
+    #[clap(name = "transfer")]
+    /// Transfer the ownership of a registered topname to another user. Only the owner of the
+    /// topname can do this.
+    Transfer {
+        /// The registered topname
+        name: String,
+        /// The new owner, which should be a hex-encoded BLS public key
+        new_owner: String,
+    },
+    #[clap(name = "grant")]
+    /// Grant write access to a registered topname to another user, so they can add subnames to
+    /// it. Only the owner of the topname can do this.
+    Grant {
+        /// The registered topname
+        name: String,
+        /// The user to grant write access to: a hex-encoded BLS public key, or 'anyone'
+        user: String,
+    },
+    #[clap(name = "revoke")]
+    /// Revoke write access to a registered topname from another user. Only the owner of the
+    /// topname can do this.
+    Revoke {
+        /// The registered topname
+        name: String,
+        /// The user to revoke write access from: a hex-encoded BLS public key, or 'anyone'
+        user: String,
+    },
+    #[clap(name = "log")]
+    /// Show the graph of versions of the NRS Map of a registered top name
+    Log {
+        /// The registered topname
+        name: String,
+        /// Maximum number of levels of previous versions to show (default is to show all of them)
+        #[clap(short = 'd', long = "depth")]
+        depth: Option<usize>,
     },
     #[clap(name = "remove")]
     /// Remove a subname from an NRS name
@@ -58,31 +111,97 @@ pub enum NrsSubCommands {
         /// The name to remove
         name: String,
     },
+    #[clap(name = "ls")]
+    /// List all the public names of a registered top name along with their links and versions
+    Ls {
+        /// The registered topname
+        name: String,
+    },
+    #[clap(name = "show")]
+    /// Show the link of a public name and the chain of steps its resolution goes through
+    Show {
+        /// The public name to show, e.g. "subname.topname". A version can be provided as well,
+        /// e.g. "subname.topname?v=<version>"
+        name: String,
+    },
+    #[clap(name = "find-links")]
+    /// Find the public names which link to the given content, regardless of the version and path
+    /// of their links, e.g. to find which names point to a FilesContainer before deleting it
+    FindLinks {
+        /// The safe:// URL of the content to look for
+        url: String,
+        /// The registered topnames to look into. The network has no index of topnames, so only
+        /// the topnames given here are searched. If none are given, the topnames registered with
+        /// this CLI (with `nrs register` or `nrs add --register-top-name`) are searched.
+        top_names: Vec<String>,
+    },
 }
 
-pub async fn nrs_commander(cmd: NrsSubCommands, output_fmt: OutputFmt, safe: &Safe) -> Result<()> {
+pub async fn nrs_commander(
+    cmd: NrsSubCommands,
+    output_fmt: OutputFmt,
+    safe: &Safe,
+    config: &Config,
+) -> Result<()> {
     match cmd {
-        NrsSubCommands::Register { name, link } => {
-            run_register_subcommand(name, link, safe, output_fmt).await
+        NrsSubCommands::Register {
+            name,
+            link,
+            writers,
+        } => run_register_subcommand(name, link, writers, safe, config, output_fmt).await,
+        NrsSubCommands::Transfer { name, new_owner } => {
+            run_transfer_subcommand(name, new_owner, safe, output_fmt).await
+        }
+        NrsSubCommands::Grant { name, user } => {
+            run_set_write_access_subcommand(name, user, true, safe, output_fmt).await
+        }
+        NrsSubCommands::Revoke { name, user } => {
+            run_set_write_access_subcommand(name, user, false, safe, output_fmt).await
         }
         NrsSubCommands::Add {
             public_name: name,
             link,
             register_top_name,
             default,
-        } => run_add_subcommand(name, link, register_top_name, default, safe, output_fmt).await,
+        } => {
+            run_add_subcommand(
+                name,
+                link,
+                register_top_name,
+                default,
+                safe,
+                config,
+                output_fmt,
+            )
+            .await
+        }
         NrsSubCommands::Remove { name } => run_remove_subcommand(name, safe, output_fmt).await,
+        NrsSubCommands::Log { name, depth } => {
+            run_log_subcommand(name, depth, safe, output_fmt).await
+        }
+        NrsSubCommands::Ls { name } => run_ls_subcommand(name, safe, output_fmt).await,
+        NrsSubCommands::Show { name } => run_show_subcommand(name, safe, output_fmt).await,
+        NrsSubCommands::FindLinks { url, top_names } => {
+            run_find_links_subcommand(url, top_names, safe, config, output_fmt).await
+        }
     }
 }
 
 async fn run_register_subcommand(
     name: String,
     link: Option<String>,
+    writers: Vec<String>,
     safe: &Safe,
+    config: &Config,
     output_fmt: OutputFmt,
 ) -> Result<()> {
-    match safe.nrs_create(&name).await {
+    let writers = writers
+        .iter()
+        .map(|writer| parse_register_user(writer))
+        .collect::<Result<BTreeSet<_>>>()?;
+    match safe.nrs_create_with_writers(&name, &writers).await {
         Ok(topname_url) => {
+            record_registered_top_name(config, &name).await?;
             let mut summary = String::new();
             write!(
                 summary,
@@ -137,6 +256,7 @@ async fn run_add_subcommand(
     register_top_name: bool,
     default: bool,
     safe: &Safe,
+    config: &Config,
     output_fmt: OutputFmt,
 ) -> Result<()> {
     let link = get_from_arg_or_stdin(link, Some("...awaiting link URL from stdin"))?;
@@ -152,6 +272,7 @@ async fn run_add_subcommand(
 
     let mut summary_header = String::new();
     if topname_was_registered {
+        record_registered_top_name(config, url.top_name()).await?;
         writeln!(summary_header, "New NRS Map created.")?;
         write!(
             summary_header,
@@ -224,6 +345,274 @@ async fn run_remove_subcommand(name: String, safe: &Safe, output_fmt: OutputFmt)
     }
 }
 
+async fn run_transfer_subcommand(
+    name: String,
+    new_owner: String,
+    safe: &Safe,
+    output_fmt: OutputFmt,
+) -> Result<()> {
+    let top_name = name.replace("safe://", "");
+    let new_owner = match parse_register_user(&new_owner)? {
+        User::Anyone => {
+            return Err(eyre!("A topname cannot be owned by anyone")
+                .suggestion("Please provide the public key of the new owner."))
+        }
+        user => user,
+    };
+    safe.nrs_transfer(&top_name, new_owner)
+        .await
+        .map_err(|error| {
+            eyre!(error).wrap_err(format!(
+                "Failed to transfer the ownership of \"safe://{}\"",
+                top_name
+            ))
+        })?;
+
+    let new_owner = register_user_to_string(new_owner);
+    if OutputFmt::Pretty == output_fmt {
+        println!(
+            "Ownership of \"safe://{}\" transferred to {}",
+            top_name, new_owner
+        );
+    } else {
+        println!("{}", serialise_output(&(top_name, new_owner), output_fmt));
+    }
+    Ok(())
+}
+
+async fn run_set_write_access_subcommand(
+    name: String,
+    user: String,
+    allowed: bool,
+    safe: &Safe,
+    output_fmt: OutputFmt,
+) -> Result<()> {
+    let top_name = name.replace("safe://", "");
+    let user = parse_register_user(&user)?;
+    safe.nrs_set_permissions(&top_name, user, Some(Permissions::new(allowed)))
+        .await
+        .map_err(|error| {
+            eyre!(error).wrap_err(format!(
+                "Failed to change who can write to \"safe://{}\"",
+                top_name
+            ))
+        })?;
+
+    let user = register_user_to_string(user);
+    if OutputFmt::Pretty == output_fmt {
+        if allowed {
+            println!(
+                "Write access to \"safe://{}\" granted to {}",
+                top_name, user
+            );
+        } else {
+            println!(
+                "Write access to \"safe://{}\" revoked from {}",
+                top_name, user
+            );
+        }
+    } else {
+        println!(
+            "{}",
+            serialise_output(&(top_name, user, allowed), output_fmt)
+        );
+    }
+    Ok(())
+}
+
+async fn run_log_subcommand(
+    name: String,
+    depth: Option<usize>,
+    safe: &Safe,
+    output_fmt: OutputFmt,
+) -> Result<()> {
+    let top_name = name.replace("safe://", "");
+    let history = safe.nrs_history(&top_name, depth).await.map_err(|error| {
+        eyre!(error).wrap_err(format!(
+            "Failed to get the history of the NRS Map for \"safe://{}\"",
+            top_name
+        ))
+    })?;
+    print_version_graph(
+        &format!(
+            "NRS Map for \"safe://{}\" has {} {}:",
+            top_name,
+            history.len(),
+            pluralize("version", "versions", history.len() as u64)
+        ),
+        &history,
+        output_fmt,
+    );
+    Ok(())
+}
+
+async fn run_ls_subcommand(name: String, safe: &Safe, output_fmt: OutputFmt) -> Result<()> {
+    let top_name = name.replace("safe://", "");
+    let entries = safe.nrs_list(&top_name).await.map_err(|error| {
+        eyre!(error).wrap_err(format!(
+            "Failed to list the public names of \"safe://{}\"",
+            top_name
+        ))
+    })?;
+
+    if OutputFmt::Pretty == output_fmt {
+        println!(
+            "NRS Map for \"safe://{}\" has {} {}:",
+            top_name,
+            entries.len(),
+            pluralize("public name", "public names", entries.len() as u64)
+        );
+        if !entries.is_empty() {
+            let mut table = Table::new();
+            table.add_row(&vec!["Public Name", "Version", "Link"]);
+            for (public_name, version, link) in entries.iter() {
+                table.add_row(&vec![
+                    public_name.clone(),
+                    version.to_string(),
+                    link.to_string(),
+                ]);
+            }
+            println!("{table}");
+        }
+    } else {
+        let entries: Vec<_> = entries
+            .into_iter()
+            .map(|(public_name, version, link)| (public_name, version.to_string(), link))
+            .collect();
+        println!("{}", serialise_output(&entries, output_fmt));
+    }
+    Ok(())
+}
+
+async fn run_show_subcommand(name: String, safe: &Safe, output_fmt: OutputFmt) -> Result<()> {
+    let url = get_target_url(&name)?;
+    let resolved_content = safe.inspect(&url.to_string()).await?;
+    if OutputFmt::Pretty != output_fmt {
+        println!(
+            "{}",
+            serialise_output(&(url.to_string(), resolved_content), output_fmt)
+        );
+        return Ok(());
+    }
+
+    println!("Public name: {}", url.public_name());
+    println!("Top name: {}", url.top_name());
+    for content in resolved_content.iter() {
+        match content {
+            SafeData::NrsMapContainer { xorurl, .. } => {
+                println!("NRS Map Container: {}", xorurl);
+            }
+            SafeData::NrsEntry {
+                resolves_into,
+                version,
+                ..
+            } => {
+                if let Some(version) = version {
+                    println!("Version: {}", VersionHash::from(version));
+                }
+                println!("Link: {}", resolves_into);
+            }
+            _ => {}
+        }
+    }
+
+    let mut table = Table::new();
+    table.add_row(&vec!["Step", "Data", "XOR-URL"]);
+    for (i, content) in resolved_content.iter().enumerate() {
+        let (data, xorurl) = match content {
+            SafeData::SafeKey { xorurl, .. } => ("SafeKey", xorurl),
+            SafeData::FilesContainer { xorurl, .. } => ("FilesContainer", xorurl),
+            SafeData::PublicFile { xorurl, .. } => ("PublicFile", xorurl),
+            SafeData::NrsMapContainer { xorurl, .. } => ("NrsMapContainer", xorurl),
+            SafeData::NrsEntry { xorurl, .. } => ("NrsEntry", xorurl),
+            SafeData::Multimap { xorurl, .. } => ("Multimap", xorurl),
+            SafeData::Register { xorurl, .. } => ("Register", xorurl),
+        };
+        table.add_row(&vec![(i + 1).to_string(), data.to_string(), xorurl.clone()]);
+    }
+    println!("Resolution chain:");
+    println!("{table}");
+    Ok(())
+}
+
+async fn run_find_links_subcommand(
+    url: String,
+    top_names: Vec<String>,
+    safe: &Safe,
+    config: &Config,
+    output_fmt: OutputFmt,
+) -> Result<()> {
+    let target = get_target_url(&url)?;
+    let top_names: Vec<String> = if top_names.is_empty() {
+        read_registered_top_names(config)
+            .await?
+            .into_iter()
+            .collect()
+    } else {
+        top_names
+            .iter()
+            .map(|name| name.replace("safe://", ""))
+            .collect()
+    };
+    if top_names.is_empty() {
+        return Err(eyre!("No topnames to look into")
+            .suggestion("Provide the topnames to search as arguments to the command."));
+    }
+    let found = safe
+        .nrs_find_links_to(&target.to_string(), &top_names)
+        .await?;
+
+    if OutputFmt::Pretty == output_fmt {
+        println!(
+            "Found {} {} linking to \"{}\"",
+            found.len(),
+            pluralize("public name", "public names", found.len() as u64),
+            target
+        );
+        if !found.is_empty() {
+            let mut table = Table::new();
+            table.add_row(&vec!["Public Name", "Link"]);
+            for (public_name, link) in found.iter() {
+                table.add_row(&vec![public_name.clone(), link.to_string()]);
+            }
+            println!("{table}");
+        }
+    } else {
+        println!("{}", serialise_output(&found, output_fmt));
+    }
+    Ok(())
+}
+
+// Location of the list of topnames registered with this CLI
+fn registered_top_names_path(config: &Config) -> PathBuf {
+    let mut path = config.cli_config_path.clone();
+    path.pop();
+    path.push(REGISTERED_TOP_NAMES_FILENAME);
+    path
+}
+
+async fn read_registered_top_names(config: &Config) -> Result<BTreeSet<String>> {
+    let path = registered_top_names_path(config);
+    if !path.exists() {
+        return Ok(BTreeSet::new());
+    }
+    let content = fs::read(&path).await?;
+    let top_names = serde_json::from_slice(&content)
+        .map_err(|err| eyre!("Failed to parse {}: {}", path.display(), err))?;
+    Ok(top_names)
+}
+
+// The network keeps no index of the topnames a user owns, so we keep track of the ones
+// registered from here for the commands which need to search them, e.g. `nrs find-links`.
+async fn record_registered_top_name(config: &Config, name: &str) -> Result<()> {
+    let mut top_names = read_registered_top_names(config).await?;
+    if top_names.insert(name.replace("safe://", "")) {
+        let content = serde_json::to_vec(&top_names)?;
+        fs::write(registered_top_names_path(config), content).await?;
+    }
+    Ok(())
+}
+
 async fn associate_url_with_public_name(
     public_name: &str,
     safe: &Safe,
@@ -308,3 +697,28 @@ fn get_topname_from_public_name(public_name: &str) -> Result<String> {
         .to_string();
     Ok(topname)
 }
+
+#[cfg(test)]
+mod registered_top_names {
+    use super::{read_registered_top_names, record_registered_top_name};
+    use crate::operations::config::Config;
+    use color_eyre::Result;
+
+    #[tokio::test]
+    async fn registered_top_names_should_be_recorded_once() -> Result<()> {
+        let config_dir = assert_fs::TempDir::new()?;
+        let config = Config::create_config(&config_dir, None).await?;
+        assert!(read_registered_top_names(&config).await?.is_empty());
+
+        record_registered_top_name(&config, "safe://bob").await?;
+        record_registered_top_name(&config, "alice").await?;
+        record_registered_top_name(&config, "bob").await?;
+
+        let top_names: Vec<String> = read_registered_top_names(&config)
+            .await?
+            .into_iter()
+            .collect();
+        assert_eq!(top_names, vec!["alice".to_string(), "bob".to_string()]);
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{
+    helpers::{parse_register_user, register_user_to_string, serialise_output},
+    OutputFmt,
+};
+use clap::Subcommand;
+use color_eyre::{eyre::eyre, Help, Result};
+use comfy_table::Table;
+use sn_api::{
+    register::{Action, Permissions, User},
+    Safe,
+};
+
+#[derive(Subcommand, Debug)]
+pub enum RegisterSubCommands {
+    #[clap(name = "policy")]
+    /// Show the owner of a Register and the permissions of its users
+    Policy {
+        /// The URL of the Register, e.g. a FilesContainer or an NRS Map
+        url: String,
+    },
+    #[clap(name = "grant")]
+    /// Grant write access to a Register. Only the owner of the Register can do this.
+    Grant {
+        /// The URL of the Register, e.g. a FilesContainer or an NRS Map
+        url: String,
+        /// The user to grant write access to: a hex-encoded BLS public key, or 'anyone'
+        user: String,
+    },
+    #[clap(name = "revoke")]
+    /// Revoke write access to a Register. Only the owner of the Register can do this.
+    Revoke {
+        /// The URL of the Register, e.g. a FilesContainer or an NRS Map
+        url: String,
+        /// The user to revoke write access from: a hex-encoded BLS public key, or 'anyone'
+        user: String,
+    },
+    #[clap(name = "transfer")]
+    /// Transfer the ownership of a Register to another user, keeping its current permissions.
+    /// Only the owner of the Register can do this.
+    Transfer {
+        /// The URL of the Register, e.g. a FilesContainer or an NRS Map
+        url: String,
+        /// The new owner: a hex-encoded BLS public key
+        new_owner: String,
+    },
+}
+
+pub async fn register_commander(
+    cmd: RegisterSubCommands,
+    output_fmt: OutputFmt,
+    safe: &Safe,
+) -> Result<()> {
+    match cmd {
+        RegisterSubCommands::Policy { url } => {
+            let policy = safe.register_policy(&url).await?;
+            if OutputFmt::Pretty == output_fmt {
+                println!(
+                    "Register at \"{}\" is owned by {}",
+                    url,
+                    register_user_to_string(policy.owner)
+                );
+                let mut table = Table::new();
+                table.add_row(&vec!["User", "Write"]);
+                for (user, permissions) in policy.permissions.iter() {
+                    let write = match permissions.is_allowed(Action::Write) {
+                        Some(true) => "allowed",
+                        Some(false) => "denied",
+                        None => "as for anyone",
+                    };
+                    table.add_row(&vec![register_user_to_string(*user), write.to_string()]);
+                }
+                println!("{table}");
+            } else {
+                println!("{}", serialise_output(&policy, output_fmt));
+            }
+        }
+        RegisterSubCommands::Grant { url, user } => {
+            let user = parse_register_user(&user)?;
+            safe.register_set_permissions(&url, user, Some(Permissions::new(true)))
+                .await?;
+            print_result(
+                output_fmt,
+                &url,
+                format!("Write access granted to {}", register_user_to_string(user)),
+            );
+        }
+        RegisterSubCommands::Revoke { url, user } => {
+            let user = parse_register_user(&user)?;
+            safe.register_set_permissions(&url, user, Some(Permissions::new(false)))
+                .await?;
+            print_result(
+                output_fmt,
+                &url,
+                format!(
+                    "Write access revoked from {}",
+                    register_user_to_string(user)
+                ),
+            );
+        }
+        RegisterSubCommands::Transfer { url, new_owner } => {
+            let new_owner = match parse_register_user(&new_owner)? {
+                User::Anyone => {
+                    return Err(eyre!("A Register cannot be owned by anyone")
+                        .suggestion("Please provide the public key of the new owner."))
+                }
+                user => user,
+            };
+            safe.register_transfer_ownership(&url, new_owner).await?;
+            print_result(
+                output_fmt,
+                &url,
+                format!(
+                    "Ownership transferred to {}",
+                    register_user_to_string(new_owner)
+                ),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn print_result(output_fmt: OutputFmt, url: &str, summary: String) {
+    if OutputFmt::Pretty == output_fmt {
+        println!("{} on Register at \"{}\"", summary, url);
+    } else {
+        println!("{}", serialise_output(&(url, summary), output_fmt));
+    }
+}
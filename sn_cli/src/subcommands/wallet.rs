@@ -7,14 +7,18 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::{
-    helpers::{get_from_arg_or_stdin, serialise_output},
+    helpers::{get_from_arg_or_stdin, notice_dry_run, serialise_output},
     OutputFmt,
 };
 use crate::operations::config::Config;
 use bls::{PublicKey, SecretKey};
 use clap::Subcommand;
 use color_eyre::{eyre::eyre, eyre::Error, Help, Result};
-use sn_api::{Error as ApiError, Safe};
+use comfy_table::Table;
+use sn_api::{
+    wallet::{CoinSelection, Token, WalletHistoryEntry, WalletReissuePlan, WalletTx},
+    Error as ApiError, Safe,
+};
 use sn_dbc::{Dbc, Error as DbcError};
 use std::path::{Path, PathBuf};
 use tokio::fs;
@@ -30,6 +34,12 @@ pub enum WalletSubCommands {
         /// The URL of wallet to query
         target: Option<String>,
     },
+    #[clap(name = "history")]
+    /// Show the history of transactions of a wallet, i.e. its deposits and reissues
+    History {
+        /// The URL of wallet to query
+        target: Option<String>,
+    },
     #[clap(name = "deposit")]
     /// Deposit a spendable DBC in a wallet. If the DBC is not bearer, we will try to deposit using
     /// the secret key configured for use with safe. If you wish to use a different key, use the
@@ -74,13 +84,18 @@ pub enum WalletSubCommands {
         /// A file path to store the content of the reissued DBC.
         #[clap(long = "save")]
         save: Option<PathBuf>,
+        /// How to choose the DBCs from the wallet to spend: [in-order, largest-first,
+        /// smallest-first, min-inputs, exact-match]. When running with --dry-run, the DBCs which
+        /// would be spent and the resulting change are reported, without spending anything.
+        #[clap(long = "coin-selection")]
+        coin_selection: Option<CoinSelection>,
     },
 }
 
 pub async fn wallet_commander(
     cmd: WalletSubCommands,
     output_fmt: OutputFmt,
-    safe: &Safe,
+    safe: &mut Safe,
     config: &Config,
 ) -> Result<()> {
     match cmd {
@@ -114,6 +129,22 @@ pub async fn wallet_commander(
 
             Ok(())
         }
+        WalletSubCommands::History { target } => {
+            let target = get_from_arg_or_stdin(
+                target,
+                Some("...awaiting wallet address/location from STDIN stream..."),
+            )?;
+
+            let history = safe.wallet_history(&target).await?;
+
+            if OutputFmt::Pretty == output_fmt {
+                print_wallet_history(&target, &history);
+            } else {
+                println!("{}", serialise_output(&history, output_fmt));
+            }
+
+            Ok(())
+        }
         WalletSubCommands::Deposit {
             wallet_url,
             name,
@@ -207,6 +238,7 @@ pub async fn wallet_commander(
             save,
             to,
             owned,
+            coin_selection,
         } => {
             if owned && to.is_some() {
                 return Err(eyre!(
@@ -231,6 +263,23 @@ pub async fn wallet_commander(
             } else {
                 None
             };
+            if let Some(selection) = coin_selection {
+                safe.wallet_coin_selection = selection;
+            }
+
+            if safe.dry_run_mode {
+                let plan = safe
+                    .wallet_reissue_plan(&from, vec![(amount.clone(), pk)])
+                    .await?;
+                if OutputFmt::Pretty == output_fmt {
+                    notice_dry_run();
+                    print_reissue_plan(&from, &amount, &plan);
+                } else {
+                    println!("{}", serialise_output(&plan, output_fmt));
+                }
+                return Ok(());
+            }
+
             let dbc = safe.wallet_reissue(&from, &amount, pk).await?;
             let dbc_hex = dbc.to_hex()?;
 
@@ -276,6 +325,88 @@ pub async fn wallet_commander(
     }
 }
 
+// Prints the DBCs a reissue would spend from a wallet, and the change it would leave
+fn print_reissue_plan(wallet_url: &str, amount: &str, plan: &WalletReissuePlan) {
+    println!(
+        "Reissuing {} safecoins from wallet at \"{}\" would spend the following DBCs:",
+        amount, wallet_url
+    );
+    let mut table = Table::new();
+    table.add_row(&vec!["Name", "Amount"]);
+    for (name, balance) in &plan.spent {
+        table.add_row(&vec![name.clone(), balance.to_string()]);
+    }
+    println!("{table}");
+
+    if plan.change.as_nano() > 0 {
+        println!(
+            "A change of {} safecoins would be deposited back into the wallet.",
+            plan.change
+        );
+    } else {
+        println!("No change would be left.");
+    }
+}
+
+// Prints the history of a wallet as a table, one row per transaction
+fn print_wallet_history(wallet_url: &str, history: &[WalletHistoryEntry]) {
+    println!(
+        "Wallet at \"{}\" has {} transactions recorded:",
+        wallet_url,
+        history.len()
+    );
+    if history.is_empty() {
+        return;
+    }
+
+    let mut table = Table::new();
+    table.add_row(&vec!["Date", "Transaction", "Amount", "Details"]);
+    for entry in history {
+        let (tx_type, amount, details) = match &entry.tx {
+            WalletTx::Deposit { name, amount } => (
+                "Deposit",
+                amount.to_string(),
+                format!("Deposited as '{}'", name),
+            ),
+            WalletTx::Reissue {
+                spent,
+                outputs,
+                change,
+            } => {
+                let mut details = outputs
+                    .iter()
+                    .map(|(amount, owner)| match owner {
+                        Some(pk) => format!("{} to {}", amount, pk.to_hex()),
+                        None => format!("{} as bearer", amount),
+                    })
+                    .collect::<Vec<_>>();
+                details.push(format!(
+                    "Spent: {}",
+                    spent
+                        .iter()
+                        .map(|(name, amount)| format!("'{}' ({})", name, amount))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ));
+                if let Some((name, amount)) = change {
+                    details.push(format!("Change: '{}' ({})", name, amount));
+                }
+                let total = outputs.iter().fold(Token::zero(), |total, (amount, _)| {
+                    total.checked_add(*amount).unwrap_or(total)
+                });
+                ("Reissue", total.to_string(), details.join("\n"))
+            }
+        };
+        table.add_row(&vec![
+            entry.timestamp.clone(),
+            tx_type.to_string(),
+            amount,
+            details,
+        ]);
+    }
+    println!("{table}");
+}
+
 /// Helper to get the secret key from the credentials that are configured for use with safe.
 ///
 /// Different error and suggestion messages need to be provided depending on the context in which
@@ -95,6 +95,40 @@ fn calling_safe_files_put_recursive() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn calling_safe_files_put_recursive_with_resume() -> Result<()> {
+    let content = safe_cmd_stdout(
+        ["files", "put", TEST_FOLDER, "--recursive", "--json"],
+        Some(0),
+    )?;
+    let (_, processed_files) = parse_files_put_or_sync_output(&content)?;
+
+    // resuming after a completed upload should reuse all the files already stored
+    let content = safe_cmd_stdout(
+        [
+            "files",
+            "put",
+            TEST_FOLDER,
+            "--recursive",
+            "--resume",
+            "--json",
+        ],
+        Some(0),
+    )?;
+    let (_, resumed_processed_files) = parse_files_put_or_sync_output(&content)?;
+
+    assert_eq!(processed_files.len(), resumed_processed_files.len());
+    for (path, change) in processed_files {
+        assert_eq!(
+            resumed_processed_files
+                .get(&path)
+                .and_then(|change| change.link()),
+            change.link()
+        );
+    }
+    Ok(())
+}
+
 #[test]
 fn calling_safe_files_put_recursive_and_set_dst_path() -> Result<()> {
     let files_container = safe_cmd_stdout(
@@ -722,6 +756,55 @@ fn calling_safe_files_add_a_url() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn calling_files_log() -> Result<()> {
+    let files_container_output = safe_cmd_stdout(
+        ["files", "put", TEST_FOLDER, "--recursive", "--json"],
+        Some(0),
+    )?;
+    let (files_container_xor, processed_files) =
+        parse_files_put_or_sync_output(&files_container_output)?;
+    let first_version = SafeUrl::from_url(&files_container_xor)?
+        .content_version()
+        .ok_or_else(|| eyre!("Missing version of the FilesContainer"))?;
+
+    let mut safeurl = SafeUrl::from_url(&files_container_xor)?;
+    safeurl.set_content_version(None);
+    let container_xorurl_no_version = safeurl.to_string();
+    safeurl.set_path("/new_test.md");
+    let link = processed_files[Path::new(TEST_FILE)]
+        .link()
+        .ok_or_else(|| eyre!("Missing xorurl link of uploaded test file"))?;
+    safe_cmd(
+        ["files", "add", link, &safeurl.to_string(), "--json"],
+        Some(0),
+    )?;
+
+    let files_log_output = safe_cmd_stdout(
+        ["files", "log", &container_xorurl_no_version, "--json"],
+        Some(0),
+    )?;
+    let graph: serde_json::Value = serde_json::from_str(&files_log_output)?;
+    let versions = graph
+        .as_array()
+        .ok_or_else(|| eyre!("Unexpected output of files log: {}", files_log_output))?;
+    assert_eq!(versions.len(), 2);
+    assert_eq!(versions[0]["latest"], true);
+    assert_eq!(
+        versions[0]["previous_versions"],
+        serde_json::json!([first_version.to_string()])
+    );
+    assert_eq!(versions[1]["version"], first_version.to_string());
+    assert_eq!(versions[1]["latest"], false);
+
+    let files_log_output = safe_cmd_stdout(
+        ["files", "log", &container_xorurl_no_version, "--depth", "0"],
+        Some(0),
+    )?;
+    assert!(files_log_output.contains("has 1 version:"));
+    Ok(())
+}
+
 #[test]
 fn calling_files_ls() -> Result<()> {
     let files_container_output = safe_cmd_stdout(
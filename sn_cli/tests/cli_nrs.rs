@@ -193,6 +193,172 @@ fn nrs_add_should_add_a_subname_to_versioned_content() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn nrs_log_should_show_the_versions_of_the_nrs_map() -> Result<()> {
+    let tmp_data_path = assert_fs::TempDir::new()?;
+    tmp_data_path.copy_from("../resources/testdata", &["**"])?;
+    let test_md_file = tmp_data_path.child("test.md");
+    let (files_container_xor, _processed_files, _) = upload_path(&test_md_file, false)?;
+    let mut url = SafeUrl::from_url(&files_container_xor)?;
+    url.set_path("test.md");
+
+    let test_name = get_random_string();
+    safe_cmd(["nrs", "register", &test_name], Some(0))?;
+    for subname in ["a", "b"] {
+        let public_name = format!("{}.{}", subname, &test_name);
+        safe_cmd(
+            ["nrs", "add", &public_name, "--link", &url.to_string()],
+            Some(0),
+        )?;
+    }
+
+    safe_cmd(["nrs", "log", &test_name], Some(0))?
+        .assert()
+        .stdout(predicate::str::contains(format!(
+            "NRS Map for \"safe://{}\" has 2 versions:",
+            test_name
+        )))
+        .stdout(predicate::str::contains("*").count(2));
+
+    Ok(())
+}
+
+#[test]
+fn nrs_register_should_grant_write_access_to_writers() -> Result<()> {
+    let topname = get_random_string();
+    let writer_pk = bls::SecretKey::random().public_key().to_hex();
+    safe_cmd(
+        ["nrs", "register", &topname, "--writer", &writer_pk],
+        Some(0),
+    )?
+    .assert()
+    .stdout(predicate::str::contains(format!(
+        "New NRS Map created for \"safe://{}\"",
+        topname
+    )));
+
+    safe_cmd(["nrs", "revoke", &topname, &writer_pk], Some(0))?
+        .assert()
+        .stdout(predicate::str::contains(format!(
+            "Write access to \"safe://{}\" revoked from {}",
+            topname, writer_pk
+        )));
+    safe_cmd(["nrs", "grant", &topname, &writer_pk], Some(0))?
+        .assert()
+        .stdout(predicate::str::contains(format!(
+            "Write access to \"safe://{}\" granted to {}",
+            topname, writer_pk
+        )));
+    Ok(())
+}
+
+#[test]
+fn nrs_transfer_should_hand_over_the_ownership_of_a_topname() -> Result<()> {
+    let topname = get_random_string();
+    let new_owner_pk = bls::SecretKey::random().public_key().to_hex();
+    safe_cmd(["nrs", "register", &topname], Some(0))?;
+
+    safe_cmd(["nrs", "transfer", &topname, &new_owner_pk], Some(0))?
+        .assert()
+        .stdout(predicate::str::contains(format!(
+            "Ownership of \"safe://{}\" transferred to {}",
+            topname, new_owner_pk
+        )));
+
+    // we are no longer the owner, so we cannot change who can write to it
+    safe_cmd(["nrs", "grant", &topname, "anyone"], Some(1))?;
+    Ok(())
+}
+
+#[test]
+fn nrs_ls_should_list_the_public_names_of_a_topname() -> Result<()> {
+    let tmp_data_path = assert_fs::TempDir::new()?;
+    tmp_data_path.copy_from("../resources/testdata", &["**"])?;
+    let test_md_file = tmp_data_path.child("test.md");
+    let (files_container_xor, _processed_files, _) = upload_path(&test_md_file, false)?;
+    let mut url = SafeUrl::from_url(&files_container_xor)?;
+    url.set_path("test.md");
+
+    let test_name = get_random_string();
+    safe_cmd(["nrs", "register", &test_name], Some(0))?;
+    for subname in ["a", "b"] {
+        let public_name = format!("{}.{}", subname, &test_name);
+        safe_cmd(
+            ["nrs", "add", &public_name, "--link", &url.to_string()],
+            Some(0),
+        )?;
+    }
+
+    safe_cmd(["nrs", "ls", &test_name], Some(0))?
+        .assert()
+        .stdout(predicate::str::contains(format!(
+            "NRS Map for \"safe://{}\" has 2 public names:",
+            test_name
+        )))
+        .stdout(predicate::str::contains(format!("a.{}", test_name)))
+        .stdout(predicate::str::contains(format!("b.{}", test_name)));
+
+    Ok(())
+}
+
+#[test]
+fn nrs_show_should_show_the_link_and_resolution_chain() -> Result<()> {
+    let tmp_data_path = assert_fs::TempDir::new()?;
+    tmp_data_path.copy_from("../resources/testdata", &["**"])?;
+    let test_md_file = tmp_data_path.child("test.md");
+    let (files_container_xor, _processed_files, _) = upload_path(&test_md_file, false)?;
+    let mut url = SafeUrl::from_url(&files_container_xor)?;
+    url.set_path("test.md");
+
+    let test_name = get_random_string();
+    let public_name = format!("a.{}", &test_name);
+    safe_cmd(
+        ["nrs", "add", &public_name, "-y", "--link", &url.to_string()],
+        Some(0),
+    )?;
+
+    safe_cmd(["nrs", "show", &public_name], Some(0))?
+        .assert()
+        .stdout(predicate::str::contains(format!(
+            "Public name: {}",
+            public_name
+        )))
+        .stdout(predicate::str::contains(format!("Link: {}", url)))
+        .stdout(predicate::str::contains("Resolution chain:"))
+        .stdout(predicate::str::contains("NrsEntry"));
+
+    Ok(())
+}
+
+#[test]
+fn nrs_find_links_should_find_the_names_linking_to_a_files_container() -> Result<()> {
+    let tmp_data_path = assert_fs::TempDir::new()?;
+    tmp_data_path.copy_from("../resources/testdata", &["**"])?;
+    let test_md_file = tmp_data_path.child("test.md");
+    let (files_container_xor, _processed_files, _) = upload_path(&test_md_file, false)?;
+    let mut url = SafeUrl::from_url(&files_container_xor)?;
+    url.set_path("test.md");
+
+    let test_name = get_random_string();
+    let public_name = format!("a.{}", &test_name);
+    safe_cmd(
+        ["nrs", "add", &public_name, "-y", "--link", &url.to_string()],
+        Some(0),
+    )?;
+
+    let mut container_url = SafeUrl::from_url(&files_container_xor)?;
+    container_url.set_content_version(None);
+    safe_cmd(
+        ["nrs", "find-links", &container_url.to_string(), &test_name],
+        Some(0),
+    )?
+    .assert()
+    .stdout(predicate::str::contains("Found 1 public name linking to"))
+    .stdout(predicate::str::contains(public_name));
+
+    Ok(())
+}
+
 #[test]
 fn nrs_add_should_add_a_subname_to_immutable_content() -> Result<()> {
     let tmp_data_path = assert_fs::TempDir::new()?;
@@ -0,0 +1,71 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use assert_cmd::prelude::*;
+use assert_fs::prelude::*;
+use color_eyre::Result;
+use predicates::prelude::*;
+use sn_cmd_test_utilities::util::{safe_cmd, upload_path};
+
+#[test]
+fn register_grant_and_revoke_should_update_the_policy() -> Result<()> {
+    let tmp_data_path = assert_fs::TempDir::new()?;
+    tmp_data_path.copy_from("../resources/testdata", &["**"])?;
+    let (files_container_xor, _processed_files, _) =
+        upload_path(&tmp_data_path.child("test.md"), false)?;
+    let user_pk = bls::SecretKey::random().public_key().to_hex();
+
+    safe_cmd(
+        ["register", "grant", &files_container_xor, &user_pk],
+        Some(0),
+    )?
+    .assert()
+    .stdout(predicate::str::contains(format!(
+        "Write access granted to {}",
+        user_pk
+    )));
+    safe_cmd(["register", "policy", &files_container_xor], Some(0))?
+        .assert()
+        .stdout(predicate::str::contains("is owned by"))
+        .stdout(predicate::str::contains(&user_pk))
+        .stdout(predicate::str::contains("allowed").count(2));
+
+    safe_cmd(
+        ["register", "revoke", &files_container_xor, &user_pk],
+        Some(0),
+    )?
+    .assert()
+    .stdout(predicate::str::contains(format!(
+        "Write access revoked from {}",
+        user_pk
+    )));
+    safe_cmd(["register", "policy", &files_container_xor], Some(0))?
+        .assert()
+        .stdout(predicate::str::contains("denied").count(1));
+
+    Ok(())
+}
+
+#[test]
+fn register_transfer_should_fail_with_invalid_owner() -> Result<()> {
+    let tmp_data_path = assert_fs::TempDir::new()?;
+    tmp_data_path.copy_from("../resources/testdata", &["**"])?;
+    let (files_container_xor, _processed_files, _) =
+        upload_path(&tmp_data_path.child("test.md"), false)?;
+
+    safe_cmd(
+        ["register", "transfer", &files_container_xor, "anyone"],
+        Some(1),
+    )?
+    .assert()
+    .stderr(predicate::str::contains(
+        "A Register cannot be owned by anyone",
+    ));
+
+    Ok(())
+}
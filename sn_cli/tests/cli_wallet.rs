@@ -10,7 +10,7 @@ use assert_cmd::prelude::*;
 use assert_fs::prelude::*;
 use color_eyre::{eyre::eyre, Result};
 use predicates::prelude::*;
-use sn_api::test_helpers::get_next_bearer_dbc;
+use sn_api::{test_helpers::get_next_bearer_dbc, wallet::Token};
 use sn_cmd_test_utilities::util::{
     get_bearer_dbc_on_file, get_owned_dbc_on_file, get_random_string, parse_keys_create_output,
     parse_wallet_create_output, safe_cmd, safe_cmd_stdout,
@@ -514,6 +514,109 @@ async fn wallet_reissue_should_reissue_a_bearer_dbc_from_a_deposited_dbc() -> Re
     Ok(())
 }
 
+#[tokio::test]
+async fn wallet_reissue_with_dry_run_should_not_spend_any_dbc() -> Result<()> {
+    let json_output = safe_cmd_stdout(["wallet", "create", "--json"], Some(0))?;
+    let wallet_xorurl = parse_wallet_create_output(&json_output)?;
+    let tmp_data_dir = assert_fs::TempDir::new()?;
+    let (dbc_file_path, _, balance) = get_bearer_dbc_on_file(&tmp_data_dir).await?;
+
+    safe_cmd(
+        [
+            "wallet",
+            "deposit",
+            "--name",
+            "my-first-dbc",
+            "--dbc",
+            &dbc_file_path.display().to_string(),
+            &wallet_xorurl,
+        ],
+        Some(0),
+    )?;
+
+    safe_cmd(
+        [
+            "wallet",
+            "reissue",
+            "1.15",
+            "--from",
+            &wallet_xorurl,
+            "--coin-selection",
+            "largest-first",
+            "--dry-run",
+        ],
+        Some(0),
+    )?
+    .assert()
+    .stdout(predicate::str::contains("dry-run mode"))
+    .stdout(predicate::str::contains(format!(
+        "Reissuing 1.15 safecoins from wallet at \"{}\" would spend the following DBCs:",
+        wallet_xorurl
+    )))
+    .stdout(predicate::str::contains("my-first-dbc"))
+    .stdout(predicate::str::contains(format!(
+        "A change of {} safecoins would be deposited back into the wallet.",
+        Token::from_nano(balance.as_nano() - 1_150_000_000)
+    )))
+    .success();
+
+    safe_cmd(["wallet", "balance", &wallet_xorurl], Some(0))?
+        .assert()
+        .stdout(format!(
+            "Wallet at \"{}\" has a total balance of {} safecoins\n",
+            wallet_xorurl, balance
+        ))
+        .success();
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn wallet_history_should_list_deposits_and_reissues() -> Result<()> {
+    let json_output = safe_cmd_stdout(["wallet", "create", "--json"], Some(0))?;
+    let wallet_xorurl = parse_wallet_create_output(&json_output)?;
+    let tmp_data_dir = assert_fs::TempDir::new()?;
+    let (dbc_file_path, _, _) = get_bearer_dbc_on_file(&tmp_data_dir).await?;
+
+    safe_cmd(
+        [
+            "wallet",
+            "deposit",
+            "--name",
+            "my-first-dbc",
+            "--dbc",
+            &dbc_file_path.display().to_string(),
+            &wallet_xorurl,
+        ],
+        Some(0),
+    )?;
+    safe_cmd(
+        ["wallet", "reissue", "1.15", "--from", &wallet_xorurl],
+        Some(0),
+    )?;
+
+    safe_cmd(["wallet", "history", &wallet_xorurl], Some(0))?
+        .assert()
+        .stdout(predicate::str::contains(format!(
+            "Wallet at \"{}\" has 2 transactions recorded:",
+            wallet_xorurl
+        )))
+        .stdout(predicate::str::contains("Deposited as 'my-first-dbc'"))
+        .stdout(predicate::str::contains("1.150000000 as bearer"))
+        .success();
+
+    let json_output = safe_cmd_stdout(["wallet", "history", &wallet_xorurl, "--json"], Some(0))?;
+    let history: serde_json::Value = serde_json::from_str(&json_output)?;
+    assert_eq!(
+        history.as_array().map(|entries| entries.len()),
+        Some(2),
+        "unexpected history: {}",
+        json_output
+    );
+
+    Ok(())
+}
+
 #[tokio::test]
 async fn wallet_reissue_should_reissue_an_owned_dbc_from_a_deposited_dbc() -> Result<()> {
     let json_output = safe_cmd_stdout(["wallet", "create", "--json"], Some(0))?;
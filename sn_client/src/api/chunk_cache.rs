@@ -0,0 +1,315 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::Result;
+
+use sn_interface::types::Chunk;
+
+use bytes::Bytes;
+use std::{
+    collections::BTreeMap,
+    ffi::OsStr,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use tokio::{fs, sync::Mutex};
+use walkdir::WalkDir;
+use xor_name::{XorName, XOR_NAME_LEN};
+
+// Chunks are written aside with this extension first, so a chunk file is never partially written
+const TMP_FILE_EXTENSION: &str = "tmp";
+
+/// A size-bounded cache of chunks on disk, which outlives the client process.
+///
+/// Chunks are immutable and their name is the hash of their content, so a chunk is stored
+/// in a file named after it, and its content is checked against its name whenever read.
+/// Once the cache is full, the least recently used chunks are evicted, the chunks written
+/// the earliest being considered the least recently used ones when the cache is opened.
+#[derive(Debug)]
+pub(crate) struct DiskChunkCache {
+    root_dir: PathBuf,
+    max_size: u64,
+    index: Mutex<CacheIndex>,
+}
+
+// Chunks held in the cache, by order of use
+#[derive(Debug, Default)]
+struct CacheIndex {
+    // size and last use of each chunk
+    entries: BTreeMap<XorName, (u64, u64)>,
+    // chunks by last use, the least recently used first
+    lru: BTreeMap<u64, XorName>,
+    next_use: u64,
+    total_size: u64,
+}
+
+impl CacheIndex {
+    // Records the use of the chunk, returning whether it's in the cache
+    fn touch(&mut self, name: &XorName) -> bool {
+        let last_use = match self.entries.get_mut(name) {
+            Some((_, last_use)) => last_use,
+            None => return false,
+        };
+        let _prev = self.lru.remove(last_use);
+        *last_use = self.next_use;
+        let _prev = self.lru.insert(self.next_use, *name);
+        self.next_use += 1;
+        true
+    }
+
+    fn insert(&mut self, name: XorName, size: u64) {
+        let _prev = self.remove(&name);
+        let _prev = self.entries.insert(name, (size, self.next_use));
+        let _prev = self.lru.insert(self.next_use, name);
+        self.next_use += 1;
+        self.total_size += size;
+    }
+
+    fn remove(&mut self, name: &XorName) -> bool {
+        match self.entries.remove(name) {
+            Some((size, last_use)) => {
+                let _name = self.lru.remove(&last_use);
+                self.total_size -= size;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Drops the least recently used chunks until they all fit in the given size,
+    // returning the names of those dropped
+    fn evict_to(&mut self, max_size: u64) -> Vec<XorName> {
+        let mut evicted = vec![];
+        while self.total_size > max_size {
+            let name = match self.lru.values().next() {
+                Some(name) => *name,
+                None => break,
+            };
+            let _removed = self.remove(&name);
+            evicted.push(name);
+        }
+        evicted
+    }
+}
+
+impl DiskChunkCache {
+    /// Opens the cache at the given directory, creating it if needed, and indexes
+    /// the chunks already in there.
+    pub(crate) fn open(root_dir: PathBuf, max_size: u64) -> Result<Self> {
+        std::fs::create_dir_all(&root_dir)?;
+
+        let mut found = vec![];
+        for entry in WalkDir::new(&root_dir).into_iter().flatten() {
+            let path = entry.path();
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            if path.extension() == Some(OsStr::new(TMP_FILE_EXTENSION)) {
+                // left behind by an interrupted write
+                let _ = std::fs::remove_file(path);
+                continue;
+            }
+            let name = match chunk_name_of(path) {
+                Some(name) => name,
+                None => continue,
+            };
+            let metadata = entry.metadata().map_err(std::io::Error::from)?;
+            let written = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            found.push((written, name, metadata.len()));
+        }
+        found.sort();
+
+        let mut index = CacheIndex::default();
+        for (_, name, size) in found {
+            index.insert(name, size);
+        }
+
+        // the max size may have been lowered since the cache was last used
+        for name in index.evict_to(max_size) {
+            if let Err(error) = std::fs::remove_file(chunk_path(&root_dir, &name)) {
+                warn!("Failed to evict chunk {name:?} from the disk cache: {error}");
+            }
+        }
+        debug!(
+            "Disk chunk cache opened at {}, holding {} chunks ({} bytes)",
+            root_dir.display(),
+            index.entries.len(),
+            index.total_size
+        );
+
+        Ok(Self {
+            root_dir,
+            max_size,
+            index: Mutex::new(index),
+        })
+    }
+
+    /// Reads the chunk from the cache, if it's there and its content matches its name.
+    /// Chunks found to be corrupt are removed.
+    pub(crate) async fn get(&self, name: &XorName) -> Option<Chunk> {
+        if !self.index.lock().await.touch(name) {
+            return None;
+        }
+
+        let path = self.chunk_path(name);
+        let chunk = match fs::read(&path).await {
+            Ok(bytes) => Chunk::new(Bytes::from(bytes)),
+            Err(error) => {
+                warn!("Failed to read chunk {name:?} from the disk cache: {error}");
+                let _removed = self.index.lock().await.remove(name);
+                return None;
+            }
+        };
+
+        if chunk.name() != name {
+            warn!("Corrupt chunk {name:?} found in the disk cache, removing it");
+            self.remove(name).await;
+            return None;
+        }
+
+        Some(chunk)
+    }
+
+    /// Writes the chunk to the cache, evicting the least recently used chunks if it's full.
+    /// Failing to do so is not an error, as the chunk can always be retrieved from the network.
+    pub(crate) async fn put(&self, chunk: &Chunk) {
+        if let Err(error) = self.try_put(chunk).await {
+            warn!(
+                "Failed to write chunk {:?} to the disk cache: {error}",
+                chunk.name()
+            );
+        }
+    }
+
+    async fn try_put(&self, chunk: &Chunk) -> Result<()> {
+        let size = chunk.value().len() as u64;
+        if size > self.max_size || self.index.lock().await.touch(chunk.name()) {
+            return Ok(());
+        }
+
+        let path = self.chunk_path(chunk.name());
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).await?;
+        }
+        let tmp_path = path.with_extension(TMP_FILE_EXTENSION);
+        fs::write(&tmp_path, chunk.value()).await?;
+        fs::rename(&tmp_path, &path).await?;
+
+        let evicted = {
+            let mut index = self.index.lock().await;
+            index.insert(*chunk.name(), size);
+            index.evict_to(self.max_size)
+        };
+        for name in evicted {
+            trace!("Evicting chunk {name:?} from the disk cache");
+            self.remove_file(&name).await;
+        }
+
+        Ok(())
+    }
+
+    async fn remove(&self, name: &XorName) {
+        let _removed = self.index.lock().await.remove(name);
+        self.remove_file(name).await;
+    }
+
+    async fn remove_file(&self, name: &XorName) {
+        match fs::remove_file(self.chunk_path(name)).await {
+            Ok(()) => {}
+            Err(error) if error.kind() == ErrorKind::NotFound => {}
+            Err(error) => warn!("Failed to remove chunk {name:?} from the disk cache: {error}"),
+        }
+    }
+
+    fn chunk_path(&self, name: &XorName) -> PathBuf {
+        chunk_path(&self.root_dir, name)
+    }
+}
+
+// Chunks are spread in directories named after the first byte of their name,
+// so no single directory holds too many files
+fn chunk_path(root_dir: &Path, name: &XorName) -> PathBuf {
+    root_dir
+        .join(format!("{:2x}", name))
+        .join(format!("{:x}", name))
+}
+
+fn chunk_name_of(path: &Path) -> Option<XorName> {
+    let bytes = hex::decode(path.file_name()?.to_str()?).ok()?;
+    let bytes: [u8; XOR_NAME_LEN] = bytes.try_into().ok()?;
+    Some(XorName(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DiskChunkCache;
+
+    use sn_interface::types::{utils::random_bytes, Chunk};
+
+    use eyre::Result;
+    use tempfile::tempdir;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn disk_chunk_cache_evicts_least_recently_used() -> Result<()> {
+        let dir = tempdir()?;
+        let cache = DiskChunkCache::open(dir.path().to_path_buf(), 3 * 1024)?;
+
+        let chunks = std::iter::repeat_with(|| Chunk::new(random_bytes(1024)))
+            .take(4)
+            .collect::<Vec<_>>();
+        for chunk in &chunks[..3] {
+            cache.put(chunk).await;
+        }
+
+        // the first chunk is used again, so the second one is evicted next
+        assert_eq!(cache.get(chunks[0].name()).await.as_ref(), Some(&chunks[0]));
+        cache.put(&chunks[3]).await;
+
+        assert!(cache.get(chunks[1].name()).await.is_none());
+        for chunk in [&chunks[0], &chunks[2], &chunks[3]] {
+            assert_eq!(cache.get(chunk.name()).await.as_ref(), Some(chunk));
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn disk_chunk_cache_outlives_the_client() -> Result<()> {
+        let dir = tempdir()?;
+        let chunk = Chunk::new(random_bytes(1024));
+        {
+            let cache = DiskChunkCache::open(dir.path().to_path_buf(), 10 * 1024)?;
+            cache.put(&chunk).await;
+        }
+
+        let cache = DiskChunkCache::open(dir.path().to_path_buf(), 10 * 1024)?;
+        assert_eq!(cache.get(chunk.name()).await, Some(chunk));
+
+        // the chunks no longer fitting are evicted if the max size is lowered
+        let cache = DiskChunkCache::open(dir.path().to_path_buf(), 512)?;
+        assert!(cache.index.lock().await.entries.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn disk_chunk_cache_drops_corrupt_chunks() -> Result<()> {
+        let dir = tempdir()?;
+        let cache = DiskChunkCache::open(dir.path().to_path_buf(), 10 * 1024)?;
+
+        let chunk = Chunk::new(random_bytes(1024));
+        cache.put(&chunk).await;
+        std::fs::write(cache.chunk_path(chunk.name()), random_bytes(1024))?;
+
+        assert!(cache.get(chunk.name()).await.is_none());
+        assert!(!cache.chunk_path(chunk.name()).exists());
+
+        Ok(())
+    }
+}
@@ -14,6 +14,7 @@
 //! # Ok(())
 //! # }
 //! ```
+use super::chunk_cache::DiskChunkCache;
 use crate::{connections::Session, Client, Error, DEFAULT_NETWORK_CONTACTS_FILE_NAME};
 
 use qp2p::Config as Qp2pConfig;
@@ -36,6 +37,16 @@ pub const ENV_MAX_RETRIES: &str = "SN_MAX_RETRIES";
 pub const ENV_CMD_TIMEOUT: &str = "SN_CMD_TIMEOUT";
 /// Environment variable used to convert into [`ClientBuilder::cmd_ack_wait`] (seconds)
 pub const ENV_AE_WAIT: &str = "SN_AE_WAIT";
+/// Environment variable used to convert into [`ClientBuilder::chunk_cache_dir`]
+pub const ENV_CHUNK_CACHE_DIR: &str = "SN_CHUNK_CACHE_DIR";
+/// Environment variable used to convert into [`ClientBuilder::chunk_cache_max_size`] (bytes)
+pub const ENV_CHUNK_CACHE_MAX_SIZE: &str = "SN_CHUNK_CACHE_MAX_SIZE";
+/// Environment variable used to convert into [`ClientBuilder::max_concurrent_chunk_queries`]
+pub const ENV_MAX_CONCURRENT_CHUNK_QUERIES: &str = "SN_MAX_CONCURRENT_CHUNK_QUERIES";
+/// Environment variable used to convert into [`ClientBuilder::chunk_prefetch`]
+pub const ENV_CHUNK_PREFETCH: &str = "SN_CHUNK_PREFETCH";
+/// Environment variable used to convert into [`ClientBuilder::chunk_query_retries`]
+pub const ENV_CHUNK_QUERY_RETRIES: &str = "SN_CHUNK_QUERY_RETRIES";
 
 /// Bind by default to all network interfaces on a OS assigned port
 pub const DEFAULT_LOCAL_ADDR: (Ipv4Addr, u16) = (Ipv4Addr::UNSPECIFIED, 0);
@@ -46,6 +57,14 @@ pub const DEFAULT_QUERY_CMD_TIMEOUT: Duration = Duration::from_secs(30);
 pub const DEFAULT_MAX_QUERY_CMD_RETRIES: usize = 15;
 /// Default timeout for waiting for potential Anti-Entropy messages
 pub const DEFAULT_ACK_WAIT: Duration = Duration::from_secs(10);
+/// Default max size of the chunks kept on disk, when a [`ClientBuilder::chunk_cache_dir`] is set
+pub const DEFAULT_CHUNK_CACHE_MAX_SIZE: u64 = 1024 * 1024 * 1024;
+/// Default max number of chunks being queried at once when reading content
+pub const DEFAULT_MAX_CONCURRENT_CHUNK_QUERIES: usize = 8;
+/// Default number of chunks following those read which are fetched ahead of being read
+pub const DEFAULT_CHUNK_PREFETCH: usize = 4;
+/// Default number of times a chunk query is retried, each time at the next adult holding the chunk
+pub const DEFAULT_CHUNK_QUERY_RETRIES: usize = 3;
 
 /// Build a [`crate::Client`]
 #[derive(Debug, Default)]
@@ -59,6 +78,11 @@ pub struct ClientBuilder {
     cmd_timeout: Option<Duration>,
     cmd_ack_wait: Option<Duration>,
     network_contacts: Option<SectionTree>,
+    chunk_cache_dir: Option<PathBuf>,
+    chunk_cache_max_size: Option<u64>,
+    max_concurrent_chunk_queries: Option<usize>,
+    chunk_prefetch: Option<usize>,
+    chunk_query_retries: Option<usize>,
 }
 
 impl ClientBuilder {
@@ -121,11 +145,51 @@ impl ClientBuilder {
         self
     }
 
+    /// Directory where to keep the chunks retrieved, for them to be read from disk rather than
+    /// from the network, even by later runs of the client. No chunks are kept on disk if unset.
+    pub fn chunk_cache_dir(mut self, dir: impl Into<Option<PathBuf>>) -> Self {
+        self.chunk_cache_dir = dir.into();
+        self
+    }
+
+    /// Max bytes taken by the chunks kept in the [`Self::chunk_cache_dir`], the least
+    /// recently used chunks being evicted first
+    pub fn chunk_cache_max_size(mut self, size: impl Into<Option<u64>>) -> Self {
+        self.chunk_cache_max_size = size.into();
+        self
+    }
+
+    /// Max number of chunks being queried at once when reading content, bounding
+    /// the number of chunks held in memory while being retrieved
+    pub fn max_concurrent_chunk_queries(mut self, max: impl Into<Option<usize>>) -> Self {
+        self.max_concurrent_chunk_queries = max.into();
+        self
+    }
+
+    /// Number of chunks following those read with [`Client::read_from`] which are fetched in the
+    /// background, for sequential reads of a file not to wait on the network
+    pub fn chunk_prefetch(mut self, count: impl Into<Option<usize>>) -> Self {
+        self.chunk_prefetch = count.into();
+        self
+    }
+
+    /// Number of times a chunk query left without a response is retried when reading content,
+    /// each time at the next adult holding the chunk, all within `query_timeout`
+    pub fn chunk_query_retries(mut self, retries: impl Into<Option<usize>>) -> Self {
+        self.chunk_query_retries = retries.into();
+        self
+    }
+
     /// Read options from environment variables:
     /// - [`Self::query_timeout()`] from [`ENV_QUERY_TIMEOUT`]
     /// - [`Self::max_retries()`] from [`ENV_MAX_RETRIES`]
     /// - [`Self::cmd_timeout()`] from [`ENV_CMD_TIMEOUT`]
     /// - [`Self::cmd_ack_wait()`] from [`ENV_AE_WAIT`]
+    /// - [`Self::chunk_cache_dir()`] from [`ENV_CHUNK_CACHE_DIR`]
+    /// - [`Self::chunk_cache_max_size()`] from [`ENV_CHUNK_CACHE_MAX_SIZE`]
+    /// - [`Self::max_concurrent_chunk_queries()`] from [`ENV_MAX_CONCURRENT_CHUNK_QUERIES`]
+    /// - [`Self::chunk_prefetch()`] from [`ENV_CHUNK_PREFETCH`]
+    /// - [`Self::chunk_query_retries()`] from [`ENV_CHUNK_QUERY_RETRIES`]
     pub fn from_env(mut self) -> Self {
         if let Ok(Some(v)) = env_parse(ENV_QUERY_TIMEOUT) {
             self.query_timeout = Some(Duration::from_secs(v));
@@ -139,6 +203,21 @@ impl ClientBuilder {
         if let Ok(Some(v)) = env_parse(ENV_AE_WAIT) {
             self.cmd_ack_wait = Some(Duration::from_secs(v));
         }
+        if let Ok(Some(v)) = env_parse(ENV_CHUNK_CACHE_DIR) {
+            self.chunk_cache_dir = Some(v);
+        }
+        if let Ok(Some(v)) = env_parse(ENV_CHUNK_CACHE_MAX_SIZE) {
+            self.chunk_cache_max_size = Some(v);
+        }
+        if let Ok(Some(v)) = env_parse(ENV_MAX_CONCURRENT_CHUNK_QUERIES) {
+            self.max_concurrent_chunk_queries = Some(v);
+        }
+        if let Ok(Some(v)) = env_parse(ENV_CHUNK_PREFETCH) {
+            self.chunk_prefetch = Some(v);
+        }
+        if let Ok(Some(v)) = env_parse(ENV_CHUNK_QUERY_RETRIES) {
+            self.chunk_query_retries = Some(v);
+        }
 
         self
     }
@@ -150,6 +229,10 @@ impl ClientBuilder {
     /// - `[Self::query_timeout`] and `[Self::cmd_timeout]` default to [`DEFAULT_QUERY_CMD_TIMEOUT`]
     /// - `[Self::max_retries`] and `[Self::cmd_timeout]` default to [`DEFAULT_MAX_QUERY_CMD_RETRIES`]
     /// - `[Self::cmd_ack_wait`] defaults to [`DEFAULT_ACK_WAIT`]
+    /// - `[Self::chunk_cache_max_size`] defaults to [`DEFAULT_CHUNK_CACHE_MAX_SIZE`]
+    /// - `[Self::max_concurrent_chunk_queries`] defaults to [`DEFAULT_MAX_CONCURRENT_CHUNK_QUERIES`]
+    /// - `[Self::chunk_prefetch`] defaults to [`DEFAULT_CHUNK_PREFETCH`]
+    /// - `[Self::chunk_query_retries`] defaults to [`DEFAULT_CHUNK_QUERY_RETRIES`]
     /// - [`qp2p::Config`] will default to it's [`Default`] impl
     /// - Network contacts file will be read from a standard location
     pub async fn build(self) -> Result<Client, Error> {
@@ -157,6 +240,15 @@ impl ClientBuilder {
         let query_timeout = self.query_timeout.unwrap_or(DEFAULT_QUERY_CMD_TIMEOUT);
         let cmd_timeout = self.cmd_timeout.unwrap_or(DEFAULT_QUERY_CMD_TIMEOUT);
         let cmd_ack_wait = self.cmd_ack_wait.unwrap_or(DEFAULT_ACK_WAIT);
+        // at least one chunk has to be queried at a time
+        let max_concurrent_chunk_queries = self
+            .max_concurrent_chunk_queries
+            .unwrap_or(DEFAULT_MAX_CONCURRENT_CHUNK_QUERIES)
+            .max(1);
+        let chunk_prefetch = self.chunk_prefetch.unwrap_or(DEFAULT_CHUNK_PREFETCH);
+        let chunk_query_retries = self
+            .chunk_query_retries
+            .unwrap_or(DEFAULT_CHUNK_QUERY_RETRIES);
 
         let network_contacts = match self.network_contacts {
             Some(pm) => pm,
@@ -187,6 +279,16 @@ impl ClientBuilder {
             .dbc_owner
             .unwrap_or_else(|| Owner::from_random_secret_key(&mut rand::thread_rng()));
 
+        let disk_chunks_cache = match self.chunk_cache_dir {
+            Some(dir) => {
+                let max_size = self
+                    .chunk_cache_max_size
+                    .unwrap_or(DEFAULT_CHUNK_CACHE_MAX_SIZE);
+                Some(Arc::new(DiskChunkCache::open(dir, max_size)?))
+            }
+            None => None,
+        };
+
         let client = Client {
             keypair,
             dbc_owner,
@@ -195,6 +297,11 @@ impl ClientBuilder {
             max_retries,
             cmd_timeout,
             chunks_cache: Arc::new(RwLock::new(Default::default())),
+            disk_chunks_cache,
+            max_concurrent_chunk_queries,
+            chunk_prefetch,
+            prefetching: Arc::new(RwLock::new(Default::default())),
+            chunk_query_retries,
         };
         client.connect().await?;
 
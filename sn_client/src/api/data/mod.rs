@@ -7,8 +7,10 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 mod pac_man;
+mod stream_encryptor;
 
 pub(crate) use pac_man::{encrypt_large, to_chunk, DataMapLevel};
+pub(crate) use stream_encryptor::StreamEncryptor;
 
 use crate::{Error, Result};
 
@@ -0,0 +1,272 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::pac_man::{pack, to_chunk};
+use crate::{Error, Result};
+use sn_interface::types::Chunk;
+
+use aes::{
+    cipher::{block_padding::Pkcs7, BlockEncryptMut, KeyIvInit},
+    Aes128,
+};
+use brotli::enc::BrotliEncoderParams;
+use bytes::Bytes;
+use self_encryption::{
+    ChunkInfo, DataMap, COMPRESSION_QUALITY, MAX_CHUNK_SIZE, MIN_CHUNK_SIZE, MIN_ENCRYPTABLE_BYTES,
+};
+use std::io::Cursor;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use xor_name::{XorName, XOR_NAME_LEN};
+
+type Aes128CbcEnc = cbc::Encryptor<Aes128>;
+
+const KEY_SIZE: usize = 16;
+const IV_SIZE: usize = 16;
+const PAD_SIZE: usize = (XOR_NAME_LEN * 3) - KEY_SIZE - IV_SIZE;
+
+/// Self-encrypts the content read from an [`AsyncRead`] source one chunk at a time,
+/// producing the very same chunks and `DataMap` as `self_encryption::encrypt` does
+/// for the same content, but without ever holding the whole content in memory.
+///
+/// The encryption of each chunk depends on the source hashes of the two chunks preceding it,
+/// wrapping around for the first two chunks. Thus the first two chunks are kept in memory
+/// until the end of the content is reached, and they are the last ones to be encrypted.
+pub(crate) struct StreamEncryptor<R> {
+    reader: R,
+    file_size: usize,
+    num_chunks: usize,
+    src_hashes: Vec<XorName>,
+    first_chunks: Vec<Bytes>,
+    infos: Vec<ChunkInfo>,
+}
+
+impl<R: AsyncRead + Unpin> StreamEncryptor<R> {
+    /// Enforces `file_size` >= [`MIN_ENCRYPTABLE_BYTES`] bytes.
+    /// Exactly `file_size` bytes will be read from the `reader`.
+    pub(crate) fn new(reader: R, file_size: usize) -> Result<Self> {
+        if MIN_ENCRYPTABLE_BYTES > file_size {
+            return Err(Error::TooSmallForSelfEncryption {
+                size: file_size,
+                minimum: MIN_ENCRYPTABLE_BYTES,
+            });
+        }
+
+        let num_chunks = get_num_chunks(file_size);
+        Ok(Self {
+            reader,
+            file_size,
+            num_chunks,
+            src_hashes: Vec::with_capacity(num_chunks),
+            first_chunks: Vec::with_capacity(2),
+            infos: Vec::with_capacity(num_chunks),
+        })
+    }
+
+    /// Returns the next self-encrypted chunk, or `None` once all of them were produced.
+    pub(crate) async fn next_chunk(&mut self) -> Result<Option<Chunk>> {
+        while self.src_hashes.len() < self.num_chunks {
+            let index = self.src_hashes.len();
+            let mut data = vec![0; get_chunk_size(self.file_size, self.num_chunks, index)];
+            let _ = self.reader.read_exact(&mut data).await?;
+            let data = Bytes::from(data);
+            self.src_hashes.push(XorName::from_content(&data));
+
+            if index < 2 {
+                self.first_chunks.push(data);
+            } else {
+                return self.encrypt_chunk(index, data).map(Some);
+            }
+        }
+
+        if self.first_chunks.is_empty() {
+            return Ok(None);
+        }
+
+        // All the source hashes are now known, so the first two chunks can be encrypted.
+        let index = 2 - self.first_chunks.len();
+        let data = self.first_chunks.remove(0);
+        self.encrypt_chunk(index, data).map(Some)
+    }
+
+    /// Returns the top-most chunk address through which the entire data tree can be accessed,
+    /// and the chunks holding the `DataMap`. Must be called once all the chunks were produced.
+    pub(crate) fn finish(self) -> Result<(XorName, Vec<Chunk>)> {
+        if self.infos.len() < self.num_chunks {
+            return Err(Error::NotAllDataWasChunked {
+                expected: self.num_chunks,
+                chunked: self.infos.len(),
+            });
+        }
+
+        pack(DataMap::new(self.infos), vec![])
+    }
+
+    fn encrypt_chunk(&mut self, index: usize, data: Bytes) -> Result<Chunk> {
+        let src_size = data.len();
+        let (pad, key, iv) = get_pad_key_and_iv(index, &self.src_hashes);
+
+        let mut compressed = vec![];
+        let enc_params = BrotliEncoderParams {
+            quality: COMPRESSION_QUALITY,
+            ..Default::default()
+        };
+        let _size = brotli::BrotliCompress(&mut Cursor::new(data), &mut compressed, &enc_params)
+            .map_err(|_| self_encryption::Error::Compression)?;
+
+        let cipher = Aes128CbcEnc::new(key.as_ref().into(), iv.as_ref().into());
+        let encrypted = cipher.encrypt_padded_vec_mut::<Pkcs7>(&compressed);
+        let content: Bytes = encrypted
+            .iter()
+            .zip(pad.iter().cycle())
+            .map(|(&a, &b)| a ^ b)
+            .collect();
+
+        self.infos.push(ChunkInfo {
+            index,
+            dst_hash: XorName::from_content(&content),
+            src_hash: self.src_hashes[index],
+            src_size,
+        });
+
+        Ok(to_chunk(content))
+    }
+}
+
+// Returns the number of chunks according to file size, as self_encryption does.
+fn get_num_chunks(file_size: usize) -> usize {
+    if file_size < 3 * MAX_CHUNK_SIZE {
+        3
+    } else if file_size.is_multiple_of(MAX_CHUNK_SIZE) {
+        file_size / MAX_CHUNK_SIZE
+    } else {
+        (file_size / MAX_CHUNK_SIZE) + 1
+    }
+}
+
+// Returns the size of a chunk according to file size, as self_encryption does.
+fn get_chunk_size(file_size: usize, num_chunks: usize, index: usize) -> usize {
+    if file_size < 3 * MAX_CHUNK_SIZE {
+        return if index < 2 {
+            file_size / 3
+        } else {
+            file_size - (2 * (file_size / 3))
+        };
+    }
+    if index < num_chunks - 2 {
+        return MAX_CHUNK_SIZE;
+    }
+
+    let remainder = file_size % MAX_CHUNK_SIZE;
+    let penultimate = (num_chunks - 2) == index;
+    if remainder == 0 {
+        MAX_CHUNK_SIZE
+    } else if remainder < MIN_CHUNK_SIZE {
+        if penultimate {
+            MAX_CHUNK_SIZE - MIN_CHUNK_SIZE
+        } else {
+            MIN_CHUNK_SIZE + remainder
+        }
+    } else if penultimate {
+        MAX_CHUNK_SIZE
+    } else {
+        remainder
+    }
+}
+
+// Derives the pad, key and iv of a chunk from its source hash and the ones of the two chunks preceding it.
+fn get_pad_key_and_iv(
+    index: usize,
+    src_hashes: &[XorName],
+) -> ([u8; PAD_SIZE], [u8; KEY_SIZE], [u8; IV_SIZE]) {
+    let (n_1, n_2) = match index {
+        0 => (src_hashes.len() - 1, src_hashes.len() - 2),
+        1 => (0, src_hashes.len() - 1),
+        n => (n - 1, n - 2),
+    };
+
+    let mut pad = [0u8; PAD_SIZE];
+    let mut key = [0u8; KEY_SIZE];
+    let mut iv = [0u8; IV_SIZE];
+
+    for (pad_el, element) in pad
+        .iter_mut()
+        .zip(src_hashes[index].iter().chain(src_hashes[n_2].iter()))
+    {
+        *pad_el = *element;
+    }
+
+    for (key_el, element) in key
+        .iter_mut()
+        .chain(iv.iter_mut())
+        .zip(src_hashes[n_1].iter())
+    {
+        *key_el = *element;
+    }
+
+    (pad, key, iv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StreamEncryptor;
+    use crate::api::data::encrypt_large;
+    use sn_interface::types::utils::random_bytes;
+
+    use eyre::Result;
+    use self_encryption::{MAX_CHUNK_SIZE, MIN_CHUNK_SIZE, MIN_ENCRYPTABLE_BYTES};
+    use std::collections::BTreeSet;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn stream_encryption_matches_in_memory_encryption() -> Result<()> {
+        for size in [
+            MIN_ENCRYPTABLE_BYTES,
+            MAX_CHUNK_SIZE + 7,
+            3 * MAX_CHUNK_SIZE,
+            4 * MAX_CHUNK_SIZE + MIN_CHUNK_SIZE / 2,
+            5 * MAX_CHUNK_SIZE + 3 * MIN_CHUNK_SIZE,
+        ] {
+            let bytes = random_bytes(size);
+            let (expected_address, expected_chunks) = encrypt_large(bytes.clone())?;
+
+            let mut encryptor = StreamEncryptor::new(bytes.as_ref(), size)?;
+            let mut chunks = vec![];
+            while let Some(chunk) = encryptor.next_chunk().await? {
+                chunks.push(chunk);
+            }
+            let (address, data_map_chunks) = encryptor.finish()?;
+            chunks.extend(data_map_chunks);
+
+            assert_eq!(address, expected_address);
+            assert_eq!(
+                chunks.iter().map(|c| *c.name()).collect::<BTreeSet<_>>(),
+                expected_chunks
+                    .iter()
+                    .map(|c| *c.name())
+                    .collect::<BTreeSet<_>>()
+            );
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn stream_encryption_fails_on_short_read() -> Result<()> {
+        let bytes = random_bytes(MIN_ENCRYPTABLE_BYTES);
+        let mut encryptor = StreamEncryptor::new(bytes.as_ref(), MIN_ENCRYPTABLE_BYTES + 1)?;
+
+        let result = loop {
+            match encryptor.next_chunk().await {
+                Ok(Some(_)) => continue,
+                other => break other,
+            }
+        };
+        assert!(result.is_err());
+
+        Ok(())
+    }
+}
@@ -7,25 +7,58 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::{
-    data::{encrypt_large, to_chunk, LargeFile, SmallFile},
+    data::{encrypt_large, to_chunk, LargeFile, SmallFile, StreamEncryptor},
     Client,
 };
-use crate::{api::data::DataMapLevel, Error, Result};
+use crate::{api::data::DataMapLevel, connections::QueryResult, Error, Result};
 
 use sn_interface::{
+    data_copy_count,
     messaging::data::{DataCmd, DataQueryVariant, QueryResponse},
     types::{Chunk, ChunkAddress},
 };
 
 use bincode::deserialize;
 use bytes::Bytes;
-use futures::future::join_all;
+use futures::{future::join_all, stream::FuturesUnordered, StreamExt};
 use itertools::Itertools;
-use self_encryption::{self, ChunkInfo, DataMap, EncryptedChunk};
-use tokio::task;
+use self_encryption::{self, ChunkInfo, DataMap, EncryptedChunk, MIN_ENCRYPTABLE_BYTES};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    future::Future,
+    io,
+    sync::Arc,
+};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    task::{self, JoinHandle},
+};
 use tracing::trace;
 use xor_name::XorName;
 
+/// Outcome of the verification of the content stored at an address, see [`Client::verify_file`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileVerification {
+    /// Address of the content verified
+    pub address: XorName,
+    /// Names of all the chunks of the content, the chunks holding its data maps included
+    pub chunks: Vec<XorName>,
+    /// The chunks which could not be retrieved from some of the adults expected to hold them,
+    /// along with the index of those adults, i.e. their position by distance to the chunk
+    pub missing: BTreeMap<XorName, Vec<usize>>,
+}
+
+impl FileVerification {
+    /// Whether all the chunks of the content were retrieved from all the adults expected to hold them
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+/// Max number of chunks being concurrently sent to the network when streaming content,
+/// which bounds the memory used regardless of the content size.
+const STREAM_CHUNKS_CONCURRENCY: usize = 4;
+
 impl Client {
     #[instrument(skip(self), level = "debug")]
     /// Reads [`Bytes`] from the network, whose contents are contained within on or more chunks.
@@ -82,7 +115,108 @@ impl Client {
 
     #[instrument(skip(self), level = "trace")]
     pub(crate) async fn get_chunk(&self, name: &XorName) -> Result<Chunk> {
-        // first check it's not already in our Chunks' cache
+        if let Some(chunk) = self.get_cached_chunk(name).await {
+            return Ok(chunk);
+        }
+
+        let query = DataQueryVariant::GetChunk(ChunkAddress(*name));
+        let res = self.send_query(query.clone()).await?;
+        let chunk = chunk_from_query_result(query, res)?;
+
+        self.cache_chunk(&chunk).await;
+
+        Ok(chunk)
+    }
+
+    // Gets several chunks at once, those not found in our caches being fetched from the network
+    // up to `max_concurrent_chunk_queries` at a time. Results are returned by order of the names.
+    #[instrument(skip_all, level = "trace")]
+    pub(crate) async fn get_chunks(&self, names: &[XorName]) -> Vec<Result<Chunk>> {
+        let mut results = Vec::with_capacity(names.len());
+        let mut missing = vec![];
+        for (index, name) in names.iter().enumerate() {
+            let cached = self.get_cached_chunk(name).await;
+            if cached.is_none() {
+                missing.push(index);
+            }
+            results.push(cached.map(Ok));
+        }
+
+        for indexes in missing.chunks(self.max_concurrent_chunk_queries) {
+            let window = indexes.iter().map(|index| names[*index]).collect_vec();
+            let fetched = self.fetch_chunks(&window).await;
+            for (index, result) in indexes.iter().zip(fetched) {
+                results[*index] = Some(result);
+            }
+        }
+
+        results.into_iter().flatten().collect()
+    }
+
+    // Fetches the chunks from the network, in batches of queries to the sections holding them.
+    // The queries left without a response, or answered with an error, are retried up to
+    // `chunk_query_retries` times, each time at the next adult holding the chunk.
+    async fn fetch_chunks(&self, names: &[XorName]) -> Vec<Result<Chunk>> {
+        let attempt_timeout = self
+            .query_timeout
+            .div_f32(self.chunk_query_retries as f32 + 1.0);
+        let results = fetch_chunks_with_retries(
+            names,
+            self.chunk_query_retries,
+            |queries, adult_index| async move {
+                self.send_batch_query_to_adult(&queries, adult_index, attempt_timeout)
+                    .await
+            },
+        )
+        .await;
+
+        for chunk in results.iter().flatten() {
+            self.cache_chunk(chunk).await;
+        }
+
+        results
+    }
+
+    // Gets the chunks in a background task
+    fn spawn_get_chunks(&self, chunks_info: &[ChunkInfo]) -> JoinHandle<Vec<Result<Chunk>>> {
+        let client = self.clone();
+        let names = chunks_info.iter().map(|info| info.dst_hash).collect_vec();
+        task::spawn(async move { client.get_chunks(&names).await })
+    }
+
+    // Gets the chunks in a background task, for them to be in our cache when read. The chunks
+    // being prefetched already are skipped, and no more than `chunk_prefetch` chunks are
+    // prefetched at once, across all the reads made with this client.
+    async fn prefetch_chunks<'a>(&self, chunks_info: impl Iterator<Item = &'a ChunkInfo>) {
+        let names = {
+            let mut prefetching = self.prefetching.write().await;
+            let mut names = vec![];
+            for info in chunks_info {
+                if prefetching.len() >= self.chunk_prefetch {
+                    break;
+                }
+                if prefetching.insert(info.dst_hash) {
+                    names.push(info.dst_hash);
+                }
+            }
+            names
+        };
+        if names.is_empty() {
+            return;
+        }
+
+        let client = self.clone();
+        let _handle = task::spawn(async move {
+            let _results = client.get_chunks(&names).await;
+            let mut prefetching = client.prefetching.write().await;
+            for name in &names {
+                let _ = prefetching.remove(name);
+            }
+        });
+    }
+
+    // Looks for the chunk in our in-memory cache first, then in our disk cache, if any
+    async fn get_cached_chunk(&self, name: &XorName) -> Option<Chunk> {
         if let Some(chunk) = self
             .chunks_cache
             .write()
@@ -90,23 +224,25 @@ impl Client {
             .find(|c| c.address().name() == name)
         {
             trace!("Chunk retrieved from local cache: {:?}", name);
-            return Ok(chunk.clone());
+            return Some(chunk.clone());
         }
 
-        let query = DataQueryVariant::GetChunk(ChunkAddress(*name));
-        let res = self.send_query(query.clone()).await?;
-
-        let op_id = res.operation_id;
-        let chunk: Chunk = match res.response {
-            QueryResponse::GetChunk(result) => {
-                result.map_err(|err| Error::ErrorMsg { source: err, op_id })
+        if let Some(disk_cache) = &self.disk_chunks_cache {
+            if let Some(chunk) = disk_cache.get(name).await {
+                trace!("Chunk retrieved from disk cache: {:?}", name);
+                let _ = self.chunks_cache.write().await.insert(chunk.clone());
+                return Some(chunk);
             }
-            response => return Err(Error::UnexpectedQueryResponse { query, response }),
-        }?;
+        }
 
-        let _ = self.chunks_cache.write().await.insert(chunk.clone());
+        None
+    }
 
-        Ok(chunk)
+    async fn cache_chunk(&self, chunk: &Chunk) {
+        let _ = self.chunks_cache.write().await.insert(chunk.clone());
+        if let Some(disk_cache) = &self.disk_chunks_cache {
+            disk_cache.put(chunk).await;
+        }
     }
 
     /// Tries to chunk the bytes, returning an address and chunks, without storing anything to network.
@@ -132,7 +268,7 @@ impl Client {
     /// Does not store anything to the network.
     fn package_small(file: SmallFile) -> Result<Chunk> {
         let chunk = to_chunk(file.bytes());
-        if chunk.value().len() >= self_encryption::MIN_ENCRYPTABLE_BYTES {
+        if chunk.value().len() >= MIN_ENCRYPTABLE_BYTES {
             return Err(Error::SmallFilePaddingNeeded(chunk.value().len()));
         }
         Ok(chunk)
@@ -163,6 +299,291 @@ impl Client {
         Ok((address, bytes))
     }
 
+    /// Checks that each chunk of the content stored at `address` can be retrieved from each of
+    /// the adults expected to hold a copy of it, querying the adults one by one.
+    ///
+    /// Unlike [`Client::upload_and_verify`], which succeeds as soon as the content can be read,
+    /// this finds out the copies of the chunks which were lost, or never stored, e.g. as the
+    /// upload was interrupted. Those can then be restored with [`Client::repair_file`].
+    #[instrument(skip(self), level = "debug")]
+    pub async fn verify_file(&self, address: XorName) -> Result<FileVerification> {
+        let chunks = self.chunk_names_of(address).await?;
+
+        let attempt_timeout = self
+            .query_timeout
+            .div_f32(self.chunk_query_retries as f32 + 1.0);
+        let mut missing: BTreeMap<XorName, Vec<usize>> = BTreeMap::new();
+        for names in chunks.chunks(self.max_concurrent_chunk_queries) {
+            let queries = names
+                .iter()
+                .map(|name| DataQueryVariant::GetChunk(ChunkAddress(*name)))
+                .collect_vec();
+            for adult_index in 0..data_copy_count() {
+                let responses = self
+                    .send_batch_query_to_adult(&queries, adult_index, attempt_timeout)
+                    .await;
+                for ((name, query), response) in names.iter().zip(&queries).zip(responses) {
+                    let retrieved = matches!(
+                        response.map(|res| chunk_from_query_result(query.clone(), res)),
+                        Some(Ok(_))
+                    );
+                    if !retrieved {
+                        debug!("Chunk {name:?} could not be retrieved from adult #{adult_index}");
+                        missing.entry(*name).or_default().push(adult_index);
+                    }
+                }
+            }
+        }
+
+        Ok(FileVerification {
+            address,
+            chunks,
+            missing,
+        })
+    }
+
+    /// Stores again, from a local copy of the content, the chunks found to be missing
+    /// from some of their holders by [`Client::verify_file`], returning their names.
+    #[instrument(skip(self, bytes), level = "debug")]
+    pub async fn repair_file(
+        &self,
+        bytes: Bytes,
+        verification: &FileVerification,
+    ) -> Result<Vec<XorName>> {
+        let (address, chunks) = self.chunk_bytes(bytes)?;
+        if address != verification.address {
+            return Err(Error::ContentMismatch {
+                expected: verification.address,
+                found: address,
+            });
+        }
+
+        let mut repaired = vec![];
+        for chunk in chunks {
+            if verification.missing.contains_key(chunk.name()) {
+                let name = *chunk.name();
+                self.send_cmd(DataCmd::StoreChunk(chunk)).await?;
+                repaired.push(name);
+            }
+        }
+
+        Ok(repaired)
+    }
+
+    // Lists the names of the chunks of the content stored at `address`,
+    // walking through all the levels of its data maps
+    async fn chunk_names_of(&self, address: XorName) -> Result<Vec<XorName>> {
+        let mut names = vec![address];
+        let mut chunk = self.get_chunk(&address).await?;
+        loop {
+            match deserialize(chunk.value()) {
+                Ok(DataMapLevel::First(data_map)) => {
+                    names.extend(data_map.infos().iter().map(|info| info.dst_hash));
+                    break;
+                }
+                Ok(DataMapLevel::Additional(data_map)) => {
+                    names.extend(data_map.infos().iter().map(|info| info.dst_hash));
+                    let serialized_chunk = self.read_all(data_map).await?;
+                    chunk = deserialize(&serialized_chunk)?;
+                }
+                // the content is a SmallFile, held in a single chunk
+                Err(_) => break,
+            }
+        }
+
+        Ok(names)
+    }
+
+    /// Writes the content read from an [`AsyncRead`] source to the network in the
+    /// form of immutable chunks, without any batching. Exactly `size` bytes are read.
+    ///
+    /// Unlike [`Client::upload`], the content is read, self-encrypted and sent one chunk
+    /// at a time, so only a few chunks are held in memory regardless of the content size.
+    #[instrument(skip(self, reader), level = "debug")]
+    pub async fn upload_stream<R: AsyncRead + Unpin>(
+        &self,
+        reader: R,
+        size: usize,
+    ) -> Result<XorName> {
+        self.resume_upload_stream(reader, size, &BTreeSet::new(), |_| Ok(()))
+            .await
+    }
+
+    /// Writes the content read from an [`AsyncRead`] source to the network as [`Client::upload_stream`]
+    /// does, but skipping the chunks in `stored_chunks`, which are known to have already been stored,
+    /// e.g. by a previous upload of the same content which was interrupted.
+    ///
+    /// `on_chunk_stored` is called with the name of each chunk as soon as the network
+    /// acknowledges it was stored, so the caller can keep track of the upload's progress.
+    #[instrument(skip_all, level = "debug")]
+    pub async fn resume_upload_stream<R, F>(
+        &self,
+        reader: R,
+        size: usize,
+        stored_chunks: &BTreeSet<XorName>,
+        mut on_chunk_stored: F,
+    ) -> Result<XorName>
+    where
+        R: AsyncRead + Unpin,
+        F: FnMut(XorName) -> Result<()>,
+    {
+        if size < MIN_ENCRYPTABLE_BYTES {
+            let file = SmallFile::new(read_small(reader, size).await?)?;
+            let chunk = Self::package_small(file)?;
+            let address = *chunk.name();
+            if !stored_chunks.contains(&address) {
+                self.send_cmd(DataCmd::StoreChunk(chunk)).await?;
+                on_chunk_stored(address)?;
+            }
+            return Ok(address);
+        }
+
+        let mut encryptor = StreamEncryptor::new(reader, size)?;
+        let mut tasks = FuturesUnordered::new();
+        while let Some(chunk) = encryptor.next_chunk().await? {
+            self.store_chunk_bounded(&mut tasks, chunk, stored_chunks, &mut on_chunk_stored)
+                .await?;
+        }
+
+        let (head_address, data_map_chunks) = encryptor.finish()?;
+        for chunk in data_map_chunks {
+            self.store_chunk_bounded(&mut tasks, chunk, stored_chunks, &mut on_chunk_stored)
+                .await?;
+        }
+
+        while let Some(res) = tasks.next().await {
+            // fail with any issue here
+            on_chunk_stored(res.map_err(io::Error::from)??)?;
+        }
+
+        Ok(head_address)
+    }
+
+    /// Writes the content read from an [`AsyncRead`] source to the network in the
+    /// form of immutable chunks, without any batching. Exactly `size` bytes are read.
+    /// It also attempts to verify that all the data was uploaded to the network before returning,
+    /// streaming it back without holding it in memory.
+    #[instrument(skip(self, reader), level = "trace")]
+    pub async fn upload_stream_and_verify<R: AsyncRead + Unpin>(
+        &self,
+        reader: R,
+        size: usize,
+    ) -> Result<XorName> {
+        let address = self.upload_stream(reader, size).await?;
+
+        // let's now try to retrieve it
+        let _ = self.read_stream(address, tokio::io::sink()).await?;
+
+        Ok(address)
+    }
+
+    /// Reads the content stored at `address` from the network, and writes it to the
+    /// provided [`AsyncWrite`] destination, returning the number of bytes written.
+    ///
+    /// Unlike [`Client::read_bytes`], chunks are retrieved and decrypted a few at a time,
+    /// and written out in order, so only a few chunks are held in memory regardless of the content size.
+    #[instrument(skip(self, writer), level = "debug")]
+    pub async fn read_stream<W: AsyncWrite + Unpin>(
+        &self,
+        address: XorName,
+        mut writer: W,
+    ) -> Result<u64> {
+        let chunk = self.get_chunk(&address).await?;
+
+        // First try to deserialize a LargeFile, if it works, we go and stream it.
+        // If an error occurs, we consider it to be a SmallFile.
+        let data_map = match self.unpack_chunk(chunk.clone()).await {
+            Ok(data_map) => Arc::new(data_map),
+            Err(_) => {
+                writer.write_all(chunk.value()).await?;
+                writer.flush().await?;
+                return Ok(chunk.value().len() as u64);
+            }
+        };
+
+        let expected_count = data_map.infos().len();
+        let windows = data_map
+            .infos()
+            .chunks(self.max_concurrent_chunk_queries)
+            .map(<[ChunkInfo]>::to_vec)
+            .collect_vec();
+        let mut next_window = windows.first().map(|infos| self.spawn_get_chunks(infos));
+        let mut written = 0;
+        for (index, chunks_info) in windows.iter().enumerate() {
+            let results = match next_window.take() {
+                Some(handle) => handle.await.map_err(io::Error::from)?,
+                None => break,
+            };
+            // the next chunks are fetched while these ones are decrypted and written out
+            next_window = windows
+                .get(index + 1)
+                .map(|infos| self.spawn_get_chunks(infos));
+
+            let decryptions = chunks_info.iter().zip(results).map(|(chunk_info, result)| {
+                let data_map = data_map.clone();
+                let chunk_info = chunk_info.clone();
+                task::spawn(async move {
+                    let chunk = match result {
+                        Ok(chunk) => chunk,
+                        Err(err) => {
+                            warn!(
+                                "Reading chunk {} from network, resulted in error {:?}.",
+                                chunk_info.dst_hash, err
+                            );
+                            // chunks are written out in order, thus all the previous ones were retrieved
+                            return Err(Error::NotEnoughChunksRetrieved {
+                                expected: expected_count,
+                                retrieved: chunk_info.index,
+                            });
+                        }
+                    };
+                    let encrypted_chunk = EncryptedChunk {
+                        index: chunk_info.index,
+                        content: chunk.value().clone(),
+                    };
+                    let bytes = self_encryption::decrypt_range(
+                        &data_map,
+                        &[encrypted_chunk],
+                        0,
+                        usize::MAX,
+                    )?;
+                    Ok::<_, Error>(bytes)
+                })
+            });
+
+            for res in join_all(decryptions).await {
+                let bytes = res.map_err(io::Error::from)??;
+                writer.write_all(&bytes).await?;
+                written += bytes.len() as u64;
+            }
+        }
+        writer.flush().await?;
+
+        trace!("{} bytes streamed from {:?}", written, address);
+
+        Ok(written)
+    }
+
+    /// Calculates a LargeFile's/SmallFile's address from the content read from an [`AsyncRead`] source,
+    /// without storing anything onto the network nor holding the whole content in memory.
+    #[instrument(skip(reader), level = "debug")]
+    pub async fn calculate_address_from_stream<R: AsyncRead + Unpin>(
+        reader: R,
+        size: usize,
+    ) -> Result<XorName> {
+        if size < MIN_ENCRYPTABLE_BYTES {
+            let file = SmallFile::new(read_small(reader, size).await?)?;
+            let chunk = Self::package_small(file)?;
+            return Ok(*chunk.name());
+        }
+
+        let mut encryptor = StreamEncryptor::new(reader, size)?;
+        while encryptor.next_chunk().await?.is_some() {}
+        let (head_address, _data_map_chunks) = encryptor.finish()?;
+
+        Ok(head_address)
+    }
+
     /// Calculates a LargeFile's/SmallFile's address from self encrypted chunks,
     /// without storing them onto the network.
     #[instrument(skip(bytes), level = "debug")]
@@ -216,6 +637,39 @@ impl Client {
     // ---------- Private helpers -----------------
     // --------------------------------------------
 
+    // Spawns a task storing the chunk to the network, unless it's known to have been stored already,
+    // after waiting for one of the ongoing ones to complete if there are already too many in flight.
+    async fn store_chunk_bounded<F>(
+        &self,
+        tasks: &mut FuturesUnordered<JoinHandle<Result<XorName>>>,
+        chunk: Chunk,
+        stored_chunks: &BTreeSet<XorName>,
+        on_chunk_stored: &mut F,
+    ) -> Result<()>
+    where
+        F: FnMut(XorName) -> Result<()>,
+    {
+        if stored_chunks.contains(chunk.name()) {
+            trace!("Skipping chunk already stored: {:?}", chunk.name());
+            return Ok(());
+        }
+
+        if tasks.len() >= STREAM_CHUNKS_CONCURRENCY {
+            if let Some(res) = tasks.next().await {
+                on_chunk_stored(res.map_err(io::Error::from)??)?;
+            }
+        }
+
+        let writer = self.clone();
+        tasks.push(task::spawn(async move {
+            let name = *chunk.name();
+            writer.send_cmd(DataCmd::StoreChunk(chunk)).await?;
+            Ok(name)
+        }));
+
+        Ok(())
+    }
+
     // Gets and decrypts chunks from the network using nothing else but the data map,
     // then returns the raw data.
     async fn read_all(&self, data_map: DataMap) -> Result<Bytes> {
@@ -232,6 +686,16 @@ impl Client {
         let range = &info.index_range;
         let all_infos = data_map.infos();
 
+        // the chunks following the range are fetched in the background, for them to be
+        // in our cache already if they're read next, as when reading a file sequentially
+        self.prefetch_chunks(
+            all_infos
+                .iter()
+                .skip(range.end + 1)
+                .take(self.chunk_prefetch),
+        )
+        .await;
+
         let encrypted_chunks = Self::try_get_chunks(
             self,
             (range.start..range.end + 1)
@@ -254,33 +718,28 @@ impl Client {
     ) -> Result<Vec<EncryptedChunk>> {
         let expected_count = chunks_info.len();
 
-        let tasks = chunks_info.into_iter().map(|chunk_info| {
-            let client = client.clone();
-            task::spawn(async move {
-                match client.get_chunk(&chunk_info.dst_hash).await {
-                    Ok(chunk) => Ok(EncryptedChunk {
-                        index: chunk_info.index,
-                        content: chunk.value().clone(),
-                    }),
-                    Err(err) => {
-                        warn!(
-                            "Reading chunk {} from network, resulted in error {:?}.",
-                            chunk_info.dst_hash, err
-                        );
-                        Err(err)
-                    }
-                }
-            })
-        });
+        let names = chunks_info.iter().map(|info| info.dst_hash).collect_vec();
+        let results = client.get_chunks(&names).await;
 
         // This swallowing of errors
         // is basically a compaction into a single
         // error saying "didn't get all chunks".
-        let encrypted_chunks = join_all(tasks)
-            .await
+        let encrypted_chunks = chunks_info
             .into_iter()
-            .flatten()
-            .flatten()
+            .zip(results)
+            .filter_map(|(chunk_info, result)| match result {
+                Ok(chunk) => Some(EncryptedChunk {
+                    index: chunk_info.index,
+                    content: chunk.value().clone(),
+                }),
+                Err(err) => {
+                    warn!(
+                        "Reading chunk {} from network, resulted in error {:?}.",
+                        chunk_info.dst_hash, err
+                    );
+                    None
+                }
+            })
             .collect_vec();
 
         if expected_count > encrypted_chunks.len() {
@@ -312,19 +771,107 @@ impl Client {
     }
 }
 
+// Fetches the chunks with the given queries to the nth adult holding them, retrying at the
+// next adult, up to `retries` times, those left without a response or answered with an error.
+// Results are returned by order of the names, the last error received being kept for a chunk
+// which could not be retrieved from any of the adults.
+async fn fetch_chunks_with_retries<F, Fut>(
+    names: &[XorName],
+    retries: usize,
+    query_adult: F,
+) -> Vec<Result<Chunk>>
+where
+    F: Fn(Vec<DataQueryVariant>, usize) -> Fut,
+    Fut: Future<Output = Vec<Option<QueryResult>>>,
+{
+    let mut results = Vec::with_capacity(names.len());
+    results.resize_with(names.len(), || None);
+
+    let mut pending = (0..names.len()).collect_vec();
+    let mut attempts = 0;
+    while !pending.is_empty() && attempts <= retries {
+        // There should not be more than a certain amount of adults holding copies of the data.
+        let adult_index = attempts % data_copy_count();
+        attempts += 1;
+
+        let queries = pending
+            .iter()
+            .map(|index| DataQueryVariant::GetChunk(ChunkAddress(names[*index])))
+            .collect_vec();
+        let responses = query_adult(queries.clone(), adult_index).await;
+
+        let mut unanswered = vec![];
+        for ((index, query), response) in pending.into_iter().zip(queries).zip(responses) {
+            match response.map(|res| chunk_from_query_result(query, res)) {
+                Some(Ok(chunk)) => results[index] = Some(Ok(chunk)),
+                Some(Err(error)) => {
+                    trace!(
+                        "Error for chunk {:?} from adult #{adult_index}: {error:?}",
+                        names[index]
+                    );
+                    results[index] = Some(Err(error));
+                    unanswered.push(index);
+                }
+                None => {
+                    trace!(
+                        "No response for chunk {:?} from adult #{adult_index}",
+                        names[index]
+                    );
+                    unanswered.push(index);
+                }
+            }
+        }
+        pending = unanswered;
+    }
+
+    for index in pending {
+        let _ = results[index].get_or_insert_with(|| {
+            Err(Error::ChunkNotRetrieved {
+                name: names[index],
+                attempts,
+            })
+        });
+    }
+
+    results.into_iter().flatten().collect()
+}
+
+// Extracts the chunk from the result of a query for it
+fn chunk_from_query_result(query: DataQueryVariant, res: QueryResult) -> Result<Chunk> {
+    let op_id = res.operation_id;
+    match res.response {
+        QueryResponse::GetChunk(result) => {
+            result.map_err(|err| Error::ErrorMsg { source: err, op_id })
+        }
+        response => Err(Error::UnexpectedQueryResponse { query, response }),
+    }
+}
+
+// Reads the whole content of a source which is too small to be self-encrypted.
+async fn read_small<R: AsyncRead + Unpin>(mut reader: R, size: usize) -> Result<Bytes> {
+    let mut bytes = vec![0; size];
+    let _ = reader.read_exact(&mut bytes).await?;
+    Ok(Bytes::from(bytes))
+}
+
 #[cfg(test)]
 mod tests {
+    use super::fetch_chunks_with_retries;
     use crate::{
         api::file_apis::LargeFile,
+        connections::QueryResult,
         utils::test_utils::{create_test_client, create_test_client_with, init_logger},
-        Client,
+        Client, Error,
+    };
+    use sn_interface::{
+        messaging::data::{chunk_operation_id, DataQueryVariant, Error as ErrorMsg, QueryResponse},
+        types::{log_markers::LogMarker, utils::random_bytes, Chunk},
     };
-    use sn_interface::types::{log_markers::LogMarker, utils::random_bytes};
 
     use bytes::Bytes;
     use eyre::{eyre, Result};
     use futures::future::join_all;
-    use std::time::Duration;
+    use std::{sync::Mutex, time::Duration};
     use tokio::time::Instant;
     use tracing::{instrument::Instrumented, Instrument};
     use xor_name::XorName;
@@ -351,6 +898,56 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn chunk_queries_are_retried_at_the_next_adult() -> Result<()> {
+        init_logger();
+        let held_by_all = Chunk::new(random_bytes(100));
+        let held_by_second = Chunk::new(random_bytes(100));
+        let held_by_none = Chunk::new(random_bytes(100));
+        let names = [&held_by_all, &held_by_second, &held_by_none].map(|chunk| *chunk.name());
+
+        let queried = Mutex::new(vec![]);
+        let results = fetch_chunks_with_retries(&names, 2, |queries, adult_index| {
+            queried.lock().unwrap().push((adult_index, queries.len()));
+            let responses = queries
+                .into_iter()
+                .map(|query| {
+                    let address = match query {
+                        DataQueryVariant::GetChunk(address) => address,
+                        _ => return None,
+                    };
+                    let result = [&held_by_all, &held_by_second]
+                        .into_iter()
+                        .find(|chunk| chunk.address() == &address)
+                        .filter(|chunk| adult_index > 0 || chunk.name() == held_by_all.name())
+                        .cloned()
+                        .ok_or(ErrorMsg::ChunkNotFound(*address.name()));
+                    Some(QueryResult {
+                        response: QueryResponse::GetChunk(result),
+                        operation_id: chunk_operation_id(&address).ok()?,
+                    })
+                })
+                .collect::<Vec<_>>();
+            async move { responses }
+        })
+        .await;
+
+        // only the chunks answered with an error were queried again, at the next adults
+        assert_eq!(*queried.lock().unwrap(), vec![(0, 3), (1, 2), (2, 1)]);
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().ok(), Some(&held_by_all));
+        assert_eq!(results[1].as_ref().ok(), Some(&held_by_second));
+        assert!(matches!(
+            results[2],
+            Err(Error::ErrorMsg {
+                source: ErrorMsg::ChunkNotFound(name),
+                ..
+            }) if name == *held_by_none.name()
+        ));
+
+        Ok(())
+    }
+
     // Test storing and reading min sized LargeFile.
     #[tokio::test(flavor = "multi_thread")]
     async fn store_and_read_3kb() -> Result<()> {
@@ -379,6 +976,151 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn store_and_read_stream() -> Result<()> {
+        init_logger();
+        let _start_span = tracing::info_span!("store_and_read_stream").entered();
+
+        let client = create_test_client().await?;
+
+        for size in [
+            LARGE_FILE_SIZE_MIN / 2,
+            3 * self_encryption::MAX_CHUNK_SIZE + 7,
+        ] {
+            let bytes = random_bytes(size);
+
+            let address = client
+                .upload_stream_and_verify(bytes.as_ref(), bytes.len())
+                .await?;
+            assert_eq!(address, Client::calculate_address(bytes.clone())?);
+
+            let mut read_data = vec![];
+            let written = client.read_stream(address, &mut read_data).await?;
+            assert_eq!(written, size as u64);
+
+            compare(bytes, Bytes::from(read_data))?;
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn get_chunks_in_batches() -> Result<()> {
+        init_logger();
+        let _start_span = tracing::info_span!("get_chunks_in_batches").entered();
+
+        let client = create_test_client().await?;
+
+        let file = LargeFile::new(random_bytes(10 * LARGE_FILE_SIZE_MIN))?;
+        let (_, chunks) = client.chunk_bytes(file.bytes())?;
+        let _ = client.upload_and_verify(file.bytes()).await?;
+
+        // a client with empty caches queries all the chunks in batches,
+        // along with one which doesn't exist
+        let reader = create_test_client().await?;
+        let mut names = chunks.iter().map(|chunk| *chunk.name()).collect::<Vec<_>>();
+        names.push(rand::random());
+        let results = reader.get_chunks(&names).await;
+
+        assert_eq!(results.len(), names.len());
+        for (chunk, result) in chunks.iter().zip(&results) {
+            assert_eq!(result.as_ref().ok(), Some(chunk));
+        }
+        assert!(results[chunks.len()].is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn read_with_chunk_fetch_pipeline() -> Result<()> {
+        init_logger();
+        let _start_span = tracing::info_span!("read_with_chunk_fetch_pipeline").entered();
+
+        let client = create_test_client().await?;
+        let size = 10 * LARGE_FILE_SIZE_MIN;
+        let file = LargeFile::new(random_bytes(size))?;
+        let (address, _) = client.upload_and_verify(file.bytes()).await?;
+
+        // chunks are fetched one at a time, prefetching the two following a read
+        let reader = Client::builder()
+            .max_concurrent_chunk_queries(1)
+            .chunk_prefetch(2)
+            .build()
+            .await?;
+
+        let len = LARGE_FILE_SIZE_MIN;
+        let read_data = reader.read_from(address, 0, len).await?;
+        compare(file.bytes().slice(0..len), read_data)?;
+
+        let data_map = reader
+            .unpack_chunk(reader.get_chunk(&address).await?)
+            .await?;
+        let read_range = self_encryption::seek_info(size, 0, len).index_range;
+        let prefetched = data_map
+            .infos()
+            .into_iter()
+            .skip(read_range.end + 1)
+            .take(2)
+            .collect::<Vec<_>>();
+        assert_eq!(prefetched.len(), 2);
+
+        // the prefetched chunks end up in the cache
+        let mut attempts = 0;
+        loop {
+            let mut cached = 0;
+            for info in &prefetched {
+                if reader.get_cached_chunk(&info.dst_hash).await.is_some() {
+                    cached += 1;
+                }
+            }
+            if cached == prefetched.len() {
+                break;
+            }
+            attempts += 1;
+            if attempts > 30 {
+                return Err(eyre!("The chunks following the read were not prefetched"));
+            }
+            tokio::time::sleep(Duration::from_secs(1)).await;
+        }
+
+        let read_data = reader.read_bytes(address).await?;
+        compare(file.bytes(), read_data)?;
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn verify_and_repair_file() -> Result<()> {
+        init_logger();
+        let _start_span = tracing::info_span!("verify_and_repair_file").entered();
+
+        let client = create_test_client().await?;
+        let file = LargeFile::new(random_bytes(3 * LARGE_FILE_SIZE_MIN))?;
+        let (address, _) = client.upload_and_verify(file.bytes()).await?;
+
+        // all the chunks, the data map one included, are found at all their holders
+        let verification = client.verify_file(address).await?;
+        let (_, chunks) = client.chunk_bytes(file.bytes())?;
+        assert_eq!(verification.address, address);
+        assert_eq!(verification.chunks.len(), chunks.len());
+        assert!(verification.is_complete(), "{:?}", verification.missing);
+
+        // only the chunks found missing are stored again
+        let mut incomplete = verification.clone();
+        let _ = incomplete.missing.insert(*chunks[0].name(), vec![0]);
+        let repaired = client.repair_file(file.bytes(), &incomplete).await?;
+        assert_eq!(repaired, vec![*chunks[0].name()]);
+
+        // the repair is done from a copy of the content verified only
+        let other_file = LargeFile::new(random_bytes(3 * LARGE_FILE_SIZE_MIN))?;
+        match client.repair_file(other_file.bytes(), &incomplete).await {
+            Err(Error::ContentMismatch { expected, .. }) => assert_eq!(expected, address),
+            other => return Err(eyre!("Unexpected repair result: {:?}", other)),
+        }
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn seek_with_unknown_length() -> Result<()> {
         init_logger();
@@ -6,6 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+mod chunk_cache;
 /// A [`Client`] builder
 pub mod client_builder;
 mod cmds;
@@ -16,8 +17,10 @@ mod register_apis;
 mod spentbook_apis;
 
 pub use client_builder::ClientBuilder;
+pub use file_apis::FileVerification;
 pub use register_apis::RegisterWriteAheadLog;
 
+use self::chunk_cache::DiskChunkCache;
 use crate::{connections::Session, errors::Error};
 
 use sn_dbc::Owner;
@@ -31,7 +34,7 @@ use sn_interface::{
 };
 
 use bytes::Bytes;
-use std::sync::Arc;
+use std::{collections::BTreeSet, sync::Arc};
 use tokio::{sync::RwLock, time::Duration};
 use tracing::debug;
 use uluru::LRUCache;
@@ -62,6 +65,12 @@ pub struct Client {
     pub(crate) max_retries: usize,
     pub(crate) cmd_timeout: Duration,
     chunks_cache: Arc<RwLock<ChunksCache>>,
+    disk_chunks_cache: Option<Arc<DiskChunkCache>>,
+    max_concurrent_chunk_queries: usize,
+    chunk_prefetch: usize,
+    // names of the chunks being prefetched
+    prefetching: Arc<RwLock<BTreeSet<XorName>>>,
+    chunk_query_retries: usize,
 }
 
 /// Easily manage connections to/from The Safe Network with the client and its APIs.
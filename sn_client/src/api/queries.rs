@@ -12,14 +12,17 @@ use crate::{connections::QueryResult, errors::Error};
 use sn_interface::{
     data_copy_count,
     messaging::{
-        data::{DataQuery, DataQueryVariant, ServiceMsg},
+        data::{DataQuery, DataQueryVariant, QueryResponse, ServiceMsg, MAX_QUERIES_PER_BATCH},
         ServiceAuth, WireMsg,
     },
     types::{Peer, PublicKey, Signature},
 };
 
 use bytes::Bytes;
+use futures::future::join_all;
+use itertools::Itertools;
 use rand::Rng;
+use std::{collections::BTreeMap, time::Duration};
 use tracing::{debug, info_span};
 
 impl Client {
@@ -132,6 +135,148 @@ impl Client {
         }
     }
 
+    /// Send several Queries to the network and await their responses.
+    /// Queries for data held by the same section are sent together, in batches of up to
+    /// `MAX_QUERIES_PER_BATCH` queries, the ones left without a response being then sent on
+    /// their own, with retries as with `send_query`.
+    /// Results are returned by order of the queries.
+    #[instrument(skip_all, level = "debug")]
+    pub async fn send_batch_query(
+        &self,
+        queries: Vec<DataQueryVariant>,
+    ) -> Vec<Result<QueryResult, Error>> {
+        let attempt_timeout = self.query_timeout.div_f32(self.max_retries as f32 + 1.0);
+        let mut results = self
+            .send_batch_query_to_adult(&queries, 0, attempt_timeout)
+            .await
+            .into_iter()
+            .map(|result| result.map(Ok))
+            .collect_vec();
+
+        // the queries which were left without a response are sent on their own
+        let unanswered = results
+            .iter()
+            .enumerate()
+            .filter(|(_, result)| result.is_none())
+            .map(|(index, _)| index)
+            .collect_vec();
+        let retries = join_all(
+            unanswered
+                .iter()
+                .map(|index| self.send_query(queries[*index].clone())),
+        )
+        .await;
+        for (index, result) in unanswered.into_iter().zip(retries) {
+            results[index] = Some(result);
+        }
+
+        results.into_iter().flatten().collect()
+    }
+
+    // Sends the queries in batches to the sections holding their data, each query being forwarded
+    // to the nth adult holding its data, and awaits their responses for up to the given time.
+    // Responses are returned by order of the queries, those left without a response being `None`.
+    pub(crate) async fn send_batch_query_to_adult(
+        &self,
+        queries: &[DataQueryVariant],
+        adult_index: usize,
+        timeout: Duration,
+    ) -> Vec<Option<QueryResult>> {
+        let mut results = Vec::with_capacity(queries.len());
+        results.resize_with(queries.len(), || None);
+
+        // group the queries by the section holding their data, as found in our network knowledge
+        let mut sections: BTreeMap<bls::PublicKey, (Vec<Peer>, Vec<usize>)> = BTreeMap::new();
+        for (index, query) in queries.iter().enumerate() {
+            match self.session.get_query_elders(query.dst_name()).await {
+                Ok((section_pk, elders)) => sections
+                    .entry(section_pk)
+                    .or_insert_with(|| (elders, vec![]))
+                    .1
+                    .push(index),
+                Err(error) => debug!("Query {query:?} can't be batched: {error:?}"),
+            }
+        }
+
+        let batches = sections
+            .into_iter()
+            .flat_map(|(section_pk, (elders, indexes))| {
+                indexes
+                    .chunks(MAX_QUERIES_PER_BATCH)
+                    .map(|indexes| (section_pk, elders.clone(), indexes.to_vec()))
+                    .collect_vec()
+            });
+        let tasks = batches.map(|(section_pk, elders, indexes)| {
+            let batch = indexes
+                .iter()
+                .map(|index| queries[*index].clone())
+                .collect();
+            async move {
+                let responses = self
+                    .send_batch_query_to_section(batch, adult_index, section_pk, elders, timeout)
+                    .await;
+                (indexes, responses)
+            }
+        });
+        for (indexes, responses) in join_all(tasks).await {
+            match responses {
+                Ok(responses) => {
+                    for (index, response) in indexes.into_iter().zip(responses) {
+                        results[index] = response.and_then(|response| {
+                            let operation_id = response.operation_id().ok()?;
+                            Some(QueryResult {
+                                response,
+                                operation_id,
+                            })
+                        });
+                    }
+                }
+                Err(error) => warn!("Failed to send a batch of queries: {error:?}"),
+            }
+        }
+
+        results
+    }
+
+    // Private helper to send a batch of queries to the given elders of a section.
+    // A batch of a single query is sent as that query.
+    async fn send_batch_query_to_section(
+        &self,
+        mut queries: Vec<DataQueryVariant>,
+        adult_index: usize,
+        section_pk: bls::PublicKey,
+        elders: Vec<Peer>,
+        timeout: Duration,
+    ) -> Result<Vec<Option<QueryResponse>>, Error> {
+        let variant = if queries.len() == 1 {
+            queries.remove(0)
+        } else {
+            DataQueryVariant::Batch(queries)
+        };
+        let query = DataQuery {
+            adult_index,
+            variant,
+        };
+        let msg = ServiceMsg::Query(query.clone());
+        let serialised_query = WireMsg::serialize_msg_payload(&msg)?;
+        let auth = ServiceAuth {
+            public_key: self.public_key(),
+            signature: self.keypair.sign(&serialised_query),
+        };
+
+        self.session
+            .send_batch_query(
+                query,
+                auth,
+                serialised_query,
+                #[cfg(feature = "traceroute")]
+                self.public_key(),
+                (section_pk, elders),
+                timeout,
+            )
+            .await
+    }
+
     /// Send a Query to the network and await a response.
     /// This is part of a public API, for the user to
     /// provide the serialised and already signed query.
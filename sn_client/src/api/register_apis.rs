@@ -11,19 +11,39 @@ use super::Client;
 use crate::Error;
 
 use sn_interface::{
-    messaging::data::{
-        CreateRegister, DataCmd, DataQueryVariant, EditRegister, QueryResponse, RegisterCmd,
-        RegisterQuery, SignedRegisterCreate, SignedRegisterEdit,
+    messaging::{
+        data::{
+            CreateRegister, DataCmd, DataQueryVariant, EditRegister, EditRegisterPolicy,
+            QueryResponse, RegisterCmd, RegisterQuery, ServiceMsg, SignedRegisterCreate,
+            SignedRegisterEdit, SignedRegisterPolicyEdit,
+        },
+        WireMsg,
     },
     types::{
-        register::{Action, Entry, EntryHash, Permissions, Policy, Register, User},
-        RegisterAddress as Address,
+        register::{
+            Action, Entry, EntryHash, HistoryEntry, Permissions, Policy, PolicyOp, Register, User,
+        },
+        Error as DtError, RegisterAddress as Address,
     },
 };
 
-use std::collections::BTreeSet;
+use futures::{stream, Stream};
+use std::{
+    collections::{BTreeSet, VecDeque},
+    time::Duration,
+};
+use tokio::sync::mpsc::channel;
 use xor_name::XorName;
 
+// How often the subscriptions to the changes of a register are renewed,
+// well within the time Elders hold them for
+const REGISTER_WATCH_RENEWAL_INTERVAL: Duration = Duration::from_secs(30);
+// Number of register cmds which can be waiting to be consumed from a watch stream
+const REGISTER_WATCH_BUFFER: usize = 100;
+// Number of the latest register cmds a watch stream remembers, to skip the copies
+// pushed by the other Elders, which arrive shortly after the first one
+const REGISTER_WATCH_SEEN_OPS: usize = 1000;
+
 /// Register Write Ahead Log
 ///
 /// Batches up register write operation before publishing them up to the network, in order.
@@ -124,6 +144,61 @@ impl Client {
         Ok((hash, batch))
     }
 
+    /// Replace the policy of a Register, e.g. to grant or revoke write permissions to users.
+    /// Only the owner of the Register is allowed to do this.
+    ///
+    /// Returns a write ahead log (WAL) of register operations, note that the changes are not uploaded to the
+    /// network until the WAL is published with `publish_register_ops`
+    #[instrument(skip(self), level = "debug")]
+    pub async fn update_register_policy(
+        &self,
+        address: Address,
+        policy: Policy,
+    ) -> Result<RegisterWriteAheadLog, Error> {
+        debug!("Updating policy of register at {:?}", address);
+        let mut register = self.get_register(address).await?;
+
+        // Let's generate the op on behalf of our key, the replica fails if we are not the owner.
+        let op = register.update_policy(policy, Some(User::Key(self.keypair.public_key())))?;
+        Ok(vec![self.sign_register_policy_op(address, op)?])
+    }
+
+    /// Transfer the ownership of a Register to a new owner, keeping its current permissions.
+    /// Only the owner of the Register is allowed to do this.
+    ///
+    /// Returns a write ahead log (WAL) of register operations, note that the changes are not uploaded to the
+    /// network until the WAL is published with `publish_register_ops`
+    #[instrument(skip(self), level = "debug")]
+    pub async fn transfer_register_ownership(
+        &self,
+        address: Address,
+        new_owner: User,
+    ) -> Result<RegisterWriteAheadLog, Error> {
+        debug!("Transferring ownership of register at {:?}", address);
+        let mut register = self.get_register(address).await?;
+
+        // Let's generate the op on behalf of our key, the replica fails if we are not the owner.
+        let op =
+            register.transfer_ownership(new_owner, Some(User::Key(self.keypair.public_key())))?;
+        Ok(vec![self.sign_register_policy_op(address, op)?])
+    }
+
+    // Private helper to package a policy op for the network's replicas
+    fn sign_register_policy_op(&self, address: Address, op: PolicyOp) -> Result<DataCmd, Error> {
+        let op = EditRegisterPolicy { address, edit: op };
+        let signature = self.keypair.sign(&bincode::serialize(&op)?);
+
+        let edit = SignedRegisterPolicyEdit {
+            op,
+            auth: sn_interface::messaging::ServiceAuth {
+                public_key: self.keypair.public_key(),
+                signature,
+            },
+        };
+
+        Ok(DataCmd::Register(RegisterCmd::EditPolicy(edit)))
+    }
+
     //----------------------
     // Get Register
     //---------------------
@@ -184,6 +259,127 @@ impl Client {
         }
     }
 
+    /// Get the history of a Register from the Network, i.e. its entries along with the ones each
+    /// of them was written on top of, in topological order. The history starts from the entry
+    /// with the `from` hash, or from the latest entries if not provided, and goes down `depth`
+    /// levels below it, or down to the very first entries if no `depth` is provided.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn get_register_history(
+        &self,
+        address: Address,
+        from: Option<EntryHash>,
+        depth: Option<usize>,
+    ) -> Result<Vec<HistoryEntry>, Error> {
+        let register = self.get_register(address).await?;
+        match from {
+            Some(hash) => Ok(register.ancestors(hash, depth)?),
+            None => Ok(register.history(depth)),
+        }
+    }
+
+    //----------------------
+    // Watch Register
+    //---------------------
+
+    /// Watch the changes made to a Register on the Network.
+    ///
+    /// Returns a stream of the cmds applied to the Register from now on, as pushed by the Elders
+    /// holding it. Each cmd is yielded once, even though several Elders push it, and only if it's
+    /// allowed by the Register's policy, which is checked against a replica of the Register
+    /// kept up to date with the cmds pushed. The subscription is renewed in the background for
+    /// as long as the stream is not dropped.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn watch_register(
+        &self,
+        address: Address,
+    ) -> Result<impl Stream<Item = RegisterCmd>, Error> {
+        let mut updates = self.session.add_register_watcher(address);
+        self.subscribe_to_register(address).await?;
+        let mut register = self.get_register(address).await?;
+
+        let (sender, receiver) = channel(REGISTER_WATCH_BUFFER);
+        let client = self.clone();
+        let _handle = tokio::spawn(async move {
+            let mut seen_ops = BTreeSet::new();
+            let mut seen_order = VecDeque::new();
+            let mut renewal = tokio::time::interval(REGISTER_WATCH_RENEWAL_INTERVAL);
+            // the first tick completes immediately, and we've just subscribed
+            let _ = renewal.tick().await;
+
+            loop {
+                tokio::select! {
+                    update = updates.recv() => {
+                        let cmd = match update {
+                            Some(cmd) => cmd,
+                            None => break,
+                        };
+                        // several Elders push the same cmd
+                        if let Ok(op_id) = cmd.register_operation_id() {
+                            if seen_ops.contains(&op_id) {
+                                continue;
+                            }
+                        }
+                        // our replica may be missing ops applied before we fetched it, in
+                        // which case it's fetched again before the cmd is dropped
+                        if let Err(error) = apply_pushed_cmd(&mut register, &cmd) {
+                            let refetched = match client.get_register(address).await {
+                                Ok(refetched) => refetched,
+                                Err(_) => {
+                                    warn!("Dropping a cmd pushed for register {address:?}: {error}");
+                                    continue;
+                                }
+                            };
+                            register = refetched;
+                            if let Err(error) = apply_pushed_cmd(&mut register, &cmd) {
+                                warn!("Dropping a cmd pushed for register {address:?}: {error}");
+                                continue;
+                            }
+                        }
+                        if let Ok(op_id) = cmd.register_operation_id() {
+                            let _ = seen_ops.insert(op_id.clone());
+                            seen_order.push_back(op_id);
+                            if seen_order.len() > REGISTER_WATCH_SEEN_OPS {
+                                if let Some(oldest) = seen_order.pop_front() {
+                                    let _ = seen_ops.remove(&oldest);
+                                }
+                            }
+                        }
+                        if sender.send(cmd).await.is_err() {
+                            break;
+                        }
+                    }
+                    _ = renewal.tick() => {
+                        if sender.is_closed() {
+                            break;
+                        }
+                        if let Err(error) = client.subscribe_to_register(address).await {
+                            warn!("Failed to renew the subscription to register {address:?}: {error}");
+                        }
+                    }
+                }
+            }
+            debug!("Stopped watching register {address:?}");
+        });
+
+        Ok(stream::unfold(receiver, |mut receiver| async move {
+            receiver.recv().await.map(|cmd| (cmd, receiver))
+        }))
+    }
+
+    // Subscribes to the changes of the Register with the Elders holding it,
+    // or renews the subscription.
+    async fn subscribe_to_register(&self, address: Address) -> Result<(), Error> {
+        let serialised_msg = WireMsg::serialize_msg_payload(&ServiceMsg::WatchRegister(address))?;
+        let signature = self.keypair.sign(&serialised_msg);
+        self.send_signed_cmd(
+            *address.name(),
+            self.public_key(),
+            serialised_msg,
+            signature,
+        )
+        .await
+    }
+
     //----------------------
     // Ownership
     //---------------------
@@ -263,6 +459,30 @@ fn section_auth() -> sn_interface::messaging::SectionAuth {
     }
 }
 
+// Checks a cmd pushed for a watched Register is allowed by the policy of our replica of it,
+// the same way the Adults holding it do, and applies it to the replica if so. The signature
+// of the cmd was checked when it was received.
+fn apply_pushed_cmd(register: &mut Register, cmd: &RegisterCmd) -> Result<(), Error> {
+    match cmd {
+        // the Register exists already, as we got a replica of it
+        RegisterCmd::Create { .. } => Ok(()),
+        RegisterCmd::Edit(SignedRegisterEdit { op, auth }) => {
+            register.check_permissions(Action::Write, Some(User::Key(auth.public_key)))?;
+            register.apply_op(op.edit.clone())?;
+            Ok(())
+        }
+        RegisterCmd::EditPolicy(SignedRegisterPolicyEdit { op, auth }) => {
+            // the op must have been generated by its signer, whose ownership of the Register
+            // is then checked against the policy being replaced
+            if op.edit.source != User::Key(auth.public_key) {
+                return Err(DtError::AccessDenied(User::Key(auth.public_key)).into());
+            }
+            register.apply_policy_op(op.edit.clone())?;
+            Ok(())
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
@@ -271,20 +491,25 @@ mod tests {
         Error,
     };
 
+    use super::apply_pushed_cmd;
     use sn_interface::{
-        messaging::data::Error as ErrorMsg,
+        messaging::{
+            data::{EditRegister, Error as ErrorMsg, RegisterCmd, SignedRegisterEdit},
+            ServiceAuth,
+        },
         types::{
             log_markers::LogMarker,
-            register::{Action, EntryHash, Permissions, Policy, User},
+            register::{Action, EntryHash, Permissions, Policy, Register, User},
             Keypair,
         },
     };
 
     use eyre::{bail, eyre, Result};
+    use futures::StreamExt;
     use rand::Rng;
     use std::{
         collections::{BTreeMap, BTreeSet},
-        time::Instant,
+        time::{Duration, Instant},
     };
     use tracing::Instrument;
 
@@ -570,6 +795,51 @@ mod tests {
         }
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn register_watch() -> Result<()> {
+        init_logger();
+        let _outer_span = tracing::info_span!("test__register_watch").entered();
+
+        let client = create_test_client().await?;
+        let watcher = create_test_client().await?;
+
+        let name = rand::random();
+        let tag = 10;
+        let owner = User::Key(client.public_key());
+
+        let (address, batch) = client.create_register(name, tag, policy(owner)).await?;
+        client.publish_register_ops(batch).await?;
+        let _ = retry_loop_for_pattern!(client.get_register(address), Ok(_))?;
+
+        let updates = watcher.watch_register(address).await?;
+        futures::pin_mut!(updates);
+
+        let value = random_register_entry();
+        let (hash, batch) = client
+            .write_to_register(address, value.clone(), BTreeSet::new())
+            .await?;
+        client.publish_register_ops(batch).await?;
+
+        let update = tokio::time::timeout(Duration::from_secs(30), updates.next())
+            .await?
+            .ok_or_else(|| eyre!("The register watch stream ended unexpectedly"))?;
+        match update {
+            RegisterCmd::Edit(edit) => {
+                assert_eq!(edit.op.address, address);
+                assert_eq!(EntryHash(edit.op.edit.crdt_op.hash()), hash);
+                assert_eq!(edit.op.edit.crdt_op.value, value);
+            }
+            other => bail!("Unexpected register cmd pushed: {:?}", other),
+        }
+
+        // the same cmd pushed by the other Elders is not yielded again
+        assert!(tokio::time::timeout(Duration::from_secs(5), updates.next())
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn register_owner() -> Result<()> {
         init_logger();
@@ -592,6 +862,63 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test(flavor = "multi_thread")]
+    async fn register_policy_update_and_ownership_transfer() -> Result<()> {
+        init_logger();
+        let _outer_span =
+            tracing::info_span!("test__register_policy_update_and_ownership_transfer").entered();
+
+        let client = create_test_client().await?;
+
+        let name = rand::random();
+        let tag = 10;
+        let owner = User::Key(client.public_key());
+
+        let (address, batch) = client.create_register(name, tag, policy(owner)).await?;
+        client.publish_register_ops(batch).await?;
+
+        // grant write access to another user
+        let other_user = User::Key(Keypair::new_ed25519().public_key());
+        let mut new_policy = policy(owner);
+        let _prev = new_policy
+            .permissions
+            .insert(other_user, Permissions::new(true));
+        let batch = client
+            .update_register_policy(address, new_policy.clone())
+            .await?;
+        client.publish_register_ops(batch).await?;
+
+        let permissions = retry_loop_for_pattern!(
+            client.get_register_permissions_for_user(address, other_user),
+            Ok(_)
+        )?;
+        assert_eq!(Some(true), permissions.is_allowed(Action::Write));
+
+        // and then hand the ownership over to it
+        let batch = client
+            .transfer_register_ownership(address, other_user)
+            .await?;
+        client.publish_register_ops(batch).await?;
+
+        let current_owner = retry_loop_for_pattern!(
+            client.get_register_owner(address),
+            Ok(current_owner) if *current_owner == other_user
+        )?;
+        assert_eq!(other_user, current_owner);
+
+        // we are no longer the owner, so we cannot change the policy anymore
+        match client.update_register_policy(address, new_policy).await {
+            Err(Error::NetworkDataError(sn_interface::types::Error::AccessDenied(user))) => {
+                assert_eq!(user, owner);
+                Ok(())
+            }
+            other => Err(eyre!(
+                "Unexpected result when updating the policy without being the owner: {:?}",
+                other
+            )),
+        }
+    }
+
     #[tokio::test(flavor = "multi_thread")]
     async fn ae_checks_register_test() -> Result<()> {
         init_logger();
@@ -627,4 +954,41 @@ mod tests {
         let _ = permissions.insert(owner, Permissions::new(None));
         Policy { owner, permissions }
     }
+
+    #[test]
+    fn pushed_cmds_are_checked_against_the_policy() -> Result<()> {
+        let owner = Keypair::new_ed25519();
+        let other = Keypair::new_ed25519();
+        let name = rand::random();
+        let tag = 10;
+        let policy = policy(User::Key(owner.public_key()));
+        let mut replica = Register::new(User::Key(owner.public_key()), name, tag, policy.clone());
+
+        let edit_by = |keypair: &Keypair| -> Result<RegisterCmd> {
+            let authority = User::Key(keypair.public_key());
+            let mut register = Register::new(authority, name, tag, policy.clone());
+            let (_, edit) = register.write(random_register_entry(), BTreeSet::new())?;
+            let op = EditRegister {
+                address: *register.address(),
+                edit,
+            };
+            let signature = keypair.sign(&bincode::serialize(&op)?);
+            Ok(RegisterCmd::Edit(SignedRegisterEdit {
+                op,
+                auth: ServiceAuth {
+                    public_key: keypair.public_key(),
+                    signature,
+                },
+            }))
+        };
+
+        // a cmd by a user who's not a writer is not applied, even though it's validly signed
+        assert!(apply_pushed_cmd(&mut replica, &edit_by(&other)?).is_err());
+        assert_eq!(replica.size(), 0);
+
+        apply_pushed_cmd(&mut replica, &edit_by(&owner)?)?;
+        assert_eq!(replica.size(), 1);
+
+        Ok(())
+    }
 }
@@ -9,21 +9,25 @@
 use super::Session;
 
 use crate::{
-    connections::{messaging::NUM_OF_ELDERS_SUBSET_FOR_QUERIES, PendingCmdAcks},
+    connections::{
+        messaging::NUM_OF_ELDERS_SUBSET_FOR_QUERIES, PendingCmdAcks, PendingQueryResponses,
+    },
     Error, Result,
 };
 
 use sn_interface::{
     at_least_one_correct_elder,
     messaging::{
-        data::{CmdError, ServiceMsg},
+        data::{CmdError, QueryResponse, RegisterCmd, ServiceMsg},
         system::{AntiEntropyKind, KeyedSig, NodeMsgAuthorityUtils, SectionAuth, SystemMsg},
-        AuthKind, AuthorityProof, Dst, MsgId, MsgType, NodeMsgAuthority, ServiceAuth, WireMsg,
+        AuthKind, AuthorityProof, Dst, MsgId, MsgType, NodeMsgAuthority, ServiceAuth,
+        VerifyAuthority, WireMsg,
     },
-    network_knowledge::{NetworkKnowledge, SectionAuthorityProvider},
+    network_knowledge::{NetworkKnowledge, SectionAuthorityProvider, SectionTree},
     types::{log_markers::LogMarker, Peer},
 };
 
+use bincode::serialize;
 use bls::PublicKey as BlsPublicKey;
 use bytes::Bytes;
 use itertools::Itertools;
@@ -31,6 +35,7 @@ use qp2p::{Close, ConnectionError, ConnectionIncoming as IncomingMsgs, SendError
 use rand::{rngs::OsRng, seq::SliceRandom};
 use secured_linked_list::SecuredLinkedList;
 use std::net::SocketAddr;
+use tokio::sync::RwLock;
 use tracing::Instrument;
 
 impl Session {
@@ -213,6 +218,42 @@ impl Session {
         }
     }
 
+    // Relays the query response to the channels waiting for it
+    fn send_query_response(
+        queries: &PendingQueryResponses,
+        response: QueryResponse,
+        msg_id: MsgId,
+        correlation_id: MsgId,
+    ) {
+        if let Ok(op_id) = response.operation_id() {
+            if let Some(entry) = queries.get(&op_id) {
+                let all_senders = entry.value();
+                // Only valid response shall get broadcast to all
+                for (ori_msg_id, sender) in all_senders {
+                    let res = if response.is_success() || ori_msg_id == &correlation_id {
+                        sender.try_send(response.clone())
+                    } else {
+                        continue;
+                    };
+                    if res.is_err() {
+                        trace!("Error relaying query response internally on a channel for {:?} op_id {:?}: {:?}. (It has likely been removed)", msg_id, op_id, res)
+                    }
+                }
+            } else {
+                // TODO: The trace is only needed when we have an identified case of not finding a channel, but expecting one.
+                // When expecting one, we can log "No channel found for operation", (and then probably at warn or error level).
+                // But when we have received enough responses, we aren't really expecting a channel there, so there is no reason to log anything.
+                // Right now, if we have already received enough responses for a query,
+                // we drop the channels and drop any further responses for that query.
+                // but we should not drop it immediately, but clean it up after a while
+                // and then not log that "no channel was found" when we already had enough responses.
+                //trace!("No channel found for operation {}", op_id);
+            }
+        } else {
+            warn!("Ignoring query response without operation id");
+        }
+    }
+
     // Handle msgs intended for client consumption (re: queries + cmds)
     #[instrument(skip(session), level = "debug")]
     fn handle_client_msg(
@@ -228,6 +269,8 @@ impl Session {
         );
         let queries = session.pending_queries.clone();
         let cmds = session.pending_cmds;
+        let register_watchers = session.register_watchers;
+        let network = session.network;
 
         let _handle = tokio::spawn(async move {
             match msg {
@@ -246,33 +289,14 @@ impl Session {
                     // Once we are satisfied with the response this is channel is discarded in
                     // ConnectionManager::send_query
 
-                    if let Ok(op_id) = response.operation_id() {
-                        if let Some(entry) = queries.get(&op_id) {
-                            let all_senders = entry.value();
-                            // Only valid response shall get broadcast to all
-                            for (ori_msg_id, sender) in all_senders {
-                                let res = if response.is_success() || ori_msg_id == &correlation_id
-                                {
-                                    sender.try_send(response.clone())
-                                } else {
-                                    continue;
-                                };
-                                if res.is_err() {
-                                    trace!("Error relaying query response internally on a channel for {:?} op_id {:?}: {:?}. (It has likely been removed)", msg_id, op_id, res)
-                                }
-                            }
-                        } else {
-                            // TODO: The trace is only needed when we have an identified case of not finding a channel, but expecting one.
-                            // When expecting one, we can log "No channel found for operation", (and then probably at warn or error level).
-                            // But when we have received enough responses, we aren't really expecting a channel there, so there is no reason to log anything.
-                            // Right now, if we have already received enough responses for a query,
-                            // we drop the channels and drop any further responses for that query.
-                            // but we should not drop it immediately, but clean it up after a while
-                            // and then not log that "no channel was found" when we already had enough responses.
-                            //trace!("No channel found for operation {}", op_id);
-                        }
-                    } else {
-                        warn!("Ignoring query response without operation id");
+                    // The responses of a batch are each dispatched on their own, as they may have been
+                    // queried individually as well
+                    let responses = match response {
+                        QueryResponse::Batch(responses) => responses,
+                        response => vec![response],
+                    };
+                    for response in responses {
+                        Self::send_query_response(&queries, response, msg_id, correlation_id);
                     }
                 }
                 ServiceMsg::CmdError {
@@ -292,6 +316,32 @@ impl Session {
                     );
                     Self::send_cmd_response(cmds, correlation_id, src_peer.addr(), None);
                 }
+                ServiceMsg::RegisterUpdate(cmd) => {
+                    let address = cmd.dst_address();
+                    debug!(
+                        "RegisterUpdate for {:?} was received from {:?}",
+                        address,
+                        src_peer.addr()
+                    );
+                    if let Err(error) = Self::check_register_update(&network, &cmd, &src_peer).await
+                    {
+                        warn!(
+                            "Dropping an update of register {address:?} from {src_peer:?}: {error}"
+                        );
+                        return;
+                    }
+                    if let Some(mut watchers) = register_watchers.get_mut(&address) {
+                        // the watchers which went away are discarded
+                        watchers.retain(|sender| !sender.is_closed());
+                        for sender in watchers.iter() {
+                            if let Err(error) = sender.try_send(cmd.clone()) {
+                                warn!("Dropping an update of register {address:?}: {error}");
+                            }
+                        }
+                    }
+                    let _ =
+                        register_watchers.remove_if(&address, |_, watchers| watchers.is_empty());
+                }
                 _ => {
                     warn!("Ignoring unexpected msg type received: {:?}", msg);
                 }
@@ -301,6 +351,40 @@ impl Session {
         Ok(())
     }
 
+    // Checks a register update is signed by its author, and pushed by an Elder of the section
+    // holding the register, as only these are subscribed to
+    async fn check_register_update(
+        network: &RwLock<SectionTree>,
+        cmd: &RegisterCmd,
+        src_peer: &Peer,
+    ) -> Result<(), String> {
+        let name = *cmd.dst_address().name();
+        let sap = network
+            .read()
+            .await
+            .section_by_name(&name)
+            .map_err(|error| error.to_string())?;
+        if sap.get_elder(&src_peer.name()) != Some(src_peer) {
+            return Err("the sender is not an Elder of the register's section".to_string());
+        }
+
+        let verification = match cmd {
+            RegisterCmd::Create { cmd, .. } => {
+                serialize(&cmd.op).map(|payload| cmd.auth.clone().verify_authority(payload))
+            }
+            RegisterCmd::Edit(cmd) => {
+                serialize(&cmd.op).map(|payload| cmd.auth.clone().verify_authority(payload))
+            }
+            RegisterCmd::EditPolicy(cmd) => {
+                serialize(&cmd.op).map(|payload| cmd.auth.clone().verify_authority(payload))
+            }
+        };
+        match verification {
+            Ok(Ok(_)) => Ok(()),
+            _ => Err("the cmd signature is invalid".to_string()),
+        }
+    }
+
     // Handle Anti-Entropy Redirect or Retry msgs
     #[instrument(skip_all, level = "debug")]
     async fn handle_ae_msg(
@@ -485,3 +569,71 @@ impl Session {
         Ok(Some((msg_id, target_elders, service_msg, dst, auth)))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sn_interface::{
+        messaging::data::{EditRegister, SignedRegisterEdit},
+        network_knowledge::test_utils::{random_sap, section_signed},
+        types::{
+            register::{Register, User},
+            Keypair,
+        },
+    };
+
+    use eyre::{eyre, Result};
+    use std::collections::BTreeSet;
+    use xor_name::Prefix;
+
+    fn signed_edit(keypair: &Keypair, signer: &Keypair) -> Result<RegisterCmd> {
+        let owner = User::Key(keypair.public_key());
+        let mut register = Register::new_owned(owner, rand::random(), 10);
+        let (_hash, edit) = register.write(b"entry".to_vec(), BTreeSet::new())?;
+        let op = EditRegister {
+            address: *register.address(),
+            edit,
+        };
+        let auth = ServiceAuth {
+            public_key: keypair.public_key(),
+            signature: signer.sign(&serialize(&op)?),
+        };
+        Ok(RegisterCmd::Edit(SignedRegisterEdit { op, auth }))
+    }
+
+    #[tokio::test]
+    async fn register_update_is_checked() -> Result<()> {
+        let (sap, nodes, sk_set) = random_sap(Prefix::default(), 3, 1, None);
+        let mut network = SectionTree::new(sk_set.public_keys().public_key());
+        assert!(network.insert_without_chain(section_signed(sk_set.secret_key(), sap.clone())?));
+        let network = RwLock::new(network);
+
+        let elder = sap
+            .elders()
+            .next()
+            .copied()
+            .ok_or_else(|| eyre!("The section should have elders"))?;
+        let adult = nodes
+            .last()
+            .map(|node| node.peer())
+            .ok_or_else(|| eyre!("The section should have an adult"))?;
+        let keypair = Keypair::new_ed25519();
+
+        let cmd = signed_edit(&keypair, &keypair)?;
+        assert!(Session::check_register_update(&network, &cmd, &elder)
+            .await
+            .is_ok());
+        // only the elders of the register's section push its updates
+        assert!(Session::check_register_update(&network, &cmd, &adult)
+            .await
+            .is_err());
+
+        // nor is an update accepted when not signed by its author
+        let forged = signed_edit(&keypair, &Keypair::new_ed25519())?;
+        assert!(Session::check_register_update(&network, &forged, &elder)
+            .await
+            .is_err());
+
+        Ok(())
+    }
+}
@@ -18,7 +18,7 @@ use sn_interface::{
 
 use sn_interface::{
     messaging::{
-        data::{DataQuery, DataQueryVariant, QueryResponse},
+        data::{DataQuery, DataQueryVariant, OperationId, QueryResponse},
         AuthKind, Dst, MsgId, ServiceAuth, WireMsg,
     },
     network_knowledge::supermajority,
@@ -28,10 +28,11 @@ use sn_interface::{
 use backoff::{backoff::Backoff, ExponentialBackoff};
 use bytes::Bytes;
 use futures::future::join_all;
+use itertools::Itertools;
 use qp2p::{Close, ConnectionError, SendError};
 use rand::{rngs::OsRng, seq::SliceRandom};
-use std::time::Duration;
-use tokio::{sync::mpsc::channel, task::JoinHandle};
+use std::{collections::BTreeMap, time::Duration};
+use tokio::{sync::mpsc::channel, task::JoinHandle, time::Instant};
 use tracing::{debug, error, trace, warn};
 use xor_name::XorName;
 
@@ -321,6 +322,150 @@ impl Session {
         }
     }
 
+    #[instrument(
+        skip(self, auth, payload, client_pk),
+        level = "debug",
+        name = "session send batch query"
+    )]
+    /// Send a batch of queries to the given elders, awaiting for the responses to each of them
+    /// until they're all settled or the timeout is hit.
+    /// The responses are returned by order of the queries, those left without a response being `None`.
+    pub(crate) async fn send_batch_query(
+        &self,
+        query: DataQuery,
+        auth: ServiceAuth,
+        payload: Bytes,
+        #[cfg(feature = "traceroute")] client_pk: PublicKey,
+        dst_section_info: (bls::PublicKey, Vec<Peer>),
+        timeout: Duration,
+    ) -> Result<Vec<Option<QueryResponse>>> {
+        let queries = match &query.variant {
+            DataQueryVariant::Batch(queries) => queries.clone(),
+            variant => vec![variant.clone()],
+        };
+        let dst = query.variant.dst_name();
+        let (section_pk, elders) = dst_section_info;
+        let elders_len = elders.len();
+        let msg_id = MsgId::new();
+
+        debug!(
+            "Sending batch query message {:?} of {} queries, from {}, to the {} Elders closest to data name: {:?}",
+            msg_id,
+            queries.len(),
+            self.endpoint.public_addr(),
+            elders_len,
+            elders
+        );
+
+        // All the queries of the batch share the same channel, their responses being told apart
+        // by their operation id, which is that of the corresponding queries sent on their own.
+        let (sender, mut receiver) = channel::<QueryResponse>(queries.len() * elders_len + 1);
+        let mut pending: BTreeMap<OperationId, Vec<usize>> = BTreeMap::new();
+        for (index, query) in queries.iter().enumerate() {
+            match query.operation_id() {
+                Ok(op_id) => pending.entry(op_id).or_default().push(index),
+                Err(_) => warn!("No op_id found for query {query:?}, leaving it out of the batch"),
+            }
+        }
+        for op_id in pending.keys() {
+            self.pending_queries
+                .entry(*op_id)
+                .or_default()
+                .push((msg_id, sender.clone()));
+        }
+        let op_ids = pending.keys().copied().collect_vec();
+
+        let dst = Dst {
+            name: dst,
+            section_key: section_pk,
+        };
+        let auth = AuthKind::Service(auth);
+
+        #[allow(unused_mut)]
+        let mut wire_msg = WireMsg::new_msg(msg_id, payload, auth, dst);
+
+        #[cfg(feature = "traceroute")]
+        wire_msg.append_trace(&mut Traceroute(vec![Entity::Client(client_pk)]));
+
+        let mut responses = vec![None; queries.len()];
+        if let Err(error) = self.clone().send_msg_in_bg(elders, wire_msg, msg_id) {
+            self.remove_query_channels(&op_ids, msg_id);
+            return Err(error);
+        }
+
+        // As with single queries, we accept the first valid response to each query,
+        // errors being only returned once each elder reported one
+        let mut errors: BTreeMap<OperationId, usize> = BTreeMap::new();
+        let deadline = Instant::now() + timeout;
+        while !pending.is_empty() {
+            let response = match tokio::time::timeout_at(deadline, receiver.recv()).await {
+                Ok(Some(response)) => response,
+                Ok(None) => {
+                    debug!("QueryResponse channel closed.");
+                    break;
+                }
+                Err(_) => {
+                    debug!("Timed out waiting for the responses to batch query {msg_id:?}");
+                    break;
+                }
+            };
+            let op_id = match response.operation_id() {
+                Ok(op_id) => op_id,
+                Err(_) => continue,
+            };
+            let indexes = match pending.get(&op_id) {
+                Some(indexes) => indexes,
+                None => continue,
+            };
+
+            if response.is_success() {
+                if let QueryResponse::GetChunk(Ok(chunk)) = &response {
+                    // the Chunk content must match its XorName, or it could be a byzantine node
+                    let valid = indexes.iter().all(|index| {
+                        matches!(&queries[*index], DataQueryVariant::GetChunk(address) if address.name() == chunk.name())
+                    });
+                    if !valid {
+                        warn!("We received an invalid Chunk response from one of the nodes");
+                        continue;
+                    }
+                }
+            } else {
+                debug!("QueryResponse error received (but may be overridden by a non-error response from another elder): {:#?}", &response);
+                let errors = errors.entry(op_id).or_default();
+                *errors += 1;
+                if *errors < elders_len {
+                    continue;
+                }
+            }
+
+            if let Some(indexes) = pending.remove(&op_id) {
+                for index in indexes {
+                    responses[index] = Some(response.clone());
+                }
+            }
+        }
+
+        self.remove_query_channels(&op_ids, msg_id);
+
+        debug!(
+            "Responses obtained for {} out of {} queries of batch query w/id {:?}",
+            responses.iter().flatten().count(),
+            queries.len(),
+            msg_id
+        );
+
+        Ok(responses)
+    }
+
+    // Removes the response channels of a query message, once done with it
+    fn remove_query_channels(&self, op_ids: &[OperationId], msg_id: MsgId) {
+        for op_id in op_ids {
+            if let Some(mut entry) = self.pending_queries.get_mut(op_id) {
+                entry.value_mut().retain(|(id, _sender)| *id != msg_id);
+            }
+        }
+    }
+
     #[instrument(skip_all, level = "debug")]
     pub(crate) async fn make_contact_with_nodes(
         &self,
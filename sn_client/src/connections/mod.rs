@@ -12,17 +12,20 @@ mod messaging;
 use crate::Result;
 use sn_interface::{
     messaging::{
-        data::{CmdError, OperationId, QueryResponse},
+        data::{CmdError, OperationId, QueryResponse, RegisterCmd},
         MsgId,
     },
     network_knowledge::SectionTree,
-    types::PeerLinks,
+    types::{PeerLinks, RegisterAddress},
 };
 
 use dashmap::DashMap;
 use qp2p::{Config as QuicP2pConfig, Endpoint};
 use std::{net::SocketAddr, sync::Arc, time::Duration};
-use tokio::sync::{mpsc::Sender, RwLock};
+use tokio::sync::{
+    mpsc::{channel, Receiver, Sender},
+    RwLock,
+};
 
 // Here we dont track the msg_id across the network, but just use it as a local identifier to remove the correct listener
 type PendingQueryResponses = Arc<DashMap<OperationId, Vec<(MsgId, QueryResponseSender)>>>;
@@ -31,6 +34,11 @@ type QueryResponseSender = Sender<QueryResponse>;
 type CmdResponse = (SocketAddr, Option<CmdError>);
 type PendingCmdAcks = Arc<DashMap<MsgId, Sender<CmdResponse>>>;
 
+type RegisterWatchers = Arc<DashMap<RegisterAddress, Vec<Sender<RegisterCmd>>>>;
+
+// Number of register cmds pushed by Elders which can be held for a watcher before dropping them
+const REGISTER_WATCHER_BUFFER: usize = 100;
+
 #[derive(Debug)]
 pub struct QueryResult {
     pub response: QueryResponse,
@@ -45,6 +53,8 @@ pub(super) struct Session {
     pending_queries: PendingQueryResponses,
     // Channels for sending CmdAck to upper layers
     pending_cmds: PendingCmdAcks,
+    // Channels for sending the register cmds pushed by Elders to upper layers
+    register_watchers: RegisterWatchers,
     /// All elders we know about from AE messages
     pub(super) network: Arc<RwLock<SectionTree>>,
     /// Standard time to await potential AE messages:
@@ -68,6 +78,7 @@ impl Session {
         let session = Session {
             pending_queries: Arc::new(DashMap::default()),
             pending_cmds: Arc::new(DashMap::default()),
+            register_watchers: Arc::new(DashMap::default()),
             endpoint,
             network: Arc::new(RwLock::new(network_contacts)),
             cmd_ack_wait,
@@ -76,4 +87,15 @@ impl Session {
 
         Ok(session)
     }
+
+    /// Returns a channel receiving the cmds Elders push for the register, once subscribed to it.
+    /// The channel is discarded once the receiver is dropped.
+    pub(crate) fn add_register_watcher(&self, address: RegisterAddress) -> Receiver<RegisterCmd> {
+        let (sender, receiver) = channel(REGISTER_WATCHER_BUFFER);
+        self.register_watchers
+            .entry(address)
+            .or_default()
+            .push(sender);
+        receiver
+    }
 }
@@ -170,6 +170,22 @@ pub enum Error {
         /// Number of Chunks retrieved
         retrieved: usize,
     },
+    /// No response was received for a chunk from any of the adults it was queried at.
+    #[error("No response received for chunk {name:?}, even after {attempts} attempts")]
+    ChunkNotRetrieved {
+        /// Name of the chunk
+        name: XorName,
+        /// Number of attempts made, each at a different adult
+        attempts: usize,
+    },
+    /// The content provided is not the one stored at the address expected.
+    #[error("The content provided is stored at {found:?} rather than at {expected:?}")]
+    ContentMismatch {
+        /// Address of the content expected
+        expected: XorName,
+        /// Address of the content provided
+        found: XorName,
+    },
     /// Could not chunk all the data required to encrypt the data. (Expected, Actual)
     #[error("Not all data was chunked, expected {expected}, but we have {chunked}.)")]
     NotAllDataWasChunked {
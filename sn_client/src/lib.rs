@@ -54,7 +54,9 @@ mod connections;
 mod errors;
 
 // Export public API.
-pub use api::{Client, RegisterWriteAheadLog, DEFAULT_NETWORK_CONTACTS_FILE_NAME};
+pub use api::{
+    Client, FileVerification, RegisterWriteAheadLog, DEFAULT_NETWORK_CONTACTS_FILE_NAME,
+};
 pub use errors::{Error, Result};
 pub use qp2p::Config as QuicP2pConfig;
 pub use sn_interface::messaging::data::Error as ErrorMsg;
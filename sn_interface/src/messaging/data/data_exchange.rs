@@ -6,13 +6,13 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::RegisterCmd;
+use super::{RegisterCmd, RegisterCmdId};
 use crate::{
     messaging::SectionAuth,
-    types::{Error, RegisterAddress, Result, SpentbookAddress},
+    types::{register::Register, Error, RegisterAddress, Result, SpentbookAddress},
 };
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use xor_name::XorName;
 
 /// Metadata (register and chunk holders) replication.
@@ -33,10 +33,24 @@ pub struct ReplicatedRegisterLog {
     pub address: RegisterAddress,
     /// This is a duplicated entry as it should exist in first cmd
     pub section_auth: SectionAuth,
-    ///
+    /// Snapshot of the Register state, standing in for the ops it covers, if any was taken
+    pub snapshot: Option<RegisterSnapshot>,
+    /// Ops not covered by the snapshot, always including the one creating the Register
     pub op_log: Vec<RegisterCmd>,
 }
 
+/// Materialised state of a Register, which replaces the ops it was built from,
+/// so they don't need to be stored, replayed, nor replicated anymore.
+/// The state is only exchanged between the nodes of a section, which trust it as it is,
+/// while the ops which came after it are replicated signed and verified on their own.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegisterSnapshot {
+    /// The Register with all the covered ops applied
+    pub state: Register,
+    /// Ids of the ops applied to the state
+    pub ops: BTreeSet<RegisterCmdId>,
+}
+
 /// Data to be exchanged between Spentbook stores.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SpentbookStoreExport(pub Vec<ReplicatedSpentbookLog>);
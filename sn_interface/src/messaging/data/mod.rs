@@ -18,15 +18,15 @@ mod spentbook;
 pub use self::{
     cmd::DataCmd,
     data_exchange::{
-        MetadataExchange, RegisterStoreExport, ReplicatedRegisterLog, ReplicatedSpentbookLog,
-        SpentbookStoreExport, StorageLevel,
+        MetadataExchange, RegisterSnapshot, RegisterStoreExport, ReplicatedRegisterLog,
+        ReplicatedSpentbookLog, SpentbookStoreExport, StorageLevel,
     },
     errors::{Error, Result},
     query::DataQuery,
-    query::DataQueryVariant,
+    query::{DataQueryVariant, MAX_QUERIES_PER_BATCH},
     register::{
-        CreateRegister, EditRegister, RegisterCmd, RegisterCmdId, RegisterQuery,
-        SignedRegisterCreate, SignedRegisterEdit,
+        CreateRegister, EditRegister, EditRegisterPolicy, RegisterCmd, RegisterCmdId,
+        RegisterQuery, SignedRegisterCreate, SignedRegisterEdit, SignedRegisterPolicyEdit,
     },
     spentbook::{SpentbookCmd, SpentbookQuery},
 };
@@ -34,7 +34,7 @@ pub use self::{
 use crate::messaging::{data::Error as ErrorMsg, MsgId};
 use crate::types::{
     register::{Entry, EntryHash, Permissions, Policy, Register, User},
-    utils, Chunk, ChunkAddress, ReplicatedDataAddress as DataAddress,
+    utils, Chunk, ChunkAddress, RegisterAddress, ReplicatedDataAddress as DataAddress,
 };
 
 use bytes::Bytes;
@@ -142,6 +142,21 @@ pub enum ServiceMsg {
         /// [`Cmd`]: Self::Cmd
         correlation_id: MsgId,
     },
+    /// Subscribes the client to the changes made to the [`Register`] at the given address.
+    ///
+    /// The receiving Elder acknowledges the subscription with a [`CmdAck`], and from then on
+    /// pushes a [`RegisterUpdate`] to the client for each cmd it handles for that [`Register`],
+    /// once an Adult reports it stored, until the subscription expires. Clients are thus
+    /// expected to renew it periodically.
+    ///
+    /// [`CmdAck`]: Self::CmdAck
+    /// [`RegisterUpdate`]: Self::RegisterUpdate
+    WatchRegister(RegisterAddress),
+    /// A cmd handled for a [`Register`] the client has subscribed to with [`WatchRegister`],
+    /// and stored by an Adult holding it.
+    ///
+    /// [`WatchRegister`]: Self::WatchRegister
+    RegisterUpdate(RegisterCmd),
 }
 
 impl ServiceMsg {
@@ -150,6 +165,7 @@ impl ServiceMsg {
         match self {
             Self::Cmd(cmd) => Some(cmd.dst_name()),
             Self::Query(query) => Some(query.variant.dst_name()),
+            Self::WatchRegister(address) => Some(*address.name()),
             _ => None,
         }
     }
@@ -180,6 +196,12 @@ impl Display for ServiceMsg {
                 write!(f, "ServiceMsg::QueryResponse({:?})", response)
             }
             ServiceMsg::ServiceError(error) => write!(f, "ServiceMsg::ServiceError({:?})", error),
+            ServiceMsg::WatchRegister(address) => {
+                write!(f, "ServiceMsg::WatchRegister({:?})", address)
+            }
+            ServiceMsg::RegisterUpdate(cmd) => {
+                write!(f, "ServiceMsg::RegisterUpdate({:?})", cmd.dst_address())
+            }
         }
     }
 }
@@ -229,6 +251,9 @@ pub enum QueryResponse {
     //
     // ===== Other =====
     //
+    /// Response to [`DataQueryVariant::Batch`], carrying the responses to the queries of the
+    /// batch which could be fulfilled, in no particular order.
+    Batch(Vec<QueryResponse>),
     /// Failed to create id generation
     FailedToCreateOperationId,
 }
@@ -246,6 +271,7 @@ impl QueryResponse {
             GetRegisterPolicy((result, _op_id)) => result.is_ok(),
             GetRegisterUserPermissions((result, _op_id)) => result.is_ok(),
             SpentProofShares((result, _op_id)) => result.is_ok(),
+            Batch(responses) => responses.iter().all(QueryResponse::is_success),
             FailedToCreateOperationId => false,
         }
     }
@@ -287,6 +313,9 @@ impl QueryResponse {
                 Ok(_) => false,
                 Err(error) => matches!(*error, ErrorMsg::DataNotFound(_)),
             },
+            Batch(responses) => responses
+                .iter()
+                .all(QueryResponse::failed_with_data_not_found),
             FailedToCreateOperationId => false,
         }
     }
@@ -323,7 +352,8 @@ impl QueryResponse {
             | GetRegisterPolicy((_, operation_id))
             | GetRegisterUserPermissions((_, operation_id))
             | SpentProofShares((_, operation_id)) => Ok(*operation_id),
-            FailedToCreateOperationId => Err(Error::NoOperationId),
+            // each of the responses of a batch has its own operation id
+            Batch(_) | FailedToCreateOperationId => Err(Error::NoOperationId),
         }
     }
 }
@@ -381,7 +411,10 @@ mod tests {
     use crate::types::{utils::random_bytes, Chunk, Keypair, PublicKey};
     use bytes::Bytes;
     use eyre::{eyre, Result};
-    use std::convert::{TryFrom, TryInto};
+    use std::{
+        convert::{TryFrom, TryInto},
+        iter,
+    };
 
     fn gen_keypairs() -> Vec<Keypair> {
         let mut rng = rand::thread_rng();
@@ -441,6 +474,75 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn batch_query_response() -> Result<()> {
+        let chunks = (0..3)
+            .map(|_| Chunk::new(random_bytes(1024)))
+            .collect::<Vec<_>>();
+        let batch = DataQueryVariant::Batch(
+            chunks
+                .iter()
+                .map(|chunk| DataQueryVariant::GetChunk(*chunk.address()))
+                .collect(),
+        );
+
+        // the batch is sent to the section of its queries, and told apart from the queries sent on their own
+        assert_eq!(batch.dst_name(), *chunks[0].name());
+        assert_ne!(
+            batch.operation_id()?,
+            DataQueryVariant::GetChunk(*chunks[0].address()).operation_id()?
+        );
+
+        // each of the responses of a batch keeps the operation id of its query
+        let response = batch.error(Error::ChunkNotFound(*chunks[0].name()))?;
+        assert!(response.failed_with_data_not_found());
+        assert!(response.operation_id().is_err());
+        let responses = match response {
+            QueryResponse::Batch(responses) => responses,
+            response => return Err(eyre!("Unexpected response {:?}", response)),
+        };
+        assert_eq!(responses.len(), chunks.len());
+
+        // a partial batch of responses is only a success if all its responses are
+        let partial = QueryResponse::Batch(vec![
+            QueryResponse::GetChunk(Ok(chunks[1].clone())),
+            QueryResponse::GetChunk(Ok(chunks[2].clone())),
+        ]);
+        assert!(partial.is_success());
+        assert!(!QueryResponse::Batch(vec![
+            QueryResponse::GetChunk(Ok(chunks[1].clone())),
+            QueryResponse::GetChunk(Err(Error::ChunkNotFound(*chunks[2].name()))),
+        ])
+        .is_success());
+
+        Ok(())
+    }
+
+    #[test]
+    fn batch_query_size_is_checked() {
+        let query = || DataQueryVariant::GetChunk(*Chunk::new(random_bytes(10)).address());
+        assert!(query().check_batch_size().is_ok());
+
+        let batch = |len| DataQueryVariant::Batch(iter::repeat_with(query).take(len).collect());
+        assert!(batch(1).check_batch_size().is_ok());
+        assert!(batch(MAX_QUERIES_PER_BATCH).check_batch_size().is_ok());
+        // an empty batch has no destination of its own
+        assert!(matches!(
+            batch(0).check_batch_size(),
+            Err(Error::InvalidOperation(_))
+        ));
+        assert!(matches!(
+            batch(MAX_QUERIES_PER_BATCH + 1).check_batch_size(),
+            Err(Error::InvalidOperation(_))
+        ));
+        // nor can a batch hold another one
+        let nested = DataQueryVariant::Batch(vec![query(), batch(1)]);
+        assert!(matches!(
+            nested.check_batch_size(),
+            Err(Error::InvalidOperation(_))
+        ));
+    }
+
     #[test]
     fn wire_msg_payload() -> Result<()> {
         use crate::messaging::data::DataCmd;
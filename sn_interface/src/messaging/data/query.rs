@@ -12,8 +12,13 @@ use super::{
 };
 use crate::types::{ChunkAddress, ReplicatedDataAddress, SpentbookAddress};
 use serde::{Deserialize, Serialize};
+use tiny_keccak::{Hasher, Sha3};
 use xor_name::XorName;
 
+/// Maximum number of queries a [`DataQueryVariant::Batch`] can hold, so a batch and its
+/// responses don't make for too large messages.
+pub const MAX_QUERIES_PER_BATCH: usize = 10;
+
 /// A query for requesting (meta)data at a particular adult.
 #[derive(Hash, Eq, PartialEq, PartialOrd, Clone, Serialize, Deserialize, Debug)]
 pub struct DataQuery {
@@ -48,6 +53,13 @@ pub enum DataQueryVariant {
     #[cfg(feature = "spentbook")]
     /// Spentbook read operation.
     Spentbook(SpentbookQuery),
+    /// Several queries for data held by the same section, sent as a single message.
+    ///
+    /// This should eventually lead to [`Batch`] responses, each carrying the responses to the
+    /// queries which could be fulfilled so far. Batches can't be nested.
+    ///
+    /// [`Batch`]: QueryResponse::Batch
+    Batch(Vec<DataQueryVariant>),
 }
 
 impl DataQueryVariant {
@@ -62,6 +74,39 @@ impl DataQueryVariant {
             Register(q) => q.error(error),
             #[cfg(feature = "spentbook")]
             Spentbook(q) => q.error(error),
+            Batch(queries) => Ok(QueryResponse::Batch(
+                queries
+                    .iter()
+                    .map(|query| query.error(error.clone()))
+                    .collect::<Result<_>>()?,
+            )),
+        }
+    }
+
+    /// Checks a batch holds at least one query and at most [`MAX_QUERIES_PER_BATCH`], none of
+    /// them a batch, as its destination is that of its first query. Other queries are always
+    /// valid in this respect.
+    pub fn check_batch_size(&self) -> Result<()> {
+        match self {
+            DataQueryVariant::Batch(queries) if queries.is_empty() => Err(Error::InvalidOperation(
+                "A batch must hold at least one query".to_string(),
+            )),
+            DataQueryVariant::Batch(queries) if queries.len() > MAX_QUERIES_PER_BATCH => {
+                Err(Error::InvalidOperation(format!(
+                    "A batch can hold at most {MAX_QUERIES_PER_BATCH} queries, not {}",
+                    queries.len()
+                )))
+            }
+            DataQueryVariant::Batch(queries)
+                if queries
+                    .iter()
+                    .any(|query| matches!(query, DataQueryVariant::Batch(_))) =>
+            {
+                Err(Error::InvalidOperation(
+                    "A batch cannot hold another batch".to_string(),
+                ))
+            }
+            _ => Ok(()),
         }
     }
 
@@ -75,6 +120,11 @@ impl DataQueryVariant {
             Register(q) => q.dst_name(),
             #[cfg(feature = "spentbook")]
             Spentbook(q) => q.dst_name(),
+            // all the queries of a batch are destined to the same section
+            Batch(queries) => queries
+                .first()
+                .map(DataQueryVariant::dst_name)
+                .unwrap_or_default(),
         }
     }
 
@@ -89,6 +139,10 @@ impl DataQueryVariant {
             DataQueryVariant::Spentbook(read) => {
                 ReplicatedDataAddress::Spentbook(SpentbookAddress::new(*read.dst_address().name()))
             }
+            DataQueryVariant::Batch(queries) => match queries.first() {
+                Some(query) => query.address(),
+                None => ReplicatedDataAddress::Chunk(ChunkAddress(XorName::default())),
+            },
         }
     }
 
@@ -104,6 +158,14 @@ impl DataQueryVariant {
             DataQueryVariant::Register(read) => read.operation_id(),
             #[cfg(feature = "spentbook")]
             DataQueryVariant::Spentbook(read) => read.operation_id(),
+            DataQueryVariant::Batch(_) => {
+                let bytes = crate::types::utils::encode(&self).map_err(|_| Error::NoOperationId)?;
+                let mut hasher = Sha3::v256();
+                let mut output = [0; 32];
+                hasher.update(bytes.as_bytes());
+                hasher.finalize(&mut output);
+                Ok(OperationId(output))
+            }
         }
     }
 }
@@ -12,7 +12,7 @@ use crate::messaging::{data::OperationId, SectionAuth};
 #[allow(unused_imports)] // needed by rustdocs links
 use crate::types::register::Register;
 use crate::types::{
-    register::{Entry, EntryHash, Policy, RegisterOp, User},
+    register::{Entry, EntryHash, Policy, PolicyOp, RegisterOp, User},
     utils::serialise,
     RegisterAddress,
 };
@@ -91,6 +91,9 @@ pub enum RegisterCmd {
     },
     /// Edit the [`Register`].
     Edit(SignedRegisterEdit),
+    /// Edit the policy of the [`Register`], e.g. to change the permissions
+    /// or to transfer its ownership.
+    EditPolicy(SignedRegisterPolicyEdit),
 }
 
 ///
@@ -129,6 +132,15 @@ pub struct EditRegister {
     pub edit: RegisterOp<Entry>,
 }
 
+/// Replace the policy of a [`Register`], e.g. to transfer its ownership.
+#[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
+pub struct EditRegisterPolicy {
+    /// The address of the [`Register`] to edit.
+    pub address: RegisterAddress,
+    /// The policy operation to perform.
+    pub edit: PolicyOp,
+}
+
 /// A signed cmd to create a [`Register`].
 #[derive(Debug, Eq, PartialEq, Clone, Serialize, Deserialize)]
 pub struct SignedRegisterCreate {
@@ -151,6 +163,17 @@ pub struct SignedRegisterEdit {
     pub auth: crate::messaging::ServiceAuth,
 }
 
+/// A [`Register`] policy operation signed by the requester.
+#[derive(Eq, PartialEq, Clone, Serialize, Deserialize, Debug)]
+pub struct SignedRegisterPolicyEdit {
+    /// The operation to perform.
+    pub op: EditRegisterPolicy,
+    /// A signature carrying authority to perform the operation.
+    ///
+    /// This will be verified against the register's owner.
+    pub auth: crate::messaging::ServiceAuth,
+}
+
 impl SignedRegisterCreate {
     /// Returns the dst address of the register.
     pub fn dst_address(&self) -> RegisterAddress {
@@ -165,6 +188,13 @@ impl SignedRegisterEdit {
     }
 }
 
+impl SignedRegisterPolicyEdit {
+    /// Returns the dst address of the register.
+    pub fn dst_address(&self) -> &RegisterAddress {
+        &self.op.address
+    }
+}
+
 impl RegisterQuery {
     /// Creates a Response containing an error, with the Response variant corresponding to the
     /// Request variant.
@@ -265,6 +295,7 @@ impl RegisterCmd {
         match self {
             Self::Create { cmd, .. } => cmd.dst_address(),
             Self::Edit(cmd) => *cmd.dst_address(),
+            Self::EditPolicy(cmd) => *cmd.dst_address(),
         }
     }
 
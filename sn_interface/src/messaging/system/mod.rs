@@ -223,7 +223,8 @@ impl SystemMsg {
             | SystemMsg::MembershipVotes(_)
             | SystemMsg::MembershipAE(_)
             | SystemMsg::HandoverAE(_)
-            | SystemMsg::HandoverVotes(_) => MEMBERSHIP_PRIORITY,
+            | SystemMsg::HandoverVotes(_)
+            | SystemMsg::NodeEvent(NodeEvent::LeavingSection { .. }) => MEMBERSHIP_PRIORITY,
 
             // Inter-node comms for joining, relocating etc.
             SystemMsg::Relocate(_)
@@ -240,6 +241,7 @@ impl SystemMsg {
             // Inter-node comms related to processing client requests
             SystemMsg::NodeCmd(_)
             | SystemMsg::NodeEvent(NodeEvent::CouldNotStoreData { .. })
+            | SystemMsg::NodeEvent(NodeEvent::ReplicatedDataStored { .. })
             | SystemMsg::NodeQuery(_)
             | SystemMsg::NodeQueryResponse { .. } => NODE_DATA_MSG_PRIORITY,
         }
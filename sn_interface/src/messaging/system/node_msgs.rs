@@ -7,7 +7,10 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use crate::messaging::{
-    data::{DataQueryVariant, MetadataExchange, OperationId, QueryResponse, Result, StorageLevel},
+    data::{
+        DataQueryVariant, MetadataExchange, OperationId, QueryResponse, RegisterCmdId, Result,
+        StorageLevel,
+    },
     EndUser, MsgId, ServiceAuth,
 };
 use crate::types::{
@@ -59,6 +62,23 @@ pub enum NodeEvent {
         /// Whether store failed due to full
         full: bool,
     },
+    #[cfg(any(feature = "chunks", feature = "registers"))]
+    /// Sent by an Adult to the node which asked it to replicate some data, once it stored it
+    ReplicatedDataStored {
+        /// Node Id
+        node_id: PublicKey,
+        /// Addresses of the data stored
+        data: Vec<ReplicatedDataAddress>,
+        /// Ids of the register cmds stored, which Elders then push to the clients watching
+        /// the register, as these cmds were found valid as per the register's policy
+        register_cmds: Vec<RegisterCmdId>,
+    },
+    /// Sent by a node which is shutting down, and asks the Elders to vote it out of the section.
+    /// The node hands its data off to the new holders before it goes.
+    LeavingSection {
+        /// Node Id
+        node_id: PublicKey,
+    },
 }
 
 /// Query originating at a node
@@ -120,6 +140,8 @@ pub enum NodeQueryResponse {
     //
     // ===== Other =====
     //
+    /// Response to [`crate::messaging::data::DataQueryVariant::Batch`].
+    Batch(Vec<NodeQueryResponse>),
     /// Failed to create id generation
     FailedToCreateOperationId,
 }
@@ -144,6 +166,12 @@ impl NodeQueryResponse {
             GetRegisterUserPermissions(res) => QueryResponse::GetRegisterUserPermissions(res),
             #[cfg(feature = "spentbook")]
             SpentProofShares(res) => QueryResponse::SpentProofShares(res),
+            Batch(responses) => QueryResponse::Batch(
+                responses
+                    .into_iter()
+                    .map(NodeQueryResponse::convert)
+                    .collect(),
+            ),
             FailedToCreateOperationId => QueryResponse::FailedToCreateOperationId,
         }
     }
@@ -53,6 +53,7 @@ pub enum LogMarker {
     ChunkQueryResponseReceviedFromAdult,
     ChunkQueryReceviedAtElder,
     ChunkQueryReceviedAtAdult,
+    CorruptChunkQuarantined,
     // Data reorganisation
     RequestForAnyMissingData,
     DataReorganisationUnderway,
@@ -8,12 +8,15 @@
 
 mod metadata;
 mod policy;
+mod policy_crdt;
 mod reg_crdt;
 
 pub use metadata::{Action, Entry};
 pub use policy::{Permissions, Policy, User};
-pub use reg_crdt::EntryHash;
+pub use policy_crdt::{PolicyHash, PolicyOp};
+pub use reg_crdt::{EntryHash, HistoryEntry};
 
+pub(crate) use policy_crdt::PolicyCrdt;
 pub(crate) use reg_crdt::{CrdtOperation, RegisterCrdt};
 
 use super::{Error, Result};
@@ -27,7 +30,7 @@ use std::{
 use xor_name::XorName;
 
 /// Arbitrary maximum size of a register entry.
-const MAX_REG_ENTRY_SIZE: usize = MIN_ENCRYPTABLE_BYTES / 3; // 1024 bytes
+pub const MAX_REG_ENTRY_SIZE: usize = MIN_ENCRYPTABLE_BYTES / 3; // 1024 bytes
 
 /// Maximum number of entries of a register.
 const MAX_REG_NUM_ENTRIES: u16 = u16::MAX;
@@ -40,7 +43,7 @@ pub type RegisterOp<T> = CrdtOperation<T>;
 pub struct Register {
     authority: User,
     pub(super) crdt: RegisterCrdt, // Temporarily exposed to 'super' till spentbook fully implemented.
-    policy: Policy,
+    policy: PolicyCrdt,
 }
 
 impl Register {
@@ -50,7 +53,7 @@ impl Register {
         Self {
             authority,
             crdt: RegisterCrdt::new(address),
-            policy,
+            policy: PolicyCrdt::new(policy),
         }
     }
 
@@ -83,7 +86,7 @@ impl Register {
 
     /// Return the owner of the data.
     pub fn owner(&self) -> User {
-        *self.policy.owner()
+        *self.policy().owner()
     }
 
     /// Return the PK which the messages are expected to be signed with by this replica.
@@ -106,6 +109,34 @@ impl Register {
         self.crdt.get(hash).ok_or(Error::NoSuchEntry)
     }
 
+    /// Return the entries the one corresponding to the provided 'hash' was written on top of.
+    pub fn children(&self, hash: EntryHash) -> Result<BTreeSet<(EntryHash, Entry)>> {
+        let _ = self.get(hash)?;
+        Ok(self.crdt.children(hash))
+    }
+
+    /// Return the entries written on top of the one corresponding to the provided 'hash'.
+    pub fn parents(&self, hash: EntryHash) -> Result<BTreeSet<(EntryHash, Entry)>> {
+        let _ = self.get(hash)?;
+        Ok(self.crdt.parents(hash))
+    }
+
+    /// Return the current entries along with the entries they were written on top of, down to
+    /// `depth` levels below them, or the whole history if no `depth` is provided.
+    /// Entries are in topological order, i.e. each one comes after those it was written on top of.
+    pub fn history(&self, depth: Option<usize>) -> Vec<HistoryEntry> {
+        let heads = self.read().into_iter().map(|(hash, _)| hash).collect();
+        self.crdt.ancestors(heads, depth)
+    }
+
+    /// Return the entry corresponding to the provided 'hash' along with the entries it was written
+    /// on top of, down to `depth` levels below it, or all of them if no `depth` is provided.
+    /// Entries are in topological order, i.e. each one comes after those it was written on top of.
+    pub fn ancestors(&self, hash: EntryHash, depth: Option<usize>) -> Result<Vec<HistoryEntry>> {
+        let _ = self.get(hash)?;
+        Ok(self.crdt.ancestors(BTreeSet::from([hash]), depth))
+    }
+
     /// Read the last entry, or entries when there are branches, if the register is not empty.
     pub fn read(&self) -> BTreeSet<(EntryHash, Entry)> {
         self.crdt.read()
@@ -113,12 +144,47 @@ impl Register {
 
     /// Return user permissions, if applicable.
     pub fn permissions(&self, user: User) -> Result<Permissions> {
-        self.policy.permissions(user).ok_or(Error::NoSuchEntry)
+        self.policy().permissions(user).ok_or(Error::NoSuchEntry)
     }
 
     /// Return the policy.
     pub fn policy(&self) -> &Policy {
-        &self.policy
+        self.policy.policy()
+    }
+
+    /// Replace the policy of the Register on behalf of the given requester, or this replica's
+    /// authority if not provided, returning the generated unsigned policy operation so the
+    /// caller can sign and broadcast it to other replicas.
+    /// Only the owner of the Register is allowed to do this.
+    pub fn update_policy(&mut self, policy: Policy, requester: Option<User>) -> Result<PolicyOp> {
+        self.check_num_policy_ops()?;
+        let requester = requester.unwrap_or(self.authority);
+        self.policy.update(*self.address(), policy, requester)
+    }
+
+    /// Transfer the ownership of the Register to a new owner, keeping the permissions, on behalf
+    /// of the given requester, or this replica's authority if not provided, returning the
+    /// generated unsigned policy operation so the caller can sign and broadcast it to other
+    /// replicas. Only the owner of the Register is allowed to do this.
+    pub fn transfer_ownership(
+        &mut self,
+        new_owner: User,
+        requester: Option<User>,
+    ) -> Result<PolicyOp> {
+        let policy = Policy {
+            owner: new_owner,
+            permissions: self.policy().permissions.clone(),
+        };
+        self.update_policy(policy, requester)
+    }
+
+    /// Apply a signed policy operation.
+    pub fn apply_policy_op(&mut self, op: PolicyOp) -> Result<()> {
+        if *self.address() != op.address {
+            return Err(Error::CrdtWrongAddress(op.address));
+        }
+        self.check_num_policy_ops()?;
+        self.policy.apply_op(op)
     }
 
     /// Write an entry to the Register, returning the generated unsigned
@@ -155,6 +221,16 @@ impl Register {
         Ok(())
     }
 
+    // Private helper to check the Register hasn't already reached the maximum number of policy ops.
+    fn check_num_policy_ops(&self) -> Result<()> {
+        let num_ops = self.policy.num_ops();
+        if num_ops >= MAX_REG_NUM_ENTRIES.into() {
+            return Err(Error::TooManyEntries(num_ops));
+        }
+
+        Ok(())
+    }
+
     /// Helper to check permissions for given `action`
     /// for the given requester's public key.
     ///
@@ -163,14 +239,17 @@ impl Register {
     /// `Err::AccessDenied` if the action is not allowed.
     pub fn check_permissions(&self, action: Action, requester: Option<User>) -> Result<()> {
         let requester = requester.unwrap_or(self.authority);
-        self.policy.is_action_allowed(requester, action)
+        self.policy().is_action_allowed(requester, action)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::super::{
-        register::{Entry, EntryHash, Permissions, Register, RegisterOp, User},
+        register::{
+            Action, Entry, EntryHash, HistoryEntry, Permissions, PolicyOp, Register, RegisterOp,
+            User,
+        },
         utils, Error, Keypair, Result,
     };
     use crate::{types::register::Policy, types::RegisterAddress as Address};
@@ -299,6 +378,286 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn register_get_children() -> eyre::Result<()> {
+        let (_, register) = &mut create_reg_replicas(1)[0];
+
+        let entry1 = random_register_entry();
+        let entry2 = random_register_entry();
+        let entry3 = random_register_entry();
+
+        let (entry1_hash, _) = register.write(entry1.clone(), BTreeSet::new())?;
+        let (entry2_hash, _) = register.write(entry2.clone(), BTreeSet::new())?;
+        let children = vec![entry1_hash, entry2_hash].into_iter().collect();
+        let (entry3_hash, _) = register.write(entry3, children)?;
+
+        assert!(register.children(entry1_hash)?.is_empty());
+        assert_eq!(
+            register.children(entry3_hash)?,
+            vec![(entry1_hash, entry1), (entry2_hash, entry2)]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(
+            register.children(EntryHash::default()),
+            Err(Error::NoSuchEntry)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_get_parents() -> eyre::Result<()> {
+        let (_, register) = &mut create_reg_replicas(1)[0];
+
+        let entry1 = random_register_entry();
+        let entry2 = random_register_entry();
+        let entry3 = random_register_entry();
+
+        let (entry1_hash, _) = register.write(entry1, BTreeSet::new())?;
+        let children = vec![entry1_hash].into_iter().collect();
+        let (entry2_hash, _) = register.write(entry2.clone(), children)?;
+        let children = vec![entry1_hash].into_iter().collect();
+        let (entry3_hash, _) = register.write(entry3.clone(), children)?;
+
+        assert!(register.parents(entry2_hash)?.is_empty());
+        assert_eq!(
+            register.parents(entry1_hash)?,
+            vec![(entry2_hash, entry2), (entry3_hash, entry3)]
+                .into_iter()
+                .collect()
+        );
+        assert_eq!(
+            register.parents(EntryHash::default()),
+            Err(Error::NoSuchEntry)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_history_is_topologically_sorted() -> eyre::Result<()> {
+        let (_, register) = &mut create_reg_replicas(1)[0];
+
+        // 1 <- 2 <- 4 <- 5
+        //   <- 3 <-/
+        //   <------------ 6
+        let (hash1, _) = register.write(random_register_entry(), BTreeSet::new())?;
+        let (hash2, _) = register.write(random_register_entry(), BTreeSet::from([hash1]))?;
+        let (hash3, _) = register.write(random_register_entry(), BTreeSet::from([hash1]))?;
+        let (hash4, _) = register.write(random_register_entry(), BTreeSet::from([hash2, hash3]))?;
+        let (hash5, _) = register.write(random_register_entry(), BTreeSet::from([hash4]))?;
+        let (hash6, _) = register.write(random_register_entry(), BTreeSet::from([hash1]))?;
+
+        let history = register.history(None);
+        assert_eq!(history.len(), 6);
+        let position = |hash| history.iter().position(|item| item.hash == hash);
+        for item in history.iter() {
+            assert_eq!(register.get(item.hash)?, &item.entry);
+            for child in item.children.iter() {
+                assert!(position(*child) < position(item.hash));
+            }
+        }
+        assert_eq!(history[0].hash, hash1);
+        assert!(position(hash6).is_some());
+        let item4 = history.iter().find(|item| item.hash == hash4);
+        assert_eq!(
+            item4.map(|item| item.children.clone()),
+            Some(BTreeSet::from([hash2, hash3]))
+        );
+        assert!(position(hash5) > position(hash4));
+
+        // the order is deterministic
+        assert_eq!(register.history(None), history);
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_history_with_depth() -> eyre::Result<()> {
+        let (_, register) = &mut create_reg_replicas(1)[0];
+        assert!(register.history(None).is_empty());
+
+        // 1 <- 2 <- 3 <- 4
+        //   <-------/
+        let (hash1, _) = register.write(random_register_entry(), BTreeSet::new())?;
+        let (hash2, _) = register.write(random_register_entry(), BTreeSet::from([hash1]))?;
+        let (hash3, _) = register.write(random_register_entry(), BTreeSet::from([hash1, hash2]))?;
+        let (hash4, _) = register.write(random_register_entry(), BTreeSet::from([hash3]))?;
+
+        let hashes = |history: Vec<HistoryEntry>| -> Vec<EntryHash> {
+            history.into_iter().map(|item| item.hash).collect()
+        };
+        assert_eq!(hashes(register.history(Some(0))), vec![hash4]);
+        assert_eq!(hashes(register.history(Some(1))), vec![hash3, hash4]);
+        assert_eq!(
+            hashes(register.history(Some(2))),
+            vec![hash1, hash2, hash3, hash4]
+        );
+        assert_eq!(
+            hashes(register.ancestors(hash3, Some(1))?),
+            vec![hash1, hash2, hash3]
+        );
+        assert_eq!(hashes(register.ancestors(hash2, None)?), vec![hash1, hash2]);
+        assert_eq!(
+            register.ancestors(EntryHash::default(), None),
+            Err(Error::NoSuchEntry)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_update_policy() -> eyre::Result<()> {
+        let (owner_keypair, register) = &mut create_reg_replicas(1)[0];
+        let owner = User::Key(owner_keypair.public_key());
+        let user = User::Key(Keypair::new_ed25519().public_key());
+        assert_eq!(
+            register.check_permissions(Action::Write, Some(user)),
+            Err(Error::AccessDenied(user))
+        );
+
+        // the owner grants write access to another user
+        let mut permissions = BTreeMap::new();
+        let _prev = permissions.insert(user, Permissions::new(true));
+        let policy = Policy { owner, permissions };
+        let op = register.update_policy(policy.clone(), None)?;
+        assert_eq!(op.previous, None);
+        assert_eq!(op.source, owner);
+        assert_eq!(register.policy(), &policy);
+        assert_eq!(
+            register.check_permissions(Action::Write, Some(user)),
+            Ok(())
+        );
+
+        // which is not allowed to change the policy though
+        assert_eq!(
+            register.update_policy(policy, Some(user)),
+            Err(Error::AccessDenied(user))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_transfer_ownership() -> eyre::Result<()> {
+        let (owner_keypair, register) = &mut create_reg_replicas(1)[0];
+        let owner = User::Key(owner_keypair.public_key());
+        let new_owner = User::Key(Keypair::new_ed25519().public_key());
+
+        let op = register.transfer_ownership(new_owner, None)?;
+        assert_eq!(register.owner(), new_owner);
+        assert_eq!(
+            register.check_permissions(Action::Write, Some(owner)),
+            Err(Error::AccessDenied(owner))
+        );
+
+        // the previous owner cannot take the ownership back
+        assert_eq!(
+            register.transfer_ownership(owner, None),
+            Err(Error::AccessDenied(owner))
+        );
+
+        // but the new owner can hand it over to the previous one
+        let op2 = register.transfer_ownership(owner, Some(new_owner))?;
+        assert_eq!(op2.previous, Some(op.hash()?));
+        assert_eq!(register.owner(), owner);
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_policy_ops_converge() -> eyre::Result<()> {
+        let name = xor_name::rand::random();
+        let tag = 43_000;
+        let owner_keypair = Keypair::new_ed25519();
+        let owner = User::Key(owner_keypair.public_key());
+        let mut replicas = gen_reg_replicas(Some(owner_keypair), name, tag, None, 3);
+        let (_, mut replica3) = replicas.remove(2);
+        let (_, mut replica2) = replicas.remove(1);
+        let (_, mut replica1) = replicas.remove(0);
+
+        // concurrent policy updates by the owner on two replicas
+        let user1 = User::Key(Keypair::new_ed25519().public_key());
+        let user2 = User::Key(Keypair::new_ed25519().public_key());
+        let op1 = replica1.transfer_ownership(user1, None)?;
+        let op2 = replica2.transfer_ownership(user2, None)?;
+        // the new owner of the first replica then changes the policy again
+        let op3 = replica1.transfer_ownership(owner, Some(user1))?;
+
+        // apply them in different orders, even before the ops they replace were applied
+        replica2.apply_policy_op(op3.clone())?;
+        replica2.apply_policy_op(op1.clone())?;
+        replica3.apply_policy_op(op3)?;
+        replica3.apply_policy_op(op2.clone())?;
+        replica3.apply_policy_op(op1)?;
+        // the handover taken up on the first replica supersedes the concurrent one
+        assert_eq!(
+            replica1.apply_policy_op(op2),
+            Err(Error::AccessDenied(owner))
+        );
+
+        assert_eq!(replica1.policy(), replica2.policy());
+        assert_eq!(replica1.policy(), replica3.policy());
+        assert_eq!(replica1.owner(), owner);
+
+        Ok(())
+    }
+
+    #[test]
+    fn register_revoked_owner_cannot_fork_policy() -> eyre::Result<()> {
+        let name = xor_name::rand::random();
+        let tag = 43_000;
+        let owner_keypair = Keypair::new_ed25519();
+        let owner = User::Key(owner_keypair.public_key());
+        let mut replicas = gen_reg_replicas(Some(owner_keypair), name, tag, None, 2);
+        let (_, mut replica2) = replicas.remove(1);
+        let (_, mut replica1) = replicas.remove(0);
+
+        // the owner changes the policy, then hands the ownership over,
+        // which the new owner takes up by changing the policy in turn
+        let user = User::Key(Keypair::new_ed25519().public_key());
+        let new_owner = User::Key(Keypair::new_ed25519().public_key());
+        let mut permissions = BTreeMap::new();
+        let _prev = permissions.insert(user, Permissions::new(true));
+        let op1 = replica1.update_policy(Policy { owner, permissions }, None)?;
+        let op2 = replica1.transfer_ownership(new_owner, None)?;
+        let op3 = replica1.update_policy(
+            Policy {
+                owner: new_owner,
+                permissions: BTreeMap::new(),
+            },
+            Some(new_owner),
+        )?;
+
+        // the previous owner then tries to take the ownership back,
+        // by forking from the initial policy or from the one it replaced
+        let forks = [None, Some(op1.hash()?)].map(|previous| PolicyOp {
+            address: *replica1.address(),
+            policy: Policy {
+                owner,
+                permissions: BTreeMap::new(),
+            },
+            previous,
+            source: owner,
+        });
+        for fork in &forks {
+            assert_eq!(
+                replica1.apply_policy_op(fork.clone()),
+                Err(Error::AccessDenied(owner))
+            );
+        }
+        assert_eq!(replica1.owner(), new_owner);
+
+        // a replica getting the forks before the ops they compete with still converges
+        for op in forks.into_iter().chain([op1, op2, op3]) {
+            replica2.apply_policy_op(op)?;
+        }
+        assert_eq!(replica2.policy(), replica1.policy());
+
+        Ok(())
+    }
+
     #[test]
     fn register_query_public_policy() -> eyre::Result<()> {
         let name = xor_name::rand::random();
@@ -0,0 +1,243 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{
+    super::{utils, Error, RegisterAddress, Result},
+    Policy, User,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt::{Debug, Display, Formatter, Result as FmtResult},
+    hash::Hash,
+};
+use tiny_keccak::{Hasher, Sha3};
+
+/// Hash of a policy operation. Logging as the same format of `XorName`.
+#[derive(Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PolicyHash(pub [u8; 32]);
+
+impl Debug for PolicyHash {
+    fn fmt(&self, formatter: &mut Formatter) -> FmtResult {
+        write!(formatter, "{}", self)
+    }
+}
+
+impl Display for PolicyHash {
+    fn fmt(&self, formatter: &mut Formatter) -> FmtResult {
+        write!(
+            formatter,
+            "{:02x}{:02x}{:02x}..",
+            self.0[0], self.0[1], self.0[2]
+        )
+    }
+}
+
+/// Operation replacing the policy of a Register, e.g. to change the permissions
+/// or to transfer its ownership to another user.
+/// It's only valid if it was generated by the owner as per the policy it replaces.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PolicyOp {
+    /// Address of a Register object on the network.
+    pub address: RegisterAddress,
+    /// The new policy of the Register.
+    pub policy: Policy,
+    /// Hash of the operation which set the policy being replaced,
+    /// or `None` if it's the policy the Register was created with.
+    pub previous: Option<PolicyHash>,
+    /// The user that generated the operation, which must be the owner as per the replaced policy.
+    pub source: User,
+}
+
+impl PolicyOp {
+    /// Returns the hash of the operation.
+    pub fn hash(&self) -> Result<PolicyHash> {
+        let bytes = utils::serialise(self)?;
+        let mut hasher = Sha3::v256();
+        let mut output = [0; 32];
+        hasher.update(&bytes);
+        hasher.finalize(&mut output);
+        Ok(PolicyHash(output))
+    }
+}
+
+/// Policy of a Register as a CRDT.
+///
+/// All the operations applied are kept, and the current policy is the one found by following
+/// the chain of valid operations starting from the policy the Register was created with.
+/// An owner who handed the ownership over to another user, who then took it up by generating
+/// an operation of their own, has no authority left over the policies it replaced, so its
+/// operations forking from any of those are superseded. When there are concurrent operations
+/// replacing the same policy, those not superseded win, and then the one with the greatest
+/// hash, so all replicas converge to the same policy regardless of the order the operations
+/// were applied. Operations replacing a policy which is not known yet are kept till it is.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd)]
+pub(crate) struct PolicyCrdt {
+    /// The policy the Register was created with.
+    initial: Policy,
+    /// All the operations applied, by their hash.
+    ops: BTreeMap<PolicyHash, PolicyOp>,
+    /// Hash of the operation which set the current policy, if any.
+    head: Option<PolicyHash>,
+    /// The current policy.
+    current: Policy,
+}
+
+impl PolicyCrdt {
+    /// Constructs a new '`PolicyCrdt`' from the policy the Register is created with.
+    pub(crate) fn new(policy: Policy) -> Self {
+        Self {
+            initial: policy.clone(),
+            ops: BTreeMap::new(),
+            head: None,
+            current: policy,
+        }
+    }
+
+    /// Returns the current policy.
+    pub(crate) fn policy(&self) -> &Policy {
+        &self.current
+    }
+
+    /// Returns the number of operations applied.
+    pub(crate) fn num_ops(&self) -> usize {
+        self.ops.len()
+    }
+
+    /// Replace the current policy, returning the operation so it can be
+    /// broadcasted to other replicas. Only the current owner can do this.
+    pub(crate) fn update(
+        &mut self,
+        address: RegisterAddress,
+        policy: Policy,
+        source: User,
+    ) -> Result<PolicyOp> {
+        let op = PolicyOp {
+            address,
+            policy,
+            previous: self.head,
+            source,
+        };
+        self.apply_op(op.clone())?;
+
+        Ok(op)
+    }
+
+    /// Apply a policy operation to this replica.
+    pub(crate) fn apply_op(&mut self, op: PolicyOp) -> Result<()> {
+        // An op replacing a policy we don't know about yet is kept till we do,
+        // and it's then checked when looking for the current policy.
+        if let Some(replaced) = self.policy_set_by(op.previous) {
+            if !is_owner(replaced, op.source) {
+                return Err(Error::AccessDenied(op.source));
+            }
+        }
+
+        // An owner cannot take back the ownership it handed over, by forking
+        // from the policy it replaced or from any older one.
+        let hash = op.hash()?;
+        if !self.ops.contains_key(&hash) && self.is_superseded(&hash, &op, &self.handovers()) {
+            return Err(Error::AccessDenied(op.source));
+        }
+
+        let _prev = self.ops.insert(hash, op);
+        self.resolve_current();
+
+        Ok(())
+    }
+
+    // Returns the policy set by the op with the given hash,
+    // or the initial policy if no hash is provided.
+    fn policy_set_by(&self, hash: Option<PolicyHash>) -> Option<&Policy> {
+        match hash {
+            None => Some(&self.initial),
+            Some(hash) => self.ops.get(&hash).map(|op| &op.policy),
+        }
+    }
+
+    // Follows the chain of valid ops from the initial policy to find the current one.
+    fn resolve_current(&mut self) {
+        let handovers = self.handovers();
+        let mut head = None;
+        let mut current = &self.initial;
+        while let Some((hash, op)) = self
+            .ops
+            .iter()
+            .filter(|(_, op)| op.previous == head && is_owner(current, op.source))
+            .max_by_key(|(hash, op)| (!self.is_superseded(hash, op, &handovers), *hash))
+        {
+            head = Some(*hash);
+            current = &op.policy;
+        }
+
+        self.current = current.clone();
+        self.head = head;
+    }
+
+    // Returns the ops handing the ownership over to another user who then took it up,
+    // i.e. who generated an op replacing the policy it was handed over with.
+    fn handovers(&self) -> Vec<(PolicyHash, User)> {
+        self.ops
+            .iter()
+            .filter(|(hash, op)| {
+                let new_owner = *op.policy.owner();
+                new_owner != op.source
+                    && new_owner != User::Anyone
+                    && self
+                        .ops
+                        .values()
+                        .any(|child| child.previous == Some(**hash) && child.source == new_owner)
+            })
+            .map(|(hash, op)| (*hash, op.source))
+            .collect()
+    }
+
+    // Returns whether the op was generated by an owner who, after the policy the op replaces,
+    // handed the ownership over in another op which was taken up.
+    fn is_superseded(
+        &self,
+        hash: &PolicyHash,
+        op: &PolicyOp,
+        handovers: &[(PolicyHash, User)],
+    ) -> bool {
+        handovers.iter().any(|(handover, source)| {
+            *source == op.source && self.descends_from(*handover, op.previous, hash)
+        })
+    }
+
+    // Returns whether the chain of ops ending with the given one goes back to the given
+    // policy without going through the excluded op.
+    fn descends_from(
+        &self,
+        mut hash: PolicyHash,
+        ancestor: Option<PolicyHash>,
+        excluded: &PolicyHash,
+    ) -> bool {
+        loop {
+            if hash == *excluded {
+                return false;
+            }
+            let op = match self.ops.get(&hash) {
+                Some(op) => op,
+                None => return false,
+            };
+            if op.previous == ancestor {
+                return true;
+            }
+            match op.previous {
+                Some(previous) => hash = previous,
+                None => return false,
+            }
+        }
+    }
+}
+
+// Returns whether the user is the owner as per the given policy.
+fn is_owner(policy: &Policy, user: User) -> bool {
+    *policy.owner() == User::Anyone || *policy.owner() == user
+}
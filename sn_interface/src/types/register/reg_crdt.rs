@@ -14,12 +14,12 @@ use super::{
     User,
 };
 use crdts::{
-    merkle_reg::{MerkleReg, Node},
+    merkle_reg::{Hash as NodeHash, MerkleReg, Node},
     CmRDT,
 };
 use serde::{Deserialize, Serialize};
 use std::{
-    collections::BTreeSet,
+    collections::{BTreeMap, BTreeSet},
     fmt::{self, Debug, Display, Formatter, Result as FmtResult},
     hash::Hash,
 };
@@ -44,6 +44,18 @@ impl Display for EntryHash {
     }
 }
 
+/// An entry of a Register along with the hashes of the entries it was written on top of,
+/// i.e. a node of the Register's version graph.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct HistoryEntry {
+    /// Hash of the entry.
+    pub hash: EntryHash,
+    /// The entry itself.
+    pub entry: Entry,
+    /// Hashes of the entries this one was written on top of.
+    pub children: BTreeSet<EntryHash>,
+}
+
 /// CRDT Data operation applicable to other Register replica.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct CrdtOperation<T> {
@@ -145,6 +157,99 @@ impl RegisterCrdt {
         self.data.node(hash.0).map(|node| &node.value)
     }
 
+    /// Get the entries the provided `hash` was written on top of, i.e. its direct predecessors.
+    pub(crate) fn children(&self, hash: EntryHash) -> BTreeSet<(EntryHash, Entry)> {
+        self.data
+            .children(hash.0)
+            .hashes_and_nodes()
+            .map(|(hash, node)| (EntryHash(hash), node.value.clone()))
+            .collect()
+    }
+
+    /// Get the entries written on top of the provided `hash`, i.e. its direct successors.
+    pub(crate) fn parents(&self, hash: EntryHash) -> BTreeSet<(EntryHash, Entry)> {
+        self.data
+            .parents(hash.0)
+            .hashes_and_nodes()
+            .map(|(hash, node)| (EntryHash(hash), node.value.clone()))
+            .collect()
+    }
+
+    /// Get the entries corresponding to the provided hashes along with all the entries they
+    /// were written on top of, down to `depth` levels below them (all of them if `None`).
+    /// Entries are returned in topological order, i.e. every entry comes after all the entries
+    /// it was written on top of, with ties broken by hash so the order is deterministic.
+    pub(crate) fn ancestors(
+        &self,
+        from: BTreeSet<EntryHash>,
+        depth: Option<usize>,
+    ) -> Vec<HistoryEntry> {
+        // Walk down the children breadth-first to find the entries within the depth.
+        let mut nodes = BTreeMap::<NodeHash, &Node<Entry>>::new();
+        let mut level: BTreeSet<NodeHash> = from.iter().map(|hash| hash.0).collect();
+        let mut current_depth = 0;
+        while !level.is_empty() {
+            let mut next_level = BTreeSet::new();
+            for hash in level {
+                if nodes.contains_key(&hash) {
+                    continue;
+                }
+                if let Some(node) = self.data.node(hash) {
+                    let _prev = nodes.insert(hash, node);
+                    next_level.extend(node.children.iter().copied());
+                }
+            }
+            if matches!(depth, Some(depth) if current_depth >= depth) {
+                break;
+            }
+            level = next_level;
+            current_depth += 1;
+        }
+
+        // Sort them topologically, only considering the edges between the entries found.
+        let mut pending_children = BTreeMap::<NodeHash, usize>::new();
+        let mut parents = BTreeMap::<NodeHash, Vec<NodeHash>>::new();
+        for (hash, node) in nodes.iter() {
+            let children: Vec<NodeHash> = node
+                .children
+                .iter()
+                .copied()
+                .filter(|child| nodes.contains_key(child))
+                .collect();
+            let _prev = pending_children.insert(*hash, children.len());
+            for child in children {
+                parents.entry(child).or_default().push(*hash);
+            }
+        }
+
+        let mut ready: BTreeSet<NodeHash> = pending_children
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(hash, _)| *hash)
+            .collect();
+        let mut history = Vec::with_capacity(nodes.len());
+        while let Some(hash) = ready.iter().next().copied() {
+            let _ = ready.remove(&hash);
+            for parent in parents.get(&hash).into_iter().flatten() {
+                if let Some(count) = pending_children.get_mut(parent) {
+                    *count -= 1;
+                    if *count == 0 {
+                        let _ = ready.insert(*parent);
+                    }
+                }
+            }
+
+            let node = nodes[&hash];
+            history.push(HistoryEntry {
+                hash: EntryHash(hash),
+                entry: node.value.clone(),
+                children: node.children.iter().copied().map(EntryHash).collect(),
+            });
+        }
+
+        history
+    }
+
     /// Read current entries (multiple entries occur on concurrent writes).
     pub(crate) fn read(&self) -> BTreeSet<(EntryHash, Entry)> {
         self.data
@@ -269,7 +269,7 @@ fn get_new_data_store() -> Result<DataStorage> {
     let max_capacity = config.max_capacity();
 
     let used_space = UsedSpace::new(max_capacity);
-    let store = DataStorage::new(&storage_dir, used_space)?;
+    let store = DataStorage::new(&storage_dir, used_space, config.storage_backend())?;
 
     Ok(store)
 }
@@ -166,6 +166,111 @@ async fn main() -> Result<()> {
         )
     }
 
+    if command_line_args.chunk_scrub_batch_size.is_some() {
+        assert_eq!(
+            command_line_args.chunk_scrub_batch_size,
+            config.chunk_scrub_batch_size
+        )
+    } else {
+        assert_eq!(
+            file_config.chunk_scrub_batch_size,
+            config.chunk_scrub_batch_size
+        )
+    }
+
+    if command_line_args.storage_backend.is_some() {
+        assert_eq!(command_line_args.storage_backend, config.storage_backend)
+    } else {
+        assert_eq!(file_config.storage_backend, config.storage_backend)
+    }
+
+    if command_line_args.admin_addr.is_some() {
+        assert_eq!(command_line_args.admin_addr, config.admin_addr)
+    } else {
+        assert_eq!(file_config.admin_addr, config.admin_addr)
+    }
+
+    if command_line_args.metrics_addr.is_some() {
+        assert_eq!(command_line_args.metrics_addr, config.metrics_addr)
+    } else {
+        assert_eq!(file_config.metrics_addr, config.metrics_addr)
+    }
+
+    if command_line_args.max_capacity.is_some() {
+        assert_eq!(command_line_args.max_capacity, config.max_capacity)
+    } else {
+        assert_eq!(file_config.max_capacity, config.max_capacity)
+    }
+
+    if command_line_args.max_chunks_capacity.is_some() {
+        assert_eq!(
+            command_line_args.max_chunks_capacity,
+            config.max_chunks_capacity
+        )
+    } else {
+        assert_eq!(file_config.max_chunks_capacity, config.max_chunks_capacity)
+    }
+
+    if command_line_args.max_registers_capacity.is_some() {
+        assert_eq!(
+            command_line_args.max_registers_capacity,
+            config.max_registers_capacity
+        )
+    } else {
+        assert_eq!(
+            file_config.max_registers_capacity,
+            config.max_registers_capacity
+        )
+    }
+
+    if command_line_args.max_spentbooks_capacity.is_some() {
+        assert_eq!(
+            command_line_args.max_spentbooks_capacity,
+            config.max_spentbooks_capacity
+        )
+    } else {
+        assert_eq!(
+            file_config.max_spentbooks_capacity,
+            config.max_spentbooks_capacity
+        )
+    }
+
+    if command_line_args.storage_high_watermark.is_some() {
+        assert_eq!(
+            command_line_args.storage_high_watermark,
+            config.storage_high_watermark
+        )
+    } else {
+        assert_eq!(
+            file_config.storage_high_watermark,
+            config.storage_high_watermark
+        )
+    }
+
+    if command_line_args.min_tolerated_msgs_per_s.is_some() {
+        assert_eq!(
+            command_line_args.min_tolerated_msgs_per_s,
+            config.min_tolerated_msgs_per_s
+        )
+    } else {
+        assert_eq!(
+            file_config.min_tolerated_msgs_per_s,
+            config.min_tolerated_msgs_per_s
+        )
+    }
+
+    if command_line_args.max_tolerated_msgs_per_s.is_some() {
+        assert_eq!(
+            command_line_args.max_tolerated_msgs_per_s,
+            config.max_tolerated_msgs_per_s
+        )
+    } else {
+        assert_eq!(
+            file_config.max_tolerated_msgs_per_s,
+            config.max_tolerated_msgs_per_s
+        )
+    }
+
     clear_disk_config().await?;
 
     Ok(())
@@ -4,11 +4,17 @@ use sn_interface::LogFormatter;
 use sn_node::node::Config;
 
 use eyre::Result;
+use tracing::Level;
 use tracing_appender::non_blocking::WorkerGuard;
 use tracing_subscriber::filter::{EnvFilter, Targets};
 use tracing_subscriber::fmt::Layer;
 use tracing_subscriber::layer::Filter;
-use tracing_subscriber::{prelude::*, Registry};
+use tracing_subscriber::{prelude::*, reload, Registry};
+
+type BoxedFilter = Box<dyn Filter<Registry> + Send + Sync>;
+
+/// Handle to replace the filter of the node logs while running.
+pub type LogFilterHandle = reload::Handle<BoxedFilter, Registry>;
 
 #[cfg(feature = "otlp")]
 macro_rules! otlp_layer {
@@ -49,12 +55,13 @@ macro_rules! otlp_layer {
 macro_rules! fmt_layer {
     ($config:expr) => {{
         // Filter by log level either from `RUST_LOG` or default to crate only.
-        let target_filter: Box<dyn Filter<Registry> + Send + Sync> =
-            if let Ok(f) = EnvFilter::try_from_default_env() {
-                Box::new(f)
-            } else {
-                Box::new(Targets::new().with_target(current_crate_str(), $config.verbose()))
-            };
+        let target_filter: BoxedFilter = if let Ok(f) = EnvFilter::try_from_default_env() {
+            Box::new(f)
+        } else {
+            Box::new(Targets::new().with_target(current_crate_str(), $config.verbose()))
+        };
+        // The filter can be replaced later on, e.g. through the admin endpoint.
+        let (target_filter, filter_handle) = reload::Layer::new(target_filter);
         let mut guard: Option<WorkerGuard> = None;
         let fmt_layer: Layer<Registry> = tracing_subscriber::fmt::layer()
             .with_thread_names(true)
@@ -92,18 +99,18 @@ macro_rules! fmt_layer {
                 .boxed()
         };
 
-        (fmt_layer, guard)
+        (fmt_layer, guard, filter_handle)
     }};
 }
 
-/// Inits node logging, returning the global node guard if required.
-/// This guard should be held for the life of the program.
+/// Inits node logging, returning the global node guard if required, along with
+/// the handle to replace the log filter. This guard should be held for the life of the program.
 ///
 /// Logging should be instantiated only once.
-pub fn init_node_logging(config: &Config) -> Result<Option<WorkerGuard>> {
+pub fn init_node_logging(config: &Config) -> Result<(Option<WorkerGuard>, LogFilterHandle)> {
     let reg = tracing_subscriber::registry();
 
-    let (fmt, guard) = fmt_layer!(config);
+    let (fmt, guard, filter_handle) = fmt_layer!(config);
     let reg = reg.with(fmt);
 
     #[cfg(feature = "tokio-console")]
@@ -114,7 +121,25 @@ pub fn init_node_logging(config: &Config) -> Result<Option<WorkerGuard>> {
 
     reg.init();
 
-    Ok(guard)
+    Ok((guard, filter_handle))
+}
+
+/// Replaces the filter of the node logs with the given directives,
+/// in the format of the `RUST_LOG` env var (e.g. `sn_node=debug,qp2p=info`).
+pub fn set_log_filter(
+    handle: &LogFilterHandle,
+    directives: &str,
+) -> std::result::Result<(), String> {
+    let filter: BoxedFilter =
+        Box::new(EnvFilter::try_new(directives).map_err(|error| error.to_string())?);
+    handle.reload(filter).map_err(|error| error.to_string())
+}
+
+/// Replaces the filter of the node logs with the one for the given level,
+/// as set with the verbosity option.
+pub fn set_log_level(handle: &LogFilterHandle, level: Level) -> std::result::Result<(), String> {
+    let filter: BoxedFilter = Box::new(Targets::new().with_target(current_crate_str(), level));
+    handle.reload(filter).map_err(|error| error.to_string())
 }
 
 /// Get current root module name (e.g. "sn_node")
@@ -27,7 +27,10 @@
     unused_results
 )]
 
-use sn_node::node::{start_node, Config, Error as NodeError, Event, MembershipEvent};
+use sn_node::node::{
+    start_node, AdminRequest, Config, Error as NodeError, Event, EventReceiver, MembershipEvent,
+    NodeRef,
+};
 
 use clap::{CommandFactory, Parser};
 use clap_complete::{generate, Shell};
@@ -43,6 +46,14 @@ const BOOTSTRAP_RETRY_TIME_SEC: u64 = 5;
 
 mod log;
 
+/// How a node instance finished running.
+enum NodeExit {
+    /// The node stopped on its own, a new instance is to be started.
+    Restart,
+    /// A shutdown was requested, the process is to exit.
+    Shutdown,
+}
+
 fn main() -> Result<()> {
     color_eyre::install()?;
 
@@ -56,12 +67,15 @@ fn main() -> Result<()> {
 
     rt.block_on(async {
         let mut config = Config::new().await?;
-        let _guard = log::init_node_logging(&config)?;
+        let (_guard, log_filter) = log::init_node_logging(&config)?;
         trace!("Initial node config: {config:?}");
 
         loop {
             info!("Node runtime started");
-            create_runtime_and_node(&config).await?;
+            if let NodeExit::Shutdown = create_runtime_and_node(&config, &log_filter).await? {
+                info!("Node has been shut down");
+                return Ok(());
+            }
 
             // pull config again in case it has been updated meanwhile
             config = Config::new().await?;
@@ -70,33 +84,38 @@ fn main() -> Result<()> {
 }
 
 /// Create a tokio runtime per `run_node` instance.
-async fn create_runtime_and_node(config: &Config) -> Result<()> {
+async fn create_runtime_and_node(
+    config: &Config,
+    log_filter: &log::LogFilterHandle,
+) -> Result<NodeExit> {
     let local = tokio::task::LocalSet::new();
 
-    local
+    let exit = local
         .run_until(async move {
             // loops ready to catch any ChurnJoinMiss
-            match run_node(config).await {
-                Ok(_) => {
+            match run_node(config, log_filter).await {
+                Ok(exit) => {
                     info!("Node has finished running, no runtime errors were reported");
+                    exit
                 }
                 Err(error) => {
                     warn!("Node instance finished with an error: {error:?}");
+                    NodeExit::Restart
                 }
-            };
+            }
         })
         .await;
 
-    Ok(())
+    Ok(exit)
 }
 
-async fn run_node(config: &Config) -> Result<()> {
+async fn run_node(config: &Config, log_filter: &log::LogFilterHandle) -> Result<NodeExit> {
     if let Some(c) = &config.completions() {
         let shell = c.parse().map_err(|err: String| eyre!(err))?;
         let buf = gen_completions_for_shell(shell, Config::command()).map_err(|err| eyre!(err))?;
         std::io::stdout().write_all(&buf)?;
 
-        return Ok(());
+        return Ok(NodeExit::Shutdown);
     }
 
     if config.update() || config.update_only() {
@@ -129,7 +148,7 @@ async fn run_node(config: &Config) -> Result<()> {
     let join_timeout = Duration::from_secs(JOIN_TIMEOUT_SEC);
     let bootstrap_retry_duration = Duration::from_secs(BOOTSTRAP_RETRY_TIME_SEC);
 
-    let (_node, mut event_stream) = loop {
+    let (mut node, mut event_stream) = loop {
         match start_node(config, join_timeout).await {
             Ok(result) => break result,
             Err(NodeError::CannotConnectEndpoint(qp2p::EndpointError::Upnp(error))) => {
@@ -205,15 +224,177 @@ async fn run_node(config: &Config) -> Result<()> {
         }
     }
 
+    let terminate = terminate_signal();
+    tokio::pin!(terminate);
+    let mut reload_signal = ReloadSignal::new();
+    let mut config = config.clone();
+
     // this keeps node running
-    while let Some(event) = event_stream.next().await {
-        trace!("Node event! {}", event);
-        if let Event::Membership(MembershipEvent::RemovedFromSection) = event {
-            return Err(NodeError::RemovedFromSection).map_err(ErrReport::msg);
+    loop {
+        tokio::select! {
+            event = event_stream.next() => {
+                let event = match event {
+                    Some(event) => event,
+                    None => break,
+                };
+                trace!("Node event! {}", event);
+                if let Event::Membership(MembershipEvent::RemovedFromSection) = event {
+                    return Err(NodeError::RemovedFromSection).map_err(ErrReport::msg);
+                }
+            }
+            request = node.next_admin_request() => match request {
+                AdminRequest::Shutdown => {
+                    info!("Shutdown requested through the admin endpoint");
+                    return Ok(NodeExit::Shutdown);
+                }
+                AdminRequest::Leave => {
+                    info!("Leave requested through the admin endpoint");
+                    return Ok(leave_section(&node, &mut event_stream).await);
+                }
+                AdminRequest::SetLogLevel {
+                    directives,
+                    response,
+                } => {
+                    let result = log::set_log_filter(log_filter, &directives);
+                    match &result {
+                        Ok(()) => info!("Log filter set to '{directives}' through the admin endpoint"),
+                        Err(error) => warn!("Failed to set log filter to '{directives}': {error}"),
+                    }
+                    let _ = response.send(result);
+                }
+                AdminRequest::ReloadConfig { response } => {
+                    info!("Config reload requested through the admin endpoint");
+                    let result = reload_config(&mut config, &node, log_filter)
+                        .await
+                        .map_err(|error| format!("{error:?}"));
+                    if let Err(error) = &result {
+                        warn!("Failed to reload config: {error}");
+                    }
+                    let _ = response.send(result);
+                }
+            },
+            () = reload_signal.recv() => {
+                info!("Config reload signal received");
+                if let Err(error) = reload_config(&mut config, &node, log_filter).await {
+                    warn!("Failed to reload config: {error:?}");
+                }
+            }
+            () = &mut terminate => {
+                info!("Termination signal received");
+                return Ok(leave_section(&node, &mut event_stream).await);
+            }
+        }
+    }
+
+    Ok(NodeExit::Restart)
+}
+
+/// Reloads the config, applying the settings which can be changed while the node is running.
+/// Returns the names of the other settings which changed, as they need a restart to take effect.
+async fn reload_config(
+    config: &mut Config,
+    node: &NodeRef,
+    log_filter: &log::LogFilterHandle,
+) -> Result<Vec<String>> {
+    // the reloaded config is only kept once it's been applied
+    let mut reloaded = config.clone();
+    let requires_restart = reloaded.reload().await?;
+
+    node.apply_config(&reloaded).await?;
+    if reloaded.verbose() != config.verbose() {
+        log::set_log_level(log_filter, reloaded.verbose()).map_err(|error| eyre!(error))?;
+        info!("Log level set to {}", reloaded.verbose());
+    }
+    *config = reloaded;
+
+    if !requires_restart.is_empty() {
+        warn!(
+            "Config changes to {} only take effect once the node restarts",
+            requires_restart.join(", ")
+        );
+    }
+
+    Ok(requires_restart.into_iter().map(String::from).collect())
+}
+
+/// Leaves the section gracefully, while the node events keep being consumed, then shuts down.
+/// The node shuts down regardless if leaving fails or takes too long.
+async fn leave_section(node: &NodeRef, event_stream: &mut EventReceiver) -> NodeExit {
+    let leave = node.leave_section();
+    tokio::pin!(leave);
+
+    loop {
+        tokio::select! {
+            result = &mut leave => {
+                match result {
+                    Ok(()) => info!("Node has left the section"),
+                    Err(error) => warn!("Failed to leave the section gracefully: {error:?}"),
+                }
+                return NodeExit::Shutdown;
+            }
+            event = event_stream.next() => match event {
+                Some(event) => trace!("Node event! {}", event),
+                None => return NodeExit::Shutdown,
+            }
+        }
+    }
+}
+
+/// Listens for the process being asked to reload its config, i.e. for SIGHUP.
+struct ReloadSignal {
+    #[cfg(unix)]
+    signal: Option<tokio::signal::unix::Signal>,
+}
+
+impl ReloadSignal {
+    #[cfg(unix)]
+    fn new() -> Self {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let signal = signal(SignalKind::hangup())
+            .map_err(|error| warn!("Failed to listen for SIGHUP: {error:?}"))
+            .ok();
+        Self { signal }
+    }
+
+    #[cfg(not(unix))]
+    fn new() -> Self {
+        Self {}
+    }
+
+    /// Resolves on the next request to reload the config, never if there can't be any.
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(signal) = &mut self.signal {
+            if signal.recv().await.is_some() {
+                return;
+            }
+            self.signal = None;
+        }
+        std::future::pending().await
+    }
+}
+
+/// Resolves once the process is asked to terminate, i.e. on SIGTERM.
+#[cfg(unix)]
+async fn terminate_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    match signal(SignalKind::terminate()) {
+        Ok(mut sigterm) => {
+            let _ = sigterm.recv().await;
+        }
+        Err(error) => {
+            warn!("Failed to listen for SIGTERM: {error:?}");
+            std::future::pending::<()>().await;
         }
     }
+}
 
-    Ok(())
+/// Resolves once the process is asked to terminate, which is never on this platform.
+#[cfg(not(unix))]
+async fn terminate_signal() {
+    std::future::pending().await
 }
 
 fn update() -> Result<Status, Box<dyn (::std::error::Error)>> {
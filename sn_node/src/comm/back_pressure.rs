@@ -16,10 +16,18 @@ const SANITY_MIN_PER_S_AND_PEER: f64 = 1.0; // 1 every s
 
 type OutgoingReport = (Instant, f64);
 
+/// Bounds of the msgs per s and peer we tolerate.
+#[derive(Clone, Copy, Debug)]
+struct Bounds {
+    min: f64,
+    max: f64,
+}
+
 #[derive(Clone)]
 pub(crate) struct BackPressure {
     monitoring: RateLimits,
     last_report: Arc<RwLock<Option<OutgoingReport>>>,
+    bounds: Arc<RwLock<Bounds>>,
 }
 
 impl BackPressure {
@@ -27,9 +35,23 @@ impl BackPressure {
         Self {
             monitoring,
             last_report: Arc::new(RwLock::new(None)),
+            bounds: Arc::new(RwLock::new(Bounds {
+                min: SANITY_MIN_PER_S_AND_PEER,
+                max: SANITY_MAX_PER_S_AND_PEER,
+            })),
         }
     }
 
+    /// Sets the bounds of the msgs per s and peer we tolerate, the defaults being used for any not given.
+    pub(crate) async fn set_bounds(&self, min: Option<f64>, max: Option<f64>) {
+        let bounds = Bounds {
+            min: min.unwrap_or(SANITY_MIN_PER_S_AND_PEER),
+            max: max.unwrap_or(SANITY_MAX_PER_S_AND_PEER),
+        };
+        debug!("Back-pressure bounds set to {bounds:?}");
+        *self.bounds.write().await = bounds;
+    }
+
     pub(crate) async fn count_msg(&self) {
         self.monitoring.increment_msgs().await;
     }
@@ -44,15 +66,16 @@ impl BackPressure {
     async fn try_get_new_value(&self, sessions_count: usize, now: Instant) -> Option<f64> {
         let msgs_per_s = self.monitoring.max_msgs_per_s().await;
         let num_callers = sessions_count as f64;
+        let bounds = *self.bounds.read().await;
 
         // avoid divide by 0 errors
         let msgs_per_s_and_peer = msgs_per_s / f64::max(1.0, num_callers);
 
         // make sure not more than sanity max
-        let msgs_per_s_and_peer = f64::min(SANITY_MAX_PER_S_AND_PEER, msgs_per_s_and_peer);
+        let msgs_per_s_and_peer = f64::min(bounds.max, msgs_per_s_and_peer);
 
         // make sure not less than sanity min
-        let msgs_per_s_and_peer = f64::max(SANITY_MIN_PER_S_AND_PEER, msgs_per_s_and_peer);
+        let msgs_per_s_and_peer = f64::max(bounds.min, msgs_per_s_and_peer);
 
         debug!("Number of callers {:?}", num_callers);
         debug!("Msgs per s and peer {:?}", msgs_per_s_and_peer);
@@ -81,7 +104,7 @@ impl BackPressure {
                 (false, false)
             }
         } else {
-            let change_ratio = msgs_per_s_and_peer / SANITY_MAX_PER_S_AND_PEER;
+            let change_ratio = msgs_per_s_and_peer / bounds.max;
             if significant_change(change_ratio) {
                 // we want to store the value, and report the change
                 (true, true)
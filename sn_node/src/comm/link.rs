@@ -15,6 +15,10 @@ use priority_queue::DoublePriorityQueue;
 use qp2p::{Endpoint, RetryConfig};
 use std::{
     collections::BTreeMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
     time::{Duration, Instant},
 };
 
@@ -28,9 +32,38 @@ type ConnId = usize;
 // unforseen bursts, but at the same time puts a sane cap on the max
 // number continuously held for an - obviously malfunctioning - peer (i.e. edge case).
 const CAPACITY: usize = 255;
-// How long before quiet connections are cleaned up.
+// How long before quiet connections are cleaned up, unless configured otherwise.
 const UNUSED_TTL: Duration = Duration::from_secs(120);
 
+/// How long quiet connections are kept, shared by all links so that it can be changed
+/// while the node is running.
+#[derive(Clone, Debug)]
+pub(crate) struct UnusedTtl(Arc<AtomicU64>);
+
+impl Default for UnusedTtl {
+    fn default() -> Self {
+        Self(Arc::new(AtomicU64::new(UNUSED_TTL.as_millis() as u64)))
+    }
+}
+
+impl UnusedTtl {
+    /// Sets the ttl, the default being used if none is given. A zero ttl keeps quiet connections
+    /// around until the transport times them out.
+    pub(crate) fn set(&self, ttl: Option<Duration>) {
+        let ttl = ttl.unwrap_or(UNUSED_TTL);
+        debug!("Unused connections ttl set to {ttl:?}");
+        self.0.store(ttl.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// Whether something last used at the given instant has been quiet for longer than the ttl.
+    fn has_passed_since(&self, last_used: Instant) -> bool {
+        match self.0.load(Ordering::Relaxed) {
+            0 => false,
+            millis => last_used.elapsed() > Duration::from_millis(millis),
+        }
+    }
+}
+
 /// A link to a peer in our network.
 ///
 /// The upper layers will add incoming connections to the link,
@@ -48,11 +81,17 @@ pub(crate) struct Link {
     queue: DoublePriorityQueue<ConnId, Priority>,
     access_counter: u64,
     listener: MsgListener,
-    expiration_check: Instant,
+    unused_ttl: UnusedTtl,
+    last_expiration_check: Instant,
 }
 
 impl Link {
-    pub(crate) fn new(peer: Peer, endpoint: Endpoint, listener: MsgListener) -> Self {
+    pub(crate) fn new(
+        peer: Peer,
+        endpoint: Endpoint,
+        listener: MsgListener,
+        unused_ttl: UnusedTtl,
+    ) -> Self {
         Self {
             peer,
             endpoint,
@@ -60,7 +99,8 @@ impl Link {
             queue: DoublePriorityQueue::new(),
             access_counter: 0,
             listener,
-            expiration_check: expiration(),
+            unused_ttl,
+            last_expiration_check: Instant::now(),
         }
     }
 
@@ -68,9 +108,10 @@ impl Link {
         peer: Peer,
         endpoint: Endpoint,
         listener: MsgListener,
+        unused_ttl: UnusedTtl,
         conn: qp2p::Connection,
     ) -> Self {
-        let mut instance = Self::new(peer, endpoint, listener);
+        let mut instance = Self::new(peer, endpoint, listener, unused_ttl);
         instance.insert(conn);
         instance
     }
@@ -161,7 +202,7 @@ impl Link {
         self.queue
             .peek_max()
             .and_then(|(id, _)| self.connections.get(id))
-            .map(|conn| !conn.expired())
+            .map(|conn| !self.unused_ttl.has_passed_since(conn.last_used))
             .unwrap_or(false)
     }
 
@@ -199,7 +240,10 @@ impl Link {
     fn insert(&mut self, conn: qp2p::Connection) {
         let id = conn.id();
 
-        let _ = self.connections.insert(id, ExpiringConn::new(conn));
+        let last_used = Instant::now();
+        let _ = self
+            .connections
+            .insert(id, ExpiringConn { conn, last_used });
 
         let prio = self.priority();
         let _ = self.queue.push(id, prio);
@@ -210,7 +254,7 @@ impl Link {
         let _ = self.queue.change_priority(&id, prio);
 
         if let Some(conn) = self.connections.get_mut(&id) {
-            conn.touch()
+            conn.last_used = Instant::now();
         }
     }
 
@@ -233,11 +277,11 @@ impl Link {
 
     /// Remove expired connections.
     pub(crate) fn remove_expired(&mut self) {
-        if Instant::now() < self.expiration_check {
+        if !self.unused_ttl.has_passed_since(self.last_expiration_check) {
             return;
         }
 
-        self.expiration_check = expiration();
+        self.last_expiration_check = Instant::now();
 
         let mut expired_ids = vec![];
 
@@ -248,7 +292,7 @@ impl Link {
             }
 
             if let Some(conn) = self.connections.get_mut(&id) {
-                if conn.expired() {
+                if self.unused_ttl.has_passed_since(conn.last_used) {
                     expired_ids.push(id);
                 }
             }
@@ -306,26 +350,5 @@ impl SendToOneError {
 #[derive(Clone, Debug)]
 struct ExpiringConn {
     conn: qp2p::Connection,
-    expiry: Instant,
-}
-
-impl ExpiringConn {
-    fn new(conn: qp2p::Connection) -> Self {
-        ExpiringConn {
-            conn,
-            expiry: expiration(),
-        }
-    }
-
-    fn expired(&self) -> bool {
-        self.expiry < Instant::now()
-    }
-
-    fn touch(&mut self) {
-        self.expiry = expiration();
-    }
-}
-
-fn expiration() -> Instant {
-    Instant::now() + UNUSED_TTL
+    last_used: Instant,
 }
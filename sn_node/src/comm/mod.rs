@@ -17,7 +17,7 @@ mod peer_session;
 use self::back_pressure::BackPressure;
 
 use self::{
-    link::Link,
+    link::{Link, UnusedTtl},
     listener::{ListenerEvent, MsgListener},
     peer_session::{PeerSession, SendStatus, SendWatcher},
 };
@@ -48,6 +48,7 @@ pub(crate) struct Comm {
     #[cfg(feature = "back-pressure")]
     back_pressure: BackPressure,
     sessions: Arc<DashMap<Peer, PeerSession>>,
+    unused_ttl: UnusedTtl,
 }
 
 /// Commands for interacting with Comm.
@@ -57,6 +58,14 @@ pub(crate) enum Cmd {
     #[cfg(feature = "back-pressure")]
     /// Set message rate for peer to the desired msgs per second
     Regulate { peer: Peer, msgs_per_s: f64 },
+    #[cfg(feature = "back-pressure")]
+    /// Set the bounds of the msgs per second we tolerate from each peer
+    SetBackPressureBounds {
+        min_msgs_per_s: Option<f64>,
+        max_msgs_per_s: Option<f64>,
+    },
+    /// Set how long quiet connections to peers are kept, the default being used if none is given
+    SetIdleTimeout(Option<Duration>),
 }
 
 impl Comm {
@@ -114,6 +123,16 @@ impl Comm {
         match cmd {
             #[cfg(feature = "back-pressure")]
             Cmd::Regulate { peer, msgs_per_s } => self.regulate(&peer, msgs_per_s).await,
+            #[cfg(feature = "back-pressure")]
+            Cmd::SetBackPressureBounds {
+                min_msgs_per_s,
+                max_msgs_per_s,
+            } => {
+                self.back_pressure
+                    .set_bounds(min_msgs_per_s, max_msgs_per_s)
+                    .await
+            }
+            Cmd::SetIdleTimeout(idle_timeout) => self.unused_ttl.set(idle_timeout),
         }
     }
 
@@ -308,7 +327,12 @@ impl Comm {
         if let Some(entry) = self.sessions.get(peer) {
             return entry.value().clone();
         }
-        let link = Link::new(*peer, self.our_endpoint.clone(), self.msg_listener.clone());
+        let link = Link::new(
+            *peer,
+            self.our_endpoint.clone(),
+            self.msg_listener.clone(),
+            self.unused_ttl.clone(),
+        );
         let session = PeerSession::new(link);
         let _ = self.sessions.insert(*peer, session.clone());
         session
@@ -327,6 +351,7 @@ impl Comm {
                 *peer,
                 self.our_endpoint.clone(),
                 self.msg_listener.clone(),
+                self.unused_ttl.clone(),
                 conn,
             );
             let session = PeerSession::new(link);
@@ -395,6 +420,7 @@ fn setup(
         #[cfg(feature = "back-pressure")]
         back_pressure: back_pressure.clone(),
         sessions: Arc::new(DashMap::new()),
+        unused_ttl: UnusedTtl::default(),
     };
 
     #[cfg(feature = "back-pressure")]
@@ -0,0 +1,672 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! Local admin endpoint, to inspect and control a running node.
+//!
+//! Requests are JSON-RPC 2.0 objects, sent one per line over a TCP connection to the
+//! configured loopback address. Each of them gets a single line response, e.g.:
+//!
+//! ```text
+//! > {"jsonrpc":"2.0","id":1,"method":"pending_cmds","token":"<token>"}
+//! < {"jsonrpc":"2.0","id":1,"result":{"pending_cmds":3}}
+//! ```
+//!
+//! Every request must carry the token the node generates when it starts, which it writes to
+//! the `admin_token` file in its root dir, readable by the user running the node only.
+
+use crate::node::{Node, Result};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::BTreeMap,
+    net::SocketAddr,
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+use tokio::{
+    fs::{self, OpenOptions},
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::{TcpListener, TcpStream},
+    sync::{mpsc, oneshot, RwLock},
+};
+use xor_name::XorName;
+
+const ADMIN_REQUESTS_CHANNEL_SIZE: usize = 10;
+
+/// Name of the file, in the root dir of the node, holding the token admin requests must carry.
+pub(crate) const ADMIN_TOKEN_FILENAME: &str = "admin_token";
+
+// Error codes defined by the JSON-RPC 2.0 spec
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const INTERNAL_ERROR: i64 = -32603;
+// Error code for requests without a valid token, from the range left to implementations
+const UNAUTHORISED: i64 = -32001;
+
+/// An action requested through the admin endpoint, which is to be carried out
+/// by the process running the node.
+#[derive(Debug)]
+pub enum AdminRequest {
+    /// Stop the node and exit the process.
+    Shutdown,
+    /// Leave the section gracefully, handing our data off, then exit the process.
+    Leave,
+    /// Reload the config, applying the settings which can be changed while running.
+    ReloadConfig {
+        /// Where to report the names of the changed settings which need a restart
+        /// to take effect, or why the config could not be reloaded
+        response: oneshot::Sender<std::result::Result<Vec<String>, String>>,
+    },
+    /// Replace the log filter, e.g. with `sn_node=debug`.
+    SetLogLevel {
+        /// Filter directives, in the format of the `RUST_LOG` env var
+        directives: String,
+        /// Where to report whether the new filter was applied
+        response: oneshot::Sender<std::result::Result<(), String>>,
+    },
+}
+
+/// Receiving end of the actions requested through the admin endpoint.
+pub type AdminRequestReceiver = mpsc::Receiver<AdminRequest>;
+
+#[derive(Debug, Deserialize)]
+struct RpcRequest {
+    #[serde(default)]
+    id: Value,
+    method: String,
+    #[serde(default)]
+    params: Value,
+    #[serde(default)]
+    token: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct RpcResponse {
+    jsonrpc: &'static str,
+    id: Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, Serialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcResponse {
+    fn new(id: Value, result: std::result::Result<Value, RpcError>) -> Self {
+        let (result, error) = match result {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+        Self {
+            jsonrpc: "2.0",
+            id,
+            result,
+            error,
+        }
+    }
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+/// State shared by the connections to the admin endpoint.
+#[derive(Clone)]
+struct AdminCtx {
+    node: Arc<RwLock<Node>>,
+    pending_cmds: Arc<AtomicUsize>,
+    requests: mpsc::Sender<AdminRequest>,
+    token: Arc<String>,
+}
+
+/// Starts serving the admin endpoint at the given address, returning the receiver of the
+/// actions which are to be carried out by the process running the node.
+/// A new token for the requests is written to [`ADMIN_TOKEN_FILENAME`] in the root dir.
+pub(crate) async fn run_admin_server(
+    addr: SocketAddr,
+    root_dir: &Path,
+    node: Arc<RwLock<Node>>,
+    pending_cmds: Arc<AtomicUsize>,
+) -> Result<AdminRequestReceiver> {
+    let token = hex::encode(rand::random::<[u8; 32]>());
+    let token_path = root_dir.join(ADMIN_TOKEN_FILENAME);
+    write_token(&token_path, &token).await?;
+
+    let listener = TcpListener::bind(addr).await?;
+    let (requests, receiver) = mpsc::channel(ADMIN_REQUESTS_CHANNEL_SIZE);
+    let ctx = AdminCtx {
+        node,
+        pending_cmds,
+        requests,
+        token: Arc::new(token),
+    };
+
+    info!(
+        "Serving admin endpoint at {}, with the token for requests in {}",
+        listener.local_addr()?,
+        token_path.display()
+    );
+
+    let _handle = tokio::task::spawn_local(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, peer_addr)) => {
+                    debug!("Admin connection from {peer_addr}");
+                    let ctx = ctx.clone();
+                    let _handle = tokio::task::spawn_local(async move {
+                        if let Err(error) = ctx.serve(stream).await {
+                            warn!("Admin connection from {peer_addr} failed: {error:?}");
+                        }
+                    });
+                }
+                Err(error) => {
+                    error!("Failed to accept admin connection: {error:?}");
+                }
+            }
+        }
+    });
+
+    Ok(receiver)
+}
+
+impl AdminCtx {
+    async fn serve(&self, stream: TcpStream) -> Result<()> {
+        let (reader, mut writer) = stream.into_split();
+        let mut lines = BufReader::new(reader).lines();
+
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let response = self.respond(&line).await;
+            let mut bytes = serde_json::to_vec(&response)?;
+            bytes.push(b'\n');
+            writer.write_all(&bytes).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn respond(&self, line: &str) -> RpcResponse {
+        let request = match serde_json::from_str::<RpcRequest>(line) {
+            Ok(request) => request,
+            Err(error) => {
+                return RpcResponse::new(
+                    Value::Null,
+                    Err(RpcError::new(PARSE_ERROR, error.to_string())),
+                )
+            }
+        };
+
+        if !self.is_authorised(request.token.as_deref()) {
+            warn!("Admin request without a valid token: {}", request.method);
+            return RpcResponse::new(
+                request.id,
+                Err(RpcError::new(UNAUTHORISED, "Missing or invalid token")),
+            );
+        }
+
+        trace!("Admin request: {}", request.method);
+        let result = self.handle(&request.method, request.params).await;
+        RpcResponse::new(request.id, result)
+    }
+
+    // Compares the whole tokens regardless of where they differ, not to leak how much of it
+    // was guessed right through the time taken
+    fn is_authorised(&self, token: Option<&str>) -> bool {
+        token.is_some_and(|token| {
+            token.len() == self.token.len()
+                && token
+                    .bytes()
+                    .zip(self.token.bytes())
+                    .fold(0, |diff, (lhs, rhs)| diff | (lhs ^ rhs))
+                    == 0
+        })
+    }
+
+    async fn handle(&self, method: &str, params: Value) -> std::result::Result<Value, RpcError> {
+        match method {
+            "status" => Ok(self.status().await),
+            "section_tree" => Ok(self.section_tree().await),
+            "storage" => Ok(self.storage().await),
+            "adult_levels" => Ok(self.adult_levels().await),
+            "dysfunction_scores" => Ok(self.dysfunction_scores().await),
+            "pending_cmds" => Ok(json!({
+                "pending_cmds": self.pending_cmds.load(Ordering::Relaxed)
+            })),
+            "set_log_level" => self.set_log_level(params).await,
+            "reload_config" => self.reload_config().await,
+            "shutdown" => {
+                self.send(AdminRequest::Shutdown).await?;
+                Ok(Value::Null)
+            }
+            "leave" => {
+                self.send(AdminRequest::Leave).await?;
+                Ok(Value::Null)
+            }
+            other => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("Unknown method: {other}"),
+            )),
+        }
+    }
+
+    /// Our identity, and the membership of our section.
+    async fn status(&self) -> Value {
+        let node = self.node.read().await;
+        let info = node.info();
+        let network_knowledge = node.network_knowledge();
+
+        let elders: Vec<_> = network_knowledge
+            .elders()
+            .iter()
+            .map(|peer| json!({ "name": hex_name(&peer.name()), "addr": peer.addr() }))
+            .collect();
+        let members: Vec<_> = network_knowledge
+            .section_members()
+            .iter()
+            .map(|state| {
+                let peer = state.peer();
+                json!({
+                    "name": hex_name(&peer.name()),
+                    "addr": peer.addr(),
+                    "age": state.age(),
+                })
+            })
+            .collect();
+
+        json!({
+            "name": hex_name(&info.name()),
+            "age": info.age(),
+            "addr": info.addr,
+            "is_elder": node.is_elder(),
+            "prefix": network_knowledge.prefix().to_string(),
+            "section_key": hex::encode(network_knowledge.section_key().to_bytes()),
+            "section_chain_len": network_knowledge.chain_len(),
+            "elders": elders,
+            "members": members,
+        })
+    }
+
+    /// The sections we know about.
+    async fn section_tree(&self) -> Value {
+        let node = self.node.read().await;
+        let network_knowledge = node.network_knowledge();
+        let section_tree = network_knowledge.section_tree();
+        let stats = section_tree.network_stats(&network_knowledge.authority_provider());
+
+        let sections: Vec<_> = section_tree
+            .all()
+            .iter()
+            .map(|sap| {
+                json!({
+                    "prefix": sap.prefix().to_string(),
+                    "section_key": hex::encode(sap.section_key().to_bytes()),
+                    "elder_count": sap.elder_count(),
+                })
+            })
+            .collect();
+
+        json!({
+            "genesis_key": hex::encode(section_tree.genesis_key().to_bytes()),
+            "known_sections": section_tree.known_sections_count(),
+            "network_stats": stats.to_string(),
+            "sections": sections,
+        })
+    }
+
+    /// Space used by the data we hold.
+    async fn storage(&self) -> Value {
+        // the data is counted without holding the node lock
+        let data_storage = self.node.read().await.data_storage.clone();
+        let used_space = data_storage.used_space();
+        let (chunks, registers) = data_storage.data_counts().await;
+
+        json!({
+            "used_bytes": used_space.used(),
+            "max_capacity_bytes": used_space.max_capacity(),
+            "chunks": chunks,
+            "registers": registers,
+        })
+    }
+
+    /// Storage levels reported by the adults of our section.
+    async fn adult_levels(&self) -> Value {
+        let node = self.node.read().await;
+        let levels: BTreeMap<_, _> = node
+            .capacity
+            .levels()
+            .into_iter()
+            .map(|(name, level)| (hex_name(&name), level.value()))
+            .collect();
+
+        json!(levels)
+    }
+
+    /// Dysfunction scores of the nodes we track, per type of issue.
+    async fn dysfunction_scores(&self) -> Value {
+        let node = self.node.read().await;
+        let scores = node.dysfunction_tracking.calculate_scores();
+
+        json!({
+            "communication": hex_keys(scores.communication_scores),
+            "dkg": hex_keys(scores.dkg_scores),
+            "knowledge": hex_keys(scores.knowledge_scores),
+            "op": hex_keys(scores.op_scores),
+            "probe": hex_keys(scores.probe_scores),
+        })
+    }
+
+    async fn set_log_level(&self, params: Value) -> std::result::Result<Value, RpcError> {
+        let directives = params
+            .get("directives")
+            .and_then(Value::as_str)
+            .ok_or_else(|| RpcError::new(INVALID_PARAMS, "Missing `directives` string param"))?
+            .to_string();
+
+        let (response, result) = oneshot::channel();
+        self.send(AdminRequest::SetLogLevel {
+            directives,
+            response,
+        })
+        .await?;
+
+        match result.await {
+            Ok(Ok(())) => Ok(Value::Null),
+            Ok(Err(error)) => Err(RpcError::new(INVALID_PARAMS, error)),
+            Err(_) => Err(RpcError::new(
+                INTERNAL_ERROR,
+                "Log level change was dropped without a response",
+            )),
+        }
+    }
+
+    async fn reload_config(&self) -> std::result::Result<Value, RpcError> {
+        let (response, result) = oneshot::channel();
+        self.send(AdminRequest::ReloadConfig { response }).await?;
+
+        match result.await {
+            Ok(Ok(requires_restart)) => Ok(json!({ "requires_restart": requires_restart })),
+            Ok(Err(error)) => Err(RpcError::new(INTERNAL_ERROR, error)),
+            Err(_) => Err(RpcError::new(
+                INTERNAL_ERROR,
+                "Config reload was dropped without a response",
+            )),
+        }
+    }
+
+    async fn send(&self, request: AdminRequest) -> std::result::Result<(), RpcError> {
+        self.requests.send(request).await.map_err(|_| {
+            RpcError::new(
+                INTERNAL_ERROR,
+                "Admin requests are not handled by this process",
+            )
+        })
+    }
+}
+
+// Writes the token to a new file only the user running the node can access
+async fn write_token(path: &Path, token: &str) -> Result<()> {
+    // a file left by a previous run may be readable by others
+    if let Err(error) = fs::remove_file(path).await {
+        if error.kind() != std::io::ErrorKind::NotFound {
+            return Err(error.into());
+        }
+    }
+
+    let mut options = OpenOptions::new();
+    let _ = options.write(true).create_new(true);
+    #[cfg(unix)]
+    let _ = options.mode(0o600);
+
+    let mut file = options.open(path).await?;
+    file.write_all(token.as_bytes()).await?;
+    file.flush().await?;
+    Ok(())
+}
+
+fn hex_name(name: &XorName) -> String {
+    format!("{:x}", name)
+}
+
+fn hex_keys(scores: BTreeMap<XorName, f32>) -> BTreeMap<String, f32> {
+    scores
+        .into_iter()
+        .map(|(name, score)| (hex_name(&name), score))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        write_token, AdminCtx, AdminRequest, AdminRequestReceiver, RpcError, RpcRequest,
+        RpcResponse, ADMIN_REQUESTS_CHANNEL_SIZE, INVALID_PARAMS, METHOD_NOT_FOUND, UNAUTHORISED,
+    };
+    use crate::node::flow_ctrl::tests::network_utils::TestNodeBuilder;
+
+    use eyre::Result;
+    use serde_json::{json, Value};
+    use sn_interface::elder_count;
+    use std::sync::{atomic::AtomicUsize, Arc};
+    use tempfile::tempdir;
+    use tokio::sync::mpsc;
+    use xor_name::Prefix;
+
+    const TOKEN: &str = "0123456789abcdef";
+
+    async fn create_admin_ctx() -> Result<(AdminCtx, AdminRequestReceiver)> {
+        let (dispatcher, ..) = TestNodeBuilder::new(Prefix::default(), elder_count())
+            .build()
+            .await?;
+        let (requests, receiver) = mpsc::channel(ADMIN_REQUESTS_CHANNEL_SIZE);
+        let ctx = AdminCtx {
+            node: dispatcher.node(),
+            pending_cmds: Arc::new(AtomicUsize::new(3)),
+            requests,
+            token: Arc::new(TOKEN.to_string()),
+        };
+        Ok((ctx, receiver))
+    }
+
+    async fn request(ctx: &AdminCtx, request: Value) -> Result<Value> {
+        Ok(serde_json::to_value(
+            ctx.respond(&request.to_string()).await,
+        )?)
+    }
+
+    #[test]
+    fn rpc_request_defaults_missing_id_and_params() {
+        let request: RpcRequest = serde_json::from_str(r#"{"method":"status"}"#).unwrap();
+        assert_eq!(request.method, "status");
+        assert_eq!(request.id, Value::Null);
+        assert_eq!(request.params, Value::Null);
+    }
+
+    #[test]
+    fn rpc_response_carries_either_result_or_error() {
+        let ok = RpcResponse::new(json!(1), Ok(json!({ "pending_cmds": 0 })));
+        assert_eq!(
+            serde_json::to_value(&ok).unwrap(),
+            json!({ "jsonrpc": "2.0", "id": 1, "result": { "pending_cmds": 0 } })
+        );
+
+        let err = RpcResponse::new(json!(2), Err(RpcError::new(METHOD_NOT_FOUND, "nope")));
+        assert_eq!(
+            serde_json::to_value(&err).unwrap(),
+            json!({ "jsonrpc": "2.0", "id": 2, "error": { "code": METHOD_NOT_FOUND, "message": "nope" } })
+        );
+    }
+
+    #[tokio::test]
+    async fn requests_without_the_token_are_rejected() -> Result<()> {
+        let local = tokio::task::LocalSet::new();
+        local
+            .run_until(async move {
+                let (ctx, mut receiver) = create_admin_ctx().await?;
+
+                for token in [None, Some(""), Some("0123456789abcdeF"), Some("0123")] {
+                    let response = request(
+                        &ctx,
+                        json!({ "jsonrpc": "2.0", "id": 1, "method": "shutdown", "token": token }),
+                    )
+                    .await?;
+                    assert_eq!(response["error"]["code"], json!(UNAUTHORISED));
+                    assert_eq!(response["id"], json!(1));
+                }
+                assert!(receiver.try_recv().is_err());
+
+                Ok(())
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn handlers_report_the_node_state() -> Result<()> {
+        let local = tokio::task::LocalSet::new();
+        local
+            .run_until(async move {
+                let (ctx, _receiver) = create_admin_ctx().await?;
+                let name = format!("{:x}", ctx.node.read().await.info().name());
+
+                let response = request(
+                    &ctx,
+                    json!({ "jsonrpc": "2.0", "id": 1, "method": "status", "token": TOKEN }),
+                )
+                .await?;
+                assert_eq!(response["result"]["name"], json!(name));
+                assert_eq!(response["result"]["is_elder"], json!(true));
+                assert_eq!(
+                    response["result"]["elders"].as_array().map(Vec::len),
+                    Some(elder_count())
+                );
+
+                let response = request(
+                    &ctx,
+                    json!({ "jsonrpc": "2.0", "id": 2, "method": "pending_cmds", "token": TOKEN }),
+                )
+                .await?;
+                assert_eq!(response["result"], json!({ "pending_cmds": 3 }));
+
+                let response = request(
+                    &ctx,
+                    json!({ "jsonrpc": "2.0", "id": 3, "method": "storage", "token": TOKEN }),
+                )
+                .await?;
+                assert_eq!(response["result"]["chunks"], json!(0));
+                assert_eq!(response["result"]["registers"], json!(0));
+
+                let response = request(
+                    &ctx,
+                    json!({ "jsonrpc": "2.0", "id": 4, "method": "section_tree", "token": TOKEN }),
+                )
+                .await?;
+                assert_eq!(response["result"]["known_sections"], json!(1));
+
+                let response = request(
+                    &ctx,
+                    json!({ "jsonrpc": "2.0", "id": 5, "method": "restart", "token": TOKEN }),
+                )
+                .await?;
+                assert_eq!(response["error"]["code"], json!(METHOD_NOT_FOUND));
+
+                Ok(())
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn control_requests_are_passed_on_to_the_process() -> Result<()> {
+        let local = tokio::task::LocalSet::new();
+        local
+            .run_until(async move {
+                let (ctx, mut receiver) = create_admin_ctx().await?;
+
+                let response = request(
+                    &ctx,
+                    json!({ "jsonrpc": "2.0", "id": 1, "method": "leave", "token": TOKEN }),
+                )
+                .await?;
+                assert_eq!(response["result"], Value::Null);
+                assert!(matches!(receiver.try_recv(), Ok(AdminRequest::Leave)));
+
+                let response = request(
+                    &ctx,
+                    json!({ "jsonrpc": "2.0", "id": 2, "method": "set_log_level", "token": TOKEN }),
+                )
+                .await?;
+                assert_eq!(response["error"]["code"], json!(INVALID_PARAMS));
+
+                // the log level change is reported once the process applied it
+                let handler = tokio::task::spawn_local(async move {
+                    match receiver.recv().await {
+                        Some(AdminRequest::SetLogLevel {
+                            directives,
+                            response,
+                        }) => {
+                            let _ = response.send(Ok(()));
+                            Some(directives)
+                        }
+                        _ => None,
+                    }
+                });
+                let response = request(
+                    &ctx,
+                    json!({
+                        "jsonrpc": "2.0",
+                        "id": 3,
+                        "method": "set_log_level",
+                        "params": { "directives": "sn_node=debug" },
+                        "token": TOKEN,
+                    }),
+                )
+                .await?;
+                assert_eq!(response["result"], Value::Null);
+                assert_eq!(handler.await?, Some("sn_node=debug".to_string()));
+
+                Ok(())
+            })
+            .await
+    }
+
+    #[tokio::test]
+    async fn token_file_is_replaced_and_private() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join(super::ADMIN_TOKEN_FILENAME);
+        std::fs::write(&path, "previous token")?;
+
+        write_token(&path, TOKEN).await?;
+        assert_eq!(std::fs::read_to_string(&path)?, TOKEN);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(
+                std::fs::metadata(&path)?.permissions().mode() & 0o777,
+                0o600
+            );
+        }
+
+        Ok(())
+    }
+}
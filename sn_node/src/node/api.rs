@@ -9,7 +9,7 @@
 use crate::{
     node::{
         flow_ctrl::{cmds::Cmd, event_channel::EventSender},
-        Error, Event, Node, Result, GENESIS_DBC_AMOUNT,
+        DataEvent, Error, Event, Node, Result, StorageBackendKind, GENESIS_DBC_AMOUNT,
     },
     UsedSpace,
 };
@@ -36,6 +36,7 @@ impl Node {
         event_sender: EventSender,
         used_space: UsedSpace,
         root_storage_dir: PathBuf,
+        storage_backend: StorageBackendKind,
         genesis_sk_set: bls::SecretKeySet,
     ) -> Result<(Self, Dbc)> {
         let info = NodeInfo {
@@ -57,6 +58,7 @@ impl Node {
             event_sender,
             used_space,
             root_storage_dir,
+            storage_backend,
         )
         .await?;
 
@@ -118,6 +120,10 @@ impl Node {
         self.event_sender.send(event).await
     }
 
+    pub(crate) fn send_data_event(&self, event: DataEvent) {
+        self.event_sender.send_data_event(event)
+    }
+
     // ----------------------------------------------------------------------------------------
     //   ---------------------------------- Mut ------------------------------------------
     // ----------------------------------------------------------------------------------------
@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::node::{Error, NetworkConfig, Result};
+use crate::node::{DataKind, Error, NetworkConfig, Result, StorageBackendKind};
 use clap::Parser;
 use serde::{Deserialize, Serialize};
 use std::{
@@ -27,9 +27,11 @@ const DEFAULT_ROOT_DIR_NAME: &str = "root_dir";
 const DEFAULT_MAX_CAPACITY: usize = 10 * 1024 * 1024 * 1024; // 10GB
 #[cfg(any(target_arch = "arm", target_arch = "armv7"))]
 const DEFAULT_MAX_CAPACITY: usize = usize::MAX; // This will be 2^32 on these architectures.
+const DEFAULT_CHUNK_SCRUB_BATCH_SIZE: usize = 50;
 
 /// Node configuration
 #[derive(Default, Clone, Debug, Serialize, Deserialize, clap::StructOpt)]
+#[serde(default)]
 #[clap(rename_all = "kebab-case", bin_name = "sn_node", version)]
 #[clap(global_settings = &[clap::AppSettings::ColoredHelp])]
 pub struct Config {
@@ -121,6 +123,10 @@ pub struct Config {
     /// supplied we'll default to the documented constant.
     ///
     /// The interval is in milliseconds. A value of 0 disables this feature.
+    ///
+    /// This can be changed while the node is running, like `max-capacity`. The new value then
+    /// applies to how long quiet connections are kept, while the transport keeps the one it was
+    /// started with.
     #[clap(long)]
     pub idle_timeout_msec: Option<u64>,
     /// Interval to send keep-alives if we are idling so that the peer does not disconnect from us
@@ -132,6 +138,75 @@ pub struct Config {
     /// Duration of a UPnP port mapping.
     #[clap(long)]
     pub upnp_lease_duration: Option<u32>,
+    /// Number of stored chunks to re-hash on each round of integrity scrubbing, which runs every
+    /// minute. Corrupt chunks are quarantined and a fresh copy is requested from other holders.
+    /// If none is supplied we'll default to the documented constant.
+    ///
+    /// A value of 0 disables this feature.
+    #[clap(long)]
+    pub chunk_scrub_batch_size: Option<usize>,
+    /// Layout used to persist data on disk: `files` (one file per chunk) or `pack` (records
+    /// appended to segment files). If none is supplied we'll default to `files`.
+    ///
+    /// Data stored with one backend is not migrated when switching to the other.
+    #[clap(long)]
+    pub storage_backend: Option<StorageBackendKind>,
+    /// Upper limit in bytes for the data stored by the node. If none is supplied we'll default to
+    /// the documented constant.
+    ///
+    /// This can be changed while the node is running, by editing the config file and sending
+    /// SIGHUP to the node.
+    #[clap(long)]
+    pub max_capacity: Option<usize>,
+    /// Upper limit in bytes for the chunks stored by the node, within `max-capacity`.
+    /// Unlimited if none is supplied.
+    ///
+    /// This can be changed while the node is running, like `max-capacity`.
+    #[clap(long)]
+    pub max_chunks_capacity: Option<usize>,
+    /// Upper limit in bytes for the registers stored by the node, within `max-capacity`.
+    /// Unlimited if none is supplied.
+    ///
+    /// This can be changed while the node is running, like `max-capacity`.
+    #[clap(long)]
+    pub max_registers_capacity: Option<usize>,
+    /// Upper limit in bytes for the spentbooks stored by the node, within `max-capacity`.
+    /// Unlimited if none is supplied.
+    ///
+    /// This can be changed while the node is running, like `max-capacity`.
+    #[clap(long)]
+    pub max_spentbooks_capacity: Option<usize>,
+    /// Percentage of `max-capacity` at which the node reports itself as full to its section
+    /// and stops accepting new data. If none is supplied the whole capacity can be used.
+    ///
+    /// This can be changed while the node is running, like `max-capacity`.
+    #[clap(long)]
+    pub storage_high_watermark: Option<u8>,
+    /// Lower bound of the msgs per second each peer is asked to send us at most, when the node
+    /// reports back-pressure. If none is supplied we'll default to the documented constant.
+    ///
+    /// This can be changed while the node is running, like `max-capacity`.
+    #[clap(long)]
+    pub min_tolerated_msgs_per_s: Option<f64>,
+    /// Upper bound of the msgs per second each peer is asked to send us at most, when the node
+    /// reports back-pressure. If none is supplied we'll default to the documented constant.
+    ///
+    /// This can be changed while the node is running, like `max-capacity`.
+    #[clap(long)]
+    pub max_tolerated_msgs_per_s: Option<f64>,
+    /// Local address where to serve the admin endpoint, which takes JSON-RPC requests to inspect
+    /// and control the running node, e.g. 127.0.0.1:12001. It is disabled if none is supplied.
+    ///
+    /// It must use a loopback IP. Requests must carry the token written to the `admin_token`
+    /// file in the root dir when the node starts.
+    #[clap(long)]
+    pub admin_addr: Option<SocketAddr>,
+    /// Address where to serve node metrics for Prometheus to scrape, e.g. 127.0.0.1:12002.
+    /// It is disabled if none is supplied.
+    ///
+    /// The node needs to be built with the `metrics` feature for this to take effect.
+    #[clap(long)]
+    pub metrics_addr: Option<SocketAddr>,
     #[clap(skip)]
     #[allow(missing_docs)]
     pub network_config: NetworkConfig,
@@ -141,12 +216,7 @@ impl Config {
     /// Returns a new `Config` instance.  Tries to read from the default node config file location,
     /// and overrides values with any equivalent cmd line args.
     pub async fn new() -> Result<Self, Error> {
-        let mut config = Config::default();
-
-        let cmd_line_args = Config::parse();
-        cmd_line_args.validate()?;
-
-        config.merge(cmd_line_args);
+        let config = Config::read().await?;
 
         config.clear_data_from_disk().await.unwrap_or_else(|_| {
             error!("Error deleting data file from disk");
@@ -156,7 +226,85 @@ impl Config {
         Ok(config)
     }
 
-    /// Validate configuration that came from the cmd line.
+    /// Reads the config again, and takes on the new values of the settings which can be changed
+    /// while the node is running, i.e. the log verbosity, the storage limits, the back-pressure
+    /// bounds and the idle timeout. Nothing is changed if the new config is not valid.
+    ///
+    /// Returns the names of the other settings which changed, as they only take effect once the
+    /// node restarts.
+    pub async fn reload(&mut self) -> Result<Vec<&'static str>> {
+        let config = Config::read().await?;
+        let requires_restart = self.take_runtime_settings(config);
+
+        info!("Node config reloaded: {:?}", self);
+        Ok(requires_restart)
+    }
+
+    /// Takes on the values of the settings which can be changed while the node is running from
+    /// the given config, and returns the names of the other settings which differ.
+    fn take_runtime_settings(&mut self, config: Config) -> Vec<&'static str> {
+        let mut requires_restart = vec![];
+        macro_rules! check_unchanged {
+            ($($field:ident),*) => {
+                $(
+                    if self.$field != config.$field {
+                        requires_restart.push(stringify!($field));
+                    }
+                )*
+            };
+        }
+        check_unchanged!(
+            wallet_id,
+            root_dir,
+            log_dir,
+            logs_retained,
+            logs_max_bytes,
+            logs_max_lines,
+            logs_uncompressed,
+            json_logs,
+            resource_logs,
+            first,
+            network_contacts_file,
+            local_addr,
+            public_addr,
+            skip_auto_port_forwarding,
+            max_msg_size_allowed,
+            keep_alive_interval_msec,
+            upnp_lease_duration,
+            chunk_scrub_batch_size,
+            storage_backend,
+            admin_addr,
+            metrics_addr
+        );
+
+        self.verbose = config.verbose;
+        self.idle_timeout_msec = config.idle_timeout_msec;
+        self.max_capacity = config.max_capacity;
+        self.max_chunks_capacity = config.max_chunks_capacity;
+        self.max_registers_capacity = config.max_registers_capacity;
+        self.max_spentbooks_capacity = config.max_spentbooks_capacity;
+        self.storage_high_watermark = config.storage_high_watermark;
+        self.min_tolerated_msgs_per_s = config.min_tolerated_msgs_per_s;
+        self.max_tolerated_msgs_per_s = config.max_tolerated_msgs_per_s;
+
+        requires_restart
+    }
+
+    /// Reads the default node config file, and overrides its values with any cmd line args.
+    async fn read() -> Result<Self> {
+        let mut config = Config::default();
+
+        if let Some(file_config) = Config::read_from_file().await? {
+            config.merge(file_config);
+        }
+
+        config.merge(Config::parse());
+        config.validate()?;
+
+        Ok(config)
+    }
+
+    /// Validate the configuration, once the config file and the cmd line have been merged.
     ///
     /// `StructOpt` doesn't support validation that crosses multiple field values.
     fn validate(&self) -> Result<(), Error> {
@@ -181,6 +329,16 @@ impl Config {
             }
         }
 
+        if let Some(admin_addr) = self.admin_addr {
+            if !admin_addr.ip().is_loopback() {
+                return Err(Error::Configuration(
+                    "The --admin-addr must use a loopback IP. The admin endpoint can stop \
+                    the node, so it must not be reachable from other machines."
+                        .to_string(),
+                ));
+            }
+        }
+
         let local_ip_unspecified = self
             .local_addr
             .map(|addr| addr.ip().is_unspecified())
@@ -255,16 +413,64 @@ impl Config {
 
         if let Some(idle_timeout) = config.idle_timeout_msec {
             self.idle_timeout_msec = Some(idle_timeout);
+            self.network_config.idle_timeout = Some(Duration::from_millis(idle_timeout));
         }
 
         if let Some(keep_alive) = config.keep_alive_interval_msec {
             self.keep_alive_interval_msec = Some(keep_alive);
+            self.network_config.keep_alive_interval =
+                Some(Duration::from_millis(keep_alive as u64));
         }
 
         if let Some(upnp_lease_duration) = config.upnp_lease_duration {
+            self.upnp_lease_duration = Some(upnp_lease_duration);
             self.network_config.upnp_lease_duration =
                 Some(Duration::from_millis(upnp_lease_duration as u64));
         }
+
+        if let Some(batch_size) = config.chunk_scrub_batch_size {
+            self.chunk_scrub_batch_size = Some(batch_size);
+        }
+
+        if let Some(storage_backend) = config.storage_backend {
+            self.storage_backend = Some(storage_backend);
+        }
+
+        if let Some(max_capacity) = config.max_capacity {
+            self.max_capacity = Some(max_capacity);
+        }
+
+        if let Some(max_capacity) = config.max_chunks_capacity {
+            self.max_chunks_capacity = Some(max_capacity);
+        }
+
+        if let Some(max_capacity) = config.max_registers_capacity {
+            self.max_registers_capacity = Some(max_capacity);
+        }
+
+        if let Some(max_capacity) = config.max_spentbooks_capacity {
+            self.max_spentbooks_capacity = Some(max_capacity);
+        }
+
+        if let Some(high_watermark) = config.storage_high_watermark {
+            self.storage_high_watermark = Some(high_watermark);
+        }
+
+        if let Some(msgs_per_s) = config.min_tolerated_msgs_per_s {
+            self.min_tolerated_msgs_per_s = Some(msgs_per_s);
+        }
+
+        if let Some(msgs_per_s) = config.max_tolerated_msgs_per_s {
+            self.max_tolerated_msgs_per_s = Some(msgs_per_s);
+        }
+
+        if let Some(admin_addr) = config.admin_addr {
+            self.admin_addr = Some(admin_addr);
+        }
+
+        if let Some(metrics_addr) = config.metrics_addr {
+            self.metrics_addr = Some(metrics_addr);
+        }
     }
 
     /// The address to be credited when this node farms `SafeCoin`.
@@ -282,9 +488,60 @@ impl Config {
         self.network_contacts_file.clone()
     }
 
+    /// How long connections to peers are kept while quiet, if set. Zero keeps them around until
+    /// the transport times them out.
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        self.idle_timeout_msec.map(Duration::from_millis)
+    }
+
     /// Upper limit in bytes for allowed network storage on this node.
     pub fn max_capacity(&self) -> usize {
-        DEFAULT_MAX_CAPACITY
+        self.max_capacity.unwrap_or(DEFAULT_MAX_CAPACITY)
+    }
+
+    /// Upper limit in bytes for the given kind of data stored on this node, if set.
+    pub fn max_capacity_of(&self, kind: DataKind) -> Option<usize> {
+        match kind {
+            DataKind::Chunk => self.max_chunks_capacity,
+            DataKind::Register => self.max_registers_capacity,
+            DataKind::Spentbook => self.max_spentbooks_capacity,
+        }
+    }
+
+    /// Percentage of the capacity at which this node stops accepting new data, if set.
+    pub fn storage_high_watermark(&self) -> Option<u8> {
+        self.storage_high_watermark
+    }
+
+    /// Lower bound of the msgs per second each peer is asked to send us at most, if set.
+    pub fn min_tolerated_msgs_per_s(&self) -> Option<f64> {
+        self.min_tolerated_msgs_per_s
+    }
+
+    /// Upper bound of the msgs per second each peer is asked to send us at most, if set.
+    pub fn max_tolerated_msgs_per_s(&self) -> Option<f64> {
+        self.max_tolerated_msgs_per_s
+    }
+
+    /// Number of stored chunks to re-hash on each round of integrity scrubbing, 0 when disabled.
+    pub fn chunk_scrub_batch_size(&self) -> usize {
+        self.chunk_scrub_batch_size
+            .unwrap_or(DEFAULT_CHUNK_SCRUB_BATCH_SIZE)
+    }
+
+    /// Layout used to persist data on disk.
+    pub fn storage_backend(&self) -> StorageBackendKind {
+        self.storage_backend.unwrap_or_default()
+    }
+
+    /// Local address of the admin endpoint, if enabled.
+    pub fn admin_addr(&self) -> Option<SocketAddr> {
+        self.admin_addr
+    }
+
+    /// Address of the metrics scrape endpoint, if enabled.
+    pub fn metrics_addr(&self) -> Option<SocketAddr> {
+        self.metrics_addr
     }
 
     /// Root directory for dbs and cached state. If not set, it defaults to
@@ -380,7 +637,6 @@ impl Config {
     }
 
     /// Reads the default node config file.
-    #[allow(unused)]
     async fn read_from_file() -> Result<Option<Config>> {
         let path = project_dirs()?.join(CONFIG_FILE);
 
@@ -459,7 +715,61 @@ fn smoke() {
     // NOTE: IF this value is being changed due to a change in the config,
     // the change in config also be handled in Config::merge()
     // and in examples/config_handling.rs
-    let expected_size = 432;
+    let expected_size = 616;
 
     assert_eq!(std::mem::size_of::<Config>(), expected_size);
 }
+
+#[test]
+fn reload_takes_runtime_settings_only() {
+    let mut config = Config {
+        first: true,
+        ..Default::default()
+    };
+    let mut reloaded = Config {
+        first: true,
+        verbose: 3,
+        idle_timeout_msec: Some(5_000),
+        max_capacity: Some(1024),
+        max_tolerated_msgs_per_s: Some(10.0),
+        chunk_scrub_batch_size: Some(8),
+        ..Default::default()
+    };
+    reloaded.set_root_dir("/tmp/reloaded");
+
+    let requires_restart = config.take_runtime_settings(reloaded);
+
+    assert_eq!(requires_restart, vec!["root_dir", "chunk_scrub_batch_size"]);
+    assert_eq!(config.verbose(), Level::DEBUG);
+    assert_eq!(config.idle_timeout(), Some(Duration::from_millis(5_000)));
+    assert_eq!(config.max_capacity(), 1024);
+    assert_eq!(config.max_tolerated_msgs_per_s(), Some(10.0));
+    assert_eq!(
+        config.chunk_scrub_batch_size(),
+        DEFAULT_CHUNK_SCRUB_BATCH_SIZE
+    );
+    assert!(config.root_dir.is_none());
+}
+
+#[test]
+fn merged_config_is_validated() {
+    let local_addr = "127.0.0.1:0".parse().ok();
+
+    // the file makes a first node, the cmd line sets a contacts file: each alone is valid
+    let mut config = Config::default();
+    config.merge(Config {
+        first: true,
+        local_addr,
+        ..Default::default()
+    });
+    assert!(config.validate().is_ok());
+
+    let cmd_line_args = Config {
+        network_contacts_file: Some(PathBuf::from("/tmp/contacts")),
+        ..Default::default()
+    };
+    assert!(cmd_line_args.validate().is_ok());
+
+    config.merge(cmd_line_args);
+    assert!(matches!(config.validate(), Err(Error::Configuration(_))));
+}
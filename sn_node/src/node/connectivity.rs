@@ -6,7 +6,13 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::node::{flow_ctrl::cmds::Cmd, Node, Proposal, Result};
+use crate::node::{flow_ctrl::cmds::Cmd, messaging::Peers, Node, Proposal, Result};
+
+use sn_interface::{
+    messaging::system::{NodeEvent, SystemMsg},
+    types::PublicKey,
+};
+
 use std::{collections::BTreeSet, net::SocketAddr};
 use xor_name::XorName;
 
@@ -52,4 +58,26 @@ impl Node {
         }
         Ok(result)
     }
+
+    /// Asks the other elders of our section to vote us out, as we're shutting down.
+    /// Returns `None` if there are no other elders to do so.
+    pub(crate) fn announce_leaving(&self) -> Option<Cmd> {
+        let our_name = self.info().name();
+        let elders: BTreeSet<_> = self
+            .network_knowledge
+            .elders()
+            .into_iter()
+            .filter(|peer| peer.name() != our_name)
+            .collect();
+
+        if elders.is_empty() {
+            return None;
+        }
+
+        info!("Asking our elders to vote us out of the section");
+        let msg = SystemMsg::NodeEvent(NodeEvent::LeavingSection {
+            node_id: PublicKey::from(self.keypair.public),
+        });
+        Some(self.send_system_msg(msg, Peers::Multiple(elders)))
+    }
 }
@@ -56,7 +56,7 @@ impl Capacity {
     }
 
     /// Storage levels of nodes in the section.
-    pub(super) fn levels(&self) -> BTreeMap<XorName, StorageLevel> {
+    pub(crate) fn levels(&self) -> BTreeMap<XorName, StorageLevel> {
         let mut map = BTreeMap::new();
         for (name, level) in self.adult_levels.iter() {
             let _prev = map.insert(*name, *level);
@@ -8,5 +8,6 @@
 
 mod capacity;
 mod records;
+mod subscriptions;
 
 pub(crate) use self::capacity::{Capacity, MIN_LEVEL_WHEN_FULL};
@@ -7,7 +7,7 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use crate::node::{
-    error::convert_to_error_msg, messaging::Peers, Cmd, Error, Node, Prefix, Result,
+    error::convert_to_error_msg, messaging::Peers, Cmd, DataEvent, Error, Node, Prefix, Result,
     MAX_WAITING_PEERS_PER_QUERY,
 };
 
@@ -17,7 +17,7 @@ use sn_interface::messaging::Traceroute;
 use sn_interface::{
     data_copy_count,
     messaging::{
-        data::{CmdError, DataQuery, MetadataExchange, StorageLevel},
+        data::{CmdError, DataQuery, DataQueryVariant, MetadataExchange, StorageLevel},
         system::{NodeCmd, NodeQuery, SystemMsg},
         AuthorityProof, EndUser, MsgId, ServiceAuth,
     },
@@ -25,7 +25,10 @@ use sn_interface::{
 };
 
 use itertools::Itertools;
-use std::{cmp::Ordering, collections::BTreeSet};
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+};
 use tracing::info;
 use xor_name::XorName;
 
@@ -42,6 +45,10 @@ impl Node {
             data.name(),
             &targets,
         );
+        self.send_data_event(DataEvent::ReplicationSent {
+            address: data.address(),
+            recipients: targets.iter().map(Peer::name).collect(),
+        });
 
         self.trace_system_msg(
             SystemMsg::NodeCmd(NodeCmd::ReplicateData(vec![data])),
@@ -59,6 +66,33 @@ impl Node {
         source_client: Peer,
         #[cfg(feature = "traceroute")] mut traceroute: Traceroute,
     ) -> Result<Vec<Cmd>> {
+        if let Err(error) = query.variant.check_batch_size() {
+            warn!("Rejecting query {msg_id:?} from {source_client:?}: {error}");
+
+            #[cfg(feature = "traceroute")]
+            traceroute.0.push(self.identity());
+
+            return Ok(vec![self.send_cmd_error_response(
+                CmdError::Data(error),
+                source_client,
+                msg_id,
+                #[cfg(feature = "traceroute")]
+                traceroute,
+            )]);
+        }
+
+        if let DataQueryVariant::Batch(queries) = query.variant {
+            return self.read_batch_from_adults(
+                queries,
+                query.adult_index,
+                msg_id,
+                auth,
+                source_client,
+                #[cfg(feature = "traceroute")]
+                traceroute,
+            );
+        }
+
         let address = query.variant.address();
         let operation_id = query.variant.operation_id()?;
         trace!(
@@ -134,6 +168,111 @@ impl Node {
         Ok(cmds)
     }
 
+    // Forwards each query of a batch to the nth adult holding its data, as a single
+    // batch of queries per adult, conflating the queries already pending
+    fn read_batch_from_adults(
+        &self,
+        queries: Vec<DataQueryVariant>,
+        adult_index: usize,
+        msg_id: MsgId,
+        auth: AuthorityProof<ServiceAuth>,
+        source_client: Peer,
+        #[cfg(feature = "traceroute")] mut traceroute: Traceroute,
+    ) -> Result<Vec<Cmd>> {
+        trace!(
+            "{:?} preparing to query adults for a batch of {} queries",
+            LogMarker::DataQueryReceviedAtElder,
+            queries.len()
+        );
+
+        let mut cmds = vec![];
+        let mut batches: BTreeMap<Peer, Vec<DataQueryVariant>> = BTreeMap::new();
+        let mut any_target = false;
+        for query in queries {
+            if matches!(query, DataQueryVariant::Batch(_)) {
+                warn!("Ignoring a batch of queries nested in the batch {msg_id:?}");
+                continue;
+            }
+            let operation_id = query.operation_id()?;
+
+            // Query only the nth adult
+            let target = if let Some(peer) = self
+                .target_data_holders_including_full(&query.dst_name())
+                .into_iter()
+                .nth(adult_index)
+            {
+                peer
+            } else {
+                debug!("No targets found for {operation_id:?} of {msg_id:?}");
+                continue;
+            };
+            any_target = true;
+
+            // here we conflate adults targetted!!
+            if let Some(peers) = self
+                .pending_data_queries
+                .get(&(operation_id, target.name()))
+            {
+                if peers.len() > MAX_WAITING_PEERS_PER_QUERY {
+                    warn!("Dropping {operation_id:?} of {msg_id:?} from {source_client:?}, there are more than {MAX_WAITING_PEERS_PER_QUERY} waiting already");
+                } else {
+                    cmds.push(Cmd::AddToPendingQueries {
+                        origin: source_client,
+                        operation_id,
+                        target_adult: target.name(),
+                    });
+                }
+                continue;
+            }
+
+            cmds.push(Cmd::AddToPendingQueries {
+                origin: source_client,
+                operation_id,
+                target_adult: target.name(),
+            });
+            cmds.push(Cmd::TrackNodeIssueInDysfunction {
+                name: target.name(),
+                issue: IssueType::PendingRequestOperation(operation_id),
+            });
+            batches.entry(target).or_default().push(query);
+        }
+
+        if !any_target {
+            let error = convert_to_error_msg(Error::NoAdults(self.network_knowledge().prefix()));
+            debug!("No targets found for {msg_id:?}");
+
+            #[cfg(feature = "traceroute")]
+            traceroute.0.push(self.identity());
+
+            return Ok(vec![self.send_cmd_error_response(
+                CmdError::Data(error),
+                source_client,
+                msg_id,
+                #[cfg(feature = "traceroute")]
+                traceroute,
+            )]);
+        }
+
+        let auth = auth.into_inner();
+        for (target, queries) in batches {
+            let msg = SystemMsg::NodeQuery(NodeQuery::Data {
+                query: DataQueryVariant::Batch(queries),
+                auth: auth.clone(),
+                origin: EndUser(source_client.name()),
+                correlation_id: msg_id,
+            });
+
+            cmds.push(self.trace_system_msg(
+                msg,
+                Peers::Single(target),
+                #[cfg(feature = "traceroute")]
+                traceroute.clone(),
+            ));
+        }
+
+        Ok(cmds)
+    }
+
     pub(crate) fn get_metadata_of(&self, prefix: &Prefix) -> MetadataExchange {
         // Load tracked adult_levels
         let adult_levels = self.capacity.levels_matching(*prefix);
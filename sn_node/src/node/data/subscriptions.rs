@@ -0,0 +1,103 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::node::{
+    messaging::{OutgoingMsg, Peers},
+    Cmd, Node, MAX_SUBSCRIBERS_PER_REGISTER, REGISTER_SUBSCRIPTION_TTL,
+};
+
+use sn_interface::{
+    messaging::data::{RegisterCmd, RegisterCmdId, ServiceMsg},
+    types::{Peer, RegisterAddress},
+};
+
+use std::{collections::BTreeSet, time::Instant};
+use xor_name::XorName;
+
+impl Node {
+    /// Subscribes the client to the changes of the register, or renews its subscription.
+    pub(crate) fn add_register_subscriber(&mut self, address: RegisterAddress, client: Peer) {
+        let now = Instant::now();
+        let mut subscribers = self
+            .register_subscribers
+            .remove(&address)
+            .unwrap_or_default();
+        subscribers.retain(|_, expiry| *expiry > now);
+
+        if subscribers.len() >= MAX_SUBSCRIBERS_PER_REGISTER && !subscribers.contains_key(&client) {
+            warn!("Dropping subscription of {client:?} to register {address:?}, there are {MAX_SUBSCRIBERS_PER_REGISTER} subscribers already");
+        } else {
+            trace!("Subscribing {client:?} to the changes of register {address:?}");
+            let _prev = subscribers.insert(client, now + REGISTER_SUBSCRIPTION_TTL);
+        }
+
+        // the whole entry is set again, so it doesn't expire before its latest subscription does
+        let _prev = self.register_subscribers.set(address, subscribers, None);
+    }
+
+    /// Holds the register cmd till an adult reports it stored. Only then was it checked against
+    /// the register's policy, so it's not pushed to the clients watching the register before.
+    pub(crate) fn await_register_cmd_stored(&mut self, cmd: RegisterCmd) {
+        match cmd.register_operation_id() {
+            Ok(op_id) => {
+                let key = XorName::from_content(op_id.as_bytes());
+                let _prev = self.pending_register_updates.set(key, cmd, None);
+            }
+            Err(error) => warn!("Not pushing a register cmd to its subscribers: {error}"),
+        }
+    }
+
+    /// Forms the cmds to push the register cmds an adult reported stored to the clients
+    /// subscribed to their register. Each cmd is pushed once, for the first adult reporting it.
+    pub(crate) fn notify_register_cmds_stored(&mut self, op_ids: Vec<RegisterCmdId>) -> Vec<Cmd> {
+        let stored: Vec<_> = op_ids
+            .iter()
+            .filter_map(|op_id| {
+                let key = XorName::from_content(op_id.as_bytes());
+                self.pending_register_updates.remove(&key)
+            })
+            .collect();
+        stored
+            .iter()
+            .filter_map(|cmd| self.notify_register_subscribers(cmd))
+            .collect()
+    }
+
+    // Forms a cmd to push the register cmd to the clients subscribed to that register, if any.
+    fn notify_register_subscribers(&self, cmd: &RegisterCmd) -> Option<Cmd> {
+        let address = cmd.dst_address();
+        let subscribers = self.live_register_subscribers(&address);
+        if subscribers.is_empty() {
+            return None;
+        }
+
+        trace!(
+            "Pushing a cmd of register {address:?} to {} subscribers",
+            subscribers.len()
+        );
+        Some(Cmd::send_msg(
+            OutgoingMsg::Service(ServiceMsg::RegisterUpdate(cmd.clone())),
+            Peers::Multiple(subscribers),
+        ))
+    }
+
+    /// The clients subscribed to the register, leaving out those whose subscription expired.
+    pub(crate) fn live_register_subscribers(&self, address: &RegisterAddress) -> BTreeSet<Peer> {
+        let now = Instant::now();
+        self.register_subscribers
+            .get(address)
+            .map(|subscribers| {
+                subscribers
+                    .iter()
+                    .filter(|(_, expiry)| **expiry > now)
+                    .map(|(peer, _)| *peer)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
@@ -107,6 +107,9 @@ pub enum Error {
     /// Timeout when trying to join the network
     #[error("Timeout when trying to join the network")]
     JoinTimeout,
+    /// Timeout when trying to leave the section gracefully
+    #[error("Timeout when trying to leave the section gracefully")]
+    LeaveTimeout,
     /// Join occured during section churn and new elders missed it, need to re-join the network
     #[error("Node was removed from the section")]
     RemovedFromSection,
@@ -134,6 +137,10 @@ pub enum Error {
     /// I/O error.
     #[error("I/O error: {0}")]
     Io(#[from] io::Error),
+    /// Metrics endpoint error.
+    #[cfg(feature = "metrics")]
+    #[error("Metrics endpoint error:: {0}")]
+    MetricsEndpoint(#[from] hyper::Error),
     /// JSON serialisation error.
     #[error("JSON serialisation error:: {0}")]
     JsonSerialisation(#[from] serde_json::Error),
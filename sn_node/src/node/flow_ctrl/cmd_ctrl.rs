@@ -50,6 +50,7 @@ pub(crate) struct CmdCtrl {
     stopped: bool,
     pub(crate) dispatcher: Arc<Dispatcher>,
     id_counter: usize,
+    pending_cmds: Arc<AtomicUsize>,
 }
 
 impl CmdCtrl {
@@ -61,6 +62,7 @@ impl CmdCtrl {
             stopped: false,
             dispatcher: Arc::new(dispatcher),
             id_counter: 0,
+            pending_cmds: Arc::new(AtomicUsize::new(0)),
         }
     }
 
@@ -72,6 +74,11 @@ impl CmdCtrl {
         self.push_internal(cmd, None).await
     }
 
+    /// Number of cmds waiting in the queue, which can be read from outside the cmd loop
+    pub(crate) fn pending_cmds(&self) -> Arc<AtomicUsize> {
+        self.pending_cmds.clone()
+    }
+
     /// Does the cmd_queue contain _anything_
     pub(crate) fn has_items_queued(&self) -> bool {
         !self.cmd_queue.is_empty()
@@ -83,6 +90,7 @@ impl CmdCtrl {
     pub(crate) async fn stop(mut self) {
         self.stopped = true;
         self.cmd_queue.clear();
+        self.pending_cmds.store(0, Ordering::Relaxed);
     }
 
     async fn push_internal(&mut self, cmd: Cmd, parent_id: Option<usize>) -> Result<()> {
@@ -97,6 +105,8 @@ impl CmdCtrl {
 
         let prio = job.priority();
         let _ = self.cmd_queue.push(job, prio);
+        self.pending_cmds
+            .store(self.cmd_queue.len(), Ordering::Relaxed);
         Ok(())
     }
 
@@ -107,7 +117,10 @@ impl CmdCtrl {
 
     /// Get the next Cmd going off of priority
     pub(crate) fn next_cmd(&mut self) -> Option<CmdJob> {
-        self.cmd_queue.pop().map(|(job, _prio)| job)
+        let next = self.cmd_queue.pop().map(|(job, _prio)| job);
+        self.pending_cmds
+            .store(self.cmd_queue.len(), Ordering::Relaxed);
+        next
     }
 
     /// Wait if required by the cmd rate monitoring
@@ -162,7 +175,15 @@ impl CmdCtrl {
 
         let dispatcher = self.dispatcher.clone();
         let _ = tokio::task::spawn_local(async move {
-            match dispatcher.process_cmd(cmd).await {
+            #[cfg(feature = "metrics")]
+            let started = Instant::now();
+
+            let result = dispatcher.process_cmd(cmd).await;
+
+            #[cfg(feature = "metrics")]
+            crate::node::metrics::record_cmd_processing_time(&cmd_string, started.elapsed());
+
+            match result {
                 Ok(cmds) => {
                     monitoring.increment_cmds().await;
 
@@ -17,12 +17,12 @@ use sn_dysfunction::IssueType;
 use sn_interface::messaging::Traceroute;
 use sn_interface::{
     messaging::{
-        data::{OperationId, ServiceMsg},
+        data::{OperationId, RegisterCmd, ServiceMsg},
         system::{DkgFailureSigSet, KeyedSig, NodeState, SectionAuth, SystemMsg},
         AuthorityProof, MsgId, NodeMsgAuthority, ServiceAuth, WireMsg,
     },
     network_knowledge::{SectionAuthorityProvider, SectionKeyShare},
-    types::{Peer, ReplicatedDataAddress},
+    types::{Peer, RegisterAddress, ReplicatedDataAddress},
 };
 
 use bytes::Bytes;
@@ -132,6 +132,14 @@ pub(crate) enum Cmd {
         origin: Peer,
         target_adult: XorName,
     },
+    /// Subscribes the client to the changes of the register, or renews its subscription.
+    AddRegisterSubscriber {
+        address: RegisterAddress,
+        origin: Peer,
+    },
+    /// Holds the register cmd till an adult reports it stored, to then push it to the clients
+    /// watching the register.
+    AwaitRegisterCmdStored(RegisterCmd),
     HandleValidSystemMsg {
         msg_id: MsgId,
         msg: SystemMsg,
@@ -250,6 +258,8 @@ impl Cmd {
             Comm(_) => 7,
 
             AddToPendingQueries { .. } => 6,
+            AddRegisterSubscriber { .. } => 6,
+            AwaitRegisterCmdStored(_) => 6,
 
             // See [`MsgType`] for the priority constants and the range of possible values.
             HandleValidSystemMsg { msg, .. } => msg.priority(),
@@ -300,6 +310,8 @@ impl fmt::Display for Cmd {
             }
             Cmd::ProposeVoteNodesOffline(_) => write!(f, "ProposeOffline"),
             Cmd::AddToPendingQueries { .. } => write!(f, "AddToPendingQueries"),
+            Cmd::AddRegisterSubscriber { .. } => write!(f, "AddRegisterSubscriber"),
+            Cmd::AwaitRegisterCmdStored(_) => write!(f, "AwaitRegisterCmdStored"),
             Cmd::Comm(comm) => write!(f, "Comm({:?})", comm),
         }
     }
@@ -71,6 +71,9 @@ impl Dispatcher {
                 #[cfg(feature = "traceroute")]
                 traceroute,
             } => {
+                #[cfg(feature = "metrics")]
+                let ae_kind = crate::node::metrics::ae_kind(&msg);
+
                 let peer_msgs = {
                     let node = self.node.read().await;
                     into_wire_msgs(
@@ -83,6 +86,11 @@ impl Dispatcher {
                     )?
                 };
 
+                #[cfg(feature = "metrics")]
+                if let Some(kind) = ae_kind {
+                    crate::node::metrics::record_ae_msgs_sent(kind, peer_msgs.len());
+                }
+
                 let tasks = peer_msgs
                     .into_iter()
                     .map(|(peer, msg)| self.comm.send(peer, msg));
@@ -133,6 +141,16 @@ impl Dispatcher {
 
                 Ok(vec![])
             }
+            Cmd::AddRegisterSubscriber { address, origin } => {
+                let mut node = self.node.write().await;
+                node.add_register_subscriber(address, origin);
+                Ok(vec![])
+            }
+            Cmd::AwaitRegisterCmdStored(cmd) => {
+                let mut node = self.node.write().await;
+                node.await_register_cmd_stored(cmd);
+                Ok(vec![])
+            }
             Cmd::ValidateMsg {
                 origin,
                 wire_msg,
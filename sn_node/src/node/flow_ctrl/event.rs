@@ -6,8 +6,13 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use sn_interface::messaging::{
-    data::ServiceMsg, system::SystemMsg, AuthorityProof, Dst, EndUser, MsgId, NodeAuth, ServiceAuth,
+use sn_interface::{
+    messaging::{
+        data::{ServiceMsg, StorageLevel},
+        system::SystemMsg,
+        AuthorityProof, Dst, EndUser, MsgId, NodeAuth, ServiceAuth,
+    },
+    types::{ChunkAddress, RegisterAddress, ReplicatedDataAddress, SpentbookAddress},
 };
 
 use bls::PublicKey as BlsPublicKey;
@@ -28,8 +33,6 @@ use xor_name::{Prefix, XorName};
 #[allow(clippy::large_enum_variant)]
 #[derive(custom_debug::Debug)]
 pub enum Event {
-    ///
-    Data(DataEvent),
     ///
     Messaging(MessagingEvent),
     ///
@@ -40,9 +43,66 @@ pub enum Event {
 
 /// Informing on data related changes.
 ///
-/// Currently not used.
-#[derive(Debug)]
-pub enum DataEvent {}
+/// These are not sent to the event receiver as an `Event`, they can be subscribed to
+/// by any number of subscribers instead.
+#[derive(Debug, Clone)]
+pub enum DataEvent {
+    /// A chunk was stored by this node.
+    ChunkStored {
+        /// Address of the chunk
+        address: ChunkAddress,
+    },
+    /// A chunk held by this node was read, to respond to a query.
+    ChunkServed {
+        /// Address of the chunk
+        address: ChunkAddress,
+    },
+    /// A register op was applied to a register held by this node.
+    RegisterOpApplied {
+        /// Address of the register
+        address: RegisterAddress,
+    },
+    /// A spend was recorded in a spentbook held by this node.
+    SpendRecorded {
+        /// Address of the spentbook
+        address: SpentbookAddress,
+    },
+    /// Data was sent to other nodes, for them to hold a replica of it.
+    ReplicationSent {
+        /// Address of the data
+        address: ReplicatedDataAddress,
+        /// Names of the nodes the data was sent to
+        recipients: BTreeSet<XorName>,
+    },
+    /// Data was received from another node, for this node to hold a replica of it.
+    ReplicationReceived {
+        /// Address of the data
+        address: ReplicatedDataAddress,
+        /// Name of the node which sent the data
+        sender: XorName,
+    },
+    /// The storage used by this node has passed a new level.
+    StorageLevelChanged {
+        /// The level passed
+        level: StorageLevel,
+    },
+    /// Data was removed by this node, as it's no longer responsible for it after a split.
+    DataEvicted {
+        /// Address of the data
+        address: ReplicatedDataAddress,
+    },
+}
+
+impl DataEvent {
+    /// The event for the data at the given address having been stored by this node.
+    pub(crate) fn stored(address: ReplicatedDataAddress) -> Self {
+        match address {
+            ReplicatedDataAddress::Chunk(address) => Self::ChunkStored { address },
+            ReplicatedDataAddress::Register(address) => Self::RegisterOpApplied { address },
+            ReplicatedDataAddress::Spentbook(address) => Self::SpendRecorded { address },
+        }
+    }
+}
 
 /// Informing on incoming msgs.
 ///
@@ -187,7 +247,6 @@ pub enum MembershipEvent {
 impl std::fmt::Display for Event {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Data(e) => write!(f, "{:?}", e),
             Self::Messaging(e) => write!(f, "{:?}", e),
             Self::Membership(e) => write!(f, "{:?}", e),
             Self::CmdProcessing(e) => write!(f, "{}", e),
@@ -6,13 +6,23 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::node::Event;
-use tokio::sync::mpsc;
+use crate::node::{DataEvent, Event};
+use tokio::sync::{
+    broadcast::{self, error::RecvError},
+    mpsc,
+};
+
+// Data events a subscriber can fall behind by, before it starts missing some.
+const DATA_EVENT_CHANNEL_SIZE: usize = 1000;
 
 pub(crate) fn new(channel_size: usize) -> (EventSender, EventReceiver) {
     let (event_sender, event_receiver) = mpsc::channel(channel_size);
+    let (data_event_sender, _) = broadcast::channel(DATA_EVENT_CHANNEL_SIZE);
     (
-        EventSender { event_sender },
+        EventSender {
+            event_sender,
+            data_event_sender,
+        },
         EventReceiver { event_receiver },
     )
 }
@@ -21,6 +31,7 @@ pub(crate) fn new(channel_size: usize) -> (EventSender, EventReceiver) {
 #[derive(Clone)]
 pub(crate) struct EventSender {
     event_sender: mpsc::Sender<Event>,
+    data_event_sender: broadcast::Sender<DataEvent>,
 }
 
 impl EventSender {
@@ -31,6 +42,20 @@ impl EventSender {
             error!("Event receiver has been closed");
         }
     }
+
+    /// Sends the data event to its subscribers.
+    /// Data events are frequent, so unlike other events they never hold up the node.
+    pub(crate) fn send_data_event(&self, event: DataEvent) {
+        // this only fails if there are no subscribers
+        let _ = self.data_event_sender.send(event);
+    }
+
+    /// Subscribes to the data events sent from now on.
+    pub(crate) fn subscribe_data_events(&self) -> DataEventSubscription {
+        DataEventSubscription {
+            receiver: self.data_event_sender.subscribe(),
+        }
+    }
 }
 
 /// Receiver of events
@@ -54,3 +79,55 @@ impl EventReceiver {
         }
     }
 }
+
+/// Subscription to the data events of a node
+#[allow(missing_debug_implementations)]
+pub struct DataEventSubscription {
+    receiver: broadcast::Receiver<DataEvent>,
+}
+
+impl DataEventSubscription {
+    /// Waits for, and then returns next data event.
+    /// Events missed by falling too far behind are skipped.
+    /// Returns None once the node has stopped.
+    pub async fn next(&mut self) -> Option<DataEvent> {
+        loop {
+            match self.receiver.recv().await {
+                Ok(event) => return Some(event),
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!("Data event subscription fell behind, {skipped} events were skipped");
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::new;
+    use crate::node::DataEvent;
+
+    use sn_interface::types::ChunkAddress;
+    use xor_name::XorName;
+
+    #[tokio::test]
+    async fn data_events_only_reach_subscribers() {
+        let (sender, mut receiver) = new(1);
+        let mut subscription = sender.subscribe_data_events();
+        let address = ChunkAddress(XorName::random(&mut rand::thread_rng()));
+
+        sender.send_data_event(DataEvent::ChunkStored { address });
+        sender.send_data_event(DataEvent::ChunkServed { address });
+
+        assert!(matches!(
+            subscription.next().await,
+            Some(DataEvent::ChunkStored { .. })
+        ));
+        assert!(matches!(
+            subscription.next().await,
+            Some(DataEvent::ChunkServed { .. })
+        ));
+        assert!(receiver.try_next().is_none());
+    }
+}
@@ -44,6 +44,7 @@ pub(crate) struct FlowCtrl {
     incoming_cmds_from_apis: mpsc::Receiver<(Cmd, Option<usize>)>,
     cmd_sender_channel: mpsc::Sender<(Cmd, Option<usize>)>,
     outgoing_node_event_sender: EventSender,
+    chunk_scrub_batch_size: usize,
 }
 
 impl FlowCtrl {
@@ -51,6 +52,7 @@ impl FlowCtrl {
         cmd_ctrl: CmdCtrl,
         incoming_msg_events: mpsc::Receiver<MsgEvent>,
         outgoing_node_event_sender: EventSender,
+        chunk_scrub_batch_size: usize,
     ) -> (Self, mpsc::Sender<(Cmd, Option<usize>)>) {
         let node = cmd_ctrl.node();
         let (cmd_sender_channel, incoming_cmds_from_apis) = mpsc::channel(100);
@@ -63,6 +65,7 @@ impl FlowCtrl {
                 incoming_cmds_from_apis,
                 cmd_sender_channel: cmd_sender_channel.clone(),
                 outgoing_node_event_sender,
+                chunk_scrub_batch_size,
             },
             cmd_sender_channel,
         )
@@ -134,16 +137,28 @@ impl FlowCtrl {
     /// This loop drives the periodic events internal to the node.
     pub(crate) async fn process_messages_and_periodic_checks(mut self) {
         debug!("Starting internal processing...");
+        if self.chunk_scrub_batch_size > 0 {
+            Self::start_chunk_scrubbing(
+                self.node.clone(),
+                self.cmd_sender_channel.clone(),
+                self.chunk_scrub_batch_size,
+            );
+        }
+
         let mut last_probe = Instant::now();
         let mut last_section_probe = Instant::now();
         let mut last_adult_health_check = Instant::now();
         let mut last_elder_health_check = Instant::now();
         let mut last_vote_check = Instant::now();
         let mut last_data_batch_check = Instant::now();
+        let mut last_data_eviction_check = Instant::now();
         let mut last_link_cleanup = Instant::now();
         let mut last_dysfunction_check = Instant::now();
+        let mut last_storage_compaction = Instant::now();
         #[cfg(feature = "back-pressure")]
         let mut last_backpressure_check = Instant::now();
+        #[cfg(feature = "metrics")]
+        let mut last_metrics_update = Instant::now();
 
         // the internal process loop
         loop {
@@ -183,8 +198,12 @@ impl FlowCtrl {
             self.enqueue_cmds_for_standard_periodic_checks(
                 &mut last_link_cleanup,
                 &mut last_data_batch_check,
+                &mut last_data_eviction_check,
+                &mut last_storage_compaction,
                 #[cfg(feature = "back-pressure")]
                 &mut last_backpressure_check,
+                #[cfg(feature = "metrics")]
+                &mut last_metrics_update,
             )
             .await;
 
@@ -10,7 +10,10 @@
 use super::CmdCtrl;
 use super::FlowCtrl;
 
-use crate::node::{flow_ctrl::cmds::Cmd, messaging::Peers, Node, Result};
+use crate::{
+    node::{flow_ctrl::cmds::Cmd, messaging::Peers, DataEvent, Node, Result},
+    storage::ScrubCursor,
+};
 
 use ed25519_dalek::Signer;
 #[cfg(feature = "traceroute")]
@@ -22,11 +25,14 @@ use sn_interface::{
         AuthorityProof, MsgId, ServiceAuth, WireMsg,
     },
     types::log_markers::LogMarker,
-    types::{ChunkAddress, PublicKey, Signature},
+    types::{ChunkAddress, Peer, PublicKey, Signature},
 };
 
 use std::{collections::BTreeSet, sync::Arc, time::Duration};
-use tokio::{sync::RwLock, time::Instant};
+use tokio::{
+    sync::{mpsc, RwLock},
+    time::Instant,
+};
 
 const PROBE_INTERVAL: Duration = Duration::from_secs(30);
 const MISSING_VOTE_INTERVAL: Duration = Duration::from_secs(5);
@@ -35,11 +41,17 @@ const BACKPRESSURE_INTERVAL: Duration = Duration::from_secs(60);
 const SECTION_PROBE_INTERVAL: Duration = Duration::from_secs(300);
 const LINK_CLEANUP_INTERVAL: Duration = Duration::from_secs(120);
 const DATA_BATCH_INTERVAL: Duration = Duration::from_millis(50);
+const DATA_EVICTION_CHECK_INTERVAL: Duration = Duration::from_secs(10);
 const DYSFUNCTION_CHECK_INTERVAL: Duration = Duration::from_secs(5);
 // 30 adult nodes checked per minute., so each node should be queried 10x in 10 mins
 // Which should hopefully trigger dysfunction if we're not getting responses back
 const ADULT_HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(2);
 const ELDER_HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(3);
+// The number of chunks re-hashed on each round is configurable
+const CHUNK_SCRUB_INTERVAL: Duration = Duration::from_secs(60);
+const STORAGE_COMPACTION_INTERVAL: Duration = Duration::from_secs(600);
+#[cfg(feature = "metrics")]
+const METRICS_UPDATE_INTERVAL: Duration = Duration::from_secs(15);
 
 impl FlowCtrl {
     /// Periodic tasks run for elders and adults alike
@@ -47,7 +59,10 @@ impl FlowCtrl {
         &mut self,
         last_link_cleanup: &mut Instant,
         last_data_batch_check: &mut Instant,
+        last_data_eviction_check: &mut Instant,
+        last_storage_compaction: &mut Instant,
         #[cfg(feature = "back-pressure")] last_backpressure_check: &mut Instant,
+        #[cfg(feature = "metrics")] last_metrics_update: &mut Instant,
     ) {
         let now = Instant::now();
         let mut cmds = vec![];
@@ -83,6 +98,28 @@ impl FlowCtrl {
             }
         }
 
+        if last_data_eviction_check.elapsed() > DATA_EVICTION_CHECK_INTERVAL {
+            *last_data_eviction_check = now;
+            Node::evict_handed_off_data(self.node.clone()).await;
+            // checked along, as the level drops once data is evicted, compacted or removed
+            cmds.extend(Self::report_lowered_storage_level(self.node.clone()).await);
+        }
+
+        if last_storage_compaction.elapsed() > STORAGE_COMPACTION_INTERVAL {
+            *last_storage_compaction = now;
+            Self::compact_storage(self.node.clone()).await;
+        }
+
+        #[cfg(feature = "metrics")]
+        if last_metrics_update.elapsed() > METRICS_UPDATE_INTERVAL {
+            *last_metrics_update = now;
+            let pending_cmds = self
+                .cmd_ctrl
+                .pending_cmds()
+                .load(std::sync::atomic::Ordering::Relaxed);
+            crate::node::metrics::update_node_gauges(self.node.clone(), pending_cmds).await;
+        }
+
         for cmd in cmds {
             // dont use sender here incase channel gets full
             if let Err(error) = self.fire_and_forget(cmd).await {
@@ -349,6 +386,10 @@ impl FlowCtrl {
 
                 let msg = SystemMsg::NodeCmd(NodeCmd::ReplicateData(vec![data_to_send]));
                 let node = node.read().await;
+                node.send_data_event(DataEvent::ReplicationSent {
+                    address,
+                    recipients: data_recipients.iter().map(Peer::name).collect(),
+                });
                 return Ok(Some(
                     node.send_system_msg(msg, Peers::Multiple(data_recipients)),
                 ));
@@ -358,6 +399,88 @@ impl FlowCtrl {
         Ok(None)
     }
 
+    /// Reports our storage level to our elders if it was lowered, for them to know once
+    /// we're no longer full
+    async fn report_lowered_storage_level(node: Arc<RwLock<Node>>) -> Vec<Cmd> {
+        let node = node.read().await;
+        let level = node.data_storage.lowered_storage_level();
+        match node.record_storage_level_if_any(
+            level,
+            #[cfg(feature = "traceroute")]
+            Traceroute(vec![]),
+        ) {
+            Ok(cmds) => cmds,
+            Err(error) => {
+                error!("Error reporting our lowered storage level: {error:?}");
+                vec![]
+            }
+        }
+    }
+
+    /// Starts scrubbing the stored chunks in the background, re-hashing the next `count` chunks
+    /// every round, until the node stops.
+    pub(super) fn start_chunk_scrubbing(
+        node: Arc<RwLock<Node>>,
+        cmd_sender: mpsc::Sender<(Cmd, Option<usize>)>,
+        count: usize,
+    ) {
+        let _handle = tokio::task::spawn_local(async move {
+            let mut cursor = ScrubCursor::default();
+            let mut interval = tokio::time::interval(CHUNK_SCRUB_INTERVAL);
+            // the first tick completes right away
+            let _ = interval.tick().await;
+            while !cmd_sender.is_closed() {
+                let _ = interval.tick().await;
+                if let Some(cmd) = Self::scrub_chunks(node.clone(), &mut cursor, count).await {
+                    if let Err(error) = cmd_sender.send((cmd, None)).await {
+                        error!("Error sending cmd for the corrupt chunks found: {error:?}");
+                    }
+                }
+            }
+            debug!("Chunk scrubbing stopped");
+        });
+    }
+
+    /// Re-hashes the next batch of stored chunks, quarantining any corrupt one. If any was found,
+    /// returns the cmd sending our data list to the nodes holding the same data, so they send
+    /// fresh copies back.
+    async fn scrub_chunks(
+        node: Arc<RwLock<Node>>,
+        cursor: &mut ScrubCursor,
+        count: usize,
+    ) -> Option<Cmd> {
+        debug!("Scrubbing up to {count} stored chunks");
+        // the storage is cloned so the node is not locked while chunks are read from disk
+        let data_storage = node.read().await.data_storage.clone();
+        let quarantined = data_storage.scrub_chunks(cursor, count).await;
+        if cursor.is_pass_done() {
+            debug!("All stored chunks were scrubbed, starting over on the next round");
+        }
+
+        if quarantined.is_empty() {
+            return None;
+        }
+
+        info!(
+            "{} corrupt chunks were quarantined, requesting fresh copies: {quarantined:?}",
+            quarantined.len()
+        );
+        Some(node.read().await.ask_for_any_new_data().await)
+    }
+
+    /// Reclaims the space taken by data removed from storage, in the background
+    /// since the data still needed is copied over.
+    async fn compact_storage(node: Arc<RwLock<Node>>) {
+        let data_storage = node.read().await.data_storage.clone();
+        let _handle = tokio::task::spawn_local(async move {
+            match data_storage.compact().await {
+                Ok(0) => {}
+                Ok(freed) => info!("Storage compaction freed {freed} bytes"),
+                Err(error) => error!("Error compacting storage: {error:?}"),
+            }
+        });
+    }
+
     async fn check_for_dysfunction(node: Arc<RwLock<Node>>) -> Vec<Cmd> {
         info!("Performing dysfunction checking");
         let mut cmds = vec![];
@@ -397,6 +520,8 @@ impl FlowCtrl {
 
         if let Some(load_report) = cmd_ctrl.dispatcher.comm().tolerated_msgs_per_s().await {
             trace!("New BackPressure report to disseminate: {:?}", load_report);
+            #[cfg(feature = "metrics")]
+            crate::node::metrics::record_tolerated_msgs_per_s(load_report);
             // TODO: use comms to send report to anyone connected? (can we ID end users there?)
             let cmd = the_node.read().await.send_system_msg(
                 SystemMsg::BackPressure(load_report),
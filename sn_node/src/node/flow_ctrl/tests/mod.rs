@@ -21,7 +21,7 @@ use crate::node::{
     Cmd, Error, Event, MembershipEvent, Node, Proposal, RateLimits, Result as RoutingResult,
     RESOURCE_PROOF_DATA_SIZE, RESOURCE_PROOF_DIFFICULTY,
 };
-use crate::storage::UsedSpace;
+use crate::storage::{StorageBackendKind, UsedSpace};
 
 use cmd_utils::{handle_online_cmd, run_and_collect_cmds, wrap_service_msg_for_handling};
 use sn_consensus::Decision;
@@ -32,20 +32,29 @@ use sn_interface::messaging::Traceroute;
 use sn_interface::{
     elder_count, init_logger,
     messaging::{
-        data::{DataCmd, Error as MessagingDataError, RegisterCmd, ServiceMsg, SpentbookCmd},
+        data::{
+            DataCmd, EditRegister, Error as MessagingDataError, RegisterCmd, ServiceMsg,
+            SignedRegisterEdit, SpentbookCmd,
+        },
         system::{
             JoinAsRelocatedRequest, JoinRequest, JoinResponse, KeyedSig, MembershipState,
             NodeMsgAuthorityUtils, NodeState as NodeStateMsg, RelocateDetails, ResourceProof,
             SectionAuth, SystemMsg,
         },
-        Dst, MsgId, MsgType, SectionAuth as MsgKindSectionAuth, WireMsg,
+        Dst, MsgId, MsgType, SectionAuth as MsgKindSectionAuth, ServiceAuth, WireMsg,
     },
     network_knowledge::{
         recommended_section_size, supermajority, test_utils::*, NetworkKnowledge, NodeInfo,
         NodeState, SectionAuthorityProvider, SectionKeyShare, FIRST_SECTION_MAX_AGE,
         FIRST_SECTION_MIN_AGE, MIN_ADULT_AGE,
     },
-    types::{keyed_signed, keys::ed25519, Peer, PublicKey, ReplicatedData, SecretKeySet},
+    types::{
+        keyed_signed,
+        keys::ed25519,
+        register::{Register, User},
+        utils::random_bytes,
+        Chunk, Keypair, Peer, PublicKey, ReplicatedData, ReplicatedDataAddress, SecretKeySet,
+    },
 };
 
 use assert_matches::assert_matches;
@@ -63,6 +72,7 @@ use std::{
     ops::Deref,
     path::Path,
     sync::Arc,
+    time::Instant,
 };
 use tempfile::tempdir;
 use tokio::{
@@ -604,6 +614,7 @@ async fn ae_msg_from_the_future_is_handled() -> Result<()> {
                 event_sender,
                 UsedSpace::new(max_capacity),
                 root_storage_dir,
+                StorageBackendKind::default(),
             )
             .await?;
 
@@ -814,6 +825,7 @@ async fn relocation(relocated_peer_role: RelocatedPeerRole) -> Result<()> {
                 event_channel::new(network_utils::TEST_EVENT_CHANNEL_SIZE).0,
                 UsedSpace::new(max_capacity),
                 root_storage_dir,
+                StorageBackendKind::default(),
             )
             .await?;
             let dispatcher = Dispatcher::new(Arc::new(RwLock::new(node)), comm);
@@ -884,6 +896,7 @@ async fn msg_to_self() -> Result<()> {
             event_sender,
             UsedSpace::new(max_capacity),
             root_storage_dir,
+            StorageBackendKind::default(),
             genesis_sk_set,
         )
         .await?;
@@ -997,6 +1010,7 @@ async fn handle_elders_update() -> Result<()> {
             event_sender,
             UsedSpace::new(max_capacity),
             root_storage_dir,
+            StorageBackendKind::default(),
         )
         .await?;
 
@@ -1130,6 +1144,7 @@ async fn handle_demote_during_split() -> Result<()> {
                 event_sender,
                 UsedSpace::new(max_capacity),
                 root_storage_dir,
+                StorageBackendKind::default(),
             )
             .await?;
 
@@ -1529,3 +1544,224 @@ async fn spentbook_spend_with_random_key_image_should_return_no_commands() -> Re
         })
         .await
 }
+
+#[tokio::test]
+async fn data_outside_our_prefix_is_handed_off_before_eviction() -> Result<()> {
+    let local = tokio::task::LocalSet::new();
+    local
+        .run_until(async move {
+            init_logger();
+            let prefix = Prefix::default().pushed(false);
+            let (dispatcher, section, _, _) =
+                network_utils::TestNodeBuilder::new(prefix, elder_count())
+                    .data_copy_count(2)
+                    .build()
+                    .await?;
+            let node_lock = dispatcher.node();
+            let mut node = node_lock.write().await;
+
+            // we hold a chunk on each side of our prefix after the split
+            let chunk_with = |inside: bool| {
+                iter::repeat_with(|| Chunk::new(random_bytes(1024)))
+                    .find(|chunk| prefix.matches(chunk.name()) == inside)
+                    .ok_or_else(|| eyre!("A chunk should have been generated"))
+            };
+            let (inside, outside) = (chunk_with(true)?, chunk_with(false)?);
+            let section_pk = PublicKey::Bls(section.section_key());
+            let keypair = Keypair::Ed25519(node.keypair.clone());
+            for chunk in [&inside, &outside] {
+                let _level = node
+                    .data_storage
+                    .store(
+                        &ReplicatedData::Chunk(chunk.clone()),
+                        section_pk,
+                        keypair.clone(),
+                    )
+                    .await?;
+            }
+            let inside = ReplicatedDataAddress::Chunk(*inside.address());
+            let outside = ReplicatedDataAddress::Chunk(*outside.address());
+
+            // the chunk outside of our prefix is handed off to the closest adults
+            // of our sibling section, which was part of ours before the split
+            let sibling_adults: BTreeSet<_> = iter::repeat_with(|| {
+                network_utils::create_peer_in_prefix(&prefix.sibling(), MIN_ADULT_AGE)
+            })
+            .take(3)
+            .collect();
+            let queued = node
+                .hand_off_data_outside_our_prefix(sibling_adults.clone())
+                .await;
+            assert_eq!(queued, 1);
+            assert!(!node
+                .pending_data_to_replicate_to_peers
+                .contains_key(&inside));
+            let recipients = node
+                .pending_data_to_replicate_to_peers
+                .get(&outside)
+                .ok_or_else(|| eyre!("The chunk outside of our prefix should be queued"))?;
+            assert_eq!(recipients.len(), 2);
+            assert!(recipients.is_subset(&sibling_adults));
+
+            let recipients: Vec<_> = recipients.iter().map(Peer::name).collect();
+            drop(node);
+
+            // it's not evicted before being sent, nor once only some of its holders acknowledged it
+            Node::evict_handed_off_data(node_lock.clone()).await;
+            let mut node = node_lock.write().await;
+            assert!(node.data_storage.keys().await.contains(&outside));
+            let _recipients = node.pending_data_to_replicate_to_peers.remove(&outside);
+            node.record_data_handoff_acks(recipients[0], vec![outside]);
+            drop(node);
+            Node::evict_handed_off_data(node_lock.clone()).await;
+            let mut node = node_lock.write().await;
+            assert!(node.data_storage.keys().await.contains(&outside));
+
+            // acks from peers it wasn't sent to don't count
+            let stranger =
+                network_utils::create_peer_in_prefix(&prefix.sibling(), MIN_ADULT_AGE).name();
+            node.record_data_handoff_acks(stranger, vec![outside]);
+            assert!(node.data_handed_off.contains_key(&outside));
+
+            // it's evicted once all its holders acknowledged storing it
+            node.record_data_handoff_acks(recipients[1], vec![outside]);
+            drop(node);
+            Node::evict_handed_off_data(node_lock.clone()).await;
+            let node = node_lock.read().await;
+            assert_eq!(node.data_storage.keys().await, vec![inside]);
+            assert!(node.data_handed_off.is_empty());
+
+            Result::<()>::Ok(())
+        })
+        .await
+}
+
+#[tokio::test]
+async fn data_is_handed_off_to_the_next_closest_adults_on_leaving() -> Result<()> {
+    let local = tokio::task::LocalSet::new();
+    local
+        .run_until(async move {
+            init_logger();
+            let (sap, nodes, sk_set) = random_sap(Prefix::default(), elder_count(), 4, None);
+            let (section, _) = network_utils::create_section(&sk_set, &sap)?;
+            let adults = section.adults();
+            let data_copy_count = 2;
+
+            // the first nodes are the elders, the others the adults
+            for (node_info, is_elder) in [(&nodes[0], true), (&nodes[elder_count()], false)] {
+                let (dispatcher, _, _, _) =
+                    network_utils::TestNodeBuilder::new(Prefix::default(), elder_count())
+                        .section(section.clone(), sk_set.clone(), node_info.clone())
+                        .data_copy_count(data_copy_count)
+                        .build()
+                        .await?;
+                let node = dispatcher.node();
+                let mut node = node.write().await;
+                assert_eq!(node.is_elder(), is_elder);
+
+                let section_pk = PublicKey::Bls(section.section_key());
+                let keypair = Keypair::Ed25519(node.keypair.clone());
+                let mut chunks = vec![];
+                for _ in 0..10 {
+                    let chunk = Chunk::new(random_bytes(1024));
+                    let _level = node
+                        .data_storage
+                        .store(
+                            &ReplicatedData::Chunk(chunk.clone()),
+                            section_pk,
+                            keypair.clone(),
+                        )
+                        .await?;
+                    chunks.push(chunk);
+                }
+
+                let queued = node.enqueue_data_handoff().await;
+                assert_eq!(queued, node.pending_data_to_replicate_to_peers.len());
+
+                // only the data we hold as one of its closest adults is handed off, to the
+                // adult which becomes one of the closest once we've left
+                let our_name = node_info.name();
+                for chunk in chunks {
+                    let closest: Vec<_> = adults
+                        .iter()
+                        .sorted_by(|lhs, rhs| chunk.name().cmp_distance(&lhs.name(), &rhs.name()))
+                        .take(data_copy_count + 1)
+                        .collect();
+                    let address = ReplicatedDataAddress::Chunk(*chunk.address());
+                    let recipients = node.pending_data_to_replicate_to_peers.get(&address);
+                    if closest[..data_copy_count]
+                        .iter()
+                        .any(|peer| peer.name() == our_name)
+                    {
+                        assert_eq!(
+                            recipients,
+                            Some(&BTreeSet::from([*closest[data_copy_count]]))
+                        );
+                    } else {
+                        assert_eq!(recipients, None);
+                    }
+                }
+                if is_elder {
+                    assert_eq!(queued, 0);
+                }
+            }
+
+            Result::<()>::Ok(())
+        })
+        .await
+}
+
+#[tokio::test]
+async fn register_cmds_are_pushed_to_subscribers_once_stored() -> Result<()> {
+    let local = tokio::task::LocalSet::new();
+    local
+        .run_until(async move {
+            init_logger();
+            let (dispatcher, _, _, _) =
+                network_utils::TestNodeBuilder::new(Prefix::default(), elder_count())
+                    .build()
+                    .await?;
+            let node = dispatcher.node();
+            let mut node = node.write().await;
+
+            let keypair = Keypair::new_ed25519();
+            let authority = User::Key(keypair.public_key());
+            let mut register = Register::new_owned(authority, rand::random(), 10);
+            let (_, edit) = register.write(b"entry".to_vec(), BTreeSet::new())?;
+            let op = EditRegister {
+                address: *register.address(),
+                edit,
+            };
+            let signature = keypair.sign(&bincode::serialize(&op)?);
+            let cmd = RegisterCmd::Edit(SignedRegisterEdit {
+                op,
+                auth: ServiceAuth {
+                    public_key: keypair.public_key(),
+                    signature,
+                },
+            });
+            let op_id = cmd.register_operation_id()?;
+
+            let client = network_utils::create_peer(MIN_ADULT_AGE);
+            node.add_register_subscriber(*register.address(), client);
+            node.await_register_cmd_stored(cmd.clone());
+
+            // nothing is pushed for cmds no adult reported stored
+            assert!(node
+                .notify_register_cmds_stored(vec!["another op".to_string()])
+                .is_empty());
+
+            let cmds = node.notify_register_cmds_stored(vec![op_id.clone()]);
+            assert_eq!(cmds.len(), 1);
+            assert_eq!(cmds[0].recipients()?, BTreeSet::from([client]));
+            assert_matches!(cmds[0].get_service_msg()?, ServiceMsg::RegisterUpdate(pushed) => {
+                assert_eq!(pushed, cmd);
+            });
+
+            // and the cmd is pushed once, even though the other adults report it stored too
+            assert!(node.notify_register_cmds_stored(vec![op_id]).is_empty());
+
+            Result::<()>::Ok(())
+        })
+        .await
+}
@@ -8,7 +8,7 @@ use crate::node::{
     },
     relocation_check, ChurnId, Node, RateLimits,
 };
-use crate::storage::UsedSpace;
+use crate::storage::{StorageBackendKind, UsedSpace};
 use bls::Signature;
 use ed25519_dalek::Keypair;
 use eyre::{bail, eyre, Context, Result};
@@ -205,6 +205,7 @@ impl TestNodeBuilder {
             self.node_event_sender,
             UsedSpace::new(max_capacity),
             root_storage_dir,
+            StorageBackendKind::default(),
         )
         .await?;
         let node = Arc::new(RwLock::new(node));
@@ -168,6 +168,9 @@ impl Node {
             None
         };
 
+        // our sibling section's adults are no longer known once our section split
+        let former_adults = self.network_knowledge.adults();
+
         let updated = self.network_knowledge.update_knowledge_if_valid(
             signed_sap.clone(),
             &proof_chain,
@@ -179,6 +182,12 @@ impl Node {
         // always run this, only changes will trigger events
         let mut cmds = self.update_on_elder_change(&snapshot).await?;
 
+        if updated && snapshot.prefix != self.network_knowledge.prefix() {
+            // our section split, so part of our data is now the sibling section's concern,
+            // which we hand off to it before evicting it
+            let _queued = self.hand_off_data_outside_our_prefix(former_adults).await;
+        }
+
         // Only trigger reorganize data when there is a membership change happens.
         if updated && self.is_not_elder() {
             // only done if adult, since as an elder we dont want to get any more
@@ -405,7 +414,7 @@ mod tests {
         flow_ctrl::{event_channel, tests::network_utils::create_comm},
         MIN_ADULT_AGE,
     };
-    use crate::UsedSpace;
+    use crate::{storage::StorageBackendKind, UsedSpace};
 
     use sn_interface::{
         elder_count,
@@ -686,6 +695,7 @@ mod tests {
                 event_channel::new(1).0,
                 UsedSpace::new(max_capacity),
                 root_storage_dir,
+                StorageBackendKind::default(),
                 genesis_sk_set.clone(),
             )
             .await?;
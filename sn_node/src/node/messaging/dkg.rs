@@ -144,6 +144,10 @@ impl Node {
             session_id,
             self.network_knowledge().section_key(),
         )?;
+
+        #[cfg(feature = "metrics")]
+        crate::node::metrics::record_dkg_round("started");
+
         Ok(cmds)
     }
 
@@ -291,6 +295,9 @@ impl Node {
             key_share_pk
         );
 
+        #[cfg(feature = "metrics")]
+        crate::node::metrics::record_dkg_round("completed");
+
         // Add our new keyshare to our cache, we will then use
         // it to sign any msg that needs section agreement.
         self.section_keys_provider.insert(key_share.clone());
@@ -315,6 +322,9 @@ impl Node {
     }
 
     pub(crate) fn handle_dkg_failure(&mut self, failure_set: DkgFailureSigSet) -> Cmd {
+        #[cfg(feature = "metrics")]
+        crate::node::metrics::record_dkg_round("failed");
+
         // track those failed participants
         for name in &failure_set.failed_participants {
             trace!("Logging {name} as having Dkg issue in dysfunction");
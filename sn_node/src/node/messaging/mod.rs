@@ -89,6 +89,9 @@ impl Node {
                 dst,
                 msg,
             } => {
+                #[cfg(feature = "metrics")]
+                crate::node::metrics::record_system_msg_received(&msg);
+
                 // Verify that the section key in the msg authority is trusted
                 if !self.verify_section_key(&msg_authority, &msg).await {
                     warn!(
@@ -135,6 +138,9 @@ impl Node {
                 dst,
                 auth,
             } => {
+                #[cfg(feature = "metrics")]
+                crate::node::metrics::record_service_msg_received(&msg);
+
                 let dst_name = match msg.dst_address() {
                     Some(name) => name,
                     None => {
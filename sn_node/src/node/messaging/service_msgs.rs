@@ -8,7 +8,7 @@
 
 use crate::node::messaging::{OutgoingMsg, Peers};
 
-use crate::node::{flow_ctrl::cmds::Cmd, Error, Node, Result};
+use crate::node::{flow_ctrl::cmds::Cmd, DataEvent, Error, Node, Result};
 use bytes::Bytes;
 
 use sn_dbc::{
@@ -21,8 +21,8 @@ use sn_interface::{
     data_copy_count,
     messaging::{
         data::{
-            CmdError, DataCmd, DataQueryVariant, EditRegister, Error as ErrorMsg, ServiceMsg,
-            SignedRegisterEdit, SpentbookCmd,
+            CmdError, DataCmd, DataQueryVariant, EditRegister, Error as ErrorMsg, QueryResponse,
+            ServiceMsg, SignedRegisterEdit, SpentbookCmd,
         },
         system::{NodeQueryResponse, SystemMsg},
         AuthorityProof, EndUser, MsgId, ServiceAuth,
@@ -109,6 +109,20 @@ impl Node {
             .await;
 
         trace!("data query response at adult is: {:?}", response);
+        let served = match (query, &response) {
+            (DataQueryVariant::Batch(queries), NodeQueryResponse::Batch(responses)) => {
+                queries.iter().zip(responses).collect()
+            }
+            (query, response) => vec![(query, response)],
+        };
+        for (query, response) in served {
+            if let (DataQueryVariant::GetChunk(address), NodeQueryResponse::GetChunk(Ok(_))) =
+                (query, response)
+            {
+                self.send_data_event(DataEvent::ChunkServed { address: *address });
+            }
+        }
+
         let msg = SystemMsg::NodeQueryResponse {
             response,
             correlation_id,
@@ -133,7 +147,69 @@ impl Node {
         user: EndUser,
         sending_node_pk: PublicKey,
         #[cfg(feature = "traceroute")] traceroute: Traceroute,
-    ) -> Option<Cmd> {
+    ) -> Vec<Cmd> {
+        let responses = match response {
+            NodeQueryResponse::Batch(responses) => responses,
+            response => {
+                return self
+                    .take_query_response_waiting_peers(response, &user, sending_node_pk)
+                    .map(|(waiting_peers, query_response)| {
+                        let msg = ServiceMsg::QueryResponse {
+                            response: query_response,
+                            correlation_id,
+                        };
+                        self.send_service_msg(
+                            msg,
+                            Peers::Multiple(waiting_peers),
+                            #[cfg(feature = "traceroute")]
+                            traceroute,
+                        )
+                    })
+                    .into_iter()
+                    .collect();
+            }
+        };
+
+        // the responses of a batch are sent as a batch to each of the peers waiting for some of them
+        let mut batches: BTreeMap<Peer, Vec<QueryResponse>> = BTreeMap::new();
+        for response in responses {
+            if let Some((waiting_peers, query_response)) =
+                self.take_query_response_waiting_peers(response, &user, sending_node_pk)
+            {
+                for peer in waiting_peers {
+                    batches
+                        .entry(peer)
+                        .or_default()
+                        .push(query_response.clone());
+                }
+            }
+        }
+
+        batches
+            .into_iter()
+            .map(|(peer, responses)| {
+                let msg = ServiceMsg::QueryResponse {
+                    response: QueryResponse::Batch(responses),
+                    correlation_id,
+                };
+                self.send_service_msg(
+                    msg,
+                    Peers::Single(peer),
+                    #[cfg(feature = "traceroute")]
+                    traceroute.clone(),
+                )
+            })
+            .collect()
+    }
+
+    // Records the response from an adult to a (non batch) query in liveness tracking,
+    // returning the peers waiting for it along with the response to send them, if it's to be sent
+    fn take_query_response_waiting_peers(
+        &mut self,
+        response: NodeQueryResponse,
+        user: &EndUser,
+        sending_node_pk: PublicKey,
+    ) -> Option<(BTreeSet<Peer>, QueryResponse)> {
         let op_id = if let Ok(op_id) = response.operation_id() {
             op_id
         } else {
@@ -203,17 +279,7 @@ impl Node {
             return None;
         }
 
-        let msg = ServiceMsg::QueryResponse {
-            response: query_response,
-            correlation_id,
-        };
-
-        Some(self.send_service_msg(
-            msg,
-            Peers::Multiple(waiting_peers),
-            #[cfg(feature = "traceroute")]
-            traceroute,
-        ))
+        Some((waiting_peers, query_response))
     }
 
     /// Handle incoming service msgs. Though NOT queries, as this requires
@@ -290,6 +356,17 @@ impl Node {
                     )
                     .await;
             }
+            ServiceMsg::WatchRegister(address) => {
+                return Ok(vec![
+                    Cmd::AddRegisterSubscriber { address, origin },
+                    self.send_cmd_ack(
+                        origin,
+                        msg_id,
+                        #[cfg(feature = "traceroute")]
+                        traceroute,
+                    ),
+                ]);
+            }
             _ => {
                 warn!(
                     "!!!! Unexpected ServiceMsg received, and it was not handled: {:?}",
@@ -323,6 +400,17 @@ impl Node {
             return Ok(cmds);
         }
 
+        // the register cmd is pushed to the clients watching that register once an adult
+        // reports it stored, as it's only then checked against the register's policy
+        if let ReplicatedData::RegisterWrite(cmd) = &data {
+            if !self
+                .live_register_subscribers(&cmd.dst_address())
+                .is_empty()
+            {
+                cmds.push(Cmd::AwaitRegisterCmdStored(cmd.clone()));
+            }
+        }
+
         // the replication msg sent to adults
         cmds.push(self.replicate_data(
             data,
@@ -11,8 +11,8 @@ use crate::{
     node::{
         flow_ctrl::cmds::Cmd,
         messaging::{OutgoingMsg, Peers},
-        DkgSessionInfo, Error, Event, MembershipEvent, Node, Proposal as CoreProposal, Result,
-        MIN_LEVEL_WHEN_FULL,
+        DataEvent, DkgSessionInfo, Error, Event, MembershipEvent, Node, Proposal as CoreProposal,
+        Result, MIN_LEVEL_WHEN_FULL,
     },
     storage::Error as StorageError,
 };
@@ -39,7 +39,7 @@ use sn_interface::{
         AuthorityProof, MsgId, NodeMsgAuthority, SectionAuth, WireMsg,
     },
     network_knowledge::NetworkKnowledge,
-    types::{log_markers::LogMarker, Keypair, Peer, PublicKey},
+    types::{log_markers::LogMarker, Keypair, Peer, PublicKey, ReplicatedData},
 };
 use xor_name::XorName;
 
@@ -450,6 +450,22 @@ impl Node {
                     traceroute,
                 )])
             }
+            SystemMsg::NodeEvent(NodeEvent::LeavingSection { node_id }) => {
+                info!("Processing LeavingSection event from {sender} with MsgId: {msg_id:?}");
+                if self.is_not_elder() {
+                    error!("Received unexpected message while Adult");
+                    return Ok(vec![]);
+                }
+
+                if XorName::from(node_id) != sender.name() {
+                    warn!(
+                        "Ignoring LeavingSection event from {sender} for another node: {node_id:?}"
+                    );
+                    return Ok(vec![]);
+                }
+
+                self.cast_offline_proposals(&BTreeSet::from([sender.name()]))
+            }
             SystemMsg::NodeCmd(NodeCmd::ReplicateData(data_collection)) => {
                 info!("ReplicateData MsgId: {:?}", msg_id);
 
@@ -459,6 +475,8 @@ impl Node {
                 }
 
                 let mut cmds = vec![];
+                let mut stored = vec![];
+                let mut stored_register_cmds = vec![];
 
                 let section_pk = PublicKey::Bls(self.network_knowledge.section_key());
                 let own_keypair = Keypair::Ed25519(self.keypair.clone());
@@ -474,6 +492,17 @@ impl Node {
                     {
                         Ok(level_report) => {
                             info!("Storage level report: {:?}", level_report);
+                            stored.push(data.address());
+                            if let ReplicatedData::RegisterWrite(cmd) = &data {
+                                if let Ok(op_id) = cmd.register_operation_id() {
+                                    stored_register_cmds.push(op_id);
+                                }
+                            }
+                            self.send_data_event(DataEvent::ReplicationReceived {
+                                address: data.address(),
+                                sender: sender.name(),
+                            });
+                            self.send_data_event(DataEvent::stored(data.address()));
                             cmds.extend(self.record_storage_level_if_any(
                                 level_report,
                                 #[cfg(feature = "traceroute")]
@@ -496,6 +525,11 @@ impl Node {
 
                             cmds.push(self.send_msg_to_our_elders(msg))
                         }
+                        Err(StorageError::QuotaExceeded(kind)) => {
+                            // we still have room for other kinds of data, so we're not full,
+                            // the data is left to its other holders
+                            warn!("Quota for {kind} used up, not storing {:?}", data.address());
+                        }
                         Err(error) => {
                             // the rest seem to be non-problematic errors.. (?)
                             error!("Problem storing data, but it was ignored: {error}");
@@ -503,8 +537,43 @@ impl Node {
                     }
                 }
 
+                if !stored.is_empty() {
+                    let msg = SystemMsg::NodeEvent(NodeEvent::ReplicatedDataStored {
+                        node_id: PublicKey::from(self.keypair.public),
+                        data: stored,
+                        register_cmds: stored_register_cmds,
+                    });
+                    cmds.push(self.send_system_msg(msg, Peers::Single(sender)));
+                }
+
                 Ok(cmds)
             }
+            SystemMsg::NodeEvent(NodeEvent::ReplicatedDataStored {
+                node_id,
+                data,
+                register_cmds,
+            }) => {
+                trace!(
+                    "Processing ReplicatedDataStored event from {sender} with MsgId: {msg_id:?}"
+                );
+                if XorName::from(node_id) != sender.name() {
+                    warn!("Ignoring ReplicatedDataStored event from {sender}, sent on behalf of another node");
+                    return Ok(vec![]);
+                }
+
+                if self.is_not_elder() {
+                    // the data we handed off to our sibling section, whose adults are no longer
+                    // part of our network knowledge, so only the holders it was sent to count
+                    self.record_data_handoff_acks(sender.name(), data);
+                    return Ok(vec![]);
+                }
+                if !self.network_knowledge.is_adult(&sender.name()) {
+                    warn!("Ignoring ReplicatedDataStored event from {sender}, not an adult of our section");
+                    return Ok(vec![]);
+                }
+
+                Ok(self.notify_register_cmds_stored(register_cmds))
+            }
             SystemMsg::NodeCmd(NodeCmd::SendAnyMissingRelevantData(known_data_addresses)) => {
                 info!(
                     "{:?} MsgId: {:?}",
@@ -647,14 +716,15 @@ impl Node {
         }
     }
 
-    fn record_storage_level_if_any(
+    pub(crate) fn record_storage_level_if_any(
         &self,
         level: Option<StorageLevel>,
         #[cfg(feature = "traceroute")] traceroute: Traceroute,
     ) -> Result<Vec<Cmd>> {
         let mut cmds = vec![];
         if let Some(level) = level {
-            info!("Storage has now passed {} % used.", 10 * level.value());
+            info!("Storage is now at {} % used.", 10 * level.value());
+            self.send_data_event(DataEvent::StorageLevelChanged { level });
             let node_id = PublicKey::from(self.keypair.public);
             let node_xorname = XorName::from(node_id);
 
@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::node::{flow_ctrl::cmds::Cmd, messaging::Peers, Node};
+use crate::node::{flow_ctrl::cmds::Cmd, messaging::Peers, DataEvent, Node};
 
 use sn_interface::{
     data_copy_count,
@@ -15,9 +15,125 @@ use sn_interface::{
 };
 
 use itertools::Itertools;
-use std::collections::BTreeSet;
+use std::{collections::BTreeSet, sync::Arc};
+use tokio::sync::RwLock;
+use xor_name::XorName;
 
 impl Node {
+    /// Queues the data we're no longer responsible for, as its name is outside of our prefix,
+    /// for replication to its holders in our sibling section, which happens when our section
+    /// split. The data is evicted once all its new holders acknowledged storing it.
+    /// The adults of our section before the split are given, as our sibling section's
+    /// ones are no longer part of our network knowledge.
+    /// Returns the number of data items queued.
+    #[instrument(skip(self, former_adults))]
+    pub(crate) async fn hand_off_data_outside_our_prefix(
+        &mut self,
+        former_adults: BTreeSet<Peer>,
+    ) -> usize {
+        let prefix = self.network_knowledge.prefix();
+        let data_outside = self.data_storage.data_outside(&prefix).await;
+        if data_outside.is_empty() {
+            return 0;
+        }
+
+        // elders don't hold data, so our sibling section's new elders are left out
+        let sibling_elders: BTreeSet<_> = self
+            .network_knowledge
+            .section_tree()
+            .get_signed(&prefix.sibling())
+            .map(|sap| sap.elders().map(Peer::name).collect())
+            .unwrap_or_default();
+        let sibling_adults: Vec<_> = former_adults
+            .into_iter()
+            .filter(|peer| !prefix.matches(&peer.name()))
+            .filter(|peer| !sibling_elders.contains(&peer.name()))
+            .collect();
+        if sibling_adults.is_empty() {
+            warn!("No adults known in our sibling section to hand our data off to");
+        }
+
+        let mut queued = 0;
+        for data in data_outside {
+            let holders: BTreeSet<_> = sibling_adults
+                .iter()
+                .sorted_by(|lhs, rhs| data.name().cmp_distance(&lhs.name(), &rhs.name()))
+                .take(data_copy_count())
+                .copied()
+                .collect();
+            if holders.is_empty() {
+                continue;
+            }
+
+            debug!(
+                "Handing off data {:?} to our sibling section: {:?}",
+                data, holders
+            );
+            self.data_handed_off
+                .entry(data)
+                .or_default()
+                .extend(holders.iter().map(Peer::name));
+            self.pending_data_to_replicate_to_peers
+                .entry(data)
+                .or_default()
+                .extend(holders);
+            queued += 1;
+        }
+
+        info!("{queued} data items outside of our prefix {prefix:?} queued for hand off");
+
+        queued
+    }
+
+    /// Records that one of the new holders of the data we handed off to our sibling section
+    /// acknowledged storing it.
+    pub(crate) fn record_data_handoff_acks(
+        &mut self,
+        holder: XorName,
+        stored: Vec<ReplicatedDataAddress>,
+    ) {
+        for address in stored {
+            if let Some(holders) = self.data_handed_off.get_mut(&address) {
+                let _acked = holders.remove(&holder);
+            }
+        }
+    }
+
+    /// Removes the data we're no longer responsible for, as its name is outside of our prefix,
+    /// once all its new holders in our sibling section acknowledged storing it.
+    /// The data storage is cloned, so the data is removed without holding the node lock.
+    pub(crate) async fn evict_handed_off_data(node: Arc<RwLock<Self>>) {
+        let (mut data_storage, acked) = {
+            let node = node.read().await;
+            let prefix = node.network_knowledge.prefix();
+            let acked: BTreeSet<_> = node
+                .data_handed_off
+                .iter()
+                .filter(|(address, holders)| holders.is_empty() && !prefix.matches(address.name()))
+                .map(|(address, _)| *address)
+                .collect();
+            if acked.is_empty() {
+                return;
+            }
+            (node.data_storage.clone(), acked)
+        };
+
+        let evicted = data_storage.evict_data(&acked).await;
+        if evicted.is_empty() {
+            return;
+        }
+
+        info!(
+            "Evicted {} data items handed off to our sibling section",
+            evicted.len()
+        );
+        let mut node = node.write().await;
+        for address in evicted {
+            let _holders = node.data_handed_off.remove(&address);
+            node.send_data_event(DataEvent::DataEvicted { address });
+        }
+    }
+
     /// Given what data the peer has, we shall calculate what data the peer is missing that
     /// we have, and send such data to the peer.
     #[instrument(skip(self, data_sender_has))]
@@ -116,4 +232,57 @@ impl Node {
         let msg = SystemMsg::NodeCmd(NodeCmd::SendAnyMissingRelevantData(data_i_have));
         self.send_system_msg(msg, Peers::Multiple(target_members))
     }
+
+    /// Queues our data for replication to the adults which become its holders once we've left,
+    /// i.e. those which are among the closest to the data only when we're not counted in.
+    /// Returns the number of data items queued.
+    #[instrument(skip(self))]
+    pub(crate) async fn enqueue_data_handoff(&mut self) -> usize {
+        let data_i_have = self.data_storage.keys().await;
+
+        let my_name = self.info().name();
+        let full_adults = self.full_adults();
+        let adults = self
+            .network_knowledge
+            .adults()
+            .into_iter()
+            .filter(|peer| peer.name() == my_name || !full_adults.contains(&peer.name()))
+            .collect::<Vec<_>>();
+
+        let mut queued = 0;
+        for data in data_i_have {
+            let holders_with_us: BTreeSet<_> = adults
+                .iter()
+                .sorted_by(|lhs, rhs| data.name().cmp_distance(&lhs.name(), &rhs.name()))
+                .take(data_copy_count())
+                .collect();
+
+            if !holders_with_us.iter().any(|peer| peer.name() == my_name) {
+                // we're not among the holders, so our leaving changes nothing for this data
+                continue;
+            }
+
+            let new_holders: BTreeSet<_> = adults
+                .iter()
+                .sorted_by(|lhs, rhs| data.name().cmp_distance(&lhs.name(), &rhs.name()))
+                .filter(|peer| peer.name() != my_name)
+                .take(data_copy_count())
+                .filter(|peer| !holders_with_us.contains(peer))
+                .copied()
+                .collect();
+
+            if new_holders.is_empty() {
+                continue;
+            }
+
+            debug!("Handing off data {:?} to: {:?}", data, new_holders);
+            self.pending_data_to_replicate_to_peers
+                .entry(data)
+                .or_default()
+                .extend(new_holders);
+            queued += 1;
+        }
+
+        queued
+    }
 }
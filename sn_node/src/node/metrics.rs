@@ -0,0 +1,246 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! Node metrics, served on a Prometheus scrape endpoint.
+//!
+//! Counters and histograms are recorded where the events happen, while gauges
+//! reflecting the state of the node are refreshed by a periodic check.
+
+use crate::node::{messaging::OutgoingMsg, Node, Result};
+
+use sn_interface::messaging::{
+    data::ServiceMsg,
+    system::{AntiEntropyKind, SystemMsg},
+};
+
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server,
+};
+use lazy_static::lazy_static;
+use prometheus::{
+    register_gauge, register_gauge_vec, register_histogram_vec, register_int_counter_vec,
+    register_int_gauge, Encoder, Gauge, GaugeVec, HistogramVec, IntCounterVec, IntGauge,
+    TextEncoder,
+};
+use std::{convert::Infallible, net::SocketAddr, sync::Arc, time::Duration};
+use tokio::sync::RwLock;
+
+lazy_static! {
+    static ref MSGS_RECEIVED: IntCounterVec = register_int_counter_vec!(
+        "sn_node_msgs_received_total",
+        "Msgs received from other nodes and clients, by kind of msg",
+        &["kind"]
+    )
+    .expect("Failed to register metric");
+    static ref AE_MSGS_SENT: IntCounterVec = register_int_counter_vec!(
+        "sn_node_ae_msgs_sent_total",
+        "AntiEntropy msgs sent, by kind of AE",
+        &["kind"]
+    )
+    .expect("Failed to register metric");
+    static ref DKG_ROUNDS: IntCounterVec = register_int_counter_vec!(
+        "sn_node_dkg_rounds_total",
+        "DKG sessions we took part in, by stage reached",
+        &["stage"]
+    )
+    .expect("Failed to register metric");
+    static ref CMD_PROCESSING_TIME: HistogramVec = register_histogram_vec!(
+        "sn_node_cmd_processing_seconds",
+        "Time taken to process a cmd, by kind of cmd",
+        &["cmd"]
+    )
+    .expect("Failed to register metric");
+    static ref PENDING_CMDS: IntGauge = register_int_gauge!(
+        "sn_node_pending_cmds",
+        "Cmds waiting in the queue to be processed"
+    )
+    .expect("Failed to register metric");
+    static ref TOLERATED_MSGS_PER_S: Gauge = register_gauge!(
+        "sn_node_backpressure_tolerated_msgs_per_s",
+        "Msgs per second we last asked our section to send us at most"
+    )
+    .expect("Failed to register metric");
+    static ref USED_SPACE_RATIO: Gauge = register_gauge!(
+        "sn_node_used_space_ratio",
+        "Share of the max capacity used by stored data"
+    )
+    .expect("Failed to register metric");
+    static ref STORED_CHUNKS: IntGauge =
+        register_int_gauge!("sn_node_stored_chunks", "Chunks held by this node")
+            .expect("Failed to register metric");
+    static ref STORED_REGISTERS: IntGauge =
+        register_int_gauge!("sn_node_stored_registers", "Registers held by this node")
+            .expect("Failed to register metric");
+    static ref DYSFUNCTION_SCORES: GaugeVec = register_gauge_vec!(
+        "sn_node_dysfunction_score",
+        "Dysfunction scores of the nodes we track, by type of issue",
+        &["node", "issue"]
+    )
+    .expect("Failed to register metric");
+}
+
+pub(crate) fn record_system_msg_received(msg: &SystemMsg) {
+    MSGS_RECEIVED.with_label_values(&[&msg.to_string()]).inc();
+}
+
+pub(crate) fn record_service_msg_received(msg: &ServiceMsg) {
+    let kind = match msg {
+        ServiceMsg::Cmd(_) => "ServiceMsg::Cmd",
+        ServiceMsg::CmdAck { .. } => "ServiceMsg::CmdAck",
+        ServiceMsg::CmdError { .. } => "ServiceMsg::CmdError",
+        ServiceMsg::Query(_) => "ServiceMsg::Query",
+        ServiceMsg::QueryResponse { .. } => "ServiceMsg::QueryResponse",
+        ServiceMsg::ServiceError(_) => "ServiceMsg::ServiceError",
+        ServiceMsg::WatchRegister(_) => "ServiceMsg::WatchRegister",
+        ServiceMsg::RegisterUpdate(_) => "ServiceMsg::RegisterUpdate",
+    };
+    MSGS_RECEIVED.with_label_values(&[kind]).inc();
+}
+
+/// Kind of AE carried by the msg, if it is an AE msg
+pub(crate) fn ae_kind(msg: &OutgoingMsg) -> Option<&'static str> {
+    match msg {
+        OutgoingMsg::System(SystemMsg::AntiEntropy { kind, .. }) => Some(match kind {
+            AntiEntropyKind::Update { .. } => "update",
+            AntiEntropyKind::Retry { .. } => "retry",
+            AntiEntropyKind::Redirect { .. } => "redirect",
+        }),
+        _ => None,
+    }
+}
+
+pub(crate) fn record_ae_msgs_sent(kind: &str, count: usize) {
+    AE_MSGS_SENT.with_label_values(&[kind]).inc_by(count as u64);
+}
+
+/// The stage is one of `started`, `completed` or `failed`
+pub(crate) fn record_dkg_round(stage: &str) {
+    DKG_ROUNDS.with_label_values(&[stage]).inc();
+}
+
+/// Takes the cmd as displayed, keeping only the name of its variant as label
+pub(crate) fn record_cmd_processing_time(cmd_string: &str, elapsed: Duration) {
+    let cmd = cmd_string
+        .split(|c: char| !c.is_alphanumeric())
+        .next()
+        .unwrap_or_default();
+    CMD_PROCESSING_TIME
+        .with_label_values(&[cmd])
+        .observe(elapsed.as_secs_f64());
+}
+
+#[cfg(feature = "back-pressure")]
+pub(crate) fn record_tolerated_msgs_per_s(msgs_per_s: f64) {
+    TOLERATED_MSGS_PER_S.set(msgs_per_s);
+}
+
+/// Refreshes the gauges reflecting the state of the node.
+/// The node is only locked to take what's needed from it, the stored data is counted
+/// in a task of its own.
+pub(crate) async fn update_node_gauges(node: Arc<RwLock<Node>>, pending_cmds: usize) {
+    PENDING_CMDS.set(pending_cmds as i64);
+
+    let (data_storage, scores) = {
+        let node = node.read().await;
+        (
+            node.data_storage.clone(),
+            node.dysfunction_tracking.calculate_scores(),
+        )
+    };
+
+    let used_space = data_storage.used_space();
+    USED_SPACE_RATIO.set(used_space.used() as f64 / used_space.max_capacity() as f64);
+
+    let _handle = tokio::task::spawn_local(async move {
+        let (chunks, registers) = data_storage.data_counts().await;
+        STORED_CHUNKS.set(chunks as i64);
+        STORED_REGISTERS.set(registers as i64);
+    });
+
+    // Nodes no longer tracked are dropped, rather than left with their last score
+    DYSFUNCTION_SCORES.reset();
+    for (issue, scores) in [
+        ("communication", scores.communication_scores),
+        ("dkg", scores.dkg_scores),
+        ("knowledge", scores.knowledge_scores),
+        ("op", scores.op_scores),
+        ("probe", scores.probe_scores),
+    ] {
+        for (name, score) in scores {
+            DYSFUNCTION_SCORES
+                .with_label_values(&[&format!("{:x}", name), issue])
+                .set(score as f64);
+        }
+    }
+}
+
+/// Starts serving the metrics for Prometheus to scrape at the given address
+pub(crate) fn run_metrics_server(addr: SocketAddr) -> Result<()> {
+    let listener = std::net::TcpListener::bind(addr)?;
+    listener.set_nonblocking(true)?;
+
+    let make_service =
+        make_service_fn(|_conn| async { Ok::<_, Infallible>(service_fn(serve_metrics)) });
+    let server = Server::from_tcp(listener)?.serve(make_service);
+
+    info!("Serving metrics at http://{}/metrics", server.local_addr());
+
+    let _handle = tokio::task::spawn_local(async move {
+        if let Err(error) = server.await {
+            error!("Metrics endpoint stopped: {error:?}");
+        }
+    });
+
+    Ok(())
+}
+
+async fn serve_metrics(_req: Request<Body>) -> std::result::Result<Response<Body>, Infallible> {
+    let encoder = TextEncoder::new();
+    let mut buffer = vec![];
+    if let Err(error) = encoder.encode(&prometheus::gather(), &mut buffer) {
+        error!("Failed to encode metrics: {error:?}");
+    }
+
+    let mut response = Response::new(Body::from(buffer));
+    if let Ok(content_type) = encoder.format_type().parse() {
+        let _prev = response.headers_mut().insert(CONTENT_TYPE, content_type);
+    }
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{record_cmd_processing_time, CMD_PROCESSING_TIME};
+
+    use std::time::Duration;
+
+    #[test]
+    fn cmd_processing_time_is_labelled_by_cmd_variant() {
+        record_cmd_processing_time(
+            "HandleValidSystemMsg MsgId(1234): ..",
+            Duration::from_millis(5),
+        );
+        record_cmd_processing_time("Comm(..)", Duration::from_millis(5));
+
+        assert_eq!(
+            CMD_PROCESSING_TIME
+                .with_label_values(&["HandleValidSystemMsg"])
+                .get_sample_count(),
+            1
+        );
+        assert_eq!(
+            CMD_PROCESSING_TIME
+                .with_label_values(&["Comm"])
+                .get_sample_count(),
+            1
+        );
+    }
+}
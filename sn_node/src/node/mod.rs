@@ -11,6 +11,7 @@
 /// Node Configuration
 pub mod cfg;
 
+mod admin;
 mod api;
 mod bootstrap;
 mod connectivity;
@@ -23,6 +24,8 @@ mod logging;
 mod membership;
 mod messages;
 mod messaging;
+#[cfg(feature = "metrics")]
+mod metrics;
 mod monitoring;
 mod node_starter;
 mod node_test_api;
@@ -30,13 +33,24 @@ mod proposal;
 mod relocation;
 mod split_barrier;
 
+pub use self::{
+    admin::{AdminRequest, AdminRequestReceiver},
+    cfg::config_handler::Config,
+    error::{Error, Result},
+    flow_ctrl::{
+        event::{DataEvent, Event, MembershipEvent, MessagingEvent, NodeElderChange},
+        event_channel::{DataEventSubscription, EventReceiver},
+    },
+    node_starter::{new_test_api, start_node, NodeRef},
+    node_test_api::NodeTestApi,
+};
 use self::{
     bootstrap::join_network,
     core::{
         DkgSessionInfo, Node, StateSnapshot, DATA_QUERY_LIMIT, GENESIS_DBC_AMOUNT,
-        MAX_WAITING_PEERS_PER_QUERY, RESOURCE_PROOF_DATA_SIZE, RESOURCE_PROOF_DIFFICULTY,
+        MAX_SUBSCRIBERS_PER_REGISTER, MAX_WAITING_PEERS_PER_QUERY, REGISTER_SUBSCRIPTION_TTL,
+        RESOURCE_PROOF_DATA_SIZE, RESOURCE_PROOF_DIFFICULTY,
     },
-    data::MIN_LEVEL_WHEN_FULL,
     flow_ctrl::{
         cmds::Cmd,
         event::{CmdProcessEvent, Elders},
@@ -44,19 +58,10 @@ use self::{
     node_starter::CmdChannel,
     proposal::Proposal,
 };
-pub use self::{
-    cfg::config_handler::Config,
-    error::{Error, Result},
-    flow_ctrl::{
-        event::{Event, MembershipEvent, MessagingEvent, NodeElderChange},
-        event_channel::EventReceiver,
-    },
-    node_starter::{new_test_api, start_node},
-    node_test_api::NodeTestApi,
-};
 
-pub use crate::storage::DataStorage;
+pub use crate::storage::{DataKind, DataStorage, StorageBackendKind};
 
+pub(crate) use self::data::MIN_LEVEL_WHEN_FULL;
 pub(crate) use self::monitoring::RateLimits;
 #[cfg(test)]
 pub(crate) use relocation::{check as relocation_check, ChurnId};
@@ -81,7 +86,7 @@ mod core {
             messaging::Peers,
             split_barrier::SplitBarrier,
             DataStorage, Elders, Error, Event, MembershipEvent, NodeElderChange, Prefix, Proposal,
-            Result, XorName,
+            Result, StorageBackendKind, XorName,
         },
         UsedSpace,
     };
@@ -90,7 +95,7 @@ mod core {
     use sn_interface::messaging::Entity;
     use sn_interface::{
         messaging::{
-            data::OperationId,
+            data::{OperationId, RegisterCmd},
             signature_aggregator::SignatureAggregator,
             system::{DkgSessionId, NodeState, SystemMsg},
             AuthorityProof, SectionAuth, SectionAuthorityProvider,
@@ -99,7 +104,8 @@ mod core {
             supermajority, NetworkKnowledge, NodeInfo, SectionKeyShare, SectionKeysProvider,
         },
         types::{
-            keys::ed25519::Digest256, log_markers::LogMarker, Cache, Peer, ReplicatedDataAddress,
+            keys::ed25519::Digest256, log_markers::LogMarker, Cache, Peer, RegisterAddress,
+            ReplicatedDataAddress,
         },
     };
 
@@ -112,7 +118,7 @@ mod core {
         net::SocketAddr,
         path::PathBuf,
         sync::Arc,
-        time::Duration,
+        time::{Duration, Instant},
     };
     use uluru::LRUCache;
 
@@ -148,6 +154,19 @@ mod core {
     // the section).
     const DATA_QUERY_TIMEOUT: Duration = Duration::from_secs(15);
 
+    // How long a client's subscription to a register's changes lasts. Clients renew their
+    // subscriptions well within it, so subscriptions of clients which went away eventually expire.
+    pub(crate) const REGISTER_SUBSCRIPTION_TTL: Duration = Duration::from_secs(120);
+    // This prevents the register subscriptions from growing unbound, one insert per register
+    const REGISTER_SUBSCRIPTIONS_LIMIT: usize = 10_000;
+    // per register we can have this many subscribed clients
+    pub(crate) const MAX_SUBSCRIBERS_PER_REGISTER: usize = 100;
+    // How long a register cmd is held for an adult to report it stored, before it's pushed to
+    // the clients watching the register. Those not reported stored in time are never pushed.
+    const PENDING_REGISTER_UPDATE_TIMEOUT: Duration = Duration::from_secs(30);
+    // This prevents the register cmds awaiting to be pushed from growing unbound
+    const PENDING_REGISTER_UPDATES_LIMIT: usize = 10_000;
+
     #[derive(Debug, Clone)]
     pub(crate) struct DkgSessionInfo {
         pub(crate) session_id: DkgSessionId,
@@ -168,6 +187,9 @@ mod core {
         // we need to pull data first before we form the WireMsg, we won't do that just now
         pub(crate) pending_data_to_replicate_to_peers:
             BTreeMap<ReplicatedDataAddress, BTreeSet<Peer>>,
+        /// data outside our prefix handed off to our sibling section after a split, along with
+        /// its new holders which haven't acknowledged storing it yet
+        pub(crate) data_handed_off: BTreeMap<ReplicatedDataAddress, BTreeSet<XorName>>,
         pub(crate) resource_proof: ResourceProof,
         // Network resources
         pub(crate) section_keys_provider: SectionKeysProvider,
@@ -190,6 +212,11 @@ mod core {
         pub(crate) dysfunction_tracking: DysfunctionDetection,
         /// Cache the request combo,  (OperationId -> An adult xorname), to waiting Clients peers for that combo
         pub(crate) pending_data_queries: Cache<(OperationId, XorName), BTreeSet<Peer>>,
+        /// Clients subscribed to the changes of a register, along with when their subscription expires
+        pub(crate) register_subscribers: Cache<RegisterAddress, BTreeMap<Peer, Instant>>,
+        /// Register cmds with subscribers to push them to, held till an adult reports them stored,
+        /// by the name of their op id
+        pub(crate) pending_register_updates: Cache<XorName, RegisterCmd>,
         // Caches
         pub(crate) ae_backoff_cache: AeBackoffCache,
     }
@@ -204,6 +231,7 @@ mod core {
             event_sender: EventSender,
             used_space: UsedSpace,
             root_storage_dir: PathBuf,
+            storage_backend: StorageBackendKind,
         ) -> Result<Self> {
             let membership = if let Some(key) = section_key_share.clone() {
                 let n_elders = network_knowledge
@@ -230,7 +258,8 @@ mod core {
 
             let section_keys_provider = SectionKeysProvider::new(section_key_share.clone());
 
-            let data_storage = DataStorage::new(&root_storage_dir, used_space.clone())?;
+            let data_storage =
+                DataStorage::new(&root_storage_dir, used_space.clone(), storage_backend)?;
 
             info!("Creating DysfunctionDetection checks");
             let node_dysfunction_detector = DysfunctionDetection::new(
@@ -280,7 +309,16 @@ mod core {
                 capacity: Capacity::default(),
                 dysfunction_tracking: node_dysfunction_detector,
                 pending_data_queries: Cache::with_expiry_duration(DATA_QUERY_TIMEOUT),
+                register_subscribers: Cache::with_expiry_duration_and_capacity(
+                    REGISTER_SUBSCRIPTION_TTL,
+                    REGISTER_SUBSCRIPTIONS_LIMIT,
+                ),
+                pending_register_updates: Cache::with_expiry_duration_and_capacity(
+                    PENDING_REGISTER_UPDATE_TIMEOUT,
+                    PENDING_REGISTER_UPDATES_LIMIT,
+                ),
                 pending_data_to_replicate_to_peers: BTreeMap::new(),
+                data_handed_off: BTreeMap::new(),
                 ae_backoff_cache: AeBackoffCache::default(),
                 membership,
             };
@@ -8,18 +8,19 @@
 
 use crate::comm::Comm;
 use crate::node::{
+    admin::{run_admin_server, AdminRequest, AdminRequestReceiver},
     cfg::keypair_storage::{get_reward_pk, store_network_keypair, store_new_reward_keypair},
     flow_ctrl::{
         cmds::Cmd,
         dispatcher::Dispatcher,
         event::{Elders, Event, MembershipEvent, NodeElderChange},
         event_channel,
-        event_channel::EventReceiver,
+        event_channel::{DataEventSubscription, EventReceiver},
         CmdCtrl, FlowCtrl,
     },
     join_network,
     logging::{log_ctx::LogCtx, run_system_logger},
-    Config, Error, Node, RateLimits, Result,
+    Config, DataKind, Error, Node, RateLimits, Result,
 };
 use crate::UsedSpace;
 
@@ -33,12 +34,14 @@ use std::{
     collections::BTreeSet,
     net::{Ipv4Addr, SocketAddr},
     path::Path,
+    sync::atomic::AtomicUsize,
     sync::Arc,
     time::Duration,
 };
 use tokio::{
     fs,
     sync::{mpsc, RwLock},
+    time::Instant,
 };
 use xor_name::Prefix;
 
@@ -53,6 +56,13 @@ const GENESIS_DBC_FILENAME: &str = "genesis_dbc";
 
 static EVENT_CHANNEL_SIZE: usize = 20;
 
+// How often to check on the progress of a graceful leave.
+const LEAVE_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+// Time given to leave the section gracefully, on top of the time given to hand our data off.
+const LEAVE_TIMEOUT: Duration = Duration::from_secs(120);
+// Time given to hand off each data item, as they're sent out one at a time.
+const HANDOFF_TIMEOUT_PER_ITEM: Duration = Duration::from_millis(200);
+
 pub(crate) type CmdChannel = mpsc::Sender<(Cmd, Option<usize>)>;
 
 /// Test only
@@ -60,7 +70,8 @@ pub async fn new_test_api(
     config: &Config,
     join_timeout: Duration,
 ) -> Result<(super::NodeTestApi, EventReceiver)> {
-    let (node, cmd_channel, event_receiver) = new_node(config, join_timeout).await?;
+    let (node, cmd_channel, event_receiver, _admin_requests) =
+        new_node(config, join_timeout).await?;
     Ok((super::NodeTestApi::new(node, cmd_channel), event_receiver))
 }
 
@@ -73,6 +84,78 @@ pub struct NodeRef {
     node: Arc<RwLock<Node>>,
     /// Sender which can be used to add a Cmd to the Node's CmdQueue
     cmd_channel: CmdChannel,
+    /// Actions requested through the admin endpoint, if it's enabled
+    admin_requests: Option<AdminRequestReceiver>,
+}
+
+impl NodeRef {
+    /// Waits for the next action requested through the admin endpoint.
+    /// Never resolves if the admin endpoint is not enabled.
+    pub async fn next_admin_request(&mut self) -> AdminRequest {
+        if let Some(receiver) = &mut self.admin_requests {
+            if let Some(request) = receiver.recv().await {
+                return request;
+            }
+            self.admin_requests = None;
+        }
+        std::future::pending().await
+    }
+
+    /// Applies the settings which can be changed while the node is running,
+    /// e.g. after the config has been reloaded.
+    pub async fn apply_config(&self, config: &Config) -> Result<()> {
+        apply_runtime_config(&self.node, &self.cmd_channel, config).await
+    }
+
+    /// Subscribes to the data events of the node, e.g. chunks being stored or served.
+    pub async fn subscribe_data_events(&self) -> DataEventSubscription {
+        self.node.read().await.event_sender.subscribe_data_events()
+    }
+
+    /// Leaves the section gracefully, ahead of shutting down.
+    ///
+    /// Our data is handed off to the adults which become its holders once we've left, while our
+    /// elders are asked to vote us out. Resolves once both are done, so the events from the node
+    /// must keep being consumed meanwhile. Gives up after a time scaled to the data to hand off.
+    pub async fn leave_section(&self) -> Result<()> {
+        let queued = self.node.write().await.enqueue_data_handoff().await;
+        info!("Leaving the section, handing off {queued} data items");
+        let deadline = Instant::now()
+            + LEAVE_TIMEOUT
+            + HANDOFF_TIMEOUT_PER_ITEM * queued.try_into().unwrap_or(u32::MAX);
+
+        // handing off can take a while, so we don't wait for it to ask to be voted out
+        let cmd = self.node.read().await.announce_leaving();
+        let voted_out_needed = if let Some(cmd) = cmd {
+            self.cmd_channel
+                .send((cmd, None))
+                .await
+                .map_err(|_| Error::CmdSendError)?;
+            true
+        } else {
+            warn!("No other elders to vote us out, leaving once our data is handed off");
+            false
+        };
+
+        loop {
+            {
+                let node = self.node.read().await;
+                let handed_off = node.pending_data_to_replicate_to_peers.is_empty();
+                let voted_out = !voted_out_needed
+                    || !node
+                        .network_knowledge()
+                        .is_section_member(&node.info().name());
+                if handed_off && voted_out {
+                    info!("Our data is handed off and we're no longer a member of the section");
+                    return Ok(());
+                }
+            }
+            if Instant::now() >= deadline {
+                return Err(Error::LeaveTimeout);
+            }
+            tokio::time::sleep(LEAVE_CHECK_INTERVAL).await;
+        }
+    }
 }
 
 /// Start a new node.
@@ -80,16 +163,66 @@ pub async fn start_node(
     config: &Config,
     join_timeout: Duration,
 ) -> Result<(NodeRef, EventReceiver)> {
-    let (node, cmd_channel, event_receiver) = new_node(config, join_timeout).await?;
+    let (node, cmd_channel, event_receiver, admin_requests) =
+        new_node(config, join_timeout).await?;
+
+    Ok((
+        NodeRef {
+            node,
+            cmd_channel,
+            admin_requests,
+        },
+        event_receiver,
+    ))
+}
 
-    Ok((NodeRef { node, cmd_channel }, event_receiver))
+// Applies the settings which can be changed while the node is running.
+async fn apply_runtime_config(
+    node: &Arc<RwLock<Node>>,
+    cmd_channel: &CmdChannel,
+    config: &Config,
+) -> Result<()> {
+    {
+        let node = node.read().await;
+        let used_space = node.data_storage.used_space();
+        used_space.set_max_capacity(config.max_capacity());
+        used_space.set_high_watermark(config.storage_high_watermark());
+        for kind in DataKind::ALL {
+            used_space.set_quota(kind, config.max_capacity_of(kind));
+        }
+    }
+
+    #[cfg(feature = "back-pressure")]
+    {
+        let cmd = Cmd::Comm(crate::comm::Cmd::SetBackPressureBounds {
+            min_msgs_per_s: config.min_tolerated_msgs_per_s(),
+            max_msgs_per_s: config.max_tolerated_msgs_per_s(),
+        });
+        cmd_channel
+            .send((cmd, None))
+            .await
+            .map_err(|_| Error::CmdSendError)?;
+    }
+
+    let cmd = Cmd::Comm(crate::comm::Cmd::SetIdleTimeout(config.idle_timeout()));
+    cmd_channel
+        .send((cmd, None))
+        .await
+        .map_err(|_| Error::CmdSendError)?;
+
+    Ok(())
 }
 
 // Private helper to create a new node using the given config and bootstraps it to the network.
 async fn new_node(
     config: &Config,
     join_timeout: Duration,
-) -> Result<(Arc<RwLock<Node>>, CmdChannel, EventReceiver)> {
+) -> Result<(
+    Arc<RwLock<Node>>,
+    CmdChannel,
+    EventReceiver,
+    Option<AdminRequestReceiver>,
+)> {
     let root_dir_buf = config.root_dir()?;
     let root_dir = root_dir_buf.as_path();
     fs::create_dir_all(root_dir).await?;
@@ -106,7 +239,7 @@ async fn new_node(
 
     let used_space = UsedSpace::new(config.max_capacity());
 
-    let (node, cmd_channel, network_events) =
+    let (node, cmd_channel, network_events, pending_cmds) =
         bootstrap_node(config, used_space, root_dir, join_timeout).await?;
 
     {
@@ -135,7 +268,21 @@ async fn new_node(
 
     run_system_logger(LogCtx::new(node.clone()), config.resource_logs).await;
 
-    Ok((node, cmd_channel, network_events))
+    apply_runtime_config(&node, &cmd_channel, config).await?;
+
+    if let Some(addr) = config.metrics_addr() {
+        #[cfg(feature = "metrics")]
+        super::metrics::run_metrics_server(addr)?;
+        #[cfg(not(feature = "metrics"))]
+        warn!("Metrics won't be served at {addr}, as the node was built without the `metrics` feature");
+    }
+
+    let admin_requests = match config.admin_addr() {
+        Some(addr) => Some(run_admin_server(addr, root_dir, node.clone(), pending_cmds).await?),
+        None => None,
+    };
+
+    Ok((node, cmd_channel, network_events, admin_requests))
 }
 
 // Private helper to create a new node using the given config and bootstraps it to the network.
@@ -144,7 +291,12 @@ async fn bootstrap_node(
     used_space: UsedSpace,
     root_storage_dir: &Path,
     join_timeout: Duration,
-) -> Result<(Arc<RwLock<Node>>, CmdChannel, EventReceiver)> {
+) -> Result<(
+    Arc<RwLock<Node>>,
+    CmdChannel,
+    EventReceiver,
+    Arc<AtomicUsize>,
+)> {
     let (connection_event_tx, mut connection_event_rx) = mpsc::channel(100);
 
     let local_addr = config
@@ -182,6 +334,7 @@ async fn bootstrap_node(
             event_sender.clone(),
             used_space.clone(),
             root_storage_dir.to_path_buf(),
+            config.storage_backend(),
             genesis_sk_set,
         )
         .await?;
@@ -270,6 +423,7 @@ async fn bootstrap_node(
             event_sender.clone(),
             used_space.clone(),
             root_storage_dir.to_path_buf(),
+            config.storage_backend(),
         )
         .await?;
 
@@ -281,8 +435,13 @@ async fn bootstrap_node(
 
     let node = Arc::new(RwLock::new(node));
     let cmd_ctrl = CmdCtrl::new(Dispatcher::new(node.clone(), comm), monitoring);
-    let (msg_and_period_ctrl, cmd_channel) =
-        FlowCtrl::new(cmd_ctrl, connection_event_rx, event_sender);
+    let pending_cmds = cmd_ctrl.pending_cmds();
+    let (msg_and_period_ctrl, cmd_channel) = FlowCtrl::new(
+        cmd_ctrl,
+        connection_event_rx,
+        event_sender,
+        config.chunk_scrub_batch_size(),
+    );
 
     let _ = tokio::task::spawn_local(async move {
         msg_and_period_ctrl
@@ -290,5 +449,5 @@ async fn bootstrap_node(
             .await
     });
 
-    Ok((node, cmd_channel, event_receiver))
+    Ok((node, cmd_channel, event_receiver, pending_cmds))
 }
@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::node::{CmdChannel, Error, Node, Peer, Result};
+use crate::node::{CmdChannel, DataEventSubscription, Error, Node, Peer, Result};
 
 use sn_interface::{messaging::system::SystemMsg, network_knowledge::SectionAuthorityProvider};
 
@@ -92,6 +92,11 @@ impl NodeTestApi {
         self.node.read().await.matching_section(name)
     }
 
+    /// Subscribes to the data events of the node.
+    pub async fn subscribe_data_events(&self) -> DataEventSubscription {
+        self.node.read().await.event_sender.subscribe_data_events()
+    }
+
     /// Send a system msg.
     pub async fn send(&self, msg: SystemMsg, recipients: BTreeSet<Peer>) -> Result<()> {
         let cmd = Cmd::send_msg(OutgoingMsg::System(msg), Peers::Multiple(recipients));
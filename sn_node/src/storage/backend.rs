@@ -0,0 +1,173 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{DataKind, Error, FileStore, PackStore, RegisterLog, Result, UsedSpace};
+
+use sn_interface::{
+    messaging::data::RegisterSnapshot,
+    types::{Chunk, ChunkAddress, RegisterAddress, RegisterCmd, RegisterCmdId},
+};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Debug},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+use xor_name::Prefix;
+
+/// The layout used to persist the data of a node on disk.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StorageBackendKind {
+    /// One file per chunk, and one per register op, under a prefix tree of directories.
+    Files,
+    /// Records appended to large segment files, indexed in memory and compacted in the
+    /// background. The number of files used doesn't grow with the number of chunks stored,
+    /// so nodes don't run out of inodes.
+    Pack,
+}
+
+/// Default backend is Files
+impl Default for StorageBackendKind {
+    fn default() -> Self {
+        Self::Files
+    }
+}
+
+impl FromStr for StorageBackendKind {
+    type Err = String;
+    fn from_str(str: &str) -> std::result::Result<Self, Self::Err> {
+        match str {
+            "files" => Ok(Self::Files),
+            "pack" => Ok(Self::Pack),
+            other => Err(format!(
+                "Invalid storage backend: {}. Supported values are files and pack",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for StorageBackendKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Files => write!(f, "files"),
+            Self::Pack => write!(f, "pack"),
+        }
+    }
+}
+
+/// A store where chunks and register ops are persisted.
+#[async_trait]
+pub(crate) trait StorageBackend: Debug + Send + Sync {
+    /// Fails with `Error::NotEnoughSpace` or `Error::QuotaExceeded` if there is no room left
+    /// for the given amount of bytes of the given kind of data
+    fn check_space(&self, kind: DataKind, size: usize) -> Result<()>;
+
+    // ---------------------- chunks ----------------------
+
+    async fn list_chunk_addrs(&self) -> Vec<ChunkAddress>;
+
+    /// Chunks whose name doesn't match the given prefix, e.g. those we're no longer
+    /// responsible for after our section split
+    async fn list_chunk_addrs_without_prefix(&self, prefix: &Prefix) -> Vec<ChunkAddress> {
+        self.list_chunk_addrs()
+            .await
+            .into_iter()
+            .filter(|addr| !prefix.matches(addr.name()))
+            .collect()
+    }
+
+    async fn chunk_exists(&self, addr: &ChunkAddress) -> Result<bool>;
+
+    async fn write_chunk(&self, chunk: &Chunk) -> Result<()>;
+
+    /// Fails with `Error::ChunkNotFound` if there is no such chunk
+    async fn read_chunk(&self, addr: &ChunkAddress) -> Result<Chunk>;
+
+    async fn delete_chunk(&self, addr: &ChunkAddress) -> Result<()>;
+
+    /// Moves the chunk out of the store, into a file in the given quarantine directory,
+    /// so it's no longer served nor listed, but it's kept for inspection.
+    async fn quarantine_chunk(
+        &self,
+        addr: &ChunkAddress,
+        quarantine_path: &Path,
+    ) -> Result<PathBuf>;
+
+    // ---------------------- registers ----------------------
+
+    async fn list_reg_addrs(&self) -> Vec<RegisterAddress>;
+
+    async fn reg_log_exists(&self, addr: &RegisterAddress) -> Result<bool>;
+
+    /// Returns an empty log if there is no such register
+    async fn read_reg_log(&self, addr: &RegisterAddress) -> Result<RegisterLog>;
+
+    /// Persists a single op of the log of a register, ops already stored are left untouched
+    async fn write_reg_cmd(
+        &self,
+        addr: &RegisterAddress,
+        reg_id: &RegisterCmdId,
+        cmd: &RegisterCmd,
+    ) -> Result<()>;
+
+    /// Removes the given ops from the log of a register, e.g. once they're covered by a snapshot
+    async fn delete_reg_cmds(
+        &self,
+        addr: &RegisterAddress,
+        reg_ids: &[RegisterCmdId],
+    ) -> Result<()>;
+
+    /// Removes the log of a register, along with its snapshot
+    async fn delete_reg_log(&self, addr: &RegisterAddress) -> Result<()>;
+
+    /// Returns `None` if no snapshot of the register was taken
+    async fn read_reg_snapshot(&self, addr: &RegisterAddress) -> Result<Option<RegisterSnapshot>>;
+
+    /// Persists the snapshot of a register, replacing any previous one
+    async fn write_reg_snapshot(
+        &self,
+        addr: &RegisterAddress,
+        snapshot: &RegisterSnapshot,
+    ) -> Result<()>;
+
+    // ---------------------- maintenance ----------------------
+
+    /// Reclaims the space taken by the data removed, returning the amount of bytes freed.
+    /// Nothing to do by default, for backends deleting data right away.
+    async fn compact(&self) -> Result<u64> {
+        Ok(0)
+    }
+}
+
+/// Opens a backend of the given kind at the specified root location
+pub(crate) fn open_backend(
+    kind: StorageBackendKind,
+    root_path: PathBuf,
+    used_space: UsedSpace,
+) -> Result<Arc<dyn StorageBackend>> {
+    let backend: Arc<dyn StorageBackend> = match kind {
+        StorageBackendKind::Files => Arc::new(FileStore::new(root_path, used_space)?),
+        StorageBackendKind::Pack => Arc::new(PackStore::new(root_path, used_space)?),
+    };
+
+    Ok(backend)
+}
+
+// Maps an I/O error about a missing file to the not found error of a chunk
+pub(super) fn chunk_not_found(error: Error, addr: &ChunkAddress) -> Error {
+    match error {
+        Error::Io(io_error) if io_error.kind() == std::io::ErrorKind::NotFound => {
+            Error::ChunkNotFound(*addr.name())
+        }
+        other => other,
+    }
+}
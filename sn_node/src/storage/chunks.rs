@@ -6,62 +6,158 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::{convert_to_error_msg, Error, FileStore, Result, UsedSpace};
+use super::{
+    convert_to_error_msg, open_backend, DataKind, Error, Result, StorageBackend,
+    StorageBackendKind, UsedSpace,
+};
 
 use sn_interface::{
-    messaging::{data::DataCmd, system::NodeQueryResponse},
-    types::{log_markers::LogMarker, Chunk, ChunkAddress, ReplicatedDataAddress as DataAddress},
+    messaging::system::NodeQueryResponse,
+    types::{
+        log_markers::LogMarker, Chunk, ChunkAddress, Error as NetworkDataError,
+        ReplicatedDataAddress as DataAddress,
+    },
 };
 
+use itertools::Itertools;
 use std::{
     fmt::{self, Display, Formatter},
-    io::ErrorKind,
-    path::Path,
+    path::{Path, PathBuf},
+    sync::Arc,
 };
 use tracing::info;
+use xor_name::Prefix;
 
 const CHUNKS_DB_NAME: &str = "chunks";
+const CHUNKS_QUARANTINE_DIR_NAME: &str = "chunks_quarantine";
+
+/// Position of a scrubbing pass over the stored chunks, see [`ChunkStorage::scrub`].
+#[derive(Debug, Default)]
+pub(crate) struct ScrubCursor {
+    // chunks left to check in the pass, in reverse address order
+    pending: Vec<ChunkAddress>,
+}
+
+impl ScrubCursor {
+    /// Whether all the chunks of the pass were checked, the next scrub starting a new one
+    pub(crate) fn is_pass_done(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
 
 /// Operations on data chunks.
 #[derive(Clone, Debug)]
 pub(super) struct ChunkStorage {
-    file_store: FileStore,
+    backend: Arc<dyn StorageBackend>,
+    quarantine_path: PathBuf,
 }
 
 impl ChunkStorage {
-    pub(crate) fn new(path: &Path, used_space: UsedSpace) -> Result<Self> {
+    pub(crate) fn new(
+        path: &Path,
+        used_space: UsedSpace,
+        backend: StorageBackendKind,
+    ) -> Result<Self> {
         Ok(Self {
-            file_store: FileStore::new(path.join(CHUNKS_DB_NAME), used_space)?,
+            backend: open_backend(backend, path.join(CHUNKS_DB_NAME), used_space)?,
+            quarantine_path: path.join(CHUNKS_QUARANTINE_DIR_NAME),
         })
     }
 
-    pub(crate) fn addrs(&self) -> Vec<DataAddress> {
-        self.file_store.list_all_chunk_addrs()
+    pub(crate) async fn addrs(&self) -> Vec<DataAddress> {
+        self.backend
+            .list_chunk_addrs()
+            .await
+            .into_iter()
+            .map(DataAddress::Chunk)
+            .collect()
+    }
+
+    /// Chunks whose name doesn't match the given prefix
+    pub(crate) async fn addrs_without_prefix(&self, prefix: &Prefix) -> Vec<ChunkAddress> {
+        self.backend.list_chunk_addrs_without_prefix(prefix).await
     }
 
-    #[allow(dead_code)]
     pub(crate) async fn remove_chunk(&self, address: &ChunkAddress) -> Result<()> {
         trace!("Removing chunk, {:?}", address);
-        self.file_store
-            .delete_data(&DataAddress::Chunk(*address))
-            .await
+        self.backend.delete_chunk(address).await
     }
 
     pub(crate) async fn get_chunk(&self, address: &ChunkAddress) -> Result<Chunk> {
         debug!("Getting chunk {:?}", address);
+        self.backend.read_chunk(address).await
+    }
 
-        match self
-            .file_store
-            .read_data(&DataAddress::Chunk(*address))
-            .await
-        {
-            Ok(res) => Ok(res),
-            Err(error) => match error {
-                Error::Io(io_error) if io_error.kind() == ErrorKind::NotFound => {
-                    Err(Error::ChunkNotFound(*address.name()))
+    /// Re-hashes up to `count` stored chunks, the next ones in address order of the scrubbing
+    /// pass the cursor is at, quarantining those which don't match their address anymore.
+    /// The chunks to check are listed once at the start of a pass, chunks stored meanwhile
+    /// being checked on the next pass. Returns the addresses of the quarantined chunks.
+    pub(super) async fn scrub(&self, cursor: &mut ScrubCursor, count: usize) -> Vec<ChunkAddress> {
+        if cursor.pending.is_empty() {
+            // sorted backwards for the next chunks to check to be at the end
+            cursor.pending = self
+                .backend
+                .list_chunk_addrs()
+                .await
+                .into_iter()
+                .sorted_by(|lhs, rhs| rhs.name().cmp(lhs.name()))
+                .collect();
+        }
+        let batch = cursor
+            .pending
+            .split_off(cursor.pending.len().saturating_sub(count));
+
+        let mut quarantined = vec![];
+        for address in batch.iter().rev() {
+            if !self.is_corrupt(address).await {
+                continue;
+            }
+            match self
+                .backend
+                .quarantine_chunk(address, &self.quarantine_path)
+                .await
+            {
+                Ok(path) => {
+                    warn!(
+                        "{:?} {:?} moved to {}",
+                        LogMarker::CorruptChunkQuarantined,
+                        address,
+                        path.display()
+                    );
+                    quarantined.push(*address);
                 }
-                something_else => Err(something_else),
-            },
+                Err(error) => error!("Failed to quarantine corrupt chunk {address:?}: {error:?}"),
+            }
+        }
+
+        quarantined
+    }
+
+    /// Reclaims the space taken by the chunks removed, returning the amount of bytes freed
+    pub(super) async fn compact(&self) -> Result<u64> {
+        self.backend.compact().await
+    }
+
+    // Checks if the stored chunk no longer hashes to its address, or cannot be deserialised.
+    // Chunks removed meanwhile are not reported, nor are those which failed to be read for
+    // any other reason, e.g. an I/O error, so they're checked again on the next pass.
+    async fn is_corrupt(&self, address: &ChunkAddress) -> bool {
+        match self.get_chunk(address).await {
+            Ok(chunk) => chunk.address() != address,
+            Err(Error::ChunkNotFound(_)) => false,
+            Err(
+                error @ (Error::Deserialize(_)
+                | Error::Bincode(_)
+                | Error::InvalidStore
+                | Error::NetworkData(NetworkDataError::Serialisation(_))),
+            ) => {
+                warn!("Chunk {address:?} cannot be deserialised: {error:?}");
+                true
+            }
+            Err(error) => {
+                warn!("Failed to read chunk {address:?} to verify it, skipping it: {error:?}");
+                false
+            }
         }
     }
 
@@ -74,12 +170,12 @@ impl ChunkStorage {
     /// Store a chunk in the local disk store
     /// If that chunk was already in the local store, just overwrites it
     #[instrument(skip_all)]
-    pub(super) async fn store(&self, data: DataCmd) -> Result<()> {
-        if self.file_store.data_file_exists(&data.address())? {
+    pub(super) async fn store(&self, chunk: &Chunk) -> Result<()> {
+        if self.backend.chunk_exists(chunk.address()).await? {
             info!(
                 "{}: Data already exists, not storing: {:?}",
                 self,
-                data.address()
+                chunk.address()
             );
             // Nothing more to do here
             return Err(Error::DataExists);
@@ -88,15 +184,12 @@ impl ChunkStorage {
         // cheap extra security check for space (prone to race conditions)
         // just so we don't go too much overboard
         // should not be triggered as chunks should not be sent to full adults
-        if let DataCmd::StoreChunk(chunk) = &data {
-            if !self.file_store.can_add(chunk.value().len()) {
-                return Err(Error::NotEnoughSpace);
-            }
-        }
+        self.backend
+            .check_space(DataKind::Chunk, chunk.value().len())?;
 
         // store the data
         trace!("{:?}", LogMarker::StoringChunk);
-        let _addr = self.file_store.write_data(data).await?;
+        self.backend.write_chunk(chunk).await?;
         trace!("{:?}", LogMarker::StoredNewChunk);
 
         Ok(())
@@ -6,6 +6,8 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+use super::DataKind;
+
 use sn_interface::{
     messaging::data::Error as ErrorMsg,
     types::{convert_dt_error_to_error_msg, PublicKey, ReplicatedDataAddress as DataAddress},
@@ -26,6 +28,9 @@ pub enum Error {
     /// Not enough space to store the value.
     #[error("Not enough space")]
     NotEnoughSpace,
+    /// The quota for the kind of data is used up.
+    #[error("Quota exceeded for {0}")]
+    QuotaExceeded(DataKind),
     /// Key not found.
     #[error("Key not found: {0:?}")]
     KeyNotFound(String),
@@ -79,7 +84,7 @@ pub enum Error {
 /// Convert db error to messaging error message for sending over the network.
 pub(crate) fn convert_to_error_msg(error: Error) -> ErrorMsg {
     match error {
-        Error::NotEnoughSpace => ErrorMsg::FailedToWriteFile,
+        Error::NotEnoughSpace | Error::QuotaExceeded(_) => ErrorMsg::FailedToWriteFile,
         Error::NoSuchData(address) => ErrorMsg::DataNotFound(address),
         Error::ChunkNotFound(xorname) => ErrorMsg::ChunkNotFound(xorname),
         Error::DataExists => ErrorMsg::DataExists,
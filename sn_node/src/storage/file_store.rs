@@ -6,29 +6,35 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::{Error, Result};
+use super::{backend::chunk_not_found, DataKind, Error, Result, StorageBackend};
 
 use crate::UsedSpace;
 
 use sn_interface::{
-    messaging::data::DataCmd,
+    messaging::data::{DataCmd, RegisterSnapshot},
     types::{
         utils::{deserialise, serialise},
-        Chunk, RegisterAddress, RegisterCmd, RegisterCmdId, ReplicatedDataAddress as DataAddress,
+        Chunk, ChunkAddress, RegisterAddress, RegisterCmd, RegisterCmdId,
+        ReplicatedDataAddress as DataAddress,
     },
 };
 
+use async_trait::async_trait;
 use bytes::Bytes;
 use std::{
     collections::{btree_map::Entry, BTreeMap},
+    io::ErrorKind,
     path::{Path, PathBuf},
 };
-use tokio::fs::{create_dir_all, metadata, read, remove_file, File};
+use tokio::fs::{create_dir_all, metadata, read, remove_dir_all, remove_file, rename, File};
 use tokio::io::AsyncWriteExt;
 use walkdir::WalkDir;
 use xor_name::{Prefix, XorName};
 
 const BIT_TREE_DEPTH: usize = 20;
+// Stored within the folder of the register log, along with the files of the ops
+const REG_SNAPSHOT_FILE_NAME: &str = "snapshot";
+const REG_SNAPSHOT_TMP_FILE_NAME: &str = "snapshot.tmp";
 
 pub(crate) type RegisterLog = BTreeMap<RegisterCmdId, RegisterCmd>;
 
@@ -115,6 +121,7 @@ impl FileStore {
     pub(crate) async fn list_all_reg_addrs(&self) -> Vec<RegisterAddress> {
         let iter = list_files_in(&self.file_store_path)
             .into_iter()
+            .filter(|e| !is_reg_snapshot_file(e))
             .filter_map(|e| e.parent().map(|parent| (parent.to_path_buf(), e.clone())));
 
         let mut addrs = BTreeMap::<PathBuf, RegisterAddress>::new();
@@ -134,10 +141,6 @@ impl FileStore {
 
     // ---------------------- api methods ----------------------
 
-    pub(crate) fn can_add(&self, size: usize) -> bool {
-        self.used_space.can_add(size)
-    }
-
     pub(crate) async fn write_data(&self, data: DataCmd) -> Result<DataAddress> {
         let addr = data.address();
         let filepath = self.address_to_filepath(&addr)?;
@@ -151,20 +154,43 @@ impl FileStore {
         if let DataCmd::StoreChunk(chunk) = data {
             file.write_all(chunk.value()).await?;
             self.used_space.increase(chunk.value().len());
+            self.used_space
+                .increase_kind(DataKind::Chunk, chunk.value().len());
         }
 
         Ok(addr)
     }
 
-    #[allow(dead_code)]
     pub(crate) async fn delete_data(&self, addr: &DataAddress) -> Result<()> {
         let filepath = self.address_to_filepath(addr)?;
         let meta = metadata(filepath.clone()).await?;
         remove_file(filepath).await?;
         self.used_space.decrease(meta.len() as usize);
+        self.used_space
+            .decrease_kind(DataKind::of(addr), meta.len() as usize);
         Ok(())
     }
 
+    /// Moves the file of the data out of the store, into the given quarantine directory,
+    /// so it's no longer served nor listed, but it's kept for inspection.
+    pub(crate) async fn quarantine_data(
+        &self,
+        addr: &DataAddress,
+        quarantine_path: &Path,
+    ) -> Result<PathBuf> {
+        let filepath = self.address_to_filepath(addr)?;
+        let meta = metadata(filepath.clone()).await?;
+
+        create_dir_all(quarantine_path).await?;
+        let quarantined_filepath = quarantine_path.join(addr.encode_to_zbase32()?);
+        rename(filepath, &quarantined_filepath).await?;
+        self.used_space.decrease(meta.len() as usize);
+        self.used_space
+            .decrease_kind(DataKind::of(addr), meta.len() as usize);
+
+        Ok(quarantined_filepath)
+    }
+
     pub(crate) async fn read_data(&self, addr: &DataAddress) -> Result<Chunk> {
         let file_path = self.address_to_filepath(addr)?;
         let bytes = Bytes::from(read(file_path).await?);
@@ -180,9 +206,8 @@ impl FileStore {
     pub(crate) fn list_all_files(&self) -> Vec<PathBuf> {
         list_files_in(&self.file_store_path)
     }
-    #[allow(unused)]
-    /// quickly find chunks related or not to a section, might be useful when adults change sections
-    /// not used yet
+    /// quickly find chunks not related to a section, e.g. to evict them once we no longer
+    /// belong to it after a split
     pub(crate) fn list_files_without_prefix(&self, prefix: Prefix) -> Vec<PathBuf> {
         let prefix_path = self.prefix_tree_path(prefix.name(), prefix.bit_count());
         self.list_all_files()
@@ -210,6 +235,9 @@ impl FileStore {
         if path.exists() {
             trace!("Register log path exists: {}", path.display());
             for filepath in list_files_in(&path) {
+                if is_reg_snapshot_file(&filepath) {
+                    continue;
+                }
                 let serialized_data = read(filepath).await?;
                 let cmd: RegisterCmd = deserialise(&serialized_data)?;
                 let _existing = register_log.insert(cmd.register_operation_id()?, cmd);
@@ -224,21 +252,6 @@ impl FileStore {
         Ok((register_log, path))
     }
 
-    /// Persists a RegisterLog to disk
-    pub(crate) async fn write_log_to_disk(&self, log: &RegisterLog, path: &Path) -> Result<()> {
-        trace!("Writing to register log at {}", path.display());
-
-        create_dir_all(&path).await?;
-
-        for (reg_id, cmd) in log {
-            // TODO do we want to fail here if one entry fails?
-            self.write_register_cmd(reg_id, cmd, path).await?;
-        }
-
-        trace!("Log writing successful at {}", path.display());
-        Ok(())
-    }
-
     /// Persists a RegisterCmd to disk
     pub(crate) async fn write_register_cmd(
         &self,
@@ -260,14 +273,188 @@ impl FileStore {
         let mut file = File::create(path).await?;
 
         file.write_all(&serialized_data).await?;
+        // make sure the cmd is written before it can be read back
+        file.flush().await?;
 
-        self.used_space.increase(std::mem::size_of::<RegisterCmd>());
+        self.used_space.increase(serialized_data.len());
+        self.used_space.increase_kind(
+            DataKind::of_register(&cmd.dst_address()),
+            serialized_data.len(),
+        );
 
         trace!("RegisterCmd writing successful for id {reg_id}");
         Ok(())
     }
 }
 
+#[async_trait]
+impl StorageBackend for FileStore {
+    fn check_space(&self, kind: DataKind, size: usize) -> Result<()> {
+        self.used_space.check_space(kind, size)
+    }
+
+    async fn list_chunk_addrs(&self) -> Vec<ChunkAddress> {
+        self.list_all_chunk_addrs()
+            .into_iter()
+            .filter_map(|addr| match addr {
+                DataAddress::Chunk(addr) => Some(addr),
+                _ => None,
+            })
+            .collect()
+    }
+
+    async fn list_chunk_addrs_without_prefix(&self, prefix: &Prefix) -> Vec<ChunkAddress> {
+        self.list_files_without_prefix(*prefix)
+            .iter()
+            .filter_map(|filepath| match Self::chunk_filepath_to_address(filepath) {
+                Ok(DataAddress::Chunk(addr)) => Some(addr),
+                _ => None,
+            })
+            .collect()
+    }
+
+    async fn chunk_exists(&self, addr: &ChunkAddress) -> Result<bool> {
+        self.data_file_exists(&DataAddress::Chunk(*addr))
+    }
+
+    async fn write_chunk(&self, chunk: &Chunk) -> Result<()> {
+        let _addr = self.write_data(DataCmd::StoreChunk(chunk.clone())).await?;
+        Ok(())
+    }
+
+    async fn read_chunk(&self, addr: &ChunkAddress) -> Result<Chunk> {
+        self.read_data(&DataAddress::Chunk(*addr))
+            .await
+            .map_err(|error| chunk_not_found(error, addr))
+    }
+
+    async fn delete_chunk(&self, addr: &ChunkAddress) -> Result<()> {
+        self.delete_data(&DataAddress::Chunk(*addr))
+            .await
+            .map_err(|error| chunk_not_found(error, addr))
+    }
+
+    async fn quarantine_chunk(
+        &self,
+        addr: &ChunkAddress,
+        quarantine_path: &Path,
+    ) -> Result<PathBuf> {
+        self.quarantine_data(&DataAddress::Chunk(*addr), quarantine_path)
+            .await
+    }
+
+    async fn list_reg_addrs(&self) -> Vec<RegisterAddress> {
+        self.list_all_reg_addrs().await
+    }
+
+    async fn reg_log_exists(&self, addr: &RegisterAddress) -> Result<bool> {
+        self.data_file_exists(&DataAddress::Register(*addr))
+    }
+
+    async fn read_reg_log(&self, addr: &RegisterAddress) -> Result<RegisterLog> {
+        let (log, _path) = self.open_reg_log_from_disk(addr).await?;
+        Ok(log)
+    }
+
+    async fn write_reg_cmd(
+        &self,
+        addr: &RegisterAddress,
+        reg_id: &RegisterCmdId,
+        cmd: &RegisterCmd,
+    ) -> Result<()> {
+        let path = self.address_to_filepath(&DataAddress::Register(*addr))?;
+        create_dir_all(&path).await?;
+        self.write_register_cmd(reg_id, cmd, &path).await
+    }
+
+    async fn delete_reg_cmds(
+        &self,
+        addr: &RegisterAddress,
+        reg_ids: &[RegisterCmdId],
+    ) -> Result<()> {
+        let path = self.address_to_filepath(&DataAddress::Register(*addr))?;
+        let kind = DataKind::of_register(addr);
+        for reg_id in reg_ids {
+            let file = path.join(reg_id);
+            let size = match metadata(&file).await {
+                Ok(meta) => meta.len() as usize,
+                Err(error) if error.kind() == ErrorKind::NotFound => continue,
+                Err(error) => return Err(error.into()),
+            };
+            match remove_file(file).await {
+                Ok(()) => {
+                    self.used_space.decrease(size);
+                    self.used_space.decrease_kind(kind, size);
+                }
+                Err(error) if error.kind() == ErrorKind::NotFound => {}
+                Err(error) => return Err(error.into()),
+            }
+        }
+        Ok(())
+    }
+
+    async fn delete_reg_log(&self, addr: &RegisterAddress) -> Result<()> {
+        let path = self.address_to_filepath(&DataAddress::Register(*addr))?;
+        let mut log_size = 0;
+        for file in list_files_in(&path) {
+            log_size += metadata(file).await?.len() as usize;
+        }
+        remove_dir_all(path).await?;
+        self.used_space.decrease(log_size);
+        self.used_space
+            .decrease_kind(DataKind::of_register(addr), log_size);
+        Ok(())
+    }
+
+    async fn read_reg_snapshot(&self, addr: &RegisterAddress) -> Result<Option<RegisterSnapshot>> {
+        let path = self
+            .address_to_filepath(&DataAddress::Register(*addr))?
+            .join(REG_SNAPSHOT_FILE_NAME);
+        match read(path).await {
+            Ok(serialized_data) => Ok(Some(deserialise(&serialized_data)?)),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    async fn write_reg_snapshot(
+        &self,
+        addr: &RegisterAddress,
+        snapshot: &RegisterSnapshot,
+    ) -> Result<()> {
+        let dir = self.address_to_filepath(&DataAddress::Register(*addr))?;
+        create_dir_all(&dir).await?;
+        let path = dir.join(REG_SNAPSHOT_FILE_NAME);
+        let prev_size = match metadata(&path).await {
+            Ok(meta) => meta.len() as usize,
+            Err(_) => 0,
+        };
+
+        // written aside first, so a snapshot is never partially written
+        let serialized_data = serialise(snapshot)?;
+        let tmp_path = dir.join(REG_SNAPSHOT_TMP_FILE_NAME);
+        let mut file = File::create(&tmp_path).await?;
+        file.write_all(&serialized_data).await?;
+        file.sync_data().await?;
+        rename(tmp_path, path).await?;
+
+        self.used_space.decrease(prev_size);
+        self.used_space.increase(serialized_data.len());
+        let kind = DataKind::of_register(addr);
+        self.used_space.decrease_kind(kind, prev_size);
+        self.used_space.increase_kind(kind, serialized_data.len());
+
+        Ok(())
+    }
+}
+
+fn is_reg_snapshot_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .map(|name| name == REG_SNAPSHOT_FILE_NAME || name == REG_SNAPSHOT_TMP_FILE_NAME)
+        .unwrap_or(false)
+}
+
 fn list_files_in(path: &Path) -> Vec<PathBuf> {
     if !path.exists() {
         return vec![];
@@ -6,16 +6,22 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+mod backend;
 mod chunks;
 mod errors;
 mod file_store;
+mod pack_store;
 mod registers;
 mod used_space;
 
-pub use used_space::UsedSpace;
+pub use backend::StorageBackendKind;
+pub use used_space::{DataKind, UsedSpace};
 
+pub(crate) use backend::{open_backend, StorageBackend};
+pub(crate) use chunks::ScrubCursor;
 pub(crate) use errors::{convert_to_error_msg, Error, Result};
 pub(crate) use file_store::{FileStore, RegisterLog};
+pub(crate) use pack_store::PackStore;
 
 use chunks::ChunkStorage;
 use registers::RegisterStorage;
@@ -30,13 +36,22 @@ use sn_interface::{
         system::NodeQueryResponse,
     },
     types::{
-        register::User, Keypair, PublicKey, RegisterAddress, ReplicatedData, ReplicatedDataAddress,
-        SPENTBOOK_TYPE_TAG,
+        register::User, ChunkAddress, Keypair, PublicKey, RegisterAddress, ReplicatedData,
+        ReplicatedDataAddress, SPENTBOOK_TYPE_TAG,
     },
 };
 
-use sn_interface::messaging::data::DataCmd;
-use std::path::Path;
+use crate::node::MIN_LEVEL_WHEN_FULL;
+
+use std::{
+    collections::BTreeSet,
+    path::Path,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc,
+    },
+};
+use xor_name::Prefix;
 
 /// Operations on data.
 #[derive(Debug, Clone)]
@@ -45,17 +60,18 @@ pub struct DataStorage {
     chunks: ChunkStorage,
     registers: RegisterStorage,
     used_space: UsedSpace,
-    last_recorded_level: StorageLevel,
+    // shared by the clones, as data is also removed by those used in the background
+    last_recorded_level: Arc<AtomicU8>,
 }
 
 impl DataStorage {
-    /// Set up a new `DataStorage` instance
-    pub fn new(path: &Path, used_space: UsedSpace) -> Result<Self> {
+    /// Set up a new `DataStorage` instance, persisting data with the given backend
+    pub fn new(path: &Path, used_space: UsedSpace, backend: StorageBackendKind) -> Result<Self> {
         Ok(Self {
-            chunks: ChunkStorage::new(path, used_space.clone())?,
-            registers: RegisterStorage::new(path, used_space.clone())?,
+            chunks: ChunkStorage::new(path, used_space.clone(), backend)?,
+            registers: RegisterStorage::new(path, used_space.clone(), backend)?,
             used_space,
-            last_recorded_level: StorageLevel::zero(),
+            last_recorded_level: Arc::new(AtomicU8::new(StorageLevel::zero().value())),
         })
     }
 
@@ -69,7 +85,7 @@ impl DataStorage {
     ) -> Result<Option<StorageLevel>> {
         debug!("Replicating {data:?}");
         match data.clone() {
-            ReplicatedData::Chunk(chunk) => self.chunks.store(DataCmd::StoreChunk(chunk)).await?,
+            ReplicatedData::Chunk(chunk) => self.chunks.store(&chunk).await?,
             ReplicatedData::RegisterLog(data) => {
                 info!("Updating register: {:?}", data.address);
                 self.registers
@@ -98,9 +114,20 @@ impl DataStorage {
             }
         };
 
+        // once the high watermark is reached we no longer accept new data, so we report
+        // the level at which we're considered full, for no more data to be sent to us
+        let last_recorded_level = self.last_recorded_level();
+        if last_recorded_level.value() < MIN_LEVEL_WHEN_FULL
+            && self.used_space.is_above_high_watermark()
+        {
+            debug!("High watermark for storage has been reached");
+            let full_level = StorageLevel::from(MIN_LEVEL_WHEN_FULL)?;
+            self.record_level(full_level);
+            return Ok(Some(full_level));
+        }
+
         // check if we've filled another approx. 10%-points of our storage
         // if so, update the recorded level
-        let last_recorded_level = self.last_recorded_level;
         if let Ok(next_level) = last_recorded_level.next() {
             // used_space_ratio is a heavy task that's why we don't do it all the time
             let used_space_ratio = self.used_space.ratio();
@@ -108,7 +135,7 @@ impl DataStorage {
             // every level represents 10 percentage points
             if used_space_level as u8 >= next_level.value() {
                 debug!("Next level for storage has been reached");
-                self.last_recorded_level = next_level;
+                self.record_level(next_level);
                 return Ok(Some(next_level));
             }
         }
@@ -116,12 +143,65 @@ impl DataStorage {
         Ok(None)
     }
 
+    /// Recomputes the storage level from the space used, as it drops once data is removed,
+    /// e.g. evicted or compacted. Returns the new level if it's lower than the one recorded.
+    pub(crate) fn lowered_storage_level(&self) -> Option<StorageLevel> {
+        let max_capacity = self.used_space.max_capacity();
+        if max_capacity == 0 {
+            return None;
+        }
+        // every level represents 10 percentage points
+        let used_space_level = (10 * self.used_space.used() as u128 / max_capacity as u128)
+            .min(StorageLevel::MAX as u128) as u8;
+        let level = if self.used_space.is_above_high_watermark() {
+            used_space_level.max(MIN_LEVEL_WHEN_FULL)
+        } else {
+            used_space_level
+        };
+        if level >= self.last_recorded_level().value() {
+            return None;
+        }
+
+        let level = StorageLevel::from(level).ok()?;
+        debug!("Storage level lowered to {}", level.value());
+        self.record_level(level);
+        Some(level)
+    }
+
+    fn last_recorded_level(&self) -> StorageLevel {
+        StorageLevel::from(self.last_recorded_level.load(Ordering::Relaxed))
+            .unwrap_or_else(|_| StorageLevel::zero())
+    }
+
+    fn record_level(&self, level: StorageLevel) {
+        self.last_recorded_level
+            .store(level.value(), Ordering::Relaxed);
+    }
+
     // Query the local store and return NodeQueryResponse
     pub(crate) async fn query(
         &self,
         query: &DataQueryVariant,
         requester: User,
     ) -> NodeQueryResponse {
+        if let DataQueryVariant::Batch(queries) = query {
+            // none of them is fulfilled if the elder forwarded an invalid batch
+            if let Err(error) = query.check_batch_size() {
+                warn!("Rejecting a batch of queries: {error}");
+                return NodeQueryResponse::Batch(vec![]);
+            }
+            let mut responses = Vec::with_capacity(queries.len());
+            for query in queries {
+                responses.push(self.query_single(query, requester).await);
+            }
+            NodeQueryResponse::Batch(responses)
+        } else {
+            self.query_single(query, requester).await
+        }
+    }
+
+    // Query the local store for a query which is not a batch
+    async fn query_single(&self, query: &DataQueryVariant, requester: User) -> NodeQueryResponse {
         match query {
             DataQueryVariant::GetChunk(addr) => self.chunks.get(addr).await,
             DataQueryVariant::Register(read) => self.registers.read(read, requester).await,
@@ -173,6 +253,10 @@ impl DataStorage {
                     }
                 }
             }
+            DataQueryVariant::Batch(_) => {
+                warn!("Ignoring a batch of queries nested in another batch");
+                NodeQueryResponse::Batch(vec![])
+            }
         }
     }
 
@@ -202,7 +286,6 @@ impl DataStorage {
         }
     }
 
-    #[allow(dead_code)]
     pub(crate) async fn remove(&mut self, address: &ReplicatedDataAddress) -> Result<()> {
         match address {
             ReplicatedDataAddress::Chunk(addr) => self.chunks.remove_chunk(addr).await,
@@ -214,12 +297,79 @@ impl DataStorage {
         }
     }
 
+    /// Returns the addresses of the data whose name doesn't match the given prefix,
+    /// i.e. the data we're no longer responsible for once our section split.
+    pub(crate) async fn data_outside(&self, prefix: &Prefix) -> Vec<ReplicatedDataAddress> {
+        let chunk_addrs = self
+            .chunks
+            .addrs_without_prefix(prefix)
+            .await
+            .into_iter()
+            .map(ReplicatedDataAddress::Chunk);
+        let reg_addrs = self
+            .registers
+            .addrs()
+            .await
+            .into_iter()
+            .filter(|addr| !prefix.matches(addr.name()))
+            .map(ReplicatedDataAddress::Register)
+            .collect::<Vec<_>>();
+
+        chunk_addrs.chain(reg_addrs).collect()
+    }
+
+    /// Remove the given data, e.g. once it was handed off to its new holders.
+    /// Returns the addresses of the data removed.
+    pub(crate) async fn evict_data(
+        &mut self,
+        addresses: &BTreeSet<ReplicatedDataAddress>,
+    ) -> Vec<ReplicatedDataAddress> {
+        let mut evicted = vec![];
+        for address in addresses.iter().copied() {
+            match self.remove(&address).await {
+                Ok(()) => evicted.push(address),
+                Err(error) => warn!("Failed to evict {address:?}: {error:?}"),
+            }
+        }
+
+        evicted
+    }
+
+    /// Re-hash the next `count` stored chunks of the cursor's scrubbing pass, quarantining the
+    /// corrupt ones. Returns the quarantined chunks.
+    pub(crate) async fn scrub_chunks(
+        &self,
+        cursor: &mut ScrubCursor,
+        count: usize,
+    ) -> Vec<ChunkAddress> {
+        self.chunks.scrub(cursor, count).await
+    }
+
+    /// Reclaim the space taken by the data removed, when the storage backend doesn't
+    /// delete it right away. Returns the amount of bytes freed.
+    pub(crate) async fn compact(&self) -> Result<u64> {
+        Ok(self.chunks.compact().await? + self.registers.compact().await?)
+    }
+
+    /// Tracker of the space used by the stored data
+    pub(crate) fn used_space(&self) -> &UsedSpace {
+        &self.used_space
+    }
+
+    /// Number of chunks and registers stored
+    pub(crate) async fn data_counts(&self) -> (usize, usize) {
+        (
+            self.chunks.addrs().await.len(),
+            self.registers.addrs().await.len(),
+        )
+    }
+
     /// Retrieve all keys/ReplicatedDataAddresses of stored data
     pub async fn keys(&self) -> Vec<ReplicatedDataAddress> {
         let mut all_addrs = vec![];
 
         // TODO: Parallelize this below loops
-        let chunk_addrs = self.chunks.addrs();
+        let chunk_addrs = self.chunks.addrs().await;
         chunk_addrs
             .into_iter()
             .for_each(|addr| all_addrs.push(addr));
@@ -235,12 +385,12 @@ impl DataStorage {
 
 #[cfg(test)]
 mod tests {
-    use super::{DataStorage, Error, UsedSpace};
+    use super::{DataKind, DataStorage, Error, ScrubCursor, StorageBackendKind, UsedSpace};
 
     use sn_interface::{
         init_logger,
         messaging::{
-            data::{CreateRegister, DataQueryVariant, SignedRegisterCreate},
+            data::{CreateRegister, DataQueryVariant, SignedRegisterCreate, MAX_QUERIES_PER_BATCH},
             system::NodeQueryResponse,
         },
         types::{
@@ -260,7 +410,7 @@ mod tests {
     use std::{cmp::max, collections::BTreeMap, thread, time::Duration};
     use tempfile::tempdir;
     use tokio::runtime::Runtime;
-    use xor_name::XorName;
+    use xor_name::{Prefix, XorName};
 
     const MAX_N_OPS: usize = 100;
     const CHUNK_MIN: usize = 1;
@@ -268,6 +418,15 @@ mod tests {
 
     #[tokio::test]
     async fn data_storage_basics() -> Result<(), Error> {
+        data_storage_basics_with(StorageBackendKind::Files).await
+    }
+
+    #[tokio::test]
+    async fn data_storage_basics_with_pack_backend() -> Result<(), Error> {
+        data_storage_basics_with(StorageBackendKind::Pack).await
+    }
+
+    async fn data_storage_basics_with(backend: StorageBackendKind) -> Result<(), Error> {
         // Generate temp path for storage
         // Cleaned up automatically after test completes
         let tmp_dir = tempdir()?;
@@ -275,7 +434,7 @@ mod tests {
         let used_space = UsedSpace::new(usize::MAX);
 
         // Create instance
-        let mut storage = DataStorage::new(path, used_space)?;
+        let mut storage = DataStorage::new(path, used_space, backend)?;
 
         // 5mb random data chunk
         let bytes = random_bytes(5 * 1024 * 1024);
@@ -328,7 +487,7 @@ mod tests {
         let used_space = UsedSpace::new(usize::MAX);
 
         // Create instance
-        let mut storage = DataStorage::new(path, used_space)?;
+        let mut storage = DataStorage::new(path, used_space, StorageBackendKind::Files)?;
 
         // 5mb random data chunk
         let bytes = random_bytes(5 * 1024 * 1024);
@@ -352,6 +511,199 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn data_storage_rejects_oversized_batches() -> Result<(), Error> {
+        init_logger();
+        let tmp_dir = tempdir()?;
+        let used_space = UsedSpace::new(usize::MAX);
+        let mut storage = DataStorage::new(tmp_dir.path(), used_space, StorageBackendKind::Files)?;
+
+        let chunk = Chunk::new(random_bytes(100));
+        let pk = PublicKey::Bls(bls::SecretKey::random().public_key());
+        let _ = storage
+            .store(
+                &ReplicatedData::Chunk(chunk.clone()),
+                pk,
+                Keypair::new_ed25519(),
+            )
+            .await?;
+
+        let get_chunk = DataQueryVariant::GetChunk(*chunk.address());
+        let batch = DataQueryVariant::Batch(vec![get_chunk.clone(); MAX_QUERIES_PER_BATCH]);
+        assert_eq!(
+            storage.query(&batch, User::Anyone).await,
+            NodeQueryResponse::Batch(vec![
+                NodeQueryResponse::GetChunk(Ok(chunk));
+                MAX_QUERIES_PER_BATCH
+            ])
+        );
+
+        let batch = DataQueryVariant::Batch(vec![get_chunk; MAX_QUERIES_PER_BATCH + 1]);
+        assert_eq!(
+            storage.query(&batch, User::Anyone).await,
+            NodeQueryResponse::Batch(vec![])
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn data_storage_scrub_quarantines_corrupt_chunks() -> Result<(), Error> {
+        init_logger();
+        // Generate temp path for storage
+        // Cleaned up automatically after test completes
+        let tmp_dir = tempdir()?;
+        let path = tmp_dir.path();
+        let used_space = UsedSpace::new(usize::MAX);
+
+        // Create instance
+        let mut storage = DataStorage::new(path, used_space, StorageBackendKind::Files)?;
+
+        let pk = PublicKey::Bls(bls::SecretKey::random().public_key());
+        let keypair = Keypair::new_ed25519();
+
+        let chunks = std::iter::repeat_with(|| Chunk::new(random_bytes(1024)))
+            .take(3)
+            .collect::<Vec<_>>();
+        for chunk in &chunks {
+            let data = ReplicatedData::Chunk(chunk.clone());
+            let _ = storage.store(&data, pk, keypair.clone()).await?;
+        }
+
+        // Flip the content of one of the chunks on disk
+        let corrupt_addr = ReplicatedDataAddress::Chunk(*chunks[1].address());
+        let corrupt_filename = corrupt_addr.encode_to_zbase32()?;
+        let corrupt_file = walkdir::WalkDir::new(path)
+            .into_iter()
+            .flatten()
+            .find(|entry| entry.file_name().to_str() == Some(corrupt_filename.as_str()))
+            .expect("Stored chunk file not found")
+            .into_path();
+        std::fs::write(&corrupt_file, random_bytes(1024))?;
+
+        // Scrub all chunks in two rounds
+        let mut cursor = ScrubCursor::default();
+        let mut quarantined = storage.scrub_chunks(&mut cursor, 2).await;
+        assert!(!cursor.is_pass_done());
+        let more_quarantined = storage.scrub_chunks(&mut cursor, 2).await;
+        assert!(cursor.is_pass_done());
+        quarantined.extend(more_quarantined);
+
+        assert_eq!(quarantined, vec![*chunks[1].address()]);
+        assert!(!corrupt_file.exists());
+        assert!(path
+            .join("chunks_quarantine")
+            .join(&corrupt_filename)
+            .exists());
+
+        let keys = storage.keys().await;
+        assert!(!keys.contains(&corrupt_addr));
+        assert_eq!(keys.len(), 2);
+
+        // The healthy chunks are left untouched
+        let quarantined = storage.scrub_chunks(&mut cursor, 10).await;
+        assert!(quarantined.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn data_storage_evicts_data_outside_prefix() -> Result<(), Error> {
+        data_storage_evicts_data_outside_prefix_with(StorageBackendKind::Files).await
+    }
+
+    #[tokio::test]
+    async fn data_storage_evicts_data_outside_prefix_with_pack_backend() -> Result<(), Error> {
+        data_storage_evicts_data_outside_prefix_with(StorageBackendKind::Pack).await
+    }
+
+    async fn data_storage_evicts_data_outside_prefix_with(
+        backend: StorageBackendKind,
+    ) -> Result<(), Error> {
+        init_logger();
+        // Generate temp path for storage
+        // Cleaned up automatically after test completes
+        let tmp_dir = tempdir()?;
+        let path = tmp_dir.path();
+        let used_space = UsedSpace::new(usize::MAX);
+
+        // Create instance
+        let mut storage = DataStorage::new(path, used_space.clone(), backend)?;
+
+        let pk = PublicKey::Bls(bls::SecretKey::random().public_key());
+        let keypair = Keypair::new_ed25519();
+
+        let chunks = std::iter::repeat_with(|| Chunk::new(random_bytes(1024)))
+            .take(10)
+            .collect::<Vec<_>>();
+        for chunk in &chunks {
+            let data = ReplicatedData::Chunk(chunk.clone());
+            let _ = storage.store(&data, pk, keypair.clone()).await?;
+        }
+        let used_before = used_space.used_by(DataKind::Chunk);
+
+        let prefix = Prefix::default().pushed(true);
+        let (mut kept, mut outside): (Vec<_>, Vec<_>) = chunks
+            .iter()
+            .map(|chunk| ReplicatedDataAddress::Chunk(*chunk.address()))
+            .partition(|addr| prefix.matches(addr.name()));
+        assert_eq!(storage.data_outside(&prefix).await.len(), outside.len());
+
+        // the data still to be handed off is kept
+        let handed_off = outside.pop();
+        let evicted = storage.evict_data(&outside.iter().copied().collect()).await;
+        assert_eq!(evicted.len(), outside.len());
+        assert!(outside.iter().all(|addr| evicted.contains(addr)));
+        kept.extend(handed_off);
+
+        let keys = storage.keys().await;
+        assert_eq!(keys.len(), kept.len());
+        assert!(kept.iter().all(|addr| keys.contains(addr)));
+        assert_eq!(
+            used_space.used_by(DataKind::Chunk),
+            used_before * kept.len() / chunks.len()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn data_storage_level_is_lowered_once_data_is_removed() -> Result<(), Error> {
+        let tmp_dir = tempdir()?;
+        let used_space = UsedSpace::new(20 * 1024);
+        let mut storage = DataStorage::new(tmp_dir.path(), used_space, StorageBackendKind::Files)?;
+
+        let pk = PublicKey::Bls(bls::SecretKey::random().public_key());
+        let keypair = Keypair::new_ed25519();
+
+        // the level is raised as the chunks are stored, but not lowered by itself
+        let chunks = std::iter::repeat_with(|| Chunk::new(random_bytes(1024)))
+            .take(10)
+            .collect::<Vec<_>>();
+        let mut level = None;
+        for chunk in &chunks {
+            let data = ReplicatedData::Chunk(chunk.clone());
+            level = storage.store(&data, pk, keypair.clone()).await?.or(level);
+        }
+        let level = level.expect("The storage level should have been raised");
+        assert!(level.value() >= 4);
+        assert!(storage.lowered_storage_level().is_none());
+
+        // it's lowered once half of the chunks are removed
+        let removed = chunks[..5]
+            .iter()
+            .map(|chunk| ReplicatedDataAddress::Chunk(*chunk.address()))
+            .collect();
+        assert_eq!(storage.evict_data(&removed).await.len(), 5);
+        let lowered = storage
+            .lowered_storage_level()
+            .expect("The storage level should have been lowered");
+        assert!(lowered.value() < level.value());
+        assert!(storage.lowered_storage_level().is_none());
+
+        Ok(())
+    }
+
     fn section_auth() -> sn_interface::messaging::SectionAuth {
         use sn_interface::messaging::system::KeyedSig;
 
@@ -379,7 +731,7 @@ mod tests {
         let used_space = UsedSpace::new(usize::MAX);
 
         // Create instance
-        let mut storage = DataStorage::new(path, used_space)?;
+        let mut storage = DataStorage::new(path, used_space, StorageBackendKind::Files)?;
 
         // create reg cmd
 
@@ -474,7 +826,7 @@ mod tests {
         let path = temp_dir.path();
         let used_space = UsedSpace::new(usize::MAX);
         let runtime = Runtime::new()?;
-        let mut storage = DataStorage::new(path, used_space)?;
+        let mut storage = DataStorage::new(path, used_space, StorageBackendKind::Files)?;
         let owner_pk = PublicKey::Bls(bls::SecretKey::random().public_key());
         let owner_keypair = Keypair::new_ed25519();
         for op in ops.into_iter() {
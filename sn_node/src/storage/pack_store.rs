@@ -0,0 +1,1044 @@
+// Copyright 2022 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use super::{DataKind, Error, RegisterLog, Result, StorageBackend};
+
+use crate::UsedSpace;
+
+use sn_interface::{
+    messaging::data::RegisterSnapshot,
+    types::{
+        utils::{deserialise, serialise},
+        Chunk, ChunkAddress, RegisterAddress, RegisterCmd, RegisterCmdId,
+        ReplicatedDataAddress as DataAddress,
+    },
+};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    io::{self, BufReader, ErrorKind, Read},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use tokio::{
+    fs::{create_dir_all, remove_file, rename, File, OpenOptions},
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt, SeekFrom},
+    sync::{Mutex, RwLock},
+};
+use xor_name::XorName;
+
+const SEGMENT_FILE_EXTENSION: &str = "pack";
+// Extension of the files the records of a segment being compacted are copied to
+const COMPACTION_FILE_EXTENSION: &str = "compacting";
+// Segments are sealed once they reach this size, new records being appended to a new segment
+const MAX_SEGMENT_SIZE: u64 = 64 * 1024 * 1024;
+// Sealed segments are compacted once at least this share of their bytes is no longer needed
+const COMPACTION_GARBAGE_RATIO: f64 = 0.5;
+// Size of the header of each record, which holds the length of the record which follows
+const RECORD_HEADER_SIZE: u64 = 4;
+
+// Key of each value stored in the pack files
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+enum PackKey {
+    Chunk(XorName),
+    RegisterCmd(XorName, RegisterCmdId),
+    RegisterSnapshot(XorName),
+}
+
+// Records appended to the pack files. A value is removed by appending a tombstone for its key.
+#[derive(Debug, Serialize, Deserialize)]
+enum PackRecord {
+    Value(PackKey, Bytes),
+    Tombstone(PackKey),
+}
+
+// Location of the latest record of a key in the pack files
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct RecordLocation {
+    segment: u64,
+    offset: u64,
+    len: u32,
+    // length of the value held by the record, which is what's accounted as used by its kind,
+    // as the other backends do
+    value_len: u32,
+}
+
+impl PackRecord {
+    fn value_len(&self) -> u32 {
+        match self {
+            Self::Value(_, value) => value.len() as u32,
+            Self::Tombstone(_) => 0,
+        }
+    }
+}
+
+impl RecordLocation {
+    // Bytes taken on disk by the record, header included
+    fn size(&self) -> u64 {
+        RECORD_HEADER_SIZE + self.len as u64
+    }
+}
+
+// Records read from a segment file
+#[derive(Debug, Default)]
+struct SegmentRecords {
+    records: Vec<(RecordLocation, PackRecord)>,
+    // length of the file up to the last record which could be read entirely
+    valid_len: u64,
+    // actual length of the file, longer than the above if a record couldn't be read
+    file_len: u64,
+}
+
+// Record copied out of a segment being compacted, along with its locations before and after
+#[derive(Debug)]
+struct CopiedRecord {
+    key: PackKey,
+    tombstone: bool,
+    from: RecordLocation,
+    to: RecordLocation,
+}
+
+// Accounting of the bytes of a segment, used to pick the segments worth compacting
+#[derive(Clone, Copy, Debug, Default)]
+struct SegmentStats {
+    len: u64,
+    // bytes of the records holding the latest value of a key
+    live: u64,
+    // bytes of the tombstones, only needed while an older segment may hold the removed value
+    tombstones: u64,
+}
+
+#[derive(Debug)]
+struct PackIndex {
+    // id of the segment records are appended to
+    active_id: u64,
+    // kept open in append mode, only ever written while holding the lock
+    active_file: File,
+    segments: BTreeMap<u64, SegmentStats>,
+    values: BTreeMap<PackKey, RecordLocation>,
+    // kind of the data of each register, as it cannot be told from the keys
+    reg_kinds: BTreeMap<XorName, DataKind>,
+}
+
+impl PackIndex {
+    fn stats_mut(&mut self, segment: u64) -> &mut SegmentStats {
+        stats_of(&mut self.segments, segment)
+    }
+
+    fn kind_of(&self, key: &PackKey) -> DataKind {
+        match key {
+            PackKey::Chunk(_) => DataKind::Chunk,
+            PackKey::RegisterCmd(reg_name, _) | PackKey::RegisterSnapshot(reg_name) => self
+                .reg_kinds
+                .get(reg_name)
+                .copied()
+                .unwrap_or(DataKind::Register),
+        }
+    }
+
+    // Sealed segments where enough bytes can be reclaimed, oldest first
+    fn compaction_candidates(&self) -> Vec<u64> {
+        self.segments
+            .iter()
+            .filter(|(id, _)| **id != self.active_id)
+            .filter(|(id, stats)| {
+                // tombstones can be dropped once no older segment is left
+                let has_older = self.segments.range(..**id).next().is_some();
+                let needed = stats.live + if has_older { stats.tombstones } else { 0 };
+                let garbage = stats.len.saturating_sub(needed);
+                stats.len == 0 || garbage as f64 >= stats.len as f64 * COMPACTION_GARBAGE_RATIO
+            })
+            .map(|(id, _)| *id)
+            .collect()
+    }
+}
+
+/// A store which appends all values to segment files, the location of each of them
+/// being indexed in memory.
+///
+/// Values are immutable, so storing one which already exists, e.g. a chunk replicated
+/// to us more than once, doesn't write anything. Removing a value appends a tombstone,
+/// the space taken by removed values being reclaimed by compacting the segments.
+///
+/// Used space tracks the actual size of the segment files on disk, whilst the space used by
+/// each kind of data only accounts for the records holding the latest values.
+#[derive(Clone, Debug)]
+pub(crate) struct PackStore {
+    root_path: PathBuf,
+    max_segment_size: u64,
+    used_space: UsedSpace,
+    index: Arc<RwLock<PackIndex>>,
+    // only one compaction runs at a time, as segments are copied without holding the index lock
+    compaction: Arc<Mutex<()>>,
+}
+
+impl PackStore {
+    /// Creates a new `PackStore` at the specified root location
+    ///
+    /// If the location specified already contains a `PackStore`, it is simply used,
+    /// its index being rebuilt from the records in the segment files.
+    pub(crate) fn new<P: AsRef<Path>>(root_path: P, used_space: UsedSpace) -> Result<Self> {
+        Self::with_max_segment_size(root_path, used_space, MAX_SEGMENT_SIZE)
+    }
+
+    fn with_max_segment_size<P: AsRef<Path>>(
+        root_path: P,
+        used_space: UsedSpace,
+        max_segment_size: u64,
+    ) -> Result<Self> {
+        let root_path = root_path.as_ref().to_path_buf();
+        std::fs::create_dir_all(&root_path)?;
+
+        let mut values = BTreeMap::new();
+        let mut segments = BTreeMap::new();
+        let mut reg_kinds = BTreeMap::new();
+        let ids = list_segment_ids(&root_path)?;
+        for id in &ids {
+            let id = *id;
+            let path = segment_path(&root_path, id);
+            let _stats = segments.insert(id, SegmentStats::default());
+            let segment = read_segment(&path, id)?;
+            for (location, record) in segment.records {
+                match record {
+                    PackRecord::Value(key, value) => {
+                        if let PackKey::RegisterCmd(reg_name, _)
+                        | PackKey::RegisterSnapshot(reg_name) = &key
+                        {
+                            if let Entry::Vacant(vacant) = reg_kinds.entry(*reg_name) {
+                                if let Some(kind) = reg_kind_of(&key, &value) {
+                                    let _ = vacant.insert(kind);
+                                }
+                            }
+                        }
+                        if let Some(prev) = values.insert(key, location) {
+                            stats_of(&mut segments, prev.segment).live -= prev.size();
+                        }
+                        stats_of(&mut segments, id).live += location.size();
+                    }
+                    PackRecord::Tombstone(key) => {
+                        if let Some(prev) = values.remove(&key) {
+                            stats_of(&mut segments, prev.segment).live -= prev.size();
+                        }
+                        stats_of(&mut segments, id).tombstones += location.size();
+                    }
+                }
+            }
+            // the bytes which couldn't be read are dropped from the active segment,
+            // whilst they're kept on disk in a sealed one till it's compacted
+            let len = if Some(&id) == ids.last() {
+                segment.valid_len
+            } else {
+                segment.file_len
+            };
+            stats_of(&mut segments, id).len = len;
+            used_space.increase(len as usize);
+        }
+
+        let active_id = segments.keys().last().copied().unwrap_or_default();
+        let active_len = segments.entry(active_id).or_default().len;
+        let active_file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(segment_path(&root_path, active_id))?;
+        // drop any partially written record at the end of the file
+        active_file.set_len(active_len)?;
+
+        let index = PackIndex {
+            active_id,
+            active_file: File::from_std(active_file),
+            segments,
+            values,
+            reg_kinds,
+        };
+        for (key, location) in &index.values {
+            used_space.increase_kind(index.kind_of(key), location.value_len as usize);
+        }
+
+        Ok(Self {
+            root_path,
+            max_segment_size,
+            used_space,
+            index: Arc::new(RwLock::new(index)),
+            compaction: Arc::new(Mutex::new(())),
+        })
+    }
+
+    // ---------------------- helper methods ----------------------
+
+    async fn contains(&self, key: &PackKey) -> bool {
+        self.index.read().await.values.contains_key(key)
+    }
+
+    async fn get(&self, key: &PackKey) -> Result<Option<Bytes>> {
+        // the lock is held while reading so the segment can't be compacted meanwhile
+        let index = self.index.read().await;
+        let location = match index.values.get(key) {
+            Some(location) => *location,
+            None => return Ok(None),
+        };
+
+        let mut file = File::open(segment_path(&self.root_path, location.segment)).await?;
+        let _pos = file
+            .seek(SeekFrom::Start(location.offset + RECORD_HEADER_SIZE))
+            .await?;
+        let mut record = vec![0; location.len as usize];
+        let _len = file.read_exact(&mut record).await?;
+
+        match deserialise(&record)? {
+            PackRecord::Value(stored_key, value) if &stored_key == key => Ok(Some(value)),
+            _ => Err(Error::InvalidStore),
+        }
+    }
+
+    async fn put(&self, key: PackKey, value: Bytes) -> Result<()> {
+        let mut index = self.index.write().await;
+        if index.values.contains_key(&key) {
+            // values are immutable, thus the one stored already is kept
+            trace!("PackStore: {key:?} is already stored");
+            return Ok(());
+        }
+
+        self.insert(&mut index, key, value).await
+    }
+
+    // Stores the value, replacing any previous one for the same key
+    async fn replace(&self, key: PackKey, value: Bytes) -> Result<()> {
+        let mut index = self.index.write().await;
+        self.insert(&mut index, key, value).await
+    }
+
+    async fn insert(&self, index: &mut PackIndex, key: PackKey, value: Bytes) -> Result<()> {
+        let location = self
+            .append_record(index, &PackRecord::Value(key.clone(), value))
+            .await?;
+        index.stats_mut(location.segment).live += location.size();
+        let kind = index.kind_of(&key);
+        self.used_space
+            .increase_kind(kind, location.value_len as usize);
+        if let Some(prev) = index.values.insert(key, location) {
+            index.stats_mut(prev.segment).live -= prev.size();
+            self.used_space.decrease_kind(kind, prev.value_len as usize);
+        }
+
+        Ok(())
+    }
+
+    async fn remove(&self, key: &PackKey) -> Result<bool> {
+        let mut index = self.index.write().await;
+        if !index.values.contains_key(key) {
+            return Ok(false);
+        }
+
+        let tombstone = self
+            .append_record(&mut index, &PackRecord::Tombstone(key.clone()))
+            .await?;
+        index.stats_mut(tombstone.segment).tombstones += tombstone.size();
+        if let Some(location) = index.values.remove(key) {
+            index.stats_mut(location.segment).live -= location.size();
+            let kind = index.kind_of(key);
+            self.used_space
+                .decrease_kind(kind, location.value_len as usize);
+        }
+
+        Ok(true)
+    }
+
+    async fn reg_cmd_keys(&self, reg_name: XorName) -> Vec<PackKey> {
+        self.index
+            .read()
+            .await
+            .values
+            .keys()
+            .filter(|key| matches!(key, PackKey::RegisterCmd(name, _) if *name == reg_name))
+            .cloned()
+            .collect()
+    }
+
+    // Records the kind of the data of the register, for the space used to be accounted to it
+    async fn set_reg_kind(&self, addr: &RegisterAddress) -> Result<XorName> {
+        let reg_name = addr.id()?;
+        let _prev = self
+            .index
+            .write()
+            .await
+            .reg_kinds
+            .insert(reg_name, DataKind::of_register(addr));
+        Ok(reg_name)
+    }
+
+    // Appends a record to the active segment, starting a new one if it's full,
+    // and returns the location of the record
+    async fn append_record(
+        &self,
+        index: &mut PackIndex,
+        record: &PackRecord,
+    ) -> Result<RecordLocation> {
+        let value_len = record.value_len();
+        let record = serialise(record)?;
+        let len = u32::try_from(record.len())
+            .map_err(|_| Error::Serialize("Record too large for the pack file".to_string()))?;
+        let size = RECORD_HEADER_SIZE + len as u64;
+
+        let active_len = index.stats_mut(index.active_id).len;
+        if active_len > 0 && active_len + size > self.max_segment_size {
+            self.start_new_segment(index).await?;
+        }
+
+        let mut bytes = Vec::with_capacity(size as usize);
+        bytes.extend_from_slice(&len.to_le_bytes());
+        bytes.extend_from_slice(&record);
+
+        index.active_file.write_all(&bytes).await?;
+        // make sure the record is written before it can be read back
+        index.active_file.flush().await?;
+
+        let segment = index.active_id;
+        let stats = index.stats_mut(segment);
+        let location = RecordLocation {
+            segment,
+            offset: stats.len,
+            len,
+            value_len,
+        };
+        stats.len += size;
+        self.used_space.increase(size as usize);
+
+        Ok(location)
+    }
+
+    // Seals the active segment, syncing it to disk once rather than on every record
+    async fn start_new_segment(&self, index: &mut PackIndex) -> Result<()> {
+        index.active_file.sync_data().await?;
+
+        let id = index.active_id + 1;
+        index.active_file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(segment_path(&self.root_path, id))
+            .await?;
+        index.active_id = id;
+        let _prev = index.segments.insert(id, SegmentStats::default());
+        debug!(
+            "PackStore: started segment {id} at {}",
+            self.root_path.display()
+        );
+
+        Ok(())
+    }
+
+    // Rewrites a sealed segment with only the records still needed, deleting it if none is
+    async fn compact_segment(&self, id: u64) -> Result<u64> {
+        // sealed segments are only ever replaced or deleted by a compaction, so the segment
+        // is read and copied without the lock, which is only taken to swap the index entries
+        let path = segment_path(&self.root_path, id);
+        let segment = tokio::task::spawn_blocking(move || read_segment(&path, id))
+            .await
+            .map_err(io::Error::from)??;
+
+        let copied = self.copy_records(id, segment.records).await?;
+        self.swap_records(id, copied).await
+    }
+
+    // Copies the records read from a sealed segment which are still needed to a new file
+    async fn copy_records(
+        &self,
+        id: u64,
+        records: Vec<(RecordLocation, PackRecord)>,
+    ) -> Result<Vec<CopiedRecord>> {
+        let needed: Vec<_> = {
+            let index = self.index.read().await;
+            let has_older = index.segments.range(..id).next().is_some();
+            records
+                .into_iter()
+                .filter(|(location, record)| match record {
+                    PackRecord::Value(key, _) => index.values.get(key) == Some(location),
+                    // the removed value could still be in an older segment
+                    PackRecord::Tombstone(key) => has_older && !index.values.contains_key(key),
+                })
+                .collect()
+        };
+
+        let mut bytes = vec![];
+        let mut copied = vec![];
+        for (from, record) in needed {
+            let to = RecordLocation {
+                offset: bytes.len() as u64,
+                ..from
+            };
+            bytes.extend_from_slice(&from.len.to_le_bytes());
+            bytes.extend_from_slice(&serialise(&record)?);
+            let (key, tombstone) = match record {
+                PackRecord::Value(key, _) => (key, false),
+                PackRecord::Tombstone(key) => (key, true),
+            };
+            copied.push(CopiedRecord {
+                key,
+                tombstone,
+                from,
+                to,
+            });
+        }
+
+        let mut file = File::create(compaction_path(&self.root_path, id)).await?;
+        file.write_all(&bytes).await?;
+        // the records copied must be on disk before the only other copy is replaced
+        file.sync_data().await?;
+
+        Ok(copied)
+    }
+
+    // Replaces a sealed segment with the copy of its records still needed, pointing the index
+    // to the copies of the values which haven't been removed or replaced meanwhile
+    async fn swap_records(&self, id: u64, copied: Vec<CopiedRecord>) -> Result<u64> {
+        let copy_path = compaction_path(&self.root_path, id);
+        let mut index = self.index.write().await;
+        let stats = match index.segments.get(&id) {
+            Some(stats) if id != index.active_id => *stats,
+            // it was compacted meanwhile
+            _ => {
+                remove_file(&copy_path).await?;
+                return Ok(0);
+            }
+        };
+
+        let mut new_stats = SegmentStats::default();
+        for record in copied {
+            new_stats.len += record.to.size();
+            if record.tombstone {
+                new_stats.tombstones += record.to.size();
+            } else if index.values.get(&record.key) == Some(&record.from) {
+                new_stats.live += record.to.size();
+                let _prev = index.values.insert(record.key, record.to);
+            }
+        }
+
+        let path = segment_path(&self.root_path, id);
+        if new_stats.len == 0 {
+            let _stats = index.segments.remove(&id);
+            remove_file(&path).await?;
+            remove_file(&copy_path).await?;
+        } else {
+            let _stats = index.segments.insert(id, new_stats);
+            rename(&copy_path, &path).await?;
+        }
+        self.used_space.decrease(stats.len as usize);
+        self.used_space.increase(new_stats.len as usize);
+
+        let freed = stats.len.saturating_sub(new_stats.len);
+        debug!(
+            "PackStore: compacted segment {id} at {}, {freed} bytes freed",
+            self.root_path.display()
+        );
+
+        Ok(freed)
+    }
+}
+
+#[async_trait]
+impl StorageBackend for PackStore {
+    fn check_space(&self, kind: DataKind, size: usize) -> Result<()> {
+        self.used_space.check_space(kind, size)
+    }
+
+    async fn list_chunk_addrs(&self) -> Vec<ChunkAddress> {
+        self.index
+            .read()
+            .await
+            .values
+            .keys()
+            .filter_map(|key| match key {
+                PackKey::Chunk(name) => Some(ChunkAddress(*name)),
+                PackKey::RegisterCmd(..) | PackKey::RegisterSnapshot(_) => None,
+            })
+            .collect()
+    }
+
+    async fn chunk_exists(&self, addr: &ChunkAddress) -> Result<bool> {
+        Ok(self.contains(&PackKey::Chunk(*addr.name())).await)
+    }
+
+    async fn write_chunk(&self, chunk: &Chunk) -> Result<()> {
+        self.put(PackKey::Chunk(*chunk.name()), chunk.value().clone())
+            .await
+    }
+
+    async fn read_chunk(&self, addr: &ChunkAddress) -> Result<Chunk> {
+        match self.get(&PackKey::Chunk(*addr.name())).await? {
+            Some(value) => Ok(Chunk::new(value)),
+            None => Err(Error::ChunkNotFound(*addr.name())),
+        }
+    }
+
+    async fn delete_chunk(&self, addr: &ChunkAddress) -> Result<()> {
+        if self.remove(&PackKey::Chunk(*addr.name())).await? {
+            Ok(())
+        } else {
+            Err(Error::ChunkNotFound(*addr.name()))
+        }
+    }
+
+    async fn quarantine_chunk(
+        &self,
+        addr: &ChunkAddress,
+        quarantine_path: &Path,
+    ) -> Result<PathBuf> {
+        let value = self
+            .get(&PackKey::Chunk(*addr.name()))
+            .await?
+            .ok_or(Error::ChunkNotFound(*addr.name()))?;
+
+        create_dir_all(quarantine_path).await?;
+        let quarantined_filepath =
+            quarantine_path.join(DataAddress::Chunk(*addr).encode_to_zbase32()?);
+        let mut file = File::create(&quarantined_filepath).await?;
+        file.write_all(&value).await?;
+        file.flush().await?;
+
+        self.delete_chunk(addr).await?;
+
+        Ok(quarantined_filepath)
+    }
+
+    async fn list_reg_addrs(&self) -> Vec<RegisterAddress> {
+        // the address of each register is found in any of the ops of its log
+        let mut first_cmd_keys = BTreeMap::<XorName, PackKey>::new();
+        for key in self.index.read().await.values.keys() {
+            if let PackKey::RegisterCmd(reg_name, _) = key {
+                if let Entry::Vacant(vacant) = first_cmd_keys.entry(*reg_name) {
+                    let _ = vacant.insert(key.clone());
+                }
+            }
+        }
+
+        let mut addrs = vec![];
+        for key in first_cmd_keys.values() {
+            match self.get(key).await {
+                Ok(Some(value)) => match deserialise::<RegisterCmd>(&value) {
+                    Ok(cmd) => addrs.push(cmd.dst_address()),
+                    Err(error) => warn!("PackStore: failed to deserialise {key:?}: {error}"),
+                },
+                Ok(None) => {}
+                Err(error) => warn!("PackStore: failed to read {key:?}: {error}"),
+            }
+        }
+
+        addrs
+    }
+
+    async fn reg_log_exists(&self, addr: &RegisterAddress) -> Result<bool> {
+        Ok(!self.reg_cmd_keys(addr.id()?).await.is_empty())
+    }
+
+    async fn read_reg_log(&self, addr: &RegisterAddress) -> Result<RegisterLog> {
+        let mut register_log = RegisterLog::new();
+        for key in self.reg_cmd_keys(addr.id()?).await {
+            if let Some(value) = self.get(&key).await? {
+                let cmd: RegisterCmd = deserialise(&value)?;
+                let _existing = register_log.insert(cmd.register_operation_id()?, cmd);
+            }
+        }
+
+        Ok(register_log)
+    }
+
+    async fn write_reg_cmd(
+        &self,
+        addr: &RegisterAddress,
+        reg_id: &RegisterCmdId,
+        cmd: &RegisterCmd,
+    ) -> Result<()> {
+        let key = PackKey::RegisterCmd(self.set_reg_kind(addr).await?, reg_id.clone());
+        self.put(key, Bytes::from(serialise(cmd)?)).await
+    }
+
+    async fn delete_reg_cmds(
+        &self,
+        addr: &RegisterAddress,
+        reg_ids: &[RegisterCmdId],
+    ) -> Result<()> {
+        let reg_name = addr.id()?;
+        for reg_id in reg_ids {
+            let key = PackKey::RegisterCmd(reg_name, reg_id.clone());
+            let _removed = self.remove(&key).await?;
+        }
+        Ok(())
+    }
+
+    async fn delete_reg_log(&self, addr: &RegisterAddress) -> Result<()> {
+        let reg_name = addr.id()?;
+        for key in self.reg_cmd_keys(reg_name).await {
+            let _removed = self.remove(&key).await?;
+        }
+        let _removed = self.remove(&PackKey::RegisterSnapshot(reg_name)).await?;
+        let _kind = self.index.write().await.reg_kinds.remove(&reg_name);
+        Ok(())
+    }
+
+    async fn read_reg_snapshot(&self, addr: &RegisterAddress) -> Result<Option<RegisterSnapshot>> {
+        match self.get(&PackKey::RegisterSnapshot(addr.id()?)).await? {
+            Some(value) => Ok(Some(deserialise(&value)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn write_reg_snapshot(
+        &self,
+        addr: &RegisterAddress,
+        snapshot: &RegisterSnapshot,
+    ) -> Result<()> {
+        let key = PackKey::RegisterSnapshot(self.set_reg_kind(addr).await?);
+        self.replace(key, Bytes::from(serialise(snapshot)?)).await
+    }
+
+    /// Compacts the sealed segments where enough space can be reclaimed, rewriting them
+    /// with only the records still needed.
+    async fn compact(&self) -> Result<u64> {
+        let _compacting = self.compaction.lock().await;
+        let candidates = self.index.read().await.compaction_candidates();
+
+        let mut freed = 0;
+        for id in candidates {
+            freed += self.compact_segment(id).await?;
+        }
+
+        Ok(freed)
+    }
+}
+
+// The kind of the data of the register a value belongs to, read from the value itself
+fn reg_kind_of(key: &PackKey, value: &Bytes) -> Option<DataKind> {
+    let addr = match key {
+        PackKey::Chunk(_) => return None,
+        PackKey::RegisterCmd(..) => deserialise::<RegisterCmd>(value).ok()?.dst_address(),
+        PackKey::RegisterSnapshot(_) => {
+            *deserialise::<RegisterSnapshot>(value).ok()?.state.address()
+        }
+    };
+    Some(DataKind::of_register(&addr))
+}
+
+fn segment_path(root_path: &Path, id: u64) -> PathBuf {
+    root_path.join(format!("{id:010}.{SEGMENT_FILE_EXTENSION}"))
+}
+
+fn compaction_path(root_path: &Path, id: u64) -> PathBuf {
+    root_path.join(format!("{id:010}.{COMPACTION_FILE_EXTENSION}"))
+}
+
+fn stats_of(segments: &mut BTreeMap<u64, SegmentStats>, id: u64) -> &mut SegmentStats {
+    segments.entry(id).or_default()
+}
+
+// Ids of the segment files found at the root location, in ascending order
+fn list_segment_ids(root_path: &Path) -> Result<Vec<u64>> {
+    let mut ids = vec![];
+    for entry in std::fs::read_dir(root_path)? {
+        let path = entry?.path();
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(SEGMENT_FILE_EXTENSION) => {}
+            // left over by a compaction which was interrupted, the segment is still in place
+            Some(COMPACTION_FILE_EXTENSION) => {
+                std::fs::remove_file(&path)?;
+                continue;
+            }
+            _ => continue,
+        }
+        match path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.parse().ok())
+        {
+            Some(id) => ids.push(id),
+            None => warn!("PackStore: ignoring unexpected file {}", path.display()),
+        }
+    }
+    ids.sort_unstable();
+
+    Ok(ids)
+}
+
+// Reads all the records in a segment file, stopping at the first one which cannot be read,
+// e.g. as it was partially written at the end of the file, or is corrupt.
+fn read_segment(file_path: &Path, id: u64) -> Result<SegmentRecords> {
+    let mut segment = SegmentRecords::default();
+    let file = match std::fs::File::open(file_path) {
+        Ok(file) => file,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(segment),
+        Err(error) => return Err(error.into()),
+    };
+    segment.file_len = file.metadata()?.len();
+
+    let mut reader = BufReader::new(file);
+    loop {
+        let offset = segment.valid_len;
+        let mut header = [0; RECORD_HEADER_SIZE as usize];
+        match reader.read_exact(&mut header) {
+            Ok(()) => {}
+            Err(error) if error.kind() == ErrorKind::UnexpectedEof => break,
+            Err(error) => return Err(error.into()),
+        }
+        // checked before allocating, as a corrupt header could claim any length
+        let len = u32::from_le_bytes(header);
+        if offset + RECORD_HEADER_SIZE + len as u64 > segment.file_len {
+            break;
+        }
+        let mut record = vec![0; len as usize];
+        reader.read_exact(&mut record)?;
+
+        match deserialise::<PackRecord>(&record) {
+            Ok(pack_record) => {
+                let location = RecordLocation {
+                    segment: id,
+                    offset,
+                    len,
+                    value_len: pack_record.value_len(),
+                };
+                segment.records.push((location, pack_record));
+            }
+            Err(error) => {
+                warn!(
+                    "PackStore: record at offset {offset} of {} cannot be deserialised: {error}",
+                    file_path.display()
+                );
+                break;
+            }
+        }
+        segment.valid_len += RECORD_HEADER_SIZE + len as u64;
+    }
+
+    if segment.valid_len < segment.file_len {
+        warn!(
+            "PackStore: dropping the {} bytes which cannot be read from offset {} of {}",
+            segment.file_len - segment.valid_len,
+            segment.valid_len,
+            file_path.display()
+        );
+    }
+
+    Ok(segment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sn_interface::types::utils::random_bytes;
+
+    use eyre::Result;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_pack_store_chunks() -> Result<()> {
+        let root = tempdir()?;
+        let store = PackStore::new(root.path(), UsedSpace::new(usize::MAX))?;
+
+        let chunks: Vec<Chunk> = std::iter::repeat_with(|| Chunk::new(random_bytes(100)))
+            .take(5)
+            .collect();
+        for chunk in &chunks {
+            store.write_chunk(chunk).await?;
+        }
+        for chunk in &chunks {
+            assert_eq!(store.read_chunk(chunk.address()).await?, *chunk);
+        }
+
+        store.delete_chunk(chunks[0].address()).await?;
+        assert!(!store.chunk_exists(chunks[0].address()).await?);
+        assert!(matches!(
+            store.read_chunk(chunks[0].address()).await,
+            Err(Error::ChunkNotFound(_))
+        ));
+        assert_eq!(store.list_chunk_addrs().await.len(), 4);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pack_store_reopen() -> Result<()> {
+        let root = tempdir()?;
+        let chunks: Vec<Chunk> = std::iter::repeat_with(|| Chunk::new(random_bytes(100)))
+            .take(3)
+            .collect();
+        {
+            let store = PackStore::new(root.path(), UsedSpace::new(usize::MAX))?;
+            for chunk in &chunks {
+                store.write_chunk(chunk).await?;
+            }
+            store.delete_chunk(chunks[1].address()).await?;
+        }
+
+        // simulate a crash half way through appending a record
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(segment_path(root.path(), 0))?;
+        file.write_all(&[200, 0, 0, 0, 1, 2, 3])?;
+
+        let store = PackStore::new(root.path(), UsedSpace::new(usize::MAX))?;
+        assert_eq!(store.read_chunk(chunks[0].address()).await?, chunks[0]);
+        assert_eq!(store.read_chunk(chunks[2].address()).await?, chunks[2]);
+        assert!(!store.chunk_exists(chunks[1].address()).await?);
+
+        // the store is still usable after dropping the partial record
+        let chunk = Chunk::new(random_bytes(100));
+        store.write_chunk(&chunk).await?;
+        let store = PackStore::new(root.path(), UsedSpace::new(usize::MAX))?;
+        assert_eq!(store.read_chunk(chunk.address()).await?, chunk);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pack_store_deduplicates_chunks() -> Result<()> {
+        let root = tempdir()?;
+        let used_space = UsedSpace::new(usize::MAX);
+        let store = PackStore::new(root.path(), used_space.clone())?;
+
+        let chunk = Chunk::new(random_bytes(100));
+        store.write_chunk(&chunk).await?;
+        let used = used_space.used();
+        store.write_chunk(&chunk).await?;
+
+        assert_eq!(used_space.used(), used);
+        assert_eq!(disk_usage(root.path())?, used as u64);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pack_store_compaction() -> Result<()> {
+        let root = tempdir()?;
+        let used_space = UsedSpace::new(usize::MAX);
+        let store = PackStore::with_max_segment_size(root.path(), used_space.clone(), 1024)?;
+
+        let chunks: Vec<Chunk> = std::iter::repeat_with(|| Chunk::new(random_bytes(200)))
+            .take(20)
+            .collect();
+        for chunk in &chunks {
+            store.write_chunk(chunk).await?;
+        }
+        let segments = list_segment_ids(root.path())?.len();
+        assert!(segments > 1);
+
+        // removing chunks appends tombstones, nothing is freed until compacted
+        let (removed, kept) = chunks.split_at(15);
+        for chunk in removed {
+            store.delete_chunk(chunk.address()).await?;
+        }
+        let used_before_compaction = used_space.used();
+        assert_eq!(disk_usage(root.path())?, used_before_compaction as u64);
+
+        let freed = store.compact().await?;
+        assert!(freed > 0);
+        assert!(used_space.used() < used_before_compaction);
+        assert_eq!(disk_usage(root.path())?, used_space.used() as u64);
+        assert!(list_segment_ids(root.path())?.len() < segments);
+
+        for chunk in kept {
+            assert_eq!(store.read_chunk(chunk.address()).await?, *chunk);
+        }
+        for chunk in removed {
+            assert!(!store.chunk_exists(chunk.address()).await?);
+        }
+
+        // the chunks removed are not brought back when the index is rebuilt
+        drop(store);
+        let used_space = UsedSpace::new(usize::MAX);
+        let store = PackStore::with_max_segment_size(root.path(), used_space.clone(), 1024)?;
+        assert_eq!(disk_usage(root.path())?, used_space.used() as u64);
+        assert_eq!(store.list_chunk_addrs().await.len(), kept.len());
+        for chunk in kept {
+            assert_eq!(store.read_chunk(chunk.address()).await?, *chunk);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pack_store_compaction_keeps_changes_made_while_reading() -> Result<()> {
+        let root = tempdir()?;
+        let used_space = UsedSpace::new(usize::MAX);
+        let store = PackStore::with_max_segment_size(root.path(), used_space.clone(), 1024)?;
+
+        let chunks: Vec<Chunk> = std::iter::repeat_with(|| Chunk::new(random_bytes(200)))
+            .take(10)
+            .collect();
+        for chunk in &chunks {
+            store.write_chunk(chunk).await?;
+        }
+        let first_segment = list_segment_ids(root.path())?[0];
+        let records =
+            read_segment(&segment_path(root.path(), first_segment), first_segment)?.records;
+        assert!(records.len() > 1);
+
+        // a chunk of the segment is removed after it's been copied for compaction
+        let copied = store.copy_records(first_segment, records).await?;
+        let removed = &chunks[0];
+        store.delete_chunk(removed.address()).await?;
+        let _freed = store.swap_records(first_segment, copied).await?;
+
+        assert!(!compaction_path(root.path(), first_segment).exists());
+        assert!(!store.chunk_exists(removed.address()).await?);
+        for chunk in &chunks[1..] {
+            assert_eq!(store.read_chunk(chunk.address()).await?, *chunk);
+        }
+        assert_eq!(disk_usage(root.path())?, used_space.used() as u64);
+
+        // nor is it brought back when the index is rebuilt
+        drop(store);
+        let store =
+            PackStore::with_max_segment_size(root.path(), UsedSpace::new(usize::MAX), 1024)?;
+        assert!(!store.chunk_exists(removed.address()).await?);
+        assert_eq!(store.list_chunk_addrs().await.len(), chunks.len() - 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pack_store_reopen_with_corrupt_record() -> Result<()> {
+        let root = tempdir()?;
+        let store =
+            PackStore::with_max_segment_size(root.path(), UsedSpace::new(usize::MAX), 1024)?;
+
+        let chunks: Vec<Chunk> = std::iter::repeat_with(|| Chunk::new(random_bytes(200)))
+            .take(10)
+            .collect();
+        for chunk in &chunks {
+            store.write_chunk(chunk).await?;
+        }
+        drop(store);
+
+        // a record claiming more bytes than the file holds is found in a sealed segment
+        let first_segment = list_segment_ids(root.path())?[0];
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(segment_path(root.path(), first_segment))?;
+        file.write_all(&[0xff; 32])?;
+        drop(file);
+
+        // the records before it are still read, and the whole file is accounted as used
+        let used_space = UsedSpace::new(usize::MAX);
+        let store = PackStore::with_max_segment_size(root.path(), used_space.clone(), 1024)?;
+        assert_eq!(disk_usage(root.path())?, used_space.used() as u64);
+        for chunk in &chunks {
+            assert_eq!(store.read_chunk(chunk.address()).await?, *chunk);
+        }
+
+        Ok(())
+    }
+
+    fn disk_usage(root_path: &Path) -> Result<u64> {
+        let mut usage = 0;
+        for id in list_segment_ids(root_path)? {
+            usage += std::fs::metadata(segment_path(root_path, id))?.len();
+        }
+        Ok(usage)
+    }
+}
@@ -6,13 +6,18 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use super::{convert_to_error_msg, Error, FileStore, RegisterLog, Result};
+use super::{
+    convert_to_error_msg, open_backend, DataKind, Error, RegisterLog, Result, StorageBackend,
+    StorageBackendKind,
+};
 
 use sn_interface::{
     messaging::{
         data::{
-            CreateRegister, EditRegister, OperationId, RegisterCmd, RegisterQuery,
-            RegisterStoreExport, ReplicatedRegisterLog, SignedRegisterCreate, SignedRegisterEdit,
+            CreateRegister, EditRegister, EditRegisterPolicy, OperationId, RegisterCmd,
+            RegisterCmdId, RegisterQuery, RegisterSnapshot, RegisterStoreExport,
+            ReplicatedRegisterLog, SignedRegisterCreate, SignedRegisterEdit,
+            SignedRegisterPolicyEdit,
         },
         system::NodeQueryResponse,
         SectionAuth, ServiceAuth, VerifyAuthority,
@@ -29,7 +34,8 @@ use bincode::serialize;
 use std::{
     collections::BTreeMap,
     fmt::{self, Display, Formatter},
-    path::{Path, PathBuf},
+    path::Path,
+    sync::Arc,
 };
 use tracing::info;
 #[cfg(test)]
@@ -37,42 +43,83 @@ use xor_name::Prefix;
 use xor_name::XorName;
 
 const REGISTER_DB_NAME: &str = "register";
+// Number of ops the log of a register can grow to before it's folded into a snapshot on compaction
+const REGISTER_SNAPSHOT_THRESHOLD: usize = 100;
 
 /// Operations over the data type Register.
 #[derive(Debug, Clone)]
 pub(super) struct RegisterStorage {
-    file_store: FileStore,
+    backend: Arc<dyn StorageBackend>,
+    snapshot_threshold: usize,
 }
 
 #[derive(Clone, Debug)]
 struct StoredRegister {
     state: Register,
     ops_log: RegisterLog,
-    ops_log_path: PathBuf,
+    snapshot: Option<RegisterSnapshot>,
     section_auth: SectionAuth,
 }
 
+impl StoredRegister {
+    fn contains_op(&self, reg_id: &RegisterCmdId) -> bool {
+        self.ops_log.contains_key(reg_id)
+            || self
+                .snapshot
+                .as_ref()
+                .map(|snapshot| snapshot.ops.contains(reg_id))
+                .unwrap_or(false)
+    }
+
+    // Ids of the ops of the log which can be folded into a snapshot,
+    // i.e. all of them but the one creating the Register
+    fn log_tail(&self) -> Vec<RegisterCmdId> {
+        self.ops_log
+            .iter()
+            .filter(|(_, cmd)| !matches!(cmd, RegisterCmd::Create { .. }))
+            .map(|(reg_id, _)| reg_id.clone())
+            .collect()
+    }
+}
+
 impl RegisterStorage {
     /// Create new `RegisterStorage`
-    pub(crate) fn new(path: &Path, used_space: UsedSpace) -> Result<Self> {
-        let file_store = FileStore::new(path.join(REGISTER_DB_NAME), used_space)?;
-
-        Ok(Self { file_store })
+    pub(crate) fn new(
+        path: &Path,
+        used_space: UsedSpace,
+        backend: StorageBackendKind,
+    ) -> Result<Self> {
+        let backend = open_backend(backend, path.join(REGISTER_DB_NAME), used_space)?;
+
+        Ok(Self {
+            backend,
+            snapshot_threshold: REGISTER_SNAPSHOT_THRESHOLD,
+        })
     }
 
     #[allow(dead_code)]
     pub(crate) async fn remove_register(&mut self, address: &RegisterAddress) -> Result<()> {
         trace!("Removing register, {:?}", address);
 
-        self.file_store
-            .delete_data(&DataAddress::Register(*address))
-            .await?;
+        self.backend.delete_reg_log(address).await?;
 
         Ok(())
     }
 
+    /// Folds the logs which have grown long enough into snapshots, and reclaims the space
+    /// taken by the register ops removed, returning the amount of bytes freed
+    pub(super) async fn compact(&self) -> Result<u64> {
+        for address in self.addrs().await {
+            if let Err(error) = self.snapshot_if_due(&address).await {
+                warn!("Could not take a snapshot of Register {address:?}: {error:?}");
+            }
+        }
+
+        self.backend.compact().await
+    }
+
     pub(crate) async fn addrs(&self) -> Vec<RegisterAddress> {
-        self.file_store.list_all_reg_addrs().await
+        self.backend.list_reg_addrs().await
     }
 
     /// Used for replication of data to new Adults.
@@ -118,6 +165,16 @@ impl RegisterStorage {
                             return None;
                         }
                     }
+                    RegisterCmd::EditPolicy(SignedRegisterPolicyEdit { op, auth }) => {
+                        let verification = auth.clone().verify_authority(serialize(op).ok()?);
+                        if verification.is_err() {
+                            error!(
+                                "Invalid signature found for a cmd stored in db: {:?}",
+                                stored_cmd
+                            );
+                            return None;
+                        }
+                    }
                 }
 
                 Some(stored_cmd)
@@ -127,6 +184,7 @@ impl RegisterStorage {
         Ok(ReplicatedRegisterLog {
             address: address.ok_or(Error::InvalidStore)?,
             section_auth: stored_reg.section_auth,
+            snapshot: stored_reg.snapshot,
             op_log,
         })
     }
@@ -164,21 +222,135 @@ impl RegisterStorage {
         // nested loops, slow..
         for data in registers {
             let key = data.address.id()?;
-            for replicated_cmd in data.op_log {
-                if replicated_cmd.dst_address() != data.address {
-                    warn!(
-                        "Corrupt ReplicatedRegisterLog, op log contains foreign ops: {}",
-                        key
-                    );
-                    continue;
-                }
-                self.apply(replicated_cmd).await?;
+            // the Register needs to be created before any edit can be applied to it
+            let (create_cmds, edit_cmds): (Vec<_>, Vec<_>) = data
+                .op_log
+                .into_iter()
+                .filter(|cmd| {
+                    let foreign = cmd.dst_address() != data.address;
+                    if foreign {
+                        warn!(
+                            "Corrupt ReplicatedRegisterLog, op log contains foreign ops: {}",
+                            key
+                        );
+                    }
+                    !foreign
+                })
+                .partition(|cmd| matches!(cmd, RegisterCmd::Create { .. }));
+            for replicated_cmd in create_cmds {
+                self.apply_replicated(replicated_cmd).await?;
+            }
+            // the snapshot stands in for the ops it covers, the rest are verified and applied on top
+            if let Some(snapshot) = data.snapshot {
+                self.adopt_snapshot(&data.address, snapshot).await?;
+            }
+            for replicated_cmd in edit_cmds {
+                self.apply_replicated(replicated_cmd).await?;
             }
         }
 
         Ok(())
     }
 
+    // Applies a replicated op, which we may have already.
+    // Ops failing verification are dropped, so they don't hold back the rest.
+    async fn apply_replicated(&mut self, cmd: RegisterCmd) -> Result<()> {
+        match self.apply(cmd).await {
+            Ok(()) | Err(Error::DataExists) | Err(Error::RegCmdOperationExists(_)) => Ok(()),
+            Err(
+                error @ (Error::InvalidSignature(_)
+                | Error::InvalidOwner(_)
+                | Error::NetworkData(_)),
+            ) => {
+                warn!("Replicated Register op rejected: {error}");
+                Ok(())
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    // Adopts a snapshot received from another node of our section in place of the one we
+    // hold, as long as it covers all the ops ours does. The ops of our log it doesn't cover
+    // are kept, and applied on top of it when the Register is loaded.
+    async fn adopt_snapshot(
+        &mut self,
+        address: &RegisterAddress,
+        snapshot: RegisterSnapshot,
+    ) -> Result<()> {
+        if snapshot.state.address() != address {
+            warn!("Corrupt ReplicatedRegisterLog, snapshot of a foreign Register: {address:?}");
+            return Ok(());
+        }
+
+        let stored_reg = self.try_load_stored_register(address).await?;
+        let ours = stored_reg
+            .snapshot
+            .as_ref()
+            .map(|stored| stored.ops.clone())
+            .unwrap_or_default();
+        if !snapshot.ops.is_superset(&ours) {
+            warn!(
+                "Snapshot of Register {address:?} doesn't cover all the ops of ours, keeping ours"
+            );
+            return Ok(());
+        }
+        if snapshot.ops.len() == ours.len() {
+            trace!("Snapshot of Register {address:?} has no new ops");
+            return Ok(());
+        }
+
+        let covered: Vec<_> = stored_reg
+            .log_tail()
+            .into_iter()
+            .filter(|reg_id| snapshot.ops.contains(reg_id))
+            .collect();
+
+        self.write_snapshot(address, &snapshot, &covered).await
+    }
+
+    // Folds the log of the Register into a snapshot once it has grown long enough,
+    // so it's quicker to load and replicate.
+    // As the whole state is rewritten every time, this is only done on compaction.
+    async fn snapshot_if_due(&self, address: &RegisterAddress) -> Result<()> {
+        let stored_reg = self.try_load_stored_register(address).await?;
+        let tail = stored_reg.log_tail();
+        if tail.len() < self.snapshot_threshold {
+            return Ok(());
+        }
+
+        let mut ops = stored_reg
+            .snapshot
+            .map(|snapshot| snapshot.ops)
+            .unwrap_or_default();
+        ops.extend(tail.iter().cloned());
+        let snapshot = RegisterSnapshot {
+            state: stored_reg.state,
+            ops,
+        };
+
+        self.write_snapshot(address, &snapshot, &tail).await
+    }
+
+    // Persists a snapshot of the Register state, and removes the ops of the log it covers.
+    async fn write_snapshot(
+        &self,
+        address: &RegisterAddress,
+        snapshot: &RegisterSnapshot,
+        covered: &[RegisterCmdId],
+    ) -> Result<()> {
+        // the snapshot is written first, so no op is lost if we stop half way through
+        self.backend.write_reg_snapshot(address, snapshot).await?;
+        self.backend.delete_reg_cmds(address, covered).await?;
+
+        debug!(
+            "Register {address:?} snapshot taken, covering {} ops, {} removed from the log",
+            snapshot.ops.len(),
+            covered.len()
+        );
+
+        Ok(())
+    }
+
     /// --- Writing ---
 
     pub(crate) async fn write(&mut self, cmd: RegisterCmd) -> Result<()> {
@@ -186,9 +358,8 @@ impl RegisterStorage {
 
         // rough estimate of the RegisterCmd
         let required_space = std::mem::size_of::<RegisterCmd>();
-        if !self.file_store.can_add(required_space) {
-            return Err(Error::NotEnoughSpace);
-        }
+        self.backend
+            .check_space(DataKind::of_register(&cmd.dst_address()), required_space)?;
         self.apply(cmd).await
     }
 
@@ -196,18 +367,20 @@ impl RegisterStorage {
     async fn append(
         &mut self,
         cmd: RegisterCmd,
-        mut ops_log: RegisterLog,
-        path: &Path,
+        stored_reg: Option<&StoredRegister>,
     ) -> Result<()> {
         let reg_id = cmd.register_operation_id()?;
 
-        if ops_log.get(&reg_id).is_some() {
+        if stored_reg
+            .map(|stored_reg| stored_reg.contains_op(&reg_id))
+            .unwrap_or(false)
+        {
             return Err(Error::RegCmdOperationExists(reg_id));
         }
 
-        let _old_cmd = ops_log.insert(reg_id, cmd);
-
-        self.file_store.write_log_to_disk(&ops_log, path).await?;
+        self.backend
+            .write_reg_cmd(&cmd.dst_address(), &reg_id, &cmd)
+            .await?;
 
         Ok(())
     }
@@ -230,44 +403,26 @@ impl RegisterStorage {
                     .verify_authority(serialize(&op)?)
                     .or(Err(Error::InvalidSignature(public_key)))?;
 
-                if self
-                    .file_store
-                    .data_file_exists(&DataAddress::Register(address))?
-                {
+                if self.backend.reg_log_exists(&address).await? {
                     return Err(Error::DataExists);
                 }
 
-                // init store first, to allow append to happen asap after key insert
-                // could be races, but edge case for later todos.
-                let (ops_log, path) = self.file_store.open_reg_log_from_disk(&address).await?;
-
                 trace!("Creating new register");
 
                 // insert the op to the cmds log
-                self.append(cmd, ops_log, &path).await?;
+                self.append(cmd, None).await?;
 
                 Ok(())
             }
-            RegisterCmd::Edit(SignedRegisterEdit { op, auth }) => {
-                let public_key = auth.public_key;
-                let _ = auth
-                    .verify_authority(serialize(&op)?)
-                    .or(Err(Error::InvalidSignature(public_key)))?;
-
-                let EditRegister { edit, .. } = op;
-
+            RegisterCmd::Edit(_) | RegisterCmd::EditPolicy(_) => {
                 let mut stored_reg = self.try_load_stored_register(&address).await?;
 
                 info!("Editing Register");
-                stored_reg
-                    .state
-                    .check_permissions(Action::Write, Some(User::Key(public_key)))?;
-                let result = stored_reg.state.apply_op(edit).map_err(Error::NetworkData);
+                let result = apply_edit(&mut stored_reg.state, &cmd);
 
                 match result {
                     Ok(()) => {
-                        self.append(cmd, stored_reg.ops_log, &stored_reg.ops_log_path)
-                            .await?;
+                        self.append(cmd, Some(&stored_reg)).await?;
 
                         trace!("Editing Register success!");
                         Ok(())
@@ -464,15 +619,24 @@ impl RegisterStorage {
     // gets stored register from disk
     async fn try_load_stored_register(&self, addr: &RegisterAddress) -> Result<StoredRegister> {
         // read from disk
-        let (ops_log, ops_log_path) = self.file_store.open_reg_log_from_disk(addr).await?;
+        let ops_log = self.backend.read_reg_log(addr).await?;
+        // the ops covered by the snapshot, if any, are already applied to its state
+        let snapshot = self.backend.read_reg_snapshot(addr).await?;
+        let covered = |reg_id: &RegisterCmdId| {
+            snapshot
+                .as_ref()
+                .map(|snapshot| snapshot.ops.contains(reg_id))
+                .unwrap_or(false)
+        };
 
         // RegisterEdit ops could be read after the RegisterCreate op, so we should keep them
         // till we read the RegisterCreate op from disk, and then apply all queued ops on top.
         let mut queued = Vec::new();
+        let mut queued_policy_ops = Vec::new();
 
         // apply all ops
         let mut hydrated_register = None;
-        for (_, stored_cmd) in ops_log.clone().into_iter() {
+        for (reg_id, stored_cmd) in ops_log.clone().into_iter() {
             match stored_cmd {
                 RegisterCmd::Create {
                     cmd:
@@ -485,12 +649,14 @@ impl RegisterStorage {
                     // TODO: if we already have read a RegisterCreate op, check if there
                     // is any difference this other one, ... and perhaps log a warning if so?
                     hydrated_register = hydrated_register.or_else(|| {
-                        Some((
-                            Register::new(*policy.owner(), name, tag, policy),
-                            section_auth,
-                        ))
+                        let state = match &snapshot {
+                            Some(snapshot) => snapshot.state.clone(),
+                            None => Register::new(*policy.owner(), name, tag, policy),
+                        };
+                        Some((state, section_auth))
                     });
                 }
+                RegisterCmd::Edit(_) | RegisterCmd::EditPolicy(_) if covered(&reg_id) => {}
                 RegisterCmd::Edit(SignedRegisterEdit {
                     op: EditRegister { edit, .. },
                     ..
@@ -502,6 +668,17 @@ impl RegisterStorage {
                         queued.push(edit);
                     }
                 }
+                RegisterCmd::EditPolicy(SignedRegisterPolicyEdit {
+                    op: EditRegisterPolicy { edit, .. },
+                    ..
+                }) => {
+                    if let Some((state, _)) = &mut hydrated_register {
+                        state.apply_policy_op(edit).map_err(Error::NetworkData)?;
+                    } else {
+                        // we'll apply it after we read the RegisterCreate op from disk
+                        queued_policy_ops.push(edit);
+                    }
+                }
             }
         }
 
@@ -512,11 +689,14 @@ impl RegisterStorage {
                 for op in queued {
                     state.apply_op(op).map_err(Error::NetworkData)?;
                 }
+                for op in queued_policy_ops {
+                    state.apply_policy_op(op).map_err(Error::NetworkData)?;
+                }
 
                 Ok(StoredRegister {
                     state,
                     ops_log,
-                    ops_log_path,
+                    snapshot,
                     section_auth,
                 })
             }
@@ -530,6 +710,41 @@ impl Display for RegisterStorage {
     }
 }
 
+// Verifies the signature of a cmd editing the Register, and the authority of its signer,
+// before applying it to the Register state.
+fn apply_edit(state: &mut Register, cmd: &RegisterCmd) -> Result<()> {
+    match cmd {
+        RegisterCmd::Create { .. } => Err(Error::DataExists),
+        RegisterCmd::Edit(SignedRegisterEdit { op, auth }) => {
+            let public_key = auth.public_key;
+            let _ = auth
+                .clone()
+                .verify_authority(serialize(op)?)
+                .or(Err(Error::InvalidSignature(public_key)))?;
+
+            state.check_permissions(Action::Write, Some(User::Key(public_key)))?;
+            state.apply_op(op.edit.clone()).map_err(Error::NetworkData)
+        }
+        RegisterCmd::EditPolicy(SignedRegisterPolicyEdit { op, auth }) => {
+            let public_key = auth.public_key;
+            let _ = auth
+                .clone()
+                .verify_authority(serialize(op)?)
+                .or(Err(Error::InvalidSignature(public_key)))?;
+
+            // The op must have been generated by the signer, whose ownership of the
+            // Register is then checked against the policy being replaced.
+            if op.edit.source != User::Key(public_key) {
+                return Err(Error::InvalidOwner(public_key));
+            }
+
+            state
+                .apply_policy_op(op.edit.clone())
+                .map_err(Error::NetworkData)
+        }
+    }
+}
+
 // Helper functions temporarily used for spentbook logic, but also used for tests.
 // This shouldn't be required outside of tests once we have a Spentbook data type.
 fn create_reg_w_policy(
@@ -571,23 +786,28 @@ fn section_auth() -> SectionAuth {
 
 #[cfg(test)]
 mod test {
-    use super::{create_reg_w_policy, RegisterStorage};
+    use super::{create_reg_w_policy, RegisterStorage, StorageBackendKind};
 
     use crate::node::{Error, Result};
     use crate::UsedSpace;
 
     use sn_interface::{
         messaging::{
-            data::{RegisterCmd, RegisterQuery},
+            data::{
+                EditRegister, EditRegisterPolicy, RegisterCmd, RegisterQuery, RegisterStoreExport,
+                SignedRegisterEdit, SignedRegisterPolicyEdit,
+            },
             system::NodeQueryResponse,
+            ServiceAuth,
         },
         types::{
-            register::{EntryHash, Policy, User},
-            Keypair,
+            register::{EntryHash, Policy, PolicyOp, Register, User},
+            Error as NetworkDataError, Keypair, RegisterAddress,
         },
     };
 
     use rand::Rng;
+    use std::collections::BTreeSet;
     use tempfile::tempdir;
     use xor_name::Prefix;
 
@@ -734,11 +954,237 @@ mod test {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_register_transfer_ownership() -> Result<()> {
+        // setup store
+        let mut store = new_store()?;
+
+        // create register
+        let (cmd, authority, keypair) = create_register_with_keypair()?;
+        store.write(cmd.clone()).await?;
+        let address = cmd.dst_address();
+
+        // transfer its ownership to another user
+        let (new_owner, new_owner_keypair) = random_user();
+        let mut register = get_register(&store, address, authority).await?;
+        let transfer_op = register.transfer_ownership(new_owner, Some(authority))?;
+        let transfer_hash = transfer_op.hash()?;
+        store.write(policy_edit_cmd(transfer_op, &keypair)?).await?;
+
+        let register = get_register(&store, address, authority).await?;
+        assert_eq!(register.owner(), new_owner, "Should have the new owner!");
+
+        // the previous owner can no longer change the policy
+        let policy = Policy {
+            owner: authority,
+            permissions: Default::default(),
+        };
+        let op = PolicyOp {
+            address,
+            policy: policy.clone(),
+            previous: Some(transfer_hash),
+            source: authority,
+        };
+        let res = store.write(policy_edit_cmd(op.clone(), &keypair)?).await;
+        assert_eq!(
+            res.err().map(|err| err.to_string()),
+            Some(Error::NetworkData(NetworkDataError::AccessDenied(authority)).to_string()),
+            "Should not be able to change the policy!"
+        );
+
+        // nor sign an op on behalf of the new owner
+        let op = PolicyOp {
+            source: new_owner,
+            ..op
+        };
+        let res = store.write(policy_edit_cmd(op.clone(), &keypair)?).await;
+        assert_eq!(
+            res.err().map(|err| err.to_string()),
+            Some(Error::InvalidOwner(keypair.public_key()).to_string()),
+            "Should not be able to sign on behalf of the owner!"
+        );
+
+        // but the new owner can
+        store
+            .write(policy_edit_cmd(op, &new_owner_keypair)?)
+            .await?;
+        let register = get_register(&store, address, authority).await?;
+        assert_eq!(register.policy(), &policy, "Should have the new policy!");
+
+        // the policy is kept when the register is replicated to another store
+        let prefix = Prefix::new(0, cmd.name());
+        let for_update = store.get_data_of(prefix).await?;
+        let mut new_store = new_store()?;
+        new_store.update(for_update).await?;
+        let register = get_register(&new_store, address, authority).await?;
+        assert_eq!(register.policy(), &policy, "Should have the same policy!");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_register_snapshot() -> Result<()> {
+        check_register_snapshot(StorageBackendKind::Files).await
+    }
+
+    #[tokio::test]
+    async fn test_register_snapshot_with_pack_backend() -> Result<()> {
+        check_register_snapshot(StorageBackendKind::Pack).await
+    }
+
+    async fn check_register_snapshot(backend: StorageBackendKind) -> Result<()> {
+        // setup store, folding the log into a snapshot on compaction once it has 3 edits
+        let mut store = new_store_with(backend)?;
+        store.snapshot_threshold = 3;
+
+        // create register and write to it, compacting the store in between
+        let (cmd, authority, keypair) = create_register_with_keypair()?;
+        store.write(cmd.clone()).await?;
+        let address = cmd.dst_address();
+        for i in 0..4 {
+            if i == 2 || i == 3 {
+                let _freed = store.compact().await?;
+            }
+            let edit = write_entry_cmd(&store, address, authority, &keypair).await?;
+            store.write(edit).await?;
+        }
+
+        // a log with 2 edits isn't folded, so the first 3 edits are covered by the snapshot,
+        // only the last one is left in the log
+        let replica = store.get_register_replica(&address).await?;
+        let snapshot = replica.snapshot.clone().expect("Should have a snapshot!");
+        assert_eq!(snapshot.ops.len(), 3);
+        assert_eq!(
+            replica.op_log.len(),
+            2,
+            "Should have the create op and one edit!"
+        );
+        assert_eq!(get_register(&store, address, authority).await?.size(), 4);
+
+        // the snapshot and the log tail are replicated to another store
+        let mut replica_store = new_store_with(backend)?;
+        replica_store
+            .update(RegisterStoreExport(vec![replica.clone()]))
+            .await?;
+        assert_eq!(
+            get_register(&replica_store, address, authority).await?,
+            get_register(&store, address, authority).await?,
+            "Should have the same register!"
+        );
+
+        // and merged by a store which already holds the register, with other edits
+        let mut other_store = new_store_with(backend)?;
+        other_store.write(cmd).await?;
+        let edit = write_entry_cmd(&other_store, address, authority, &keypair).await?;
+        other_store.write(edit).await?;
+        other_store
+            .update(RegisterStoreExport(vec![replica]))
+            .await?;
+        assert_eq!(
+            get_register(&other_store, address, authority).await?.size(),
+            5
+        );
+        // its own edit is kept in the log, on top of the snapshot it adopted
+        let other_replica = other_store.get_register_replica(&address).await?;
+        assert_eq!(
+            other_replica.snapshot.map(|snapshot| snapshot.ops.len()),
+            Some(3)
+        );
+        assert_eq!(other_replica.op_log.len(), 3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_register_snapshot_tail_ops_are_verified() -> Result<()> {
+        // setup store, folding the log into a snapshot on compaction once it has 2 edits
+        let mut store = new_store()?;
+        store.snapshot_threshold = 2;
+
+        let (cmd, authority, keypair) = create_register_with_keypair()?;
+        store.write(cmd.clone()).await?;
+        let address = cmd.dst_address();
+        for _ in 0..2 {
+            let edit = write_entry_cmd(&store, address, authority, &keypair).await?;
+            store.write(edit).await?;
+        }
+        let _freed = store.compact().await?;
+        let edit = write_entry_cmd(&store, address, authority, &keypair).await?;
+        store.write(edit).await?;
+        let mut replica = store.get_register_replica(&address).await?;
+        assert!(replica.snapshot.is_some(), "Should have a snapshot!");
+
+        // the node replicating the register adds an op to the log tail,
+        // signed by someone not allowed to write to the register
+        let forged = write_entry_cmd(&store, address, authority, &Keypair::new_ed25519()).await?;
+        replica.op_log.push(forged);
+
+        // only the ops signed by the owner make it to the replica
+        let mut replica_store = new_store()?;
+        replica_store
+            .update(RegisterStoreExport(vec![replica]))
+            .await?;
+        assert_eq!(
+            get_register(&replica_store, address, authority).await?,
+            get_register(&store, address, authority).await?,
+            "Should have the same register!"
+        );
+
+        Ok(())
+    }
+
+    async fn write_entry_cmd(
+        store: &RegisterStorage,
+        address: RegisterAddress,
+        authority: User,
+        keypair: &Keypair,
+    ) -> Result<RegisterCmd> {
+        let mut register = get_register(store, address, authority).await?;
+        let entry = rand::thread_rng().gen::<[u8; 32]>().to_vec();
+        let (_, edit) = register.write(entry, BTreeSet::new())?;
+        let op = EditRegister { address, edit };
+        let auth = ServiceAuth {
+            public_key: keypair.public_key(),
+            signature: keypair.sign(&bincode::serialize(&op)?),
+        };
+        Ok(RegisterCmd::Edit(SignedRegisterEdit { op, auth }))
+    }
+
+    async fn get_register(
+        store: &RegisterStorage,
+        address: RegisterAddress,
+        requester: User,
+    ) -> Result<Register> {
+        match store.read(&RegisterQuery::Get(address), requester).await {
+            NodeQueryResponse::GetRegister((Ok(register), _)) => Ok(register),
+            e => panic!("Could not read! {:?}", e),
+        }
+    }
+
+    fn policy_edit_cmd(edit: PolicyOp, keypair: &Keypair) -> Result<RegisterCmd> {
+        let op = EditRegisterPolicy {
+            address: edit.address,
+            edit,
+        };
+        let auth = ServiceAuth {
+            public_key: keypair.public_key(),
+            signature: keypair.sign(&bincode::serialize(&op)?),
+        };
+        Ok(RegisterCmd::EditPolicy(SignedRegisterPolicyEdit {
+            op,
+            auth,
+        }))
+    }
+
     fn new_store() -> Result<RegisterStorage> {
-        let tmp_dir = tempdir()?;
-        let path = tmp_dir.path();
+        new_store_with(StorageBackendKind::Files)
+    }
+
+    fn new_store_with(backend: StorageBackendKind) -> Result<RegisterStorage> {
+        // the pack store keeps using the files it opened, so the dir is kept around
+        let path = tempdir()?.into_path();
         let used_space = UsedSpace::new(usize::MAX);
-        let store = RegisterStorage::new(path, used_space)?;
+        let store = RegisterStorage::new(&path, used_space, backend)?;
         Ok(store)
     }
 
@@ -749,14 +1195,20 @@ mod test {
     }
 
     fn create_register() -> Result<(RegisterCmd, User)> {
+        let (cmd, authority, _) = create_register_with_keypair()?;
+        Ok((cmd, authority))
+    }
+
+    fn create_register_with_keypair() -> Result<(RegisterCmd, User, Keypair)> {
         let (authority, keypair) = random_user();
         let policy = Policy {
             owner: authority,
             permissions: Default::default(),
         };
         Ok((
-            create_reg_w_policy(xor_name::rand::random(), 0, policy, keypair)?,
+            create_reg_w_policy(rand::random(), 0, policy, keypair.clone())?,
             authority,
+            keypair,
         ))
     }
 }
@@ -6,29 +6,118 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use std::sync::{
-    atomic::{AtomicUsize, Ordering},
-    Arc,
+use super::{Error, Result};
+
+use sn_interface::types::{RegisterAddress, ReplicatedDataAddress, SPENTBOOK_TYPE_TAG};
+
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Display, Formatter},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
 };
 use tracing::info;
 
+/// By default the node stops accepting new data only once the whole capacity is used.
+const DEFAULT_HIGH_WATERMARK: usize = 100;
+
+/// The kinds of data stored, each of them with its own quota of the node capacity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum DataKind {
+    /// Immutable data chunks
+    Chunk,
+    /// Logs of register ops, along with their snapshots
+    Register,
+    /// Spentbooks, stored as registers for the time being
+    Spentbook,
+}
+
+impl DataKind {
+    pub(crate) const ALL: [DataKind; 3] =
+        [DataKind::Chunk, DataKind::Register, DataKind::Spentbook];
+
+    /// The kind of the data at the given address
+    pub(crate) fn of(addr: &ReplicatedDataAddress) -> Self {
+        match addr {
+            ReplicatedDataAddress::Chunk(_) => Self::Chunk,
+            ReplicatedDataAddress::Register(addr) => Self::of_register(addr),
+            ReplicatedDataAddress::Spentbook(_) => Self::Spentbook,
+        }
+    }
+
+    /// The kind of the data held by the register at the given address
+    pub(crate) fn of_register(addr: &RegisterAddress) -> Self {
+        if addr.tag() == SPENTBOOK_TYPE_TAG {
+            Self::Spentbook
+        } else {
+            Self::Register
+        }
+    }
+
+    fn index(&self) -> usize {
+        match self {
+            Self::Chunk => 0,
+            Self::Register => 1,
+            Self::Spentbook => 2,
+        }
+    }
+}
+
+impl Display for DataKind {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Chunk => write!(formatter, "chunks"),
+            Self::Register => write!(formatter, "registers"),
+            Self::Spentbook => write!(formatter, "spentbooks"),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 /// Tracking used space
 pub struct UsedSpace {
     /// the maximum (inclusive) allocated space for storage
-    max_capacity: usize,
+    max_capacity: Arc<AtomicUsize>,
     used_space: Arc<AtomicUsize>,
+    /// percentage of the capacity above which no more data is accepted
+    high_watermark: Arc<AtomicUsize>,
+    /// the maximum (inclusive) space for each kind of data, `usize::MAX` when unlimited
+    quotas: Arc<[AtomicUsize; 3]>,
+    /// space taken by the data of each kind which is still stored
+    used_by_kind: Arc<[AtomicUsize; 3]>,
 }
 
 impl UsedSpace {
     /// Create new `UsedSpace` tracker
     pub fn new(max_capacity: usize) -> Self {
         Self {
-            max_capacity,
+            max_capacity: Arc::new(AtomicUsize::new(max_capacity)),
             used_space: Arc::new(AtomicUsize::new(0)),
+            high_watermark: Arc::new(AtomicUsize::new(DEFAULT_HIGH_WATERMARK)),
+            quotas: Arc::new(DataKind::ALL.map(|_| AtomicUsize::new(usize::MAX))),
+            used_by_kind: Arc::new(DataKind::ALL.map(|_| AtomicUsize::new(0))),
         }
     }
 
+    /// Changes the maximum allocated space, e.g. when the config is reloaded
+    pub(crate) fn set_max_capacity(&self, max_capacity: usize) {
+        self.max_capacity.store(max_capacity, Ordering::Relaxed);
+    }
+
+    /// Changes the percentage of the capacity above which no more data is accepted,
+    /// `None` meaning the whole capacity can be used
+    pub(crate) fn set_high_watermark(&self, percentage: Option<u8>) {
+        let percentage = percentage.map_or(DEFAULT_HIGH_WATERMARK, |p| p.min(100) as usize);
+        self.high_watermark.store(percentage, Ordering::Relaxed);
+    }
+
+    /// Changes the maximum space for the given kind of data, `None` meaning unlimited
+    pub(crate) fn set_quota(&self, kind: DataKind, quota: Option<usize>) {
+        self.quotas[kind.index()].store(quota.unwrap_or(usize::MAX), Ordering::Relaxed);
+    }
+
     pub(crate) fn increase(&self, size: usize) {
         let _ = self.used_space.fetch_add(size, Ordering::Relaxed);
     }
@@ -37,18 +126,105 @@ impl UsedSpace {
         let _ = self.used_space.fetch_sub(size, Ordering::Relaxed);
     }
 
-    pub(crate) fn can_add(&self, size: usize) -> bool {
+    /// Accounts for data of the given kind being stored. This is tracked apart from the
+    /// overall used space, as backends may take more space on disk than the data itself.
+    pub(crate) fn increase_kind(&self, kind: DataKind, size: usize) {
+        let _ = self.used_by_kind[kind.index()].fetch_add(size, Ordering::Relaxed);
+    }
+
+    /// Accounts for data of the given kind being removed
+    pub(crate) fn decrease_kind(&self, kind: DataKind, size: usize) {
+        let _ = self.used_by_kind[kind.index()].fetch_sub(size, Ordering::Relaxed);
+    }
+
+    /// Checks there is room left for the given amount of bytes of data of the given kind,
+    /// both below the high watermark of the capacity and within the quota of the kind.
+    pub(crate) fn check_space(&self, kind: DataKind, size: usize) -> Result<()> {
         let current_used_space = self.used_space.load(Ordering::Relaxed);
-        current_used_space + size <= self.max_capacity
+        if current_used_space + size > self.accepting_capacity() {
+            return Err(Error::NotEnoughSpace);
+        }
+
+        if self.used_by(kind) + size > self.quota(kind) {
+            return Err(Error::QuotaExceeded(kind));
+        }
+
+        Ok(())
+    }
+
+    /// Whether the used space has reached the high watermark, i.e. no more data is accepted
+    pub(crate) fn is_above_high_watermark(&self) -> bool {
+        let high_watermark = self.high_watermark.load(Ordering::Relaxed);
+        high_watermark < DEFAULT_HIGH_WATERMARK && self.used() >= self.accepting_capacity()
+    }
+
+    pub(crate) fn max_capacity(&self) -> usize {
+        self.max_capacity.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn used(&self) -> usize {
+        self.used_space.load(Ordering::Relaxed)
+    }
+
+    /// Space taken by the data of the given kind
+    pub(crate) fn used_by(&self, kind: DataKind) -> usize {
+        self.used_by_kind[kind.index()].load(Ordering::Relaxed)
+    }
+
+    /// Maximum space for the given kind of data, `usize::MAX` when unlimited
+    pub(crate) fn quota(&self, kind: DataKind) -> usize {
+        self.quotas[kind.index()].load(Ordering::Relaxed)
     }
 
     pub(crate) fn ratio(&self) -> f64 {
-        let used = self.used_space.load(Ordering::Relaxed);
-        let max_capacity = self.max_capacity;
+        let used = self.used();
+        let max_capacity = self.max_capacity();
         let used_space_ratio = used as f64 / max_capacity as f64;
         info!("Used space: {:?}", used);
         info!("Max capacity: {:?}", max_capacity);
         info!("Used space ratio: {:?}", used_space_ratio);
         used_space_ratio
     }
+
+    // The share of the capacity which can be used before reaching the high watermark
+    fn accepting_capacity(&self) -> usize {
+        let high_watermark = self.high_watermark.load(Ordering::Relaxed);
+        (self.max_capacity() as u128 * high_watermark as u128 / 100) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DataKind, Error, UsedSpace};
+
+    #[test]
+    fn used_space_enforces_quotas_and_high_watermark() {
+        let used_space = UsedSpace::new(1000);
+        used_space.set_quota(DataKind::Register, Some(100));
+        used_space.set_high_watermark(Some(80));
+
+        used_space.increase(90);
+        used_space.increase_kind(DataKind::Register, 90);
+        assert!(used_space.check_space(DataKind::Chunk, 500).is_ok());
+        assert!(matches!(
+            used_space.check_space(DataKind::Register, 20),
+            Err(Error::QuotaExceeded(DataKind::Register))
+        ));
+
+        used_space.increase(700);
+        used_space.increase_kind(DataKind::Chunk, 700);
+        assert!(!used_space.is_above_high_watermark());
+        assert!(matches!(
+            used_space.check_space(DataKind::Chunk, 50),
+            Err(Error::NotEnoughSpace)
+        ));
+
+        used_space.increase(10);
+        assert!(used_space.is_above_high_watermark());
+
+        // without a watermark the whole capacity can be used
+        used_space.set_high_watermark(None);
+        assert!(!used_space.is_above_high_watermark());
+        assert!(used_space.check_space(DataKind::Chunk, 200).is_ok());
+    }
 }